@@ -12,7 +12,7 @@ if %ERRORLEVEL% NEQ 0 (
 
 REM Build the Windows executable bundle with release profile
 echo Building Windows executable bundle...
-cargo bundle --release --format msi
+cargo bundle --release --features sound --format msi
 
 echo Build process completed!
 echo Check the 'target\release\bundle\msi\' directory for the installer.
\ No newline at end of file
@@ -28,7 +28,7 @@ if [ $# -eq 0 ]; then
     elif [[ "$OSTYPE" == "linux-gnu"* ]]; then
         # Linux doesn't use cargo-bundle for releases, just regular build
         echo "Linux detected. Building with cargo build --release..."
-        cargo build --release
+        cargo build --release --features sound
         echo "Build completed. Binary available at target/release/ping-monitor"
         exit 0
     else
@@ -77,12 +77,12 @@ done
 if [ "$BUILD_WINDOWS" = true ]; then
     echo "Building Windows executable bundle..."
     if [[ "$OSTYPE" == "msys" || "$OSTYPE" == "win32" ]]; then
-        cargo bundle --release --format msi
+        cargo bundle --release --features sound --format msi
     else
         echo "Cross-compiling for Windows..."
         echo "Note: This requires the appropriate cross-compilation toolchain."
         echo "If this fails, please build on a Windows machine or use a Windows VM."
-        cargo bundle --release --target x86_64-pc-windows-gnu --format msi
+        cargo bundle --release --features sound --target x86_64-pc-windows-gnu --format msi
     fi
     echo "Windows build completed. Check the 'target/release/bundle/msi/' directory."
 fi
@@ -90,12 +90,12 @@ fi
 if [ "$BUILD_MACOS" = true ]; then
     echo "Building macOS application bundle..."
     if [[ "$OSTYPE" == "darwin"* ]]; then
-        cargo bundle --release --format osx
+        cargo bundle --release --features sound --format osx
     else
         echo "Cross-compiling for macOS..."
         echo "Note: This requires the appropriate cross-compilation toolchain."
         echo "If this fails, please build on a macOS machine or use a macOS VM."
-        cargo bundle --release --target x86_64-apple-darwin --format osx
+        cargo bundle --release --features sound --target x86_64-apple-darwin --format osx
     fi
     echo "macOS build completed. Check the 'target/release/bundle/osx/' directory."
     
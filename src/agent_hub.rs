@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::circle_color::CircleColor;
+use crate::ping::PingResult;
+use crate::remote_agent::{RemoteStatus, RemoteViewer};
+
+/// Results older than this don't count towards a cell's loss rate
+const HEALTH_WINDOW_SECS: u64 = 60;
+/// A connected agent that has sent nothing for this many intervals is shown as stale
+const STALE_INTERVALS: u64 = 3;
+
+/// An agent to watch in the aggregated view
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentEndpoint {
+    pub name: String,
+    /// host or host:port
+    pub address: String,
+    pub token: String,
+}
+
+struct AgentConnection {
+    endpoint: AgentEndpoint,
+    viewer: RemoteViewer,
+    receiver: mpsc::Receiver<PingResult>,
+    recent: VecDeque<PingResult>,
+}
+
+/// Health of one agent × target pair
+#[derive(Debug, Clone)]
+pub struct CellHealth {
+    pub color: CircleColor,
+    pub last_response_ms: Option<f64>,
+    pub loss_rate: f64,
+    pub samples: usize,
+}
+
+pub struct AgentRow {
+    pub name: String,
+    pub status: RemoteStatus,
+    /// One entry per column of `AggregateView::targets`; None where the agent doesn't probe that target
+    pub cells: Vec<Option<CellHealth>>,
+}
+
+/// Agents as rows and the targets they probe as columns
+pub struct AggregateView {
+    pub targets: Vec<String>,
+    pub rows: Vec<AgentRow>,
+}
+
+/// Keeps one connection per configured agent and summarizes their results
+pub struct AgentHub {
+    connections: Vec<AgentConnection>,
+}
+
+impl AgentHub {
+    pub fn new() -> Self {
+        Self { connections: Vec::new() }
+    }
+
+    /// Connects to new endpoints and drops removed or edited ones; unchanged connections are kept
+    pub fn sync(&mut self, endpoints: &[AgentEndpoint], ctx: &egui::Context) {
+        self.connections.retain(|connection| endpoints.contains(&connection.endpoint));
+        for endpoint in endpoints {
+            if self.connections.iter().any(|connection| &connection.endpoint == endpoint) {
+                continue;
+            }
+            let (sender, receiver) = mpsc::channel();
+            let viewer = RemoteViewer::connect(endpoint.address.clone(), endpoint.token.clone(), sender, ctx.clone());
+            self.connections.push(AgentConnection {
+                endpoint: endpoint.clone(),
+                viewer,
+                receiver,
+                recent: VecDeque::new(),
+            });
+        }
+    }
+
+    pub fn poll(&mut self) {
+        let cutoff = Instant::now().checked_sub(Duration::from_secs(HEALTH_WINDOW_SECS));
+        for connection in &mut self.connections {
+            connection.viewer.poll();
+            connection.recent.extend(connection.receiver.try_iter());
+            while connection.recent.front().is_some_and(|result| cutoff.is_some_and(|cutoff| result.sent_at < cutoff)) {
+                connection.recent.pop_front();
+            }
+        }
+    }
+
+    pub fn view(&self, green_threshold: u64, yellow_threshold: u64, very_slow_threshold: u64) -> AggregateView {
+        let mut targets: Vec<String> = Vec::new();
+        for connection in &self.connections {
+            if let RemoteStatus::Connected { target, .. } = &connection.viewer.status
+                && !targets.contains(target)
+            {
+                targets.push(target.clone());
+            }
+        }
+
+        let rows = self.connections
+            .iter()
+            .map(|connection| {
+                let cells = targets
+                    .iter()
+                    .map(|column| match &connection.viewer.status {
+                        RemoteStatus::Connected { target, interval_secs } if target == column => {
+                            Some(Self::cell_health(&connection.recent, *interval_secs, green_threshold, yellow_threshold, very_slow_threshold))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                AgentRow {
+                    name: connection.endpoint.name.clone(),
+                    status: connection.viewer.status.clone(),
+                    cells,
+                }
+            })
+            .collect();
+
+        AggregateView { targets, rows }
+    }
+
+    fn cell_health(
+        recent: &VecDeque<PingResult>,
+        interval_secs: u64,
+        green_threshold: u64,
+        yellow_threshold: u64,
+        very_slow_threshold: u64,
+    ) -> CellHealth {
+        let samples = recent.len();
+        let failed = recent.iter().filter(|result| !result.success).count();
+        let loss_rate = if samples > 0 { failed as f64 / samples as f64 * 100.0 } else { 0.0 };
+        let stale_after = Duration::from_secs(interval_secs.max(1) * STALE_INTERVALS);
+        let color = match recent.back() {
+            Some(last) if last.sent_at.elapsed() > stale_after => CircleColor::Gray,
+            Some(last) if !last.success => CircleColor::Red,
+            Some(last) => CircleColor::from_ping_response(last.response_time, green_threshold, yellow_threshold, very_slow_threshold),
+            None => CircleColor::Gray,
+        };
+        CellHealth {
+            color,
+            last_response_ms: recent.back().and_then(|result| result.response_time),
+            loss_rate,
+            samples,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+use crate::timestamp_format::TimestampFormat;
+
+/// Notes are appended to this JSON-lines file inside the config directory
+const ANNOTATIONS_FILE_NAME: &str = "annotations.jsonl";
+/// Text used when a note is dropped without typing anything
+pub const DEFAULT_NOTE: &str = "Marker";
+
+/// A timestamped note the user dropped on the timeline, e.g. "rebooted router"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub timestamp_ms: u64,
+    pub text: String,
+}
+
+impl Annotation {
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms)
+    }
+}
+
+/// Every note so far, kept in memory and appended to disk as it is added
+pub struct AnnotationStore {
+    path: Option<PathBuf>,
+    pub notes: Vec<Annotation>,
+}
+
+impl AnnotationStore {
+    /// Loads the saved notes; a line that can't be parsed is ignored
+    pub fn open() -> Self {
+        let path = AppConfig::get_config_dir().ok().map(|dir| dir.join(ANNOTATIONS_FILE_NAME));
+        let notes = path
+            .as_ref()
+            .and_then(|path| File::open(path).ok())
+            .map(|file| {
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<Annotation>(&line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, notes }
+    }
+
+    /// Records a note at `timestamp`; it is kept for this session even when saving fails
+    pub fn add(&mut self, timestamp: SystemTime, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let text = text.trim();
+        let annotation = Annotation {
+            timestamp_ms: timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            text: if text.is_empty() { DEFAULT_NOTE.to_string() } else { text.to_string() },
+        };
+        let mut line = serde_json::to_string(&annotation)?;
+        line.push('\n');
+        self.notes.push(annotation);
+
+        let path = self.path.as_ref().ok_or("Config directory is unavailable")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn since(&self, cutoff: SystemTime) -> impl Iterator<Item = &Annotation> {
+        self.notes.iter().filter(move |note| note.timestamp() >= cutoff)
+    }
+
+    /// Writes the notes made since `cutoff` as CSV (time, note) and returns how many were written
+    pub fn export_csv(&self, path: &Path, cutoff: SystemTime, format: TimestampFormat) -> io::Result<usize> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "time,note")?;
+        let mut count = 0;
+        for note in self.since(cutoff) {
+            let time = format.export(note.timestamp());
+            writeln!(writer, "{time},\"{}\"", note.text.replace('"', "\"\""))?;
+            count += 1;
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+}
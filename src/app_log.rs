@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+/// Lines kept for the in-app console
+const MAX_LOG_LINES: usize = 1000;
+/// Warnings and errors held for the UI to pick up as notifications
+const MAX_NOTICES: usize = 20;
+
+/// How much the app records, from errors only to everything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warn => "Warning",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+            LogLevel::Trace => "Trace",
+        }
+    }
+
+    pub fn to_level(self) -> Level {
+        match self {
+            LogLevel::Error => Level::Error,
+            LogLevel::Warn => Level::Warn,
+            LogLevel::Info => Level::Info,
+            LogLevel::Debug => Level::Debug,
+            LogLevel::Trace => Level::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Keeps recent lines for the console and passes everything on to env_logger, so `RUST_LOG`
+/// still controls what reaches stderr
+struct AppLogger {
+    stderr: env_logger::Logger,
+    lines: Mutex<VecDeque<LogLine>>,
+    notices: Mutex<VecDeque<LogLine>>,
+}
+
+static LOGGER: OnceLock<AppLogger> = OnceLock::new();
+/// Most verbose level kept for the console, as `Level as usize`
+static CAPTURE_LEVEL: AtomicUsize = AtomicUsize::new(Level::Info as usize);
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() as usize <= CAPTURE_LEVEL.load(Ordering::Relaxed) || self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+        let line = LogLine {
+            time: SystemTime::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if record.level() <= Level::Warn
+            && let Ok(mut notices) = self.notices.lock()
+        {
+            notices.push_back(line.clone());
+            if notices.len() > MAX_NOTICES {
+                notices.pop_front();
+            }
+        }
+        // Other crates (eframe, wgpu…) are chatty below warning level
+        let ours = record.target().starts_with(env!("CARGO_CRATE_NAME"));
+        if record.level() as usize > CAPTURE_LEVEL.load(Ordering::Relaxed) || (!ours && record.level() > Level::Warn) {
+            return;
+        }
+        let Ok(mut lines) = self.lines.lock() else {
+            return;
+        };
+        lines.push_back(line);
+        if lines.len() > MAX_LOG_LINES {
+            lines.pop_front();
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+/// Installs the logger; stderr defaults to info unless `RUST_LOG` says otherwise
+pub fn init() {
+    let stderr = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let logger = LOGGER.get_or_init(|| AppLogger { stderr, lines: Mutex::new(VecDeque::new()), notices: Mutex::new(VecDeque::new()) });
+    if log::set_logger(logger).is_ok() {
+        set_level(LogLevel::default());
+    }
+}
+
+/// Changes how much is recorded for the console from now on
+pub fn set_level(level: LogLevel) {
+    CAPTURE_LEVEL.store(level.to_level() as usize, Ordering::Relaxed);
+    let stderr_level = LOGGER.get().map_or(LevelFilter::Off, |logger| logger.stderr.filter());
+    log::set_max_level(level.to_level().to_level_filter().max(stderr_level));
+}
+
+/// Recent lines at `level` or more severe, oldest first
+pub fn recent_lines(level: LogLevel) -> Vec<LogLine> {
+    let Some(lines) = LOGGER.get().and_then(|logger| logger.lines.lock().ok()) else {
+        return Vec::new();
+    };
+    lines.iter().filter(|line| line.level <= level.to_level()).cloned().collect()
+}
+
+/// Warnings and errors logged since the last call, oldest first
+pub fn take_notices() -> Vec<LogLine> {
+    let Some(mut notices) = LOGGER.get().and_then(|logger| logger.notices.lock().ok()) else {
+        return Vec::new();
+    };
+    notices.drain(..).collect()
+}
+
+pub fn clear() {
+    if let Some(mut lines) = LOGGER.get().and_then(|logger| logger.lines.lock().ok()) {
+        lines.clear();
+    }
+}
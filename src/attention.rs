@@ -0,0 +1,98 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// How hard an alert asks for attention while the window is in the background
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttentionLevel {
+    Off,
+    /// One taskbar flash or dock bounce
+    Brief,
+    /// Flashes or bounces until the window is brought forward
+    UntilNoticed,
+}
+
+impl AttentionLevel {
+    pub const ALL: [AttentionLevel; 3] = [AttentionLevel::Off, AttentionLevel::Brief, AttentionLevel::UntilNoticed];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AttentionLevel::Off => "off",
+            AttentionLevel::Brief => "briefly",
+            AttentionLevel::UntilNoticed => "until noticed",
+        }
+    }
+
+    fn user_attention_type(self) -> Option<egui::UserAttentionType> {
+        match self {
+            AttentionLevel::Off => None,
+            AttentionLevel::Brief => Some(egui::UserAttentionType::Informational),
+            AttentionLevel::UntilNoticed => Some(egui::UserAttentionType::Critical),
+        }
+    }
+}
+
+/// How serious an alert is, which decides the attention it asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertSeverity {
+    /// The target stopped answering
+    Critical,
+    Warning,
+    /// Good news, such as an outage ending
+    Info,
+}
+
+/// Attention requested per alert severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttentionSettings {
+    pub critical: AttentionLevel,
+    pub warning: AttentionLevel,
+    pub info: AttentionLevel,
+}
+
+impl Default for AttentionSettings {
+    fn default() -> Self {
+        Self { critical: AttentionLevel::UntilNoticed, warning: AttentionLevel::Brief, info: AttentionLevel::Off }
+    }
+}
+
+impl AttentionSettings {
+    pub fn level(&self, severity: AlertSeverity) -> AttentionLevel {
+        match severity {
+            AlertSeverity::Critical => self.critical,
+            AlertSeverity::Warning => self.warning,
+            AlertSeverity::Info => self.info,
+        }
+    }
+}
+
+/// Collects alerts between frames and flashes the taskbar entry or bounces the dock icon for
+/// the strongest one, but only while the window isn't focused
+#[derive(Default)]
+pub struct AttentionRequester {
+    pending: Option<AttentionLevel>,
+    requested: bool,
+}
+
+impl AttentionRequester {
+    pub fn request(&mut self, level: AttentionLevel) {
+        self.pending = self.pending.max(Some(level));
+    }
+
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        let focused = ctx.input(|input| input.viewport().focused).unwrap_or(true);
+        if focused {
+            // Alerts seen in the window itself need no flashing
+            self.pending = None;
+            if std::mem::take(&mut self.requested) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Reset));
+            }
+            return;
+        }
+        if let Some(attention) = self.pending.take().and_then(AttentionLevel::user_attention_type) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(attention));
+            self.requested = true;
+        }
+    }
+}
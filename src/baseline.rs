@@ -0,0 +1,58 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use chrono::{DateTime, Local};
+use eframe::egui;
+use crate::history::HistoryStore;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// Yesterday's figures come from this many minutes either side of the current time of day,
+/// wide enough to smooth out single lost probes
+const BASELINE_HALF_WINDOW_MINS: u64 = 10;
+
+/// Latency and loss around the same time yesterday, for trend arrows
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    pub computed: Instant,
+    pub target: String,
+    /// Whether the baseline covers probes sent through a VPN
+    pub via_vpn: bool,
+    pub mean_ms: Option<f64>,
+    pub loss_rate: Option<f64>,
+}
+
+pub struct BaselineLoader;
+
+impl BaselineLoader {
+    pub fn spawn(store: HistoryStore, target: String, via_vpn: bool, sender: mpsc::Sender<Baseline>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let baseline = Self::compute(&store, target, via_vpn);
+            let _ = sender.send(baseline);
+            repaint.request_repaint();
+        });
+    }
+
+    fn compute(store: &HistoryStore, target: String, via_vpn: bool) -> Baseline {
+        let half_window = Duration::from_secs(BASELINE_HALF_WINDOW_MINS * 60);
+        let same_time_yesterday = SystemTime::now() - Duration::from_secs(DAY_SECS);
+        let (start, end) = (same_time_yesterday - half_window, same_time_yesterday + half_window);
+        let day = |time: SystemTime| DateTime::<Local>::from(time).date_naive();
+
+        let records: Vec<_> = store
+            .load_days(day(start), day(end))
+            .into_iter()
+            .filter(|record| record.target == target && !record.in_maintenance && record.vpn.is_some() == via_vpn)
+            .filter(|record| (start..=end).contains(&record.timestamp()))
+            .collect();
+        let replies: Vec<f64> = records.iter().filter_map(|record| record.response_ms).collect();
+        let failed = records.iter().filter(|record| !record.success).count();
+
+        Baseline {
+            computed: Instant::now(),
+            target,
+            via_vpn,
+            mean_ms: (!replies.is_empty()).then(|| replies.iter().sum::<f64>() / replies.len() as f64),
+            loss_rate: (!records.is_empty()).then(|| failed as f64 / records.len() as f64 * 100.0),
+        }
+    }
+}
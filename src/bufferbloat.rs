@@ -0,0 +1,161 @@
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use eframe::egui;
+use crate::health_check::INTERNET_ANCHOR;
+use crate::load_generator::{Direction, LoadGenerator, LoadSummary};
+use crate::ping_executor::PingExecutor;
+use crate::target_parser;
+
+const IDLE_PHASE_SECS: u64 = 5;
+const LOADED_PHASE_SECS: u64 = 10;
+const LOAD_STREAMS: usize = 4;
+const SAMPLE_INTERVAL_MS: u64 = 250;
+/// Samples from the first moments of a load phase are taken before the queues have filled
+const LOAD_RAMP_UP_MS: u64 = 1500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferbloatPhase {
+    Idle,
+    Download,
+    Upload,
+}
+
+impl BufferbloatPhase {
+    pub fn description(self) -> &'static str {
+        match self {
+            BufferbloatPhase::Idle => "Measuring idle latency…",
+            BufferbloatPhase::Download => "Measuring latency while downloading…",
+            BufferbloatPhase::Upload => "Measuring latency while uploading…",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BufferbloatReport {
+    pub target: IpAddr,
+    pub idle_ms: f64,
+    pub download_ms: Option<f64>,
+    pub upload_ms: Option<f64>,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+}
+
+impl BufferbloatReport {
+    /// Worst latency increase under load; a phase where every ping was lost counts as unbounded
+    pub fn worst_increase_ms(&self) -> f64 {
+        [self.download_ms, self.upload_ms]
+            .iter()
+            .map(|loaded| loaded.map_or(f64::INFINITY, |loaded| (loaded - self.idle_ms).max(0.0)))
+            .fold(0.0, f64::max)
+    }
+
+    /// Letter grade on the same scale as the common web bufferbloat tests
+    pub fn grade(&self) -> &'static str {
+        match self.worst_increase_ms() {
+            increase if increase < 5.0 => "A+",
+            increase if increase < 30.0 => "A",
+            increase if increase < 60.0 => "B",
+            increase if increase < 200.0 => "C",
+            increase if increase < 400.0 => "D",
+            _ => "F",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BufferbloatUpdate {
+    Phase(BufferbloatPhase),
+    Finished(Result<BufferbloatReport, String>),
+}
+
+pub struct BufferbloatTest;
+
+impl BufferbloatTest {
+    /// Measures idle latency to `target`, then latency while saturating the download and upload,
+    /// reporting each phase as it starts
+    pub fn spawn(target: String, sender: mpsc::Sender<BufferbloatUpdate>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let send = |update| {
+                let _ = sender.send(update);
+                repaint.request_repaint();
+            };
+            send(BufferbloatUpdate::Finished(Self::run(&target, |phase| send(BufferbloatUpdate::Phase(phase)))));
+        });
+    }
+
+    fn run(target: &str, on_phase: impl Fn(BufferbloatPhase)) -> Result<BufferbloatReport, String> {
+        let target = Self::resolve(target)?;
+        let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+
+        on_phase(BufferbloatPhase::Idle);
+        let idle = rt.block_on(Self::sample(target, Duration::from_secs(IDLE_PHASE_SECS), Duration::ZERO));
+        let idle_ms = median(idle).ok_or_else(|| format!("{target} did not answer pings while idle"))?;
+
+        on_phase(BufferbloatPhase::Download);
+        let (download_ms, download) = Self::loaded_phase(&rt, target, Direction::Download);
+        on_phase(BufferbloatPhase::Upload);
+        let (upload_ms, upload) = Self::loaded_phase(&rt, target, Direction::Upload);
+
+        if download.bytes == 0 && upload.bytes == 0 {
+            return Err("Could not generate load: the speed test server is unreachable".to_string());
+        }
+
+        Ok(BufferbloatReport {
+            target,
+            idle_ms,
+            download_ms,
+            upload_ms,
+            download_mbps: download.mbps(),
+            upload_mbps: upload.mbps(),
+        })
+    }
+
+    fn loaded_phase(rt: &tokio::runtime::Runtime, target: IpAddr, direction: Direction) -> (Option<f64>, LoadSummary) {
+        let load = LoadGenerator::start(direction, LOAD_STREAMS);
+        let samples = rt.block_on(Self::sample(
+            target,
+            Duration::from_secs(LOADED_PHASE_SECS),
+            Duration::from_millis(LOAD_RAMP_UP_MS),
+        ));
+        (median(samples), load.stop())
+    }
+
+    /// Pings `target` repeatedly for `duration`, keeping the response times seen after `skip`
+    async fn sample(target: IpAddr, duration: Duration, skip: Duration) -> Vec<f64> {
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        while started.elapsed() < duration {
+            let result = PingExecutor::execute_ping(target, SystemTime::now(), Instant::now(), None).await;
+            if let Some(response_time) = result.response_time
+                && started.elapsed() >= skip
+            {
+                samples.push(response_time);
+            }
+            tokio::time::sleep(Duration::from_millis(SAMPLE_INTERVAL_MS)).await;
+        }
+        samples
+    }
+
+    /// Uses the monitored target when it resolves, otherwise the internet anchor
+    fn resolve(target: &str) -> Result<IpAddr, String> {
+        if let Ok(ip) = target.parse::<IpAddr>() {
+            return Ok(ip);
+        }
+        if !target.is_empty()
+            && let Some(address) = (target_parser::to_ascii(target).as_str(), 0).to_socket_addrs().ok().and_then(|mut addrs| addrs.next())
+        {
+            return Ok(address.ip());
+        }
+        INTERNET_ANCHOR.parse::<IpAddr>().map_err(|e| e.to_string())
+    }
+}
+
+fn median(mut samples: Vec<f64>) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_by(f64::total_cmp);
+    Some(samples[samples.len() / 2])
+}
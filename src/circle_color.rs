@@ -1,7 +1,39 @@
 use egui::Color32;
+use serde::{Deserialize, Serialize};
 
-const AGE_THRESHOLD_FULL_COLOR: f64 = 35.0;
-const AGE_THRESHOLD_GRAY: f64 = 55.0;
+/// With the original 5-second interval, circles started fading at 35 s and were gray at 55 s
+/// of the 60-second dial; derived timings keep those proportions of the dial's period.
+const FADE_START_FRACTION: f64 = 35.0 / 60.0;
+const FADE_END_FRACTION: f64 = 55.0 / 60.0;
+
+/// Age at which a circle starts fading towards gray, and age at which it is fully gray
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FadeTimings {
+    pub start_secs: u64,
+    pub end_secs: u64,
+}
+
+impl FadeTimings {
+    /// Timings for a dial of `slots` circles of `slot_secs` each probed every `interval_secs`,
+    /// so a circle only turns gray when it should have been refreshed by now
+    pub fn derived(interval_secs: u64, slot_secs: u64, slots: usize) -> Self {
+        let period = (interval_secs.max(slot_secs) * slots as u64) as f64;
+        Self {
+            start_secs: (period * FADE_START_FRACTION).round() as u64,
+            end_secs: (period * FADE_END_FRACTION).round() as u64,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start_secs >= self.end_secs {
+            return Err(format!(
+                "Fade start ({} s) must be earlier than fade end ({} s)",
+                self.start_secs, self.end_secs
+            ));
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub enum CircleColor {
@@ -9,7 +41,13 @@ pub enum CircleColor {
     Green,
     Yellow,
     Orange,
+    /// Beyond the very-slow threshold, close to timing out
+    DarkRed,
     Red,
+    /// Failed while no local interface had a usable address
+    LinkDown,
+    /// Answered, but not as expected
+    Degraded,
 }
 
 impl CircleColor {
@@ -19,24 +57,57 @@ impl CircleColor {
             CircleColor::Green => Color32::GREEN,
             CircleColor::Yellow => Color32::YELLOW,
             CircleColor::Orange => Color32::from_rgb(255, 165, 0),
+            CircleColor::DarkRed => Color32::from_rgb(150, 20, 40),
             CircleColor::Red => Color32::RED,
+            CircleColor::LinkDown => Color32::from_rgb(106, 90, 205),
+            CircleColor::Degraded => Color32::from_rgb(200, 80, 200),
+        }
+    }
+
+    /// Ordering from best to worst, for combining several results into one circle
+    pub fn severity(self) -> u8 {
+        match self {
+            CircleColor::Gray => 0,
+            CircleColor::Green => 1,
+            CircleColor::Yellow => 2,
+            CircleColor::Orange => 3,
+            CircleColor::DarkRed => 4,
+            CircleColor::Degraded => 5,
+            CircleColor::Red => 6,
+            CircleColor::LinkDown => 7,
+        }
+    }
+
+    /// Spoken state for screen readers
+    pub fn description(self) -> &'static str {
+        match self {
+            CircleColor::Gray => "no reply yet",
+            CircleColor::Green => "up",
+            CircleColor::Yellow => "slow",
+            CircleColor::Orange => "very slow",
+            CircleColor::DarkRed => "extremely slow",
+            CircleColor::Red => "down",
+            CircleColor::LinkDown => "link down",
+            CircleColor::Degraded => "degraded",
         }
     }
     
-    pub fn to_color32_with_age(self, elapsed_seconds: f64) -> Color32 {
-        if elapsed_seconds >= AGE_THRESHOLD_GRAY {
+    pub fn to_color32_with_age(self, elapsed_seconds: f64, fade: FadeTimings) -> Color32 {
+        let full_color = fade.start_secs as f64;
+        let gray = fade.end_secs as f64;
+        if elapsed_seconds >= gray {
             return Color32::GRAY;
         }
         
         let base_color = self.to_color32();
         
-        if elapsed_seconds <= AGE_THRESHOLD_FULL_COLOR {
+        if elapsed_seconds <= full_color {
             return base_color;
         }
         
         // Fade from full color to gray over the age threshold range
-        let fade_range = AGE_THRESHOLD_GRAY - AGE_THRESHOLD_FULL_COLOR;
-        let fade_factor = 1.0 - (elapsed_seconds - AGE_THRESHOLD_FULL_COLOR) / fade_range;
+        let fade_range = (gray - full_color).max(1.0);
+        let fade_factor = 1.0 - (elapsed_seconds - full_color) / fade_range;
         let fade_factor = fade_factor.clamp(0.0, 1.0) as f32;
         
         Self::blend_colors(base_color, Color32::GRAY, fade_factor)
@@ -50,12 +121,45 @@ impl CircleColor {
         )
     }
 
-    pub fn from_ping_response(response_time_ms: Option<f64>, green_threshold: u64, yellow_threshold: u64) -> Self {
+    pub fn from_ping_response(
+        response_time_ms: Option<f64>,
+        green_threshold: u64,
+        yellow_threshold: u64,
+        very_slow_threshold: u64,
+    ) -> Self {
         match response_time_ms {
             Some(time) if time < green_threshold as f64 => CircleColor::Green,
             Some(time) if time < yellow_threshold as f64 => CircleColor::Yellow,
-            Some(_) => CircleColor::Orange,
+            Some(time) if time < very_slow_threshold as f64 => CircleColor::Orange,
+            Some(_) => CircleColor::DarkRed,
             None => CircleColor::Red,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derived_timings_keep_the_original_proportions() {
+        assert_eq!(FadeTimings::derived(5, 5, 12), FadeTimings { start_secs: 35, end_secs: 55 });
+        assert_eq!(FadeTimings::derived(1, 5, 12), FadeTimings { start_secs: 35, end_secs: 55 });
+        assert_eq!(FadeTimings::derived(10, 5, 12), FadeTimings { start_secs: 70, end_secs: 110 });
+    }
+
+    #[test]
+    fn fade_must_start_before_it_ends() {
+        assert!(FadeTimings { start_secs: 35, end_secs: 55 }.validate().is_ok());
+        assert!(FadeTimings { start_secs: 55, end_secs: 55 }.validate().is_err());
+    }
+
+    #[test]
+    fn circles_fade_to_gray_between_the_timings() {
+        let fade = FadeTimings { start_secs: 10, end_secs: 20 };
+        assert_eq!(CircleColor::Red.to_color32_with_age(5.0, fade), Color32::RED);
+        assert_eq!(CircleColor::Red.to_color32_with_age(20.0, fade), Color32::GRAY);
+        let halfway = CircleColor::Red.to_color32_with_age(15.0, fade);
+        assert!(halfway != Color32::RED && halfway != Color32::GRAY);
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::circle_color::CircleColor;
+
+/// Seconds covered by one circle of the 12-circle dial at intervals up to this long
+pub const SLOT_SECS: u64 = 5;
+
+/// How several results landing on the same circle are combined into its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotAggregate {
+    /// The worst outcome, so a single loss in a burst stays visible
+    #[default]
+    Worst,
+    /// The most recent outcome
+    Latest,
+    /// The mean latency of the replies; down only when nothing answered
+    Mean,
+}
+
+impl SlotAggregate {
+    pub const ALL: [SlotAggregate; 3] = [SlotAggregate::Worst, SlotAggregate::Latest, SlotAggregate::Mean];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SlotAggregate::Worst => "Worst",
+            SlotAggregate::Latest => "Latest",
+            SlotAggregate::Mean => "Mean",
+        }
+    }
+}
+
+/// Seconds one circle covers when probing every `interval_secs`: one probe per circle at 5 s
+/// and longer intervals, several combined per circle at shorter ones
+pub fn slot_secs(interval_secs: u64) -> u64 {
+    interval_secs.max(SLOT_SECS)
+}
+
+/// Which `slot_secs` window of the Unix timeline `timestamp` falls in
+pub fn window_of(timestamp: SystemTime, slot_secs: u64) -> u64 {
+    timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / slot_secs
+}
+
+/// The circle of a dial of `circles` that `timestamp` lands on
+pub fn circle_of(timestamp: SystemTime, slot_secs: u64, circles: usize) -> usize {
+    (window_of(timestamp, slot_secs) % circles as u64) as usize
+}
+
+/// Spread of the replies that landed in one slot, for drawing variance like Smokeping does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotSpread {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+impl SlotSpread {
+    /// None when nothing in the slot answered
+    pub fn of(response_times: &[f64]) -> Option<Self> {
+        let mut sorted = response_times.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let middle = sorted.len() / 2;
+        let median = match sorted.len() {
+            0 => return None,
+            len if len.is_multiple_of(2) => (sorted[middle - 1] + sorted[middle]) / 2.0,
+            _ => sorted[middle],
+        };
+        Some(Self { min: sorted[0], median, max: sorted[sorted.len() - 1] })
+    }
+}
+
+/// Every result of the current pass over one circle
+#[derive(Debug, Clone, Default)]
+pub struct ClockSlot {
+    /// Which window of the Unix timeline the results belong to
+    window: Option<u64>,
+    outcomes: Vec<(CircleColor, Option<f64>)>,
+}
+
+impl ClockSlot {
+    /// Adds a result, first dropping the ones left from the previous turn of the dial
+    pub fn record(&mut self, timestamp: SystemTime, slot_secs: u64, color: CircleColor, response_time: Option<f64>) {
+        let window = window_of(timestamp, slot_secs);
+        if self.window != Some(window) {
+            self.window = Some(window);
+            self.outcomes.clear();
+        }
+        self.outcomes.push((color, response_time));
+    }
+
+    pub fn clear(&mut self) {
+        self.window = None;
+        self.outcomes.clear();
+    }
+
+    pub fn aggregate(&self, how: SlotAggregate, green_threshold: u64, yellow_threshold: u64, very_slow_threshold: u64) -> CircleColor {
+        let worst = || self.outcomes.iter().map(|(color, _)| *color).max_by_key(|color| color.severity());
+        let color = match how {
+            SlotAggregate::Worst => worst(),
+            SlotAggregate::Latest => self.outcomes.last().map(|(color, _)| *color),
+            SlotAggregate::Mean => {
+                let replies: Vec<f64> = self.outcomes.iter().filter_map(|(_, response_time)| *response_time).collect();
+                if replies.is_empty() {
+                    worst()
+                } else {
+                    let mean = replies.iter().sum::<f64>() / replies.len() as f64;
+                    Some(CircleColor::from_ping_response(Some(mean), green_threshold, yellow_threshold, very_slow_threshold))
+                }
+            }
+        };
+        color.unwrap_or(CircleColor::Gray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn slot(outcomes: &[(CircleColor, Option<f64>)]) -> ClockSlot {
+        let mut slot = ClockSlot::default();
+        for &(color, response_time) in outcomes {
+            slot.record(UNIX_EPOCH, SLOT_SECS, color, response_time);
+        }
+        slot
+    }
+
+    fn aggregate(slot: &ClockSlot, how: SlotAggregate) -> CircleColor {
+        slot.aggregate(how, 50, 100, 200)
+    }
+
+    #[test]
+    fn worst_latest_and_mean() {
+        let slot = slot(&[(CircleColor::Green, Some(10.0)), (CircleColor::Red, None), (CircleColor::Orange, Some(150.0))]);
+        assert!(matches!(aggregate(&slot, SlotAggregate::Worst), CircleColor::Red));
+        assert!(matches!(aggregate(&slot, SlotAggregate::Latest), CircleColor::Orange));
+        // (10 + 150) / 2 = 80 ms, between the green and yellow thresholds
+        assert!(matches!(aggregate(&slot, SlotAggregate::Mean), CircleColor::Yellow));
+    }
+
+    #[test]
+    fn mean_without_replies_falls_back_to_worst() {
+        let slot = slot(&[(CircleColor::Red, None), (CircleColor::LinkDown, None)]);
+        assert!(matches!(aggregate(&slot, SlotAggregate::Mean), CircleColor::LinkDown));
+    }
+
+    #[test]
+    fn empty_or_outdated_slot_is_gray_or_reset() {
+        assert!(matches!(aggregate(&ClockSlot::default(), SlotAggregate::Worst), CircleColor::Gray));
+        let mut slot = slot(&[(CircleColor::Red, None)]);
+        slot.record(UNIX_EPOCH + Duration::from_secs(60), SLOT_SECS, CircleColor::Green, Some(10.0));
+        assert!(matches!(aggregate(&slot, SlotAggregate::Worst), CircleColor::Green));
+    }
+
+    #[test]
+    fn spread_of_replies() {
+        assert_eq!(SlotSpread::of(&[30.0, 10.0, 20.0]), Some(SlotSpread { min: 10.0, median: 20.0, max: 30.0 }));
+        assert_eq!(SlotSpread::of(&[40.0, 10.0, 20.0, 30.0]), Some(SlotSpread { min: 10.0, median: 25.0, max: 40.0 }));
+        assert_eq!(SlotSpread::of(&[7.0]), Some(SlotSpread { min: 7.0, median: 7.0, max: 7.0 }));
+        assert_eq!(SlotSpread::of(&[]), None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use std::io::Read;
+use eframe::egui;
+use crate::ping::PingResult;
+use crate::system_command;
+
+const COMMAND_TIMEOUT_SECS: u64 = 10;
+const POLL_INTERVAL_MS: u64 = 20;
+
+pub struct CommandProbe;
+
+impl CommandProbe {
+    /// Runs `command` through the system shell. Exit code 0 is a success; the last number it prints
+    /// is taken as the response time in ms, otherwise the command's own run time is used.
+    pub fn spawn(command: String, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let result = match Self::run(&command) {
+                Some(response_time) => PingResult::success(timestamp, sent_at, response_time, None),
+                None => PingResult::failure(timestamp, sent_at),
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn run(command: &str) -> Option<f64> {
+        #[cfg(windows)]
+        let mut shell = {
+            let mut shell = system_command::command("cmd");
+            shell.args(["/C", command]);
+            shell
+        };
+        #[cfg(not(windows))]
+        let mut shell = {
+            let mut shell = system_command::command("sh");
+            shell.args(["-c", command]);
+            shell
+        };
+
+        let started = Instant::now();
+        let mut child = shell.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn().ok()?;
+        let mut stdout = child.stdout.take()?;
+        // Read on a separate thread so a chatty command can't block on a full pipe
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stdout.read_to_string(&mut output);
+            output
+        });
+
+        let timeout = Duration::from_secs(COMMAND_TIMEOUT_SECS);
+        let status = loop {
+            match child.try_wait().ok()? {
+                Some(status) => break status,
+                None if started.elapsed() >= timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+                None => thread::sleep(Duration::from_millis(POLL_INTERVAL_MS)),
+            }
+        };
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let output = reader.join().unwrap_or_default();
+
+        status.success().then(|| last_number(&output).unwrap_or(elapsed_ms))
+    }
+}
+
+/// Finds the last decimal number in the output, e.g. 23.5 in "tunnel ok time=23.5ms"
+fn last_number(output: &str) -> Option<f64> {
+    output
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|token| token.trim_matches('.').parse::<f64>().ok())
+        .next_back()
+}
@@ -1,55 +1,714 @@
 use std::path::PathBuf;
 use std::fs;
+use std::io::Write;
+use std::net::IpAddr;
 use serde::{Deserialize, Serialize};
+use crate::agent_hub::AgentEndpoint;
+use crate::app_log::LogLevel;
+use crate::circle_color::FadeTimings;
+use crate::pdf_report::DEFAULT_SLA_PERCENT;
+use crate::power::BatterySaver;
+use crate::metered::MeteredSaver;
+use crate::fullscreen::FullscreenSaver;
+use crate::web_dashboard::DEFAULT_DASHBOARD_PORT;
+use crate::attention::AttentionSettings;
+use crate::grafana::GrafanaSettings;
+use crate::paging::PagingSettings;
+use crate::ntfy::NtfySettings;
+use crate::pushover::PushoverSettings;
+use crate::fallback_chain::{self, FallbackChains};
+use crate::clock_slot::SlotAggregate;
+use crate::recent_targets::RecentTarget;
+use crate::remote_agent::DEFAULT_AGENT_PORT;
+use crate::targets::TargetEntry;
+use crate::target_style::TargetStyles;
+use crate::schedule::TimeWindow;
+use crate::snmp_probe::SnmpSettings;
+use crate::target_parser;
+use crate::http_probe::{self, HttpProxySettings};
+use crate::tcp_probe;
+use crate::timestamp_format::TimestampFormat;
+use crate::wake_on_lan;
 
-#[derive(Debug, Serialize, Deserialize)]
+const CONFIG_DIR_NAME: &str = "PingMonitor";
+const CONFIG_FILE_NAME: &str = "config.json";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER_FILE: &str = "portable.txt";
+const INVALID_CONFIG_EXTENSION: &str = "json.invalid";
+const BACKUP_CONFIG_EXTENSION: &str = "json.bak";
+const TEMP_CONFIG_EXTENSION: &str = "json.tmp";
+/// Schema version written to config files; bump it and add a step to `migrate` when a
+/// setting is renamed or changes meaning
+pub const CONFIG_VERSION: u32 = 2;
+const MAX_HOSTNAME_LENGTH: usize = 253;
+pub const MAX_INTERVAL_SECS: u64 = 3600;
+/// A week; longer experiments can simply be stopped by hand
+pub const MAX_AUTO_STOP_MINS: u64 = 7 * 24 * 60;
+const ENV_PREFIX: &str = "PINGMONITOR_";
+
+/// How each interval's result is obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeType {
+    #[default]
+    Icmp,
+    /// Runs `command` through the shell; exit code 0 is a success
+    Command,
+    /// Shows results streamed from an agent running on another machine
+    Remote,
+    /// Queries `target` as an NTP server; records the delay and the clock offset
+    Ntp,
+    /// Sends an SNMP GET to `target`, for devices that drop ICMP
+    Snmp,
+    /// Connects to `target` as host:port, optionally checking the banner against `tcp_expect`
+    Tcp,
+    /// Asks `target` as a DNS server for the address of its target list entry's query name
+    Dns,
+    /// Times a GET of `target` as an http(s) URL; any response counts as up
+    Http,
+}
+
+impl ProbeType {
+    pub const ALL: [ProbeType; 8] = [
+        ProbeType::Icmp,
+        ProbeType::Command,
+        ProbeType::Remote,
+        ProbeType::Ntp,
+        ProbeType::Snmp,
+        ProbeType::Tcp,
+        ProbeType::Dns,
+        ProbeType::Http,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProbeType::Icmp => "ICMP ping",
+            ProbeType::Command => "Command",
+            ProbeType::Remote => "Remote agent",
+            ProbeType::Ntp => "NTP server",
+            ProbeType::Snmp => "SNMP GET",
+            ProbeType::Tcp => "TCP connect",
+            ProbeType::Dns => "DNS query",
+            ProbeType::Http => "HTTP GET",
+        }
+    }
+}
+
+/// What the bottom of the monitor tab shows for recent probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MainView {
+    #[default]
+    Clock,
+    /// One row per probe with the exact figures
+    List,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Schema version the file was written with; files from before versioning count as 1
+    pub version: u32,
+    pub probe: ProbeType,
     pub target: String,
+    /// Shell command run by the command probe
+    pub command: String,
     pub green_threshold: u64,
     pub yellow_threshold: u64,
+    /// Responses at or above this are shown in dark red instead of orange
+    pub very_slow_threshold: u64,
+    pub interval_secs: u64,
+    /// Clock offset reported by the NTP probe beyond which an alert is shown
+    pub ntp_offset_threshold_ms: u64,
+    /// Version, credentials and OID used by the SNMP probe
+    pub snmp: SnmpSettings,
+    /// How a circle is colored when several results land on it
+    pub slot_aggregate: SlotAggregate,
+    /// When circles fade to gray; None derives the timings from the interval
+    pub fade_timings: Option<FadeTimings>,
+    /// Most probes the app waits on at once, across the main target, comparison and target list
+    pub max_in_flight_probes: usize,
+    /// Most probes started towards any one target per second
+    pub probe_rate_limit: usize,
+    /// Regex the TCP probe expects in the first bytes the server sends; empty accepts any connection
+    pub tcp_expect: String,
+    /// SOCKS5 proxy ("host:port", e.g. an `ssh -D` forward) the TCP probe connects through; empty connects directly
+    pub tcp_proxy: String,
+    /// Proxy the HTTP probe goes through
+    pub http_proxy: HttpProxySettings,
+    /// Recurring windows whose failures are kept out of statistics and never open outages
+    pub maintenance_windows: Vec<TimeWindow>,
+    /// System-wide shortcut that starts/stops monitoring; empty disables it
+    pub toggle_hotkey: String,
+    /// System-wide shortcut that brings the window to front; empty disables it
+    pub show_hotkey: String,
+    /// Play a tone per probe whose pitch follows the response time
+    pub sonar_enabled: bool,
+    /// Have screen readers announce when the target goes up, slow or down
+    pub announce_changes: bool,
+    /// Most verbose level kept for the in-app log console
+    pub log_level: LogLevel,
+    /// Agent to view with the remote probe, as host or host:port
+    pub remote_agent: String,
+    /// Shared secret between agent and viewer
+    pub agent_token: String,
+    /// Port the agent listens on when started with `--agent`
+    pub agent_port: u16,
+    /// Agents shown side by side in the aggregated view
+    pub agents: Vec<AgentEndpoint>,
+    /// Second target pinged alongside the main one for the comparison chart; empty when off
+    pub compare_target: String,
+    /// Hosts probed as well while a target fails, keyed by target
+    pub fallback_chains: FallbackChains,
+    /// Interfaces the main target is also pinged through, for side-by-side comparison
+    pub probe_interfaces: Vec<String>,
+    /// Previously monitored targets, most recent first
+    pub recent_targets: Vec<RecentTarget>,
+    /// Additional hosts pinged alongside the main target
+    pub target_list: Vec<TargetEntry>,
+    /// Per-target settings (MAC, monitoring hours, DNS query name) of main targets that aren't in
+    /// the target list; kept here so setting them doesn't also watch the target in the list
+    pub target_settings: Vec<TargetEntry>,
+    /// Show the target list as a sortable table instead of grouped rows
+    pub target_table_view: bool,
+    /// Display names, icons and accent colors chosen for targets
+    pub target_styles: TargetStyles,
+    /// Availability promised by the provider, which the PDF report measures against; percent
+    pub sla_percent: f64,
+    /// Slower probing and redraws while the laptop runs on battery
+    pub battery_saver: BatterySaver,
+    /// Slower probing and paused speed tests on metered connections
+    pub metered_saver: MeteredSaver,
+    /// Slower or paused probing while a full-screen game or video is in front
+    pub fullscreen_saver: FullscreenSaver,
+    /// Look up the gateway and DNS servers again after network changes
+    pub rediscover_gateway: bool,
+    /// Raise an alert, not only a log entry, when the target's name resolves to a new address
+    pub alert_on_dns_change: bool,
+    /// Serve a read-only status page to browsers
+    pub web_dashboard_enabled: bool,
+    pub web_dashboard_port: u16,
+    /// Let other devices on the LAN open the dashboard; otherwise only this computer can
+    pub web_dashboard_lan: bool,
+    /// Asked for in the dashboard's address; required when it is open to the LAN
+    pub web_dashboard_token: String,
+    /// PEM certificate chain and key to serve the dashboard over HTTPS when it is open to the LAN;
+    /// both empty serves plain HTTP
+    pub web_dashboard_tls_cert: String,
+    pub web_dashboard_tls_key: String,
+    /// How timestamps are written in lists and CSV exports
+    pub timestamp_format: TimestampFormat,
+    pub main_view: MainView,
+    /// Only the clock face and last reply are shown
+    pub focus_mode: bool,
+    /// End monitoring sessions after `auto_stop_mins` and summarize them
+    pub auto_stop_enabled: bool,
+    pub auto_stop_mins: u64,
+    /// Taskbar flash or dock bounce per alert severity while the window is in the background
+    pub attention: AttentionSettings,
+    /// Outages, route changes and notes posted to Grafana's annotations API
+    pub grafana: GrafanaSettings,
+    /// Pages on-call through PagerDuty or Opsgenie during outages
+    pub paging: PagingSettings,
+    /// Phone push alerts through ntfy
+    pub ntfy: NtfySettings,
+    /// Phone alerts through Pushover, with a priority per alert severity
+    pub pushover: PushoverSettings,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
+            probe: ProbeType::Icmp,
             target: "8.8.8.8".to_string(),
+            command: String::new(),
             green_threshold: 100,
             yellow_threshold: 200,
+            very_slow_threshold: 1000,
+            interval_secs: 5,
+            ntp_offset_threshold_ms: 100,
+            snmp: SnmpSettings::default(),
+            tcp_expect: String::new(),
+            tcp_proxy: String::new(),
+            http_proxy: HttpProxySettings::default(),
+            slot_aggregate: SlotAggregate::Worst,
+            fade_timings: None,
+            max_in_flight_probes: 32,
+            probe_rate_limit: 5,
+            maintenance_windows: Vec::new(),
+            toggle_hotkey: "CmdOrCtrl+Shift+Alt+P".to_string(),
+            show_hotkey: "CmdOrCtrl+Shift+Alt+M".to_string(),
+            sonar_enabled: false,
+            announce_changes: false,
+            log_level: LogLevel::Info,
+            remote_agent: String::new(),
+            agent_token: String::new(),
+            agent_port: DEFAULT_AGENT_PORT,
+            agents: Vec::new(),
+            compare_target: String::new(),
+            fallback_chains: FallbackChains::new(),
+            probe_interfaces: Vec::new(),
+            recent_targets: Vec::new(),
+            target_list: Vec::new(),
+            target_settings: Vec::new(),
+            target_table_view: false,
+            target_styles: TargetStyles::new(),
+            sla_percent: DEFAULT_SLA_PERCENT,
+            battery_saver: BatterySaver::default(),
+            metered_saver: MeteredSaver::default(),
+            fullscreen_saver: FullscreenSaver::default(),
+            rediscover_gateway: true,
+            alert_on_dns_change: false,
+            web_dashboard_enabled: false,
+            web_dashboard_port: DEFAULT_DASHBOARD_PORT,
+            web_dashboard_lan: false,
+            web_dashboard_token: String::new(),
+            web_dashboard_tls_cert: String::new(),
+            web_dashboard_tls_key: String::new(),
+            timestamp_format: TimestampFormat::Local,
+            main_view: MainView::Clock,
+            focus_mode: false,
+            auto_stop_enabled: false,
+            auto_stop_mins: 120,
+            attention: AttentionSettings::default(),
+            grafana: GrafanaSettings::default(),
+            paging: PagingSettings::default(),
+            ntfy: NtfySettings::default(),
+            pushover: PushoverSettings::default(),
         }
     }
 }
 
 impl AppConfig {
-    pub fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let config_dir = dirs::config_dir()
-            .ok_or("Could not find config directory")?
-            .join("PingMonitor");
-        
+    /// Portable mode is enabled by the `--portable` flag or a `portable.txt` file next to the executable
+    pub fn is_portable() -> bool {
+        std::env::args().any(|arg| arg == PORTABLE_FLAG)
+            || Self::executable_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER_FILE).exists())
+    }
+
+    fn executable_dir() -> Option<PathBuf> {
+        std::env::current_exe()
+            .ok()?
+            .parent()
+            .map(PathBuf::from)
+    }
+
+    /// Directory for config and data files: beside the executable in portable mode,
+    /// otherwise the platform config directory
+    pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let config_dir = if Self::is_portable() {
+            Self::executable_dir().ok_or("Could not find executable directory")?
+        } else {
+            dirs::config_dir()
+                .ok_or("Could not find config directory")?
+                .join(CONFIG_DIR_NAME)
+        };
+
         fs::create_dir_all(&config_dir)?;
-        Ok(config_dir.join("config.json"))
+        Ok(config_dir)
+    }
+
+    pub fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Self::get_config_dir()?.join(CONFIG_FILE_NAME))
+    }
+
+    /// Loads the config file and returns it together with any problems reading it.
+    /// Value problems are reported by `validate`; a file that cannot be parsed is preserved as `config.json.invalid`
+    /// instead of being overwritten, and the previous version in `config.json.bak` is used if it can be read.
+    pub fn load() -> (Self, Vec<String>) {
+        let path = match Self::get_config_path() {
+            Ok(path) => path,
+            Err(e) => return (Self::default(), vec![format!("Could not locate config directory: {e}")]),
+        };
+
+        if !path.exists() {
+            return (Self::default(), Vec::new());
+        }
+
+        match Self::read(&path) {
+            Ok((config, notes)) => (config, notes),
+            Err(e) => {
+                let invalid_path = path.with_extension(INVALID_CONFIG_EXTENSION);
+                let invalid_note = match fs::copy(&path, &invalid_path) {
+                    Ok(_) => format!("the original was kept as {}", invalid_path.display()),
+                    Err(copy_err) => format!("the original could not be backed up ({copy_err})"),
+                };
+                let backup_path = path.with_extension(BACKUP_CONFIG_EXTENSION);
+                match Self::read(&backup_path) {
+                    Ok((config, mut notes)) => {
+                        let message = format!(
+                            "Could not read {}: {e}. Using the previous version from {}; {invalid_note}.",
+                            path.display(),
+                            backup_path.display()
+                        );
+                        notes.insert(0, message);
+                        (config, notes)
+                    }
+                    Err(_) => {
+                        let message = format!("Could not read {}: {e}. Using defaults; {invalid_note}.", path.display());
+                        (Self::default(), vec![message])
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses a config file, upgrading it from older schema versions
+    fn read(path: &std::path::Path) -> Result<(Self, Vec<String>), String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let object = value.as_object_mut().ok_or("the file does not contain a JSON object")?;
+        let version = object.get("version").and_then(serde_json::Value::as_u64).unwrap_or(1) as u32;
+
+        let mut notes = Vec::new();
+        if version > CONFIG_VERSION {
+            notes.push(format!(
+                "{} was written by a newer version of Ping Monitor (config version {version}); settings it doesn't know are ignored",
+                path.display()
+            ));
+        }
+        for from in version..CONFIG_VERSION {
+            migrate(object, from);
+        }
+        object.insert("version".to_string(), CONFIG_VERSION.into());
+
+        let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok((config, notes))
+    }
+
+    /// Applies `PINGMONITOR_*` environment variables on top of the file config.
+    /// Returns messages for variables whose values could not be parsed.
+    pub fn apply_env_overrides(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(target) = env_override("TARGET") {
+            self.target = target;
+        }
+
+        for (name, field) in [
+            ("INTERVAL", &mut self.interval_secs),
+            ("GREEN_THRESHOLD", &mut self.green_threshold),
+            ("YELLOW_THRESHOLD", &mut self.yellow_threshold),
+        ] {
+            if let Some(value) = env_override(name) {
+                match value.trim().parse::<u64>() {
+                    Ok(parsed) => *field = parsed,
+                    Err(_) => errors.push(format!("Ignoring {ENV_PREFIX}{name}={value}: expected a whole number")),
+                }
+            }
+        }
+
+        errors
     }
 
-    pub fn load() -> Self {
-        Self::get_config_path()
-            .ok()
-            .and_then(|path| {
-                if path.exists() {
-                    fs::read_to_string(&path)
-                        .ok()
-                        .and_then(|content| serde_json::from_str::<AppConfig>(&content).ok())
-                } else {
-                    None
+    /// Checks the values for consistency and returns actionable error messages
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        match self.probe {
+            ProbeType::Icmp | ProbeType::Ntp => {
+                if let Err(e) = validate_target(&self.target) {
+                    errors.push(e);
+                }
+            }
+            ProbeType::Snmp => {
+                if let Err(e) = validate_target(&self.target) {
+                    errors.push(e);
+                }
+                if let Err(e) = self.snmp.validate() {
+                    errors.push(e);
+                }
+            }
+            ProbeType::Tcp => {
+                if let Err(e) = tcp_probe::validate_address(&self.target) {
+                    errors.push(e);
+                }
+                if let Err(e) = tcp_probe::parse_expect(&self.tcp_expect) {
+                    errors.push(e);
+                }
+                if !self.tcp_proxy.trim().is_empty() && tcp_probe::validate_address(&self.tcp_proxy).is_err() {
+                    errors.push("SOCKS5 proxy needs host:port, e.g. localhost:1080".to_string());
+                }
+            }
+            ProbeType::Dns => {
+                if let Err(e) = validate_target(&self.target) {
+                    errors.push(e);
+                }
+            }
+            ProbeType::Http => {
+                if let Err(e) = http_probe::validate_url(&self.target) {
+                    errors.push(e);
+                }
+                if let Err(e) = self.http_proxy.validate() {
+                    errors.push(e);
+                }
+            }
+            ProbeType::Command => {
+                if self.command.trim().is_empty() {
+                    errors.push("Command probe needs a command to run".to_string());
+                }
+            }
+            ProbeType::Remote => {
+                if self.remote_agent.trim().is_empty() {
+                    errors.push("Remote probe needs the agent's address".to_string());
+                }
+                if self.agent_token.trim().is_empty() {
+                    errors.push("Remote probe needs the agent's token".to_string());
+                }
+            }
+        }
+
+        if self.green_threshold == 0 {
+            errors.push("Green threshold must be at least 1 ms".to_string());
+        }
+
+        if self.green_threshold >= self.yellow_threshold {
+            errors.push(format!(
+                "Green threshold ({} ms) must be lower than the yellow threshold ({} ms)",
+                self.green_threshold, self.yellow_threshold
+            ));
+        }
+
+        if self.probe == ProbeType::Ntp && self.ntp_offset_threshold_ms == 0 {
+            errors.push("Clock offset threshold must be at least 1 ms".to_string());
+        }
+
+        if self.max_in_flight_probes == 0 || self.probe_rate_limit == 0 {
+            errors.push("Probe limits must allow at least one probe".to_string());
+        }
+
+        if self.yellow_threshold >= self.very_slow_threshold {
+            errors.push(format!(
+                "Yellow threshold ({} ms) must be lower than the very slow threshold ({} ms)",
+                self.yellow_threshold, self.very_slow_threshold
+            ));
+        }
+
+        if self.interval_secs == 0 || self.interval_secs > MAX_INTERVAL_SECS {
+            errors.push(format!("Ping interval must be between 1 and {MAX_INTERVAL_SECS} seconds"));
+        }
+
+        if !(0.0..=100.0).contains(&self.sla_percent) {
+            errors.push("Agreed availability must be between 0 and 100 %".to_string());
+        }
+
+        if self.battery_saver.interval_secs == 0 || self.battery_saver.interval_secs > MAX_INTERVAL_SECS {
+            errors.push(format!("Ping interval on battery must be between 1 and {MAX_INTERVAL_SECS} seconds"));
+        }
+
+        if self.metered_saver.interval_secs == 0 || self.metered_saver.interval_secs > MAX_INTERVAL_SECS {
+            errors.push(format!("Ping interval on metered connections must be between 1 and {MAX_INTERVAL_SECS} seconds"));
+        }
+
+        if self.fullscreen_saver.interval_secs == 0 || self.fullscreen_saver.interval_secs > MAX_INTERVAL_SECS {
+            errors.push(format!("Ping interval during full-screen apps must be between 1 and {MAX_INTERVAL_SECS} seconds"));
+        }
+        if self.auto_stop_mins == 0 || self.auto_stop_mins > MAX_AUTO_STOP_MINS {
+            errors.push(format!("Session length must be between 1 and {MAX_AUTO_STOP_MINS} minutes"));
+        }
+
+        if let Err(e) = self.grafana.validate() {
+            errors.push(e);
+        }
+        if let Err(e) = self.paging.validate() {
+            errors.push(e);
+        }
+        if let Err(e) = self.ntfy.validate() {
+            errors.push(e);
+        }
+        if let Err(e) = self.pushover.validate() {
+            errors.push(e);
+        }
+
+        if let Some(fade) = &self.fade_timings
+            && let Err(e) = fade.validate()
+        {
+            errors.push(e);
+        }
+
+        if !self.compare_target.trim().is_empty()
+            && let Err(e) = validate_target(&self.compare_target)
+        {
+            errors.push(format!("Comparison target: {e}"));
+        }
+
+        for (target, chain) in &self.fallback_chains {
+            for host in fallback_chain::parse_chain(chain) {
+                if let Err(e) = validate_target(&host) {
+                    errors.push(format!("Fallback \"{host}\" for {target}: {e}"));
+                }
+            }
+        }
+
+        for entry in &self.target_list {
+            if let Err(e) = validate_target(&entry.host) {
+                errors.push(format!("Target list entry \"{}\": {e}", entry.display_name()));
+            }
+        }
+        for entry in self.target_list.iter().chain(&self.target_settings) {
+            if !entry.mac.trim().is_empty()
+                && let Err(e) = wake_on_lan::parse_mac(&entry.mac)
+            {
+                errors.push(format!("Target \"{}\": {e}", entry.display_name()));
+            }
+            if !entry.query_name.trim().is_empty()
+                && let Err(e) = validate_target(&entry.query_name)
+            {
+                errors.push(format!("Target \"{}\": DNS query name: {e}", entry.display_name()));
+            }
+        }
+
+        if self.web_dashboard_lan && self.web_dashboard_token.trim().is_empty() {
+            errors.push("Web dashboard needs a token to be opened to the LAN".to_string());
+        }
+        if self.web_dashboard_lan && self.web_dashboard_tls_cert.trim().is_empty() != self.web_dashboard_tls_key.trim().is_empty() {
+            errors.push("Web dashboard HTTPS needs both a certificate and a key file".to_string());
+        }
+
+        for agent in &self.agents {
+            if agent.name.trim().is_empty() || agent.address.trim().is_empty() || agent.token.trim().is_empty() {
+                errors.push(format!("Agent \"{}\" needs a name, an address and a token", agent.name));
+            }
+        }
+
+        for window in &self.maintenance_windows {
+            if let Err(e) = window.validate() {
+                errors.push(format!("Maintenance window \"{window}\": {e}"));
+            }
+        }
+
+        for entry in self.target_list.iter().chain(&self.target_settings) {
+            for window in &entry.monitoring_hours {
+                if let Err(e) = window.validate() {
+                    errors.push(format!("Monitoring hours \"{window}\" of {}: {e}", entry.display_name()));
                 }
-            })
-            .unwrap_or_else(|| {
-                AppConfig::default()
-            })
+            }
+        }
+
+        errors
     }
 
+    /// Writes to a temporary file and renames it over the config, so a crash mid-write leaves
+    /// the old file intact; the file being replaced is kept as `config.json.bak`
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let path = Self::get_config_path()?;
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
+        let temp_path = path.with_extension(TEMP_CONFIG_EXTENSION);
+        {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        // Only a file that still parses is worth keeping as the fallback
+        if Self::read(&path).is_ok() {
+            fs::copy(&path, path.with_extension(BACKUP_CONFIG_EXTENSION))?;
+        }
+        fs::rename(&temp_path, &path)?;
         Ok(())
     }
 }
+
+/// Timestamped file in the downloads folder (falling back to the home directory) for exports
+pub fn export_path(prefix: &str, extension: &str) -> PathBuf {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("{prefix}-{stamp}.{extension}"))
+}
+
+/// Upgrades a config object written with schema version `from` to `from + 1`
+fn migrate(config: &mut serde_json::Map<String, serde_json::Value>, from: u32) {
+    // Files from before versioning predate the very slow threshold; it takes its default unless
+    // the user raised the yellow threshold past it, which would fail validation
+    if from == 1
+        && !config.contains_key("very_slow_threshold")
+        && let Some(yellow) = config.get("yellow_threshold").and_then(serde_json::Value::as_u64)
+    {
+        let very_slow = AppConfig::default().very_slow_threshold.max(yellow * 2);
+        config.insert("very_slow_threshold".to_string(), very_slow.into());
+    }
+}
+
+fn env_override(name: &str) -> Option<String> {
+    std::env::var(format!("{ENV_PREFIX}{name}"))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Appends `default_port` unless the address already has one; bare IPv6 addresses get brackets
+pub fn with_default_port(address: &str, default_port: u16) -> String {
+    let address = &target_parser::to_ascii(address);
+    let has_port = match address.rsplit_once(':') {
+        // "host:port" or "[v6]:port"; a bare IPv6 address has several colons and no brackets
+        Some((host, port)) => port.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']')),
+        None => false,
+    };
+    if has_port {
+        address.to_string()
+    } else if address.contains(':') && !address.starts_with('[') {
+        format!("[{address}]:{default_port}")
+    } else {
+        format!("{address}:{default_port}")
+    }
+}
+
+/// Validates that a target is an IP address or a plausible hostname (optionally with a port)
+pub fn validate_target(target: &str) -> Result<(), String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("Target must not be empty; enter an IP address or hostname".to_string());
+    }
+
+    if target.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+    // Covers bracketed IPv6 with a port and zoned link-local addresses such as fe80::1%eth0
+    if target_parser::parse_target(target).is_some_and(|parsed| parsed.host.parse::<IpAddr>().is_ok()) {
+        return Ok(());
+    }
+
+    let host = target.split(':').next().unwrap_or(target);
+    let ascii_host = if host.is_ascii() {
+        host.to_string()
+    } else {
+        idna::domain_to_ascii(host).map_err(|_| format!("'{host}' is not a valid internationalized domain name"))?
+    };
+    if ascii_host.len() > MAX_HOSTNAME_LENGTH {
+        return Err(format!("Hostname is longer than {MAX_HOSTNAME_LENGTH} characters"));
+    }
+
+    if let Some(c) = host.chars().find(|c| !(c.is_alphanumeric() || *c == '.' || *c == '-')) {
+        return Err(format!("Target contains an invalid character '{c}'; use an IP address or hostname"));
+    }
+
+    let host = host.strip_suffix('.').unwrap_or(host);
+    if host.split('.').any(|label| label.is_empty() || label.starts_with('-') || label.ends_with('-')) {
+        return Err(format!("'{host}' is not a valid hostname"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn upgrade(config: serde_json::Value, from: u32) -> serde_json::Value {
+        let mut config = config.as_object().cloned().unwrap_or_default();
+        for version in from..CONFIG_VERSION {
+            migrate(&mut config, version);
+        }
+        config.into()
+    }
+
+    #[test]
+    fn unversioned_file_gets_a_very_slow_threshold_above_yellow() {
+        let default = AppConfig::default().very_slow_threshold;
+        assert_eq!(upgrade(json!({ "yellow_threshold": 100 }), 1)["very_slow_threshold"], json!(default.max(200)));
+        assert_eq!(upgrade(json!({ "yellow_threshold": default }), 1)["very_slow_threshold"], json!(default * 2));
+        assert_eq!(upgrade(json!({ "very_slow_threshold": 900 }), 1)["very_slow_threshold"], json!(900));
+    }
+}
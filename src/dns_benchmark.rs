@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use eframe::egui;
+use crate::dns_probe::{self, DNS_PORT};
+use crate::network_info;
+
+/// Each name is asked this often; the first answer may be uncached, so the median is what counts
+const ROUNDS: usize = 3;
+/// Popular names that every resolver should answer quickly from its cache
+const QUERY_NAMES: [&str; 5] = ["example.com", "wikipedia.org", "github.com", "cloudflare.com", "google.com"];
+
+/// Well-known public resolvers compared against the system's own
+pub const PUBLIC_RESOLVERS: [(&str, &str); 6] = [
+    ("Cloudflare", "1.1.1.1"),
+    ("Google", "8.8.8.8"),
+    ("Quad9", "9.9.9.9"),
+    ("OpenDNS", "208.67.222.222"),
+    ("AdGuard", "94.140.14.14"),
+    ("Control D", "76.76.2.0"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolver {
+    pub name: String,
+    pub address: IpAddr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverScore {
+    pub resolver: Resolver,
+    /// Latency of every answered query in ms
+    pub latencies: Vec<f64>,
+    pub failures: usize,
+}
+
+impl ResolverScore {
+    pub fn queries(&self) -> usize {
+        self.latencies.len() + self.failures
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        let mut sorted = self.latencies.clone();
+        sorted.sort_by(f64::total_cmp);
+        match sorted.len() {
+            0 => None,
+            len if len % 2 == 0 => Some((sorted[len / 2 - 1] + sorted[len / 2]) / 2.0),
+            len => Some(sorted[len / 2]),
+        }
+    }
+
+    pub fn failure_percent(&self) -> f64 {
+        self.failures as f64 * 100.0 / self.queries().max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BenchmarkUpdate {
+    Scored(ResolverScore),
+    Finished,
+}
+
+/// The system's resolvers, the public ones, and any extra addresses typed by the user
+/// (separated by commas or spaces), each listed once
+pub fn resolvers_to_test(extra: &str) -> Result<Vec<Resolver>, String> {
+    let mut resolvers: Vec<Resolver> = Vec::new();
+    let mut add = |name: &str, address: IpAddr| {
+        if !resolvers.iter().any(|resolver| resolver.address == address) {
+            resolvers.push(Resolver { name: name.to_string(), address });
+        }
+    };
+    for address in network_info::dns_servers() {
+        add("System", address);
+    }
+    for (name, address) in PUBLIC_RESOLVERS {
+        if let Ok(address) = address.parse() {
+            add(name, address);
+        }
+    }
+    for address in extra.split([',', ' ']).filter(|address| !address.is_empty()) {
+        let parsed = address.parse().map_err(|_| format!("\"{address}\" is not an IP address"))?;
+        add("Custom", parsed);
+    }
+    Ok(resolvers)
+}
+
+/// Most reliable first, then fastest by median latency; resolvers that never answered go last
+pub fn rank(scores: &mut [ResolverScore]) {
+    scores.sort_by(|a, b| match (a.median(), b.median()) {
+        (Some(a_median), Some(b_median)) => a.failures.cmp(&b.failures).then(a_median.total_cmp(&b_median)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+/// Writes ranked scores as CSV, one row per resolver with its latencies in ms
+pub fn export_csv(path: &Path, scores: &[ResolverScore]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "rank,name,address,median_ms,fastest_ms,slowest_ms,failed_queries,queries")?;
+    for (index, score) in scores.iter().enumerate() {
+        let format_ms = |ms: Option<f64>| ms.map(|ms| format!("{ms:.3}")).unwrap_or_default();
+        let fastest = score.latencies.iter().copied().reduce(f64::min);
+        let slowest = score.latencies.iter().copied().reduce(f64::max);
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            index + 1,
+            score.resolver.name,
+            score.resolver.address,
+            format_ms(score.median()),
+            format_ms(fastest),
+            format_ms(slowest),
+            score.failures,
+            score.queries(),
+        )?;
+    }
+    writer.flush()
+}
+
+pub struct DnsBenchmark;
+
+impl DnsBenchmark {
+    /// Queries every resolver concurrently (each one's queries in sequence) and reports each
+    /// score as soon as that resolver is done
+    pub fn spawn(resolvers: Vec<Resolver>, sender: mpsc::Sender<BenchmarkUpdate>, repaint: egui::Context) {
+        thread::spawn(move || {
+            thread::scope(|scope| {
+                for resolver in resolvers {
+                    let sender = sender.clone();
+                    let repaint = repaint.clone();
+                    scope.spawn(move || {
+                        let _ = sender.send(BenchmarkUpdate::Scored(Self::score(resolver)));
+                        repaint.request_repaint();
+                    });
+                }
+            });
+            let _ = sender.send(BenchmarkUpdate::Finished);
+            repaint.request_repaint();
+        });
+    }
+
+    fn score(resolver: Resolver) -> ResolverScore {
+        let mut score = ResolverScore { resolver, latencies: Vec::new(), failures: 0 };
+        for _ in 0..ROUNDS {
+            for name in QUERY_NAMES {
+                match dns_probe::query(SocketAddr::new(score.resolver.address, DNS_PORT), name) {
+                    Ok(latency) => score.latencies.push(latency),
+                    Err(e) => {
+                        log::debug!("{} did not answer {name}: {e}", score.resolver.address);
+                        score.failures += 1;
+                    }
+                }
+            }
+        }
+        score
+    }
+}
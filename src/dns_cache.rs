@@ -1,11 +1,14 @@
 use std::net::IpAddr;
-use std::time::{Duration, SystemTime};
-use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use std::collections::{HashMap, VecDeque};
+
+/// Distinct answers remembered per name; round-robin and CDN names rotate through a few addresses
+const RECENT_ANSWERS: usize = 8;
 
 #[derive(Debug, Clone)]
 pub struct DnsCacheEntry {
     ip_address: IpAddr,
-    cached_at: SystemTime,
+    cached_at: Instant,
     ttl: Duration,
 }
 
@@ -13,15 +16,13 @@ impl DnsCacheEntry {
     pub fn new(ip_address: IpAddr, ttl_seconds: u64) -> Self {
         Self {
             ip_address,
-            cached_at: SystemTime::now(),
+            cached_at: Instant::now(),
             ttl: Duration::from_secs(ttl_seconds),
         }
     }
     
     pub fn is_expired(&self) -> bool {
-        SystemTime::now()
-            .duration_since(self.cached_at)
-            .map_or(true, |elapsed| elapsed > self.ttl)
+        self.cached_at.elapsed() > self.ttl
     }
 
     pub fn ip_address(&self) -> IpAddr {
@@ -29,14 +30,27 @@ impl DnsCacheEntry {
     }
 }
 
+/// How a fresh answer compares with the last one for the same name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnswerChange {
+    First,
+    Unchanged,
+    /// Resolved to an address not among the recent answers, e.g. after a failover; holds the last one
+    Changed(IpAddr),
+}
+
 pub struct DnsCache {
     cache: HashMap<String, DnsCacheEntry>,
+    /// Recent distinct answers per name, newest last, kept past expiry and `clear` so changes
+    /// across them are noticed
+    recent_answers: HashMap<String, VecDeque<IpAddr>>,
 }
 
 impl DnsCache {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            recent_answers: HashMap::new(),
         }
     }
 
@@ -44,14 +58,31 @@ impl DnsCache {
         self.cache.get(hostname)
     }
 
-    pub fn insert(&mut self, hostname: String, entry: DnsCacheEntry) {
+    pub fn insert(&mut self, hostname: String, entry: DnsCacheEntry) -> AnswerChange {
+        let ip = entry.ip_address();
+        let answers = self.recent_answers.entry(hostname.clone()).or_default();
+        let change = match answers.back().copied() {
+            None => AnswerChange::First,
+            Some(_) if answers.contains(&ip) => AnswerChange::Unchanged,
+            Some(previous) => AnswerChange::Changed(previous),
+        };
+        answers.retain(|answer| *answer != ip);
+        answers.push_back(ip);
+        if answers.len() > RECENT_ANSWERS {
+            answers.pop_front();
+        }
         self.cache.insert(hostname, entry);
+        change
     }
 
     pub fn remove(&mut self, hostname: &str) {
         self.cache.remove(hostname);
     }
 
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
     pub fn get_valid_ip(&self, hostname: &str) -> Option<IpAddr> {
         self.get(hostname)
             .filter(|entry| !entry.is_expired())
@@ -59,10 +90,43 @@ impl DnsCache {
     }
 
     pub fn clean_expired(&mut self, hostname: &str) {
-        if let Some(entry) = self.get(hostname) {
-            if entry.is_expired() {
-                self.remove(hostname);
-            }
+        if self.get(hostname).is_some_and(DnsCacheEntry::is_expired) {
+            self.remove(hostname);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(cache: &mut DnsCache, ip: [u8; 4]) -> AnswerChange {
+        cache.insert("example.com".to_string(), DnsCacheEntry::new(IpAddr::from(ip), 60))
+    }
+
+    #[test]
+    fn rotating_answers_are_not_changes() {
+        let mut cache = DnsCache::new();
+        assert_eq!(answer(&mut cache, [192, 0, 2, 1]), AnswerChange::First);
+        assert_eq!(answer(&mut cache, [192, 0, 2, 2]), AnswerChange::Changed(IpAddr::from([192, 0, 2, 1])));
+        assert_eq!(answer(&mut cache, [192, 0, 2, 1]), AnswerChange::Unchanged);
+        assert_eq!(answer(&mut cache, [192, 0, 2, 2]), AnswerChange::Unchanged);
+    }
+
+    #[test]
+    fn new_address_after_rotation_is_a_change() {
+        let mut cache = DnsCache::new();
+        answer(&mut cache, [192, 0, 2, 1]);
+        answer(&mut cache, [192, 0, 2, 2]);
+        answer(&mut cache, [192, 0, 2, 1]);
+        assert_eq!(answer(&mut cache, [198, 51, 100, 7]), AnswerChange::Changed(IpAddr::from([192, 0, 2, 1])));
+    }
+
+    #[test]
+    fn clear_keeps_recent_answers() {
+        let mut cache = DnsCache::new();
+        answer(&mut cache, [192, 0, 2, 1]);
+        cache.clear();
+        assert_eq!(answer(&mut cache, [192, 0, 2, 1]), AnswerChange::Unchanged);
+    }
+}
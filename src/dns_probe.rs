@@ -0,0 +1,113 @@
+use std::io::{self, Error, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use eframe::egui;
+use crate::config;
+use crate::ping::PingResult;
+use crate::target_parser;
+
+pub const DNS_PORT: u16 = 53;
+/// Name asked for when none is configured; any resolver answers it from its cache
+pub const DEFAULT_QUERY_NAME: &str = "example.com";
+const QUERY_TIMEOUT_SECS: u64 = 2;
+const TYPE_A: u16 = 1;
+const CLASS_IN: u16 = 1;
+/// Standard query with recursion desired
+const QUERY_FLAGS: u16 = 0x0100;
+const RCODE_SERVFAIL: u8 = 2;
+const RCODE_REFUSED: u8 = 5;
+
+pub struct DnsProbe;
+
+impl DnsProbe {
+    /// Asks `server` (host or host:port) for the A record of `name`. A server failure or refusal
+    /// counts as a failure, an answer of any other kind (including "no such name") as a success.
+    pub fn spawn(server: String, name: String, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let name = target_parser::to_ascii(&name);
+            let result = match Self::resolve(&server).and_then(|address| Ok((query(address, &name)?, address))) {
+                Ok((response_time, address)) => PingResult::success(timestamp, sent_at, response_time, None).with_target(address.ip()),
+                Err(e) => {
+                    log::debug!("DNS query to {server} failed: {e}");
+                    PingResult::failure(timestamp, sent_at)
+                }
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn resolve(server: &str) -> io::Result<SocketAddr> {
+        config::with_default_port(server, DNS_PORT)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("{server} has no address")))
+    }
+}
+
+/// Sends one A query for `name` to `server` and returns the time until its answer in ms
+pub fn query(server: SocketAddr, name: &str) -> io::Result<f64> {
+    let local: IpAddr = if server.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+    let socket = UdpSocket::bind((local, 0))?;
+    socket.set_read_timeout(Some(Duration::from_secs(QUERY_TIMEOUT_SECS)))?;
+    socket.connect(server)?;
+
+    let id = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos() as u16;
+    let request = build_query(id, name);
+    let started = Instant::now();
+    socket.send(&request)?;
+
+    let mut response = [0u8; 512];
+    loop {
+        let len = socket.recv(&mut response)?;
+        // Anything that isn't the answer to this query is skipped; the read timeout ends the wait
+        if len < 12 || u16::from_be_bytes([response[0], response[1]]) != id {
+            continue;
+        }
+        let latency = started.elapsed().as_secs_f64() * 1000.0;
+        return match response[3] & 0x0f {
+            RCODE_SERVFAIL | RCODE_REFUSED => Err(Error::other(format!("answered with error code {}", response[3] & 0x0f))),
+            _ => Ok(latency),
+        };
+    }
+}
+
+fn build_query(id: u16, name: &str) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(12 + name.len() + 6);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&QUERY_FLAGS.to_be_bytes());
+    // One question, no answer, authority or additional records
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&TYPE_A.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_encodes_header_and_question() {
+        let packet = build_query(0xabcd, "www.example.com.");
+        let mut expected = vec![0xab, 0xcd, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        expected.extend_from_slice(b"\x03www\x07example\x03com\x00");
+        expected.extend_from_slice(&[0, 1, 0, 1]);
+        assert_eq!(packet, expected);
+    }
+
+    #[test]
+    fn trailing_dot_is_optional() {
+        assert_eq!(build_query(1, DEFAULT_QUERY_NAME), build_query(1, "example.com."));
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::SystemTime;
+use crate::timestamp_format::TimestampFormat;
+
+const MAX_EVENTS: usize = 500;
+
+/// What part of the app an event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Monitoring,
+    Dns,
+    Route,
+    Sleep,
+    Network,
+    Alert,
+}
+
+impl EventKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Monitoring => "monitoring",
+            EventKind::Dns => "dns",
+            EventKind::Route => "route",
+            EventKind::Sleep => "sleep",
+            EventKind::Network => "network",
+            EventKind::Alert => "alert",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggedEvent {
+    pub time: SystemTime,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+/// Chronological record of everything that happens besides the probe results themselves
+#[derive(Default)]
+pub struct EventLog {
+    pub events: VecDeque<LoggedEvent>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, kind: EventKind, message: impl Into<String>) {
+        self.push_at(SystemTime::now(), kind, message);
+    }
+
+    /// For events dated by their result rather than by now, e.g. in a replayed session
+    pub fn push_at(&mut self, time: SystemTime, kind: EventKind, message: impl Into<String>) {
+        self.events.push_back(LoggedEvent { time, kind, message: message.into() });
+        if self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    /// Moves the events of `other` dated `cutoff` or later to the end of this log
+    pub fn append_since(&mut self, other: EventLog, cutoff: SystemTime) {
+        for event in other.events.into_iter().filter(|event| event.time >= cutoff) {
+            self.push_at(event.time, event.kind, event.message);
+        }
+    }
+
+    pub fn since(&self, cutoff: SystemTime) -> impl Iterator<Item = &LoggedEvent> {
+        self.events.iter().filter(move |event| event.time >= cutoff)
+    }
+
+    /// Writes the events since `cutoff` as CSV (time, kind, message) and returns how many were written
+    pub fn export_csv(&self, path: &Path, cutoff: SystemTime, format: TimestampFormat) -> io::Result<usize> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "time,kind,message")?;
+        let mut count = 0;
+        for event in self.since(cutoff) {
+            let time = format.export(event.time);
+            writeln!(writer, "{time},{},\"{}\"", event.kind.label(), event.message.replace('"', "\"\""))?;
+            count += 1;
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use eframe::egui;
+use crate::ping::PingResult;
+use crate::ping_executor::PingExecutor;
+use crate::probe_limiter::ProbeLimiter;
+
+/// Per target, the hosts to probe as well while it fails, written "vpn-gw, 1.1.1.1". Only the
+/// main target's chain is probed; outages aren't tracked for the watch list.
+pub type FallbackChains = BTreeMap<String, String>;
+
+/// Hosts of a chain in the order given
+pub fn parse_chain(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|host| !host.is_empty()).map(str::to_string).collect()
+}
+
+struct FallbackProbe {
+    host: String,
+    sender: mpsc::Sender<PingResult>,
+    receiver: mpsc::Receiver<PingResult>,
+}
+
+/// Pings the main target's fallback chain, but only while the target is failing, so steady
+/// state costs no extra traffic
+pub struct FallbackProber {
+    probes: Vec<FallbackProbe>,
+}
+
+impl FallbackProber {
+    pub fn new() -> Self {
+        Self { probes: Vec::new() }
+    }
+
+    /// Follows edits to the chain, keeping the order it was written in
+    pub fn sync(&mut self, hosts: &[String]) {
+        if self.probes.iter().map(|probe| &probe.host).eq(hosts) {
+            return;
+        }
+        self.probes = hosts
+            .iter()
+            .map(|host| {
+                let (sender, receiver) = mpsc::channel();
+                FallbackProbe { host: host.clone(), sender, receiver }
+            })
+            .collect();
+    }
+
+    pub fn probe_all(&self, limiter: &mut ProbeLimiter, ctx: &egui::Context) {
+        for probe in &self.probes {
+            if limiter.try_start(&probe.host) {
+                PingExecutor::resolve_and_ping(probe.host.clone(), probe.sender.clone(), ctx.clone());
+            }
+        }
+    }
+
+    /// (host, answered) for every result that came back since the last call
+    pub fn poll(&self, limiter: &mut ProbeLimiter) -> Vec<(String, bool)> {
+        let mut results = Vec::new();
+        for probe in &self.probes {
+            for result in probe.receiver.try_iter() {
+                limiter.finished(&probe.host);
+                results.push((probe.host.clone(), result.success));
+            }
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_keeps_order_and_skips_blanks() {
+        assert_eq!(parse_chain(" vpn-gw , 1.1.1.1,, 2001:db8::1 "), vec!["vpn-gw", "1.1.1.1", "2001:db8::1"]);
+        assert!(parse_chain(" , ").is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::periodic_query::PeriodicQuery;
+
+/// The check shells out on Linux, so it runs rarely enough not to cost the game anything itself
+const FULLSCREEN_CHECK_INTERVAL_SECS: u64 = 15;
+
+/// What probing does while a full-screen game or video has the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FullscreenAction {
+    #[default]
+    Ignore,
+    /// Probe at the saver's longer interval
+    Throttle,
+    /// Stop probing until the full-screen app is gone
+    Pause,
+}
+
+impl FullscreenAction {
+    pub const ALL: [FullscreenAction; 3] = [FullscreenAction::Ignore, FullscreenAction::Throttle, FullscreenAction::Pause];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FullscreenAction::Ignore => "keep probing",
+            FullscreenAction::Throttle => "probe every",
+            FullscreenAction::Pause => "pause probing",
+        }
+    }
+}
+
+/// Keeps out of the way of games and videos; unless set to Ignore, redraws also drop to once a second
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FullscreenSaver {
+    pub action: FullscreenAction,
+    /// Probe interval while throttled; a longer normal interval is kept
+    pub interval_secs: u64,
+}
+
+impl Default for FullscreenSaver {
+    fn default() -> Self {
+        Self { action: FullscreenAction::Ignore, interval_secs: 30 }
+    }
+}
+
+/// Polls whether a full-screen application is in the foreground
+pub type FullscreenMonitor = PeriodicQuery<bool>;
+
+pub fn monitor() -> FullscreenMonitor {
+    PeriodicQuery::new(Duration::from_secs(FULLSCREEN_CHECK_INTERVAL_SECS), platform::query)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::system_command;
+
+    pub fn query() -> Option<bool> {
+        // X11 only: the active window's state list, e.g. "_NET_WM_STATE(ATOM) = _NET_WM_STATE_FULLSCREEN";
+        // Wayland compositors don't tell other clients about their windows
+        let active = system_command::run("xprop", &["-root", "_NET_ACTIVE_WINDOW"]).ok()?;
+        let window = active.split_whitespace().last().filter(|id| id.starts_with("0x"))?;
+        if window == "0x0" {
+            return Some(false);
+        }
+        let state = system_command::run("xprop", &["-id", window, "_NET_WM_STATE"]).ok()?;
+        Some(state.contains("_NET_WM_STATE_FULLSCREEN"))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows_sys::Win32::UI::Shell::{
+        QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN, SHQueryUserNotificationState,
+    };
+
+    pub fn query() -> Option<bool> {
+        let mut state = 0;
+        // SAFETY: the call only writes the state through the pointer it is given
+        if unsafe { SHQueryUserNotificationState(&mut state) } != 0 {
+            return None;
+        }
+        // Busy means a full-screen app; D3D is a full-screen game
+        Some(matches!(state, QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod platform {
+    pub fn query() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+use maxminddb::{Reader, geoip2};
+use crate::config::AppConfig;
+
+/// Databases are read from this folder inside the config directory
+pub const GEOIP_DIR_NAME: &str = "geoip";
+const MMDB_EXTENSION: &str = "mmdb";
+
+#[derive(Debug, Clone, Default)]
+pub struct GeoInfo {
+    pub country_code: Option<String>,
+    pub country_name: Option<String>,
+    pub asn: Option<u32>,
+    pub organization: Option<String>,
+}
+
+impl GeoInfo {
+    fn is_empty(&self) -> bool {
+        self.country_code.is_none() && self.asn.is_none()
+    }
+}
+
+impl fmt::Display for GeoInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (&self.country_code, &self.country_name) {
+            (Some(code), Some(name)) => parts.push(format!("{code} {name}")),
+            (Some(code), None) => parts.push(code.clone()),
+            (None, Some(name)) => parts.push(name.clone()),
+            (None, None) => {}
+        }
+        match (self.asn, &self.organization) {
+            (Some(asn), Some(organization)) => parts.push(format!("AS{asn} {organization}")),
+            (Some(asn), None) => parts.push(format!("AS{asn}")),
+            (None, Some(organization)) => parts.push(organization.clone()),
+            (None, None) => {}
+        }
+        write!(f, "{}", parts.join(" · "))
+    }
+}
+
+/// Offline country/ASN lookups from MaxMind-format databases (GeoLite2 Country/City/ASN, DB-IP lite)
+/// dropped into the `geoip` folder of the config directory
+#[derive(Default)]
+pub struct GeoIp {
+    readers: Vec<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    pub fn load() -> Self {
+        let readers = AppConfig::get_config_dir()
+            .ok()
+            .map(|dir| Self::open_all(&dir.join(GEOIP_DIR_NAME)))
+            .unwrap_or_default();
+        Self { readers }
+    }
+
+    fn open_all(dir: &Path) -> Vec<Reader<Vec<u8>>> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == MMDB_EXTENSION))
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .filter_map(|path| match Reader::open_readfile(&path) {
+                Ok(reader) => Some(reader),
+                Err(e) => {
+                    log::warn!("Failed to open GeoIP database {}: {e}", path.display());
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Merges what every database knows about `ip`; private and local addresses are never looked up.
+    /// A lookup is a short walk of the in-memory search tree, cheap enough to run every frame.
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        if !Self::is_public(ip) {
+            return None;
+        }
+        let mut info = GeoInfo::default();
+        for reader in &self.readers {
+            if let Ok(country) = reader.lookup::<geoip2::Country>(ip)
+                && let Some(country) = country.country
+            {
+                info.country_code = info.country_code.or(country.iso_code.map(str::to_string));
+                info.country_name = info.country_name.or(country
+                    .names
+                    .and_then(|names| names.get("en").map(|name| name.to_string())));
+            }
+            if let Ok(asn) = reader.lookup::<geoip2::Asn>(ip) {
+                info.asn = info.asn.or(asn.autonomous_system_number);
+                info.organization = info.organization.or(asn.autonomous_system_organization.map(str::to_string));
+            }
+        }
+        (!info.is_empty()).then_some(info)
+    }
+
+    fn is_public(ip: IpAddr) -> bool {
+        match ip {
+            // 100.64.0.0/10 is carrier-grade NAT space
+            IpAddr::V4(v4) => !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified()
+                || (v4.octets()[0] == 100 && v4.octets()[1] & 0xc0 == 64)),
+            IpAddr::V6(v6) => !(v6.is_loopback() || v6.is_unspecified()
+                || v6.segments()[0] & 0xfe00 == 0xfc00
+                || v6.segments()[0] & 0xffc0 == 0xfe80),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::http_post::http_post_json;
+
+/// Where outages, route changes and notes are sent as Grafana annotations
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrafanaSettings {
+    pub enabled: bool,
+    /// Base URL of the Grafana server, e.g. "https://grafana.example.com"
+    pub url: String,
+    /// Service account token with permission to write annotations
+    pub api_token: String,
+    /// Comma-separated tags added to every annotation
+    pub tags: String,
+}
+
+impl GrafanaSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let url = self.url.trim();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Grafana URL must start with http:// or https://".to_string());
+        }
+        if self.api_token.trim().is_empty() {
+            return Err("Grafana annotations need an API token".to_string());
+        }
+        Ok(())
+    }
+
+    fn tag_list(&self, kind: &str) -> Vec<String> {
+        self.tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .chain(std::iter::once(kind.to_string()))
+            .collect()
+    }
+
+    /// Posts an annotation in the background; `kind` ("outage", "route", "note") is added as a
+    /// tag so dashboards can filter on it. Failures are only logged.
+    pub fn annotate(&self, kind: &str, time: SystemTime, time_end: Option<SystemTime>, text: String) {
+        let annotation = Annotation { time: epoch_ms(time), time_end: time_end.map(epoch_ms), tags: self.tag_list(kind), text };
+        let url = format!("{}/api/annotations", self.url.trim().trim_end_matches('/'));
+        let authorization = format!("Bearer {}", self.api_token.trim());
+        http_post_json(self.enabled, self.validate(), "Grafana", url, Some(authorization), &annotation);
+    }
+}
+
+/// Body of POST /api/annotations; without a dashboard it is an organization-wide annotation
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Annotation {
+    time: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_end: Option<u64>,
+    tags: Vec<String>,
+    text: String,
+}
+
+fn epoch_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|since| since.as_millis() as u64).unwrap_or(0)
+}
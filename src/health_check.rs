@@ -0,0 +1,89 @@
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Instant, SystemTime};
+use eframe::egui;
+use crate::network_info;
+use crate::ping_executor::PingExecutor;
+
+/// Public anchor used for the "internet" leg of the health check
+pub const INTERNET_ANCHOR: &str = "1.1.1.1";
+
+#[derive(Debug, Clone)]
+pub enum CheckStatus {
+    /// The address to probe could not be determined
+    NotDetected,
+    Reachable { address: IpAddr, response_time: f64 },
+    Unreachable { address: IpAddr },
+}
+
+impl CheckStatus {
+    pub fn is_reachable(&self) -> bool {
+        matches!(self, CheckStatus::Reachable { .. })
+    }
+
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, CheckStatus::Unreachable { .. })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub gateway: CheckStatus,
+    pub dns: CheckStatus,
+    pub internet: CheckStatus,
+}
+
+impl HealthReport {
+    /// Plain-language verdict on where the problem is
+    pub fn summary(&self) -> &'static str {
+        if self.gateway.is_unreachable() {
+            "Your router is not responding: the problem is your local network or Wi-Fi"
+        } else if self.internet.is_unreachable() {
+            if self.gateway.is_reachable() {
+                "Your local network is fine, but the internet is unreachable: likely your ISP"
+            } else {
+                "The internet is unreachable"
+            }
+        } else if self.dns.is_unreachable() {
+            "The internet is reachable but your DNS server is not responding: name lookups may fail"
+        } else if self.internet.is_reachable() {
+            "Gateway, DNS and internet are all reachable"
+        } else {
+            "Could not determine network health"
+        }
+    }
+}
+
+pub struct HealthCheck;
+
+impl HealthCheck {
+    /// Detects the gateway and DNS server, then pings them and the internet anchor concurrently
+    pub fn spawn(sender: mpsc::Sender<HealthReport>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let gateway = network_info::default_gateway();
+            let dns = network_info::dns_servers().into_iter().next();
+            let internet = INTERNET_ANCHOR.parse::<IpAddr>().ok();
+
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let (gateway, dns, internet) = rt.block_on(async {
+                tokio::join!(Self::probe(gateway), Self::probe(dns), Self::probe(internet))
+            });
+
+            let _ = sender.send(HealthReport { gateway, dns, internet });
+            repaint.request_repaint();
+        });
+    }
+
+    async fn probe(address: Option<IpAddr>) -> CheckStatus {
+        let Some(address) = address else {
+            return CheckStatus::NotDetected;
+        };
+
+        let result = PingExecutor::execute_ping(address, SystemTime::now(), Instant::now(), None).await;
+        match result.response_time {
+            Some(response_time) if result.success => CheckStatus::Reachable { address, response_time },
+            _ => CheckStatus::Unreachable { address },
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+use crate::ping::PingResult;
+use crate::rollup::{self, Resolution, Rollup};
+use crate::speed_test::SpeedTestResult;
+
+/// History is kept in this folder inside the config directory, one JSON-lines file per local day
+pub const HISTORY_DIR_NAME: &str = "history";
+const HISTORY_EXTENSION: &str = "jsonl";
+/// Speed tests are few, so they share one JSON-lines file beside the daily probe files
+const SPEED_TESTS_FILE_NAME: &str = "speed_tests.jsonl";
+
+/// One probe as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp_ms: u64,
+    pub target: String,
+    pub success: bool,
+    pub response_ms: Option<f64>,
+    #[serde(default)]
+    pub in_maintenance: bool,
+    /// Set by the NTP probe
+    #[serde(default)]
+    pub clock_offset_ms: Option<f64>,
+    #[serde(default)]
+    pub degraded: bool,
+    #[serde(default)]
+    pub vpn: Option<String>,
+}
+
+impl HistoryRecord {
+    pub fn from_result(result: &PingResult, target: &str) -> Self {
+        Self {
+            timestamp_ms: result.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            target: target.to_string(),
+            success: result.success,
+            response_ms: result.response_time,
+            in_maintenance: result.in_maintenance,
+            clock_offset_ms: result.clock_offset,
+            degraded: result.degraded,
+            vpn: result.vpn.clone(),
+        }
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms)
+    }
+
+    /// Rebuilds a result so stored history can go through the same logic as live probes
+    pub fn to_result(&self, sent_at: Instant) -> PingResult {
+        let mut result = match self.response_ms {
+            Some(response_time) if self.success => PingResult::success(self.timestamp(), sent_at, response_time, None),
+            _ => PingResult::failure(self.timestamp(), sent_at),
+        };
+        result.in_maintenance = self.in_maintenance;
+        result.clock_offset = self.clock_offset_ms;
+        result.degraded = self.degraded;
+        result.vpn = self.vpn.clone();
+        result
+    }
+}
+
+/// Append-only store of every probe result, used for reports that span days or weeks
+#[derive(Clone)]
+pub struct HistoryStore {
+    dir: Option<PathBuf>,
+}
+
+impl HistoryStore {
+    pub fn open() -> Self {
+        let dir = AppConfig::get_config_dir().ok().map(|dir| dir.join(HISTORY_DIR_NAME));
+        Self { dir }
+    }
+
+    fn day_path(&self, day: NaiveDate) -> Option<PathBuf> {
+        let file_name = format!("{}.{HISTORY_EXTENSION}", day.format("%Y-%m-%d"));
+        self.dir.as_ref().map(|dir| dir.join(file_name))
+    }
+
+    fn rollup_path(&self, resolution: Resolution, day: NaiveDate) -> Option<PathBuf> {
+        let file_name = format!("{}.{HISTORY_EXTENSION}", day.format("%Y-%m-%d"));
+        self.dir.as_ref().map(|dir| dir.join(resolution.dir_name()).join(file_name))
+    }
+
+    /// Whether the stored rollups for `day` were written after its last probe was appended
+    fn rollup_current(&self, resolution: Resolution, day: NaiveDate) -> bool {
+        let modified = |path: Option<PathBuf>| path.and_then(|path| fs::metadata(path).ok()?.modified().ok());
+        match (modified(self.rollup_path(resolution, day)), modified(self.day_path(day))) {
+            (Some(rollup), Some(raw)) => rollup >= raw,
+            _ => false,
+        }
+    }
+
+    /// Local days that have raw history, oldest first
+    pub fn recorded_days(&self) -> Vec<NaiveDate> {
+        let Some(entries) = self.dir.as_ref().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut days: Vec<NaiveDate> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                NaiveDate::parse_from_str(name.strip_suffix(&format!(".{HISTORY_EXTENSION}"))?, "%Y-%m-%d").ok()
+            })
+            .collect();
+        days.sort();
+        days
+    }
+
+    /// Writes the minute and hour rollups of `day` unless they are already current; returns
+    /// whether anything was written
+    pub fn compact_day(&self, day: NaiveDate) -> Result<bool, Box<dyn std::error::Error>> {
+        let stale: Vec<Resolution> = Resolution::ALL.into_iter().filter(|resolution| !self.rollup_current(*resolution, day)).collect();
+        if stale.is_empty() {
+            return Ok(false);
+        }
+        let records = self.load_days(day, day);
+        for resolution in stale {
+            let path = self.rollup_path(resolution, day).ok_or("History directory is unavailable")?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut content = String::new();
+            for rollup in rollup::aggregate(&records, resolution) {
+                content += &serde_json::to_string(&rollup)?;
+                content.push('\n');
+            }
+            // Written aside and renamed, so a reader never sees half a file
+            let partial = path.with_extension("partial");
+            fs::write(&partial, content)?;
+            fs::rename(&partial, &path)?;
+        }
+        Ok(true)
+    }
+
+    /// Rollups for the local days `first..=last`, oldest first. Days that haven't been compacted
+    /// yet, such as today, are aggregated from the raw samples.
+    pub fn load_rollups(&self, resolution: Resolution, first: NaiveDate, last: NaiveDate) -> Vec<Rollup> {
+        let mut rollups = Vec::new();
+        for day in first.iter_days().take_while(|day| *day <= last) {
+            let stored = self.rollup_current(resolution, day)
+                .then(|| self.rollup_path(resolution, day).and_then(|path| fs::File::open(path).ok()))
+                .flatten();
+            match stored {
+                Some(file) => rollups.extend(
+                    BufReader::new(file)
+                        .lines()
+                        .map_while(Result::ok)
+                        .filter_map(|line| serde_json::from_str::<Rollup>(&line).ok()),
+                ),
+                None => rollups.extend(rollup::aggregate(&self.load_days(day, day), resolution)),
+            }
+        }
+        rollups
+    }
+
+    pub fn append(&self, record: &HistoryRecord) -> Result<(), Box<dyn std::error::Error>> {
+        let day = DateTime::<Local>::from(record.timestamp()).date_naive();
+        Self::append_line(self.day_path(day), record)
+    }
+
+    pub fn append_speed_test(&self, result: &SpeedTestResult) -> Result<(), Box<dyn std::error::Error>> {
+        Self::append_line(self.dir.as_ref().map(|dir| dir.join(SPEED_TESTS_FILE_NAME)), result)
+    }
+
+    fn append_line(path: Option<PathBuf>, value: &impl Serialize) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.ok_or("History directory is unavailable")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(value)?;
+        line.push('\n');
+        OpenOptions::new().create(true).append(true).open(&path)?.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// The latest `limit` speed tests, oldest first
+    pub fn load_speed_tests(&self, limit: usize) -> Vec<SpeedTestResult> {
+        let Some(file) = self.dir.as_ref().and_then(|dir| fs::File::open(dir.join(SPEED_TESTS_FILE_NAME)).ok()) else {
+            return Vec::new();
+        };
+        let results: Vec<SpeedTestResult> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        results[results.len().saturating_sub(limit)..].to_vec()
+    }
+
+    /// Records from the local days `first..=last`, oldest first; missing days are skipped
+    /// and a line that can't be parsed (e.g. cut short by a crash) is ignored
+    pub fn load_days(&self, first: NaiveDate, last: NaiveDate) -> Vec<HistoryRecord> {
+        let mut records = Vec::new();
+        for day in first.iter_days().take_while(|day| *day <= last) {
+            let Some(file) = self.day_path(day).and_then(|path| fs::File::open(path).ok()) else {
+                continue;
+            };
+            records.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<HistoryRecord>(&line).ok()),
+            );
+        }
+        records
+    }
+}
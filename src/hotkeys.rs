@@ -0,0 +1,80 @@
+use std::sync::mpsc;
+use eframe::egui;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    ToggleMonitoring,
+    ShowWindow,
+}
+
+/// System-wide shortcuts that work while another application has focus.
+/// The manager must be created on the main thread and kept alive for the hotkeys to stay registered.
+pub struct Hotkeys {
+    _manager: Option<GlobalHotKeyManager>,
+    bindings: Vec<(u32, HotkeyAction)>,
+    sender: mpsc::Sender<GlobalHotKeyEvent>,
+    receiver: mpsc::Receiver<GlobalHotKeyEvent>,
+    handler_installed: bool,
+}
+
+impl Hotkeys {
+    /// Registers the configured shortcuts; empty strings disable a shortcut.
+    /// Returns any problems alongside, so one bad binding doesn't disable the other.
+    pub fn register(toggle: &str, show: &str) -> (Self, Vec<String>) {
+        let (sender, receiver) = mpsc::channel();
+        let mut hotkeys = Self { _manager: None, bindings: Vec::new(), sender, receiver, handler_installed: false };
+        let requested = [(toggle, HotkeyAction::ToggleMonitoring), (show, HotkeyAction::ShowWindow)];
+        if requested.iter().all(|(shortcut, _)| shortcut.trim().is_empty()) {
+            return (hotkeys, Vec::new());
+        }
+
+        let manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => manager,
+            Err(e) => return (hotkeys, vec![format!("Global hotkeys are unavailable: {e}")]),
+        };
+
+        let mut errors = Vec::new();
+        for (shortcut, action) in requested {
+            if shortcut.trim().is_empty() {
+                continue;
+            }
+            let registered = shortcut
+                .parse::<HotKey>()
+                .map_err(|e| e.to_string())
+                .and_then(|hotkey| manager.register(hotkey).map(|_| hotkey.id()).map_err(|e| e.to_string()));
+            match registered {
+                Ok(id) => hotkeys.bindings.push((id, action)),
+                Err(e) => errors.push(format!("Could not register hotkey \"{shortcut}\": {e}")),
+            }
+        }
+        hotkeys._manager = Some(manager);
+        (hotkeys, errors)
+    }
+
+    /// Returns the actions triggered since the last call. The first call installs a handler that
+    /// wakes the UI, so hotkeys still work while the window is minimized and not repainting.
+    pub fn poll(&mut self, ctx: &egui::Context) -> Vec<HotkeyAction> {
+        if !self.handler_installed && !self.bindings.is_empty() {
+            let sender = self.sender.clone();
+            let ctx = ctx.clone();
+            GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+                let _ = sender.send(event);
+                ctx.request_repaint();
+            }));
+            self.handler_installed = true;
+        }
+
+        let mut actions = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            if event.state() != HotKeyState::Pressed {
+                continue;
+            }
+            if let Some((_, action)) = self.bindings.iter().find(|(id, _)| *id == event.id()) {
+                actions.push(*action);
+            }
+        }
+        actions
+    }
+}
@@ -0,0 +1,39 @@
+use std::thread;
+use std::time::Duration;
+use serde::Serialize;
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// POSTs `body` as JSON to a notification or annotation service in the background. Nothing is
+/// sent while the service is disabled or its settings don't validate, since half-filled settings
+/// are already reported by validation. Failures are logged, which also surfaces them as toasts.
+pub fn http_post_json(
+    enabled: bool,
+    validation: Result<(), String>,
+    service: &'static str,
+    url: String,
+    authorization: Option<String>,
+    body: &impl Serialize,
+) {
+    if !enabled || validation.is_err() {
+        return;
+    }
+    let body = match serde_json::to_string(body) {
+        Ok(body) => body,
+        Err(e) => {
+            log::warn!("Could not serialize the request to {service}: {e}");
+            return;
+        }
+    };
+    thread::spawn(move || {
+        let agent: ureq::Agent =
+            ureq::Agent::config_builder().timeout_global(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS))).build().into();
+        let mut request = agent.post(&url).header("Content-Type", "application/json");
+        if let Some(authorization) = &authorization {
+            request = request.header("Authorization", authorization);
+        }
+        if let Err(e) = request.send(&body) {
+            log::warn!("Could not reach {service}: {e}");
+        }
+    });
+}
@@ -0,0 +1,155 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use ureq::Proxy;
+use crate::ping::{FailureCause, PingResult};
+
+const REQUEST_TIMEOUT_SECS: u64 = 5;
+
+/// The proxy HTTP probes go through, for networks that allow nothing out without one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpProxySettings {
+    /// e.g. "http://proxy.example.com:8080"; empty for the system proxy, if any
+    pub url: String,
+    /// Basic authentication with the proxy; empty for none
+    pub username: String,
+    pub password: String,
+    /// Without a proxy URL, use the one from HTTPS_PROXY, HTTP_PROXY or ALL_PROXY (skipping
+    /// NO_PROXY hosts) or, on Windows, from the Internet Options; off connects directly
+    pub use_system: bool,
+}
+
+impl Default for HttpProxySettings {
+    fn default() -> Self {
+        Self { url: String::new(), username: String::new(), password: String::new(), use_system: true }
+    }
+}
+
+impl HttpProxySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.proxy().map(|_| ())
+    }
+
+    /// None to connect directly
+    fn proxy(&self) -> Result<Option<Proxy>, String> {
+        let url = self.url.trim();
+        if url.is_empty() {
+            return Ok(if self.use_system { Proxy::try_from_env() } else { None });
+        }
+        let proxy = Proxy::new(url).map_err(|_| format!("HTTP proxy \"{url}\" is not an address like http://proxy:8080"))?;
+        if self.username.is_empty() {
+            return match self.password.is_empty() {
+                true => Ok(Some(proxy)),
+                false => Err("HTTP proxy password needs a user name".to_string()),
+            };
+        }
+        // The credentials are put into the proxy's address as they are
+        if self.username.contains([':', '@', '/']) || self.password.contains(['@', '/']) {
+            return Err("HTTP proxy user name can't contain ':', '@' or '/', nor the password '@' or '/'".to_string());
+        }
+        Proxy::builder(proxy.protocol())
+            .host(proxy.host())
+            .port(proxy.port())
+            .username(&self.username)
+            .password(&self.password)
+            .build()
+            .map(Some)
+            .map_err(|e| format!("HTTP proxy credentials: {e}"))
+    }
+}
+
+/// Checks that `target` is an http:// or https:// URL with a host
+pub fn validate_url(target: &str) -> Result<(), String> {
+    let target = target.trim();
+    let rest = target.strip_prefix("http://").or_else(|| target.strip_prefix("https://"));
+    match rest.and_then(|rest| rest.split(['/', '?', '#']).next()) {
+        Some(host) if !host.is_empty() => Ok(()),
+        _ => Err("HTTP probe needs a URL, e.g. https://example.com/".to_string()),
+    }
+}
+
+pub struct HttpProbe;
+
+impl HttpProbe {
+    /// Times a GET of `url` until the response headers arrive. Any response counts as up since the
+    /// server answered; a server error (5xx) is shown as degraded, and a failing proxy as
+    /// "proxy failed" rather than the target being down.
+    pub fn spawn(url: String, proxy: HttpProxySettings, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let result = match request(url.trim(), &proxy) {
+                Ok((response_time, server_error)) => {
+                    let result = PingResult::success(timestamp, sent_at, response_time, None);
+                    if server_error { result.with_degraded() } else { result }
+                }
+                Err(None) => PingResult::failure(timestamp, sent_at),
+                Err(Some(cause)) => PingResult::failure(timestamp, sent_at).with_cause(cause),
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+}
+
+/// (response time in ms, whether the server reported an error), or why nothing came back
+type Outcome = Result<(f64, bool), Option<FailureCause>>;
+
+fn request(url: &str, proxy: &HttpProxySettings) -> Outcome {
+    let proxy = proxy.proxy().map_err(|e| {
+        log::debug!("HTTP probe of {url} not sent: {e}");
+        Some(FailureCause::ProxyFailed)
+    })?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .proxy(proxy)
+        .timeout_global(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let started = Instant::now();
+    // The body isn't read: the headers already show the server is answering
+    match agent.get(url).call() {
+        Ok(response) => Ok((started.elapsed().as_secs_f64() * 1000.0, response.status().is_server_error())),
+        Err(ureq::Error::HostNotFound) => Err(Some(FailureCause::ResolutionFailed)),
+        Err(ureq::Error::ConnectProxyFailed(e)) => {
+            log::debug!("HTTP probe of {url}: proxy failed: {e}");
+            Err(Some(FailureCause::ProxyFailed))
+        }
+        Err(_) => Err(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_url_needs_a_scheme_and_host() {
+        assert!(validate_url("https://example.com/").is_ok());
+        assert!(validate_url(" http://192.168.1.1:8080/status ").is_ok());
+        assert!(validate_url("example.com").is_err());
+        assert!(validate_url("https://").is_err());
+        assert!(validate_url("ftp://example.com/").is_err());
+    }
+
+    #[test]
+    fn proxy_credentials_are_kept_and_checked() {
+        let settings = |url: &str, username: &str, password: &str| HttpProxySettings {
+            url: url.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            use_system: false,
+        };
+        assert_eq!(settings("", "", "").proxy(), Ok(None));
+        let proxy = settings("http://proxy.example.com:3128", "alice", "s3cret").proxy().unwrap().unwrap();
+        assert_eq!((proxy.host(), proxy.port()), ("proxy.example.com", 3128));
+        assert_eq!((proxy.username(), proxy.password()), (Some("alice"), Some("s3cret")));
+        assert!(settings("http://proxy.example.com:3128", "", "s3cret").validate().is_err());
+        assert!(settings("http://proxy.example.com:3128", "corp\\alice", "p@ss").validate().is_err());
+        assert!(settings("not a proxy", "", "").validate().is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::ErrorKind;
+use std::sync::Once;
+use surge_ping::{Client, Config, ICMP};
+
+/// The fallback is announced once per run, whether startup detection or a probe finds it first
+static FALLBACK_WARNING: Once = Once::new();
+
+/// Logs that pings go through the system `ping` command, the first time only
+pub fn warn_fallback(reason: &str) {
+    FALLBACK_WARNING.call_once(|| log::warn!("This app may not send ICMP ({reason}); pinging with the system ping command instead"));
+}
+
+/// Whether this process may send ICMP itself. surge-ping tries an unprivileged (datagram)
+/// socket first and a raw one second, so failing here means both were refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcmpAccess {
+    Allowed,
+    /// ICMP pings go through the system `ping` command instead
+    Denied(String),
+}
+
+impl IcmpAccess {
+    pub fn detect() -> Self {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => return IcmpAccess::Denied(format!("could not start the network runtime: {e}")),
+        };
+        // The socket registers with the runtime, so it has to be created inside one
+        let _guard = runtime.enter();
+        if let Err(e) = Client::new(&Config::default()) {
+            return IcmpAccess::Denied(e.to_string());
+        }
+        // A host without IPv6 can't open the socket at all, which isn't a matter of rights
+        match Client::new(&Config::builder().kind(ICMP::V6).build()) {
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => IcmpAccess::Denied(format!("IPv6: {e}")),
+            _ => IcmpAccess::Allowed,
+        }
+    }
+}
+
+/// What to change on this platform so the app may send ICMP, as (explanation, command to run);
+/// the command is empty when there is nothing to paste
+pub fn fix_steps() -> Vec<(&'static str, String)> {
+    let exe = std::env::current_exe().map(|path| path.display().to_string()).unwrap_or_else(|_| "ping-monitor".to_string());
+    if cfg!(windows) {
+        vec![
+            ("Windows only lets administrators open ICMP sockets. Right-click Ping Monitor and choose \"Run as administrator\".", String::new()),
+        ]
+    } else if cfg!(target_os = "macos") {
+        vec![("macOS normally allows unprivileged ping; if it was disabled, start the app with sudo:", format!("sudo \"{exe}\""))]
+    } else {
+        vec![
+            ("Allow unprivileged ping for every group (add the setting to /etc/sysctl.d/ to keep it after a reboot):",
+                "sudo sysctl -w net.ipv4.ping_group_range=\"0 2147483647\"".to_string()),
+            ("Or let just this program open raw sockets (repeat after every update of the binary):",
+                format!("sudo setcap cap_net_raw+ep \"{exe}\"")),
+        ]
+    }
+}
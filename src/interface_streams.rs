@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use eframe::egui;
+use crate::network_watcher::InterfaceStatus;
+use crate::ping::PingResult;
+use crate::ping_executor::{CAN_PIN_INTERFACE, PingExecutor, SourceBinding};
+use crate::probe_limiter::ProbeLimiter;
+
+/// Results older than this drop out of the side-by-side figures
+const STREAM_WINDOW_SECS: u64 = 60;
+
+struct InterfaceStream {
+    interface: String,
+    sender: mpsc::Sender<PingResult>,
+    receiver: mpsc::Receiver<PingResult>,
+    recent: VecDeque<PingResult>,
+}
+
+/// The main target probed once per chosen interface on the main schedule, e.g. Wi-Fi against
+/// Ethernet or one WAN link against another
+pub struct InterfaceStreams {
+    /// Target the results are for; they are dropped when it changes
+    target: String,
+    streams: Vec<InterfaceStream>,
+}
+
+impl InterfaceStreams {
+    pub fn new() -> Self {
+        Self { target: String::new(), streams: Vec::new() }
+    }
+
+    /// Follows the chosen interfaces; streams that stay keep their results
+    pub fn sync(&mut self, interfaces: &[String]) {
+        self.streams.retain(|stream| interfaces.contains(&stream.interface));
+        for interface in interfaces {
+            if self.streams.iter().any(|stream| &stream.interface == interface) {
+                continue;
+            }
+            let (sender, receiver) = mpsc::channel();
+            self.streams.push(InterfaceStream { interface: interface.clone(), sender, receiver, recent: VecDeque::new() });
+        }
+    }
+
+    pub fn probe_all(&mut self, target: &str, interfaces: &[InterfaceStatus], limiter: &mut ProbeLimiter, ctx: &egui::Context) {
+        if !CAN_PIN_INTERFACE {
+            return;
+        }
+        if self.target != target {
+            self.target = target.to_string();
+            for stream in &mut self.streams {
+                stream.recent.clear();
+            }
+        }
+        for stream in &self.streams {
+            if !limiter.try_start(&limiter_key(target, &stream.interface)) {
+                continue;
+            }
+            let addresses = interfaces
+                .iter()
+                .find(|status| status.name == stream.interface && status.up)
+                .map(|status| status.addresses.clone())
+                .unwrap_or_default();
+            let binding = SourceBinding { interface: stream.interface.clone(), addresses };
+            PingExecutor::resolve_and_ping_via(target.to_string(), binding, stream.sender.clone(), ctx.clone());
+        }
+    }
+
+    pub fn poll(&mut self, limiter: &mut ProbeLimiter) {
+        let cutoff = Instant::now().checked_sub(Duration::from_secs(STREAM_WINDOW_SECS));
+        for stream in &mut self.streams {
+            for result in stream.receiver.try_iter() {
+                limiter.finished(&limiter_key(&self.target, &stream.interface));
+                stream.recent.push_back(result);
+            }
+            while stream.recent.front().is_some_and(|result| cutoff.is_some_and(|cutoff| result.sent_at < cutoff)) {
+                stream.recent.pop_front();
+            }
+        }
+    }
+
+    /// (interface, results of the last minute) in the order the interfaces were chosen
+    pub fn streams(&self) -> impl Iterator<Item = (&str, &VecDeque<PingResult>)> {
+        self.streams.iter().map(|stream| (stream.interface.as_str(), &stream.recent))
+    }
+}
+
+/// Each interface gets its own rate allowance, like a separate target
+fn limiter_key(target: &str, interface: &str) -> String {
+    format!("{target} via {interface}")
+}
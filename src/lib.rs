@@ -3,55 +3,537 @@ mod ping;
 mod dns_cache;
 mod ping_executor;
 mod circle_color;
+mod sleep_detector;
+mod network_watcher;
+mod system_command;
+mod traceroute;
+mod outage;
+mod route_monitor;
+mod network_info;
+mod health_check;
+mod wifi;
+mod load_generator;
+mod bufferbloat;
+mod speed_test;
+mod pcap;
+mod path_mtu;
+mod geoip;
+mod schedule;
+mod hotkeys;
+mod wake_on_lan;
+mod plugins;
+mod command_probe;
+mod sonar;
+mod remote_agent;
+mod agent_hub;
+mod history;
+mod report;
+mod baseline;
+mod subnet_sweep;
+mod mdns_discovery;
+mod recent_targets;
+mod targets;
+mod profile;
+mod ntp_probe;
+mod snmp_probe;
+mod tcp_probe;
+mod stress_test;
+mod load_window;
+mod probe_limiter;
+mod clock_slot;
+mod annotations;
+mod event_log;
+mod app_log;
+mod persistence;
+mod target_parser;
+mod resolve_preview;
+mod dns_benchmark;
+mod quick_picks;
+mod dns_probe;
+mod spike;
+mod loss_pattern;
+mod xlsx_export;
+mod pdf_report;
+mod target_style;
+mod toast;
+mod system_ping;
+mod icmp_access;
+mod power;
+mod metered;
+mod vpn;
+mod network_notifier;
+mod socks5;
+mod web_dashboard;
+mod rollup;
+mod timestamp_format;
+mod attention;
+mod grafana;
+mod paging;
+mod ntfy;
+mod pushover;
+mod fallback_chain;
+mod interface_streams;
+mod replay;
+mod loss_localization;
+mod fullscreen;
+mod periodic_query;
+mod http_post;
+mod http_probe;
 
 use std::collections::{VecDeque, HashMap};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use eframe::egui;
 use egui::{Color32, Vec2, Pos2, Stroke};
-use std::sync::mpsc;
+use std::sync::{Arc, Mutex, mpsc};
+use std::net::{IpAddr, Ipv4Addr};
 
-use config::AppConfig;
-use ping::{PingResult, PingStatistics};
-use dns_cache::{DnsCache, DnsCacheEntry};
+use config::{AppConfig, MainView, ProbeType};
+use ping::{FailureCause, PingResult, PingStatistics};
+use dns_cache::{AnswerChange, DnsCache, DnsCacheEntry};
 use ping_executor::PingExecutor;
-use circle_color::CircleColor;
+use circle_color::{CircleColor, FadeTimings};
+use sleep_detector::{SleepDetector, SleepGap};
+use network_watcher::{NetworkEvent, NetworkWatcher};
+use traceroute::{TraceHop, Traceroute, TracerouteResult};
+use outage::{OutageEvent, OutageTracker, TraceSnapshot};
+use route_monitor::RouteMonitor;
+use health_check::{CheckStatus, HealthCheck, HealthReport};
+use wifi::WifiMonitor;
+use bufferbloat::{BufferbloatPhase, BufferbloatReport, BufferbloatTest, BufferbloatUpdate};
+use speed_test::{SpeedTest, SpeedTestResult};
+use pcap::PacketCapture;
+use path_mtu::{PathMtu, PathMtuReport};
+use geoip::GeoIp;
+use schedule::TimeWindow;
+use hotkeys::{HotkeyAction, Hotkeys};
+use plugins::PluginHost;
+use command_probe::CommandProbe;
+use sonar::Sonar;
+use remote_agent::RemoteViewer;
+use agent_hub::{AgentEndpoint, AgentHub};
+use history::{HistoryRecord, HistoryStore};
+use report::{DayCurve, HourSummary, OverlayReference, PeriodSummary, Report, ReportBuilder};
+use loss_pattern::LossPattern;
+use xlsx_export::XlsxExport;
+use pdf_report::PdfReport;
+use target_style::{TargetStyle, TargetStyles};
+use toast::{ToastAction, ToastLevel, Toasts};
+use icmp_access::IcmpAccess;
+use power::{BatterySaver, PowerMonitor, PowerSource};
+use metered::{MeteredMonitor, MeteredSaver};
+use web_dashboard::{Binding, DashboardSnapshot, TlsFiles, WebDashboard};
+use rollup::{Compactor, Rollup};
+use timestamp_format::TimestampFormat;
+use attention::{AlertSeverity, AttentionLevel, AttentionRequester, AttentionSettings};
+use grafana::GrafanaSettings;
+use paging::{PagingService, PagingSettings};
+use ntfy::NtfySettings;
+use pushover::{PushoverPriority, PushoverSettings};
+use fallback_chain::{FallbackChains, FallbackProber};
+use interface_streams::InterfaceStreams;
+use replay::{Replay, REPLAY_SPEEDS};
+use loss_localization::HopLoss;
+use fullscreen::{FullscreenAction, FullscreenMonitor, FullscreenSaver};
+use baseline::{Baseline, BaselineLoader};
+use subnet_sweep::{SubnetSweep, SweepHost, SweepUpdate};
+use mdns_discovery::{MdnsDevice, MdnsDiscovery, MdnsUpdate};
+use recent_targets::RecentTarget;
+use targets::{TargetColumn, TargetEntry, TargetStatus, TargetWatch};
+use profile::MonitoringProfile;
+use ntp_probe::NtpProbe;
+use snmp_probe::{SnmpAuthProtocol, SnmpProbe, SnmpSettings, SnmpVersion};
+use tcp_probe::TcpProbe;
+use stress_test::{StressSummary, StressTest, StressUpdate};
+use load_window::LoadWindow;
+use probe_limiter::{ProbeLimiter, SharedLimiter};
+use clock_slot::{ClockSlot, SlotAggregate, SlotSpread};
+use annotations::AnnotationStore;
+use event_log::{EventKind, EventLog};
+use app_log::LogLevel;
+use persistence::ConfigSaver;
+use target_parser::ParsedTarget;
+use resolve_preview::ResolvePreview;
+use dns_benchmark::{BenchmarkUpdate, DnsBenchmark, ResolverScore};
+use dns_probe::DnsProbe;
+use http_probe::{HttpProbe, HttpProxySettings};
+
+pub use remote_agent::AGENT_FLAG;
 
 // Constants
-const PING_INTERVAL_SECS: u64 = 5;
 const MAX_PING_RESULTS: usize = 60;
 const STATISTICS_WINDOW_SECS: u64 = 60;
 const PENDING_PING_TIMEOUT_SECS: u64 = 10;
 const DNS_CACHE_TTL_SECS: u64 = 300;
 const NUM_CIRCLES: usize = 12;
+const ACTIVE_REPAINT_INTERVAL_MS: u64 = 100;
+const IDLE_REPAINT_INTERVAL_MS: u64 = 1000;
+const METERED_PAUSE_HINT: &str = "Paused while the connection is metered (see the settings below the thresholds)";
+const MIN_PING_SPACING_MS: u64 = 1000;
+const MAX_SLEEP_GAPS: usize = 20;
+const MAX_NETWORK_EVENTS: usize = 50;
+const MAX_SPEED_TESTS: usize = 50;
+/// How long to watch for a target to answer after sending a Wake-on-LAN packet
+const WAKE_WATCH_SECS: u64 = 180;
+/// Dark fill for the dial while paused by the monitoring schedule, distinct from the "no data" gray
+const OUTSIDE_SCHEDULE_COLOR: Color32 = Color32::from_rgb(45, 45, 60);
+const CAPTURE_WINDOWS_MINS: [u64; 4] = [5, 15, 60, 24 * 60];
+
+/// Reports older than this are rebuilt when the Reports tab is opened
+const REPORT_REFRESH_SECS: u64 = 300;
+const REPORT_CHART_HEIGHT: f32 = 14.0;
+/// Yesterday's baseline is recomputed this often while monitoring
+const BASELINE_REFRESH_SECS: u64 = 300;
+const COMPARE_CHART_HEIGHT: f32 = 100.0;
+const COMPARE_MAIN_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+const COMPARE_OTHER_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+const STRESS_DEFAULT_RATE: u32 = 20;
+const STRESS_DEFAULT_SECS: u64 = 10;
+/// Marks results sent while the user was generating load
+const LOAD_WINDOW_COLOR: Color32 = Color32::from_rgb(255, 200, 0);
+/// User notes on the dial and charts
+const ANNOTATION_COLOR: Color32 = Color32::from_rgb(230, 230, 230);
+/// Network changes that split the chart into before and after
+const NETWORK_CHANGE_COLOR: Color32 = Color32::from_rgb(120, 170, 255);
+/// Glyph marking isolated latency spikes on the dial
+const SPIKE_COLOR: Color32 = Color32::from_rgb(255, 90, 200);
+/// Notes listed under "Notes"; older ones stay on disk and in exports
+const MAX_LISTED_NOTES: usize = 10;
+/// Same height as the clock face, so switching views doesn't move the rest of the window
+const PROBE_LIST_HEIGHT: f32 = 240.0;
+/// Lost sequence number runs named in the statistics; the count covers all of them
+const MAX_LISTED_SEQUENCE_GAPS: usize = 5;
+/// Pause after the last keystroke before the target being edited is looked up
+const RESOLVE_PREVIEW_DELAY_MS: u64 = 500;
+const MAX_PREVIEW_ADDRESSES: usize = 4;
+const NOTE_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::M);
+const FOCUS_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+/// How long "Silence for 1 h" holds back taskbar flashes and push notifications
+const ALERT_SILENCE: Duration = Duration::from_secs(60 * 60);
+/// Latency changes smaller than this are shown as steady
+const TREND_STEADY_MS: f64 = 2.0;
+/// Loss changes smaller than this (percentage points) are shown as steady
+const TREND_STEADY_LOSS: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Monitor,
+    Reports,
+}
+
+/// Why a traceroute was started, so its result can be routed back
+#[derive(Debug, Clone, Copy)]
+pub enum TracePurpose {
+    Outage(u64),
+    RouteCheck,
+}
+
+/// What the monitor showed before a replay took it over, put back when the replay ends
+struct LiveView {
+    circles: [CircleColor; NUM_CIRCLES],
+    circle_timestamps: [Option<Instant>; NUM_CIRCLES],
+    slots: [ClockSlot; NUM_CIRCLES],
+    ping_results: VecDeque<PingResult>,
+    last_response_time: Option<f64>,
+    outage_tracker: OutageTracker,
+    event_log: EventLog,
+}
+
+impl LiveView {
+    fn empty() -> Self {
+        Self {
+            circles: [CircleColor::Gray; NUM_CIRCLES],
+            circle_timestamps: [None; NUM_CIRCLES],
+            slots: Default::default(),
+            ping_results: VecDeque::new(),
+            last_response_time: None,
+            outage_tracker: OutageTracker::new(),
+            event_log: EventLog::default(),
+        }
+    }
+}
 
 pub struct PingMonitorApp {
+    pub probe: ProbeType,
     pub target: String,
+    pub command: String,
     pub is_monitoring: bool,
     pub ping_results: VecDeque<PingResult>,
     pub circles: [CircleColor; NUM_CIRCLES],
-    pub circle_timestamps: [Option<SystemTime>; NUM_CIRCLES],
-    pub last_ping_second: Option<u64>,
+    pub circle_timestamps: [Option<Instant>; NUM_CIRCLES],
+    /// All results of the current pass over each circle
+    pub slots: [ClockSlot; NUM_CIRCLES],
+    pub slot_aggregate: SlotAggregate,
+    /// Fixed fade timings; None derives them from the interval
+    pub fade_timings: Option<FadeTimings>,
+    pub next_ping_at: Option<Instant>,
     pub ping_statistics: PingStatistics,
     pub ping_receiver: Option<mpsc::Receiver<PingResult>>,
     pub ping_sender: Option<mpsc::Sender<PingResult>>,
-    pub pending_pings: HashMap<usize, SystemTime>,
+    pub pending_pings: HashMap<usize, Instant>,
     pub dns_cache: DnsCache,
     pub green_threshold: u64,
     pub yellow_threshold: u64,
+    pub very_slow_threshold: u64,
+    pub interval_secs: u64,
+    pub ntp_offset_threshold_ms: u64,
+    pub snmp: SnmpSettings,
+    pub tcp_expect: String,
+    pub tcp_proxy: String,
+    pub http_proxy: HttpProxySettings,
+    pub maintenance_windows: Vec<TimeWindow>,
+    pub toggle_hotkey: String,
+    pub show_hotkey: String,
+    pub hotkeys: Hotkeys,
+    pub wake_sent_at: Option<Instant>,
+    pub wake_status: Option<String>,
+    pub plugins: PluginHost,
+    pub sonar_enabled: bool,
+    pub sonar: Sonar,
+    pub announce_changes: bool,
+    pub remote_agent: String,
+    pub agent_token: String,
+    pub agent_port: u16,
+    pub remote_viewer: Option<RemoteViewer>,
+    pub agents: Vec<AgentEndpoint>,
+    pub agent_hub: AgentHub,
+    /// Agent being typed into the add form
+    pub new_agent: AgentEndpoint,
     pub last_response_time: Option<f64>,
+    pub config_errors: Vec<String>,
+    pub config_saver: ConfigSaver,
+    /// Lookup of the target as it is being edited
+    pub resolve_preview: Option<ResolvePreview>,
+    /// The target the preview was last started for
+    pub previewed_target: String,
+    /// When the pending preview lookup starts, a moment after the last keystroke
+    pub resolve_preview_due: Option<Instant>,
+    pub resolve_preview_sender: mpsc::Sender<ResolvePreview>,
+    pub resolve_preview_receiver: mpsc::Receiver<ResolvePreview>,
+    pub sleep_detector: SleepDetector,
+    pub sleep_gaps: VecDeque<SleepGap>,
+    pub statistics_since: Option<Instant>,
+    pub network_watcher: NetworkWatcher,
+    pub last_recovery: Option<(SystemTime, String)>,
+    pub network_events: VecDeque<(SystemTime, NetworkEvent)>,
+    pub outage_tracker: OutageTracker,
+    pub traceroute_sender: mpsc::Sender<(TracePurpose, TracerouteResult)>,
+    pub traceroute_receiver: mpsc::Receiver<(TracePurpose, TracerouteResult)>,
+    pub route_monitor: RouteMonitor,
+    pub health_report: Option<HealthReport>,
+    pub health_check_running: bool,
+    pub health_sender: mpsc::Sender<HealthReport>,
+    pub health_receiver: mpsc::Receiver<HealthReport>,
+    pub wifi_monitor: WifiMonitor,
+    pub bufferbloat_phase: Option<BufferbloatPhase>,
+    pub bufferbloat_result: Option<Result<BufferbloatReport, String>>,
+    pub bufferbloat_sender: mpsc::Sender<BufferbloatUpdate>,
+    pub bufferbloat_receiver: mpsc::Receiver<BufferbloatUpdate>,
+    pub speed_tests: VecDeque<SpeedTestResult>,
+    pub speed_test_running: bool,
+    pub speed_test_error: Option<String>,
+    pub speed_test_sender: mpsc::Sender<Result<SpeedTestResult, String>>,
+    pub speed_test_receiver: mpsc::Receiver<Result<SpeedTestResult, String>>,
+    pub packet_capture: PacketCapture,
+    pub capture_window_mins: u64,
+    pub capture_export_status: Option<Result<String, String>>,
+    pub path_mtu_running: bool,
+    pub path_mtu_result: Option<Result<PathMtuReport, String>>,
+    pub path_mtu_sender: mpsc::Sender<Result<PathMtuReport, String>>,
+    pub path_mtu_receiver: mpsc::Receiver<Result<PathMtuReport, String>>,
+    pub geoip: GeoIp,
+    pub tab: Tab,
+    /// Earlier day the Reports tab overlays today's latency with
+    pub overlay_reference: OverlayReference,
+    pub history: HistoryStore,
+    pub history_error: Option<String>,
+    pub annotations: AnnotationStore,
+    /// Text of the next note
+    pub note_text: String,
+    pub annotation_error: Option<String>,
+    pub event_log: EventLog,
+    pub toasts: Toasts,
+    pub toast_history_open: bool,
+    /// Whether ICMP sockets may be opened, checked once at startup
+    pub icmp_access: IcmpAccess,
+    pub power_monitor: PowerMonitor,
+    pub battery_saver: BatterySaver,
+    pub metered_monitor: MeteredMonitor,
+    pub metered_saver: MeteredSaver,
+    pub fullscreen_monitor: FullscreenMonitor,
+    pub fullscreen_saver: FullscreenSaver,
+    /// Look up the gateway and DNS servers again after network changes
+    pub rediscover_gateway: bool,
+    pub alert_on_dns_change: bool,
+    pub timestamp_format: TimestampFormat,
+    pub main_view: MainView,
+    /// Only the clock face and last reply are shown
+    pub focus_mode: bool,
+    pub auto_stop_enabled: bool,
+    pub auto_stop_mins: u64,
+    /// When the current monitoring session began
+    pub session_started: Option<SystemTime>,
+    /// Figures for the last session that stopped on its own
+    pub session_summary: Option<String>,
+    /// Probes sent and answered since the session began, unlike the rolling statistics
+    pub session_sent: u64,
+    pub session_replies: u64,
+    /// Alerts until then are only logged and shown in the window
+    pub alerts_silenced_until: Option<SystemTime>,
+    /// Taskbar flash or dock bounce per alert severity while the window is in the background
+    pub attention: AttentionSettings,
+    pub attention_requester: AttentionRequester,
+    pub grafana: GrafanaSettings,
+    pub paging: PagingSettings,
+    pub ntfy: NtfySettings,
+    pub pushover: PushoverSettings,
+    pub web_dashboard_enabled: bool,
+    pub web_dashboard_port: u16,
+    pub web_dashboard_lan: bool,
+    pub web_dashboard_token: String,
+    pub web_dashboard_tls_cert: String,
+    pub web_dashboard_tls_key: String,
+    pub web_dashboard: Option<WebDashboard>,
+    /// Why the dashboard couldn't start where it was asked to listen; retried once that changes
+    pub web_dashboard_error: Option<(Binding, String)>,
+    /// Whether egui's animations are currently turned off by the battery saver
+    pub animations_off: bool,
+    pub event_export_status: Option<Result<String, String>>,
+    /// Most verbose level recorded for the log console
+    pub log_level: LogLevel,
+    /// Least severe level shown in the log console
+    pub log_view_level: LogLevel,
+    pub report: Option<Report>,
+    pub report_running: bool,
+    pub xlsx_export_running: bool,
+    pub xlsx_export_status: Option<Result<String, String>>,
+    pub xlsx_export_sender: mpsc::Sender<Result<String, String>>,
+    pub xlsx_export_receiver: mpsc::Receiver<Result<String, String>>,
+    /// First and last day of the PDF report as typed, YYYY-MM-DD
+    pub pdf_report_from: String,
+    pub pdf_report_to: String,
+    pub sla_percent: f64,
+    pub pdf_report_running: bool,
+    pub pdf_report_status: Option<Result<String, String>>,
+    pub pdf_report_sender: mpsc::Sender<Result<String, String>>,
+    pub pdf_report_receiver: mpsc::Receiver<Result<String, String>>,
+    pub report_sender: mpsc::Sender<Report>,
+    pub report_receiver: mpsc::Receiver<Report>,
+    pub baseline: Option<Baseline>,
+    pub baseline_running: bool,
+    pub baseline_sender: mpsc::Sender<Baseline>,
+    pub baseline_receiver: mpsc::Receiver<Baseline>,
+    pub compare_target: String,
+    pub fallback_chains: FallbackChains,
+    pub fallback_prober: FallbackProber,
+    pub probe_interfaces: Vec<String>,
+    pub interface_streams: InterfaceStreams,
+    pub replay: Option<Replay>,
+    pub replay_day: Option<chrono::NaiveDate>,
+    /// The live dial, results and events while a replay is shown in their place
+    live_view: Option<LiveView>,
+    pub compare_results: VecDeque<PingResult>,
+    pub compare_sender: mpsc::Sender<PingResult>,
+    pub compare_receiver: mpsc::Receiver<PingResult>,
+    pub gateway: Option<IpAddr>,
+    /// DNS servers of the system resolver, looked up together with the gateway
+    pub dns_servers: Vec<IpAddr>,
+    /// Set at startup and after network changes so the gateway is looked up again
+    pub gateway_lookup_due: bool,
+    /// (gateway, DNS servers)
+    pub gateway_sender: mpsc::Sender<(Option<IpAddr>, Vec<IpAddr>)>,
+    pub gateway_receiver: mpsc::Receiver<(Option<IpAddr>, Vec<IpAddr>)>,
+    /// Gateway pings sent while the target fails, to tell local trouble from upstream trouble
+    pub gateway_probe_sender: mpsc::Sender<PingResult>,
+    pub gateway_probe_receiver: mpsc::Receiver<PingResult>,
+    pub sweep_range: String,
+    pub sweep_hosts: Vec<SweepHost>,
+    /// (done, total) while a sweep runs
+    pub sweep_progress: Option<(usize, usize)>,
+    pub sweep_error: Option<String>,
+    pub sweep_sender: mpsc::Sender<SweepUpdate>,
+    pub sweep_receiver: mpsc::Receiver<SweepUpdate>,
+    /// Extra resolver addresses to benchmark besides the system and public ones
+    pub dns_benchmark_extra: String,
+    pub dns_scores: Vec<ResolverScore>,
+    /// (scored, total) while a benchmark runs
+    pub dns_benchmark_progress: Option<(usize, usize)>,
+    pub dns_benchmark_status: Option<Result<String, String>>,
+    pub dns_benchmark_sender: mpsc::Sender<BenchmarkUpdate>,
+    pub dns_benchmark_receiver: mpsc::Receiver<BenchmarkUpdate>,
+    pub mdns_devices: Vec<MdnsDevice>,
+    pub mdns_running: bool,
+    pub mdns_error: Option<String>,
+    pub mdns_sender: mpsc::Sender<MdnsUpdate>,
+    pub mdns_receiver: mpsc::Receiver<MdnsUpdate>,
+    pub stress_rate: u32,
+    pub stress_duration_secs: u64,
+    pub stress_running: bool,
+    pub stress_result: Option<Result<StressSummary, String>>,
+    pub stress_sender: mpsc::Sender<StressUpdate>,
+    pub stress_receiver: mpsc::Receiver<StressUpdate>,
+    pub load_window: Option<LoadWindow>,
+    pub recent_targets: Vec<RecentTarget>,
+    pub target_list: Vec<TargetEntry>,
+    pub target_settings: Vec<TargetEntry>,
+    pub target_table_view: bool,
+    pub target_sort: TargetColumn,
+    pub target_sort_ascending: bool,
+    pub target_styles: TargetStyles,
+    pub target_watch: TargetWatch,
+    pub probe_limiter: SharedLimiter,
+    pub import_path: String,
+    pub import_status: Option<String>,
+}
+
+/// Headless mode: probes with the saved settings and serves the results to remote viewers
+pub fn run_agent() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut config, mut errors) = AppConfig::load();
+    errors.extend(config.apply_env_overrides());
+    errors.extend(config.validate());
+    for error in &errors {
+        log::warn!("{error}");
+    }
+    remote_agent::run_agent(&config)
+}
+
+/// Sends log output to stderr (filtered by `RUST_LOG`) and to the in-app log console
+pub fn init_logging() {
+    app_log::init();
 }
 
 
 
 impl Default for PingMonitorApp {
     fn default() -> Self {
+        let (traceroute_sender, traceroute_receiver) = mpsc::channel();
+        let (resolve_preview_sender, resolve_preview_receiver) = mpsc::channel();
+        let (health_sender, health_receiver) = mpsc::channel();
+        let (bufferbloat_sender, bufferbloat_receiver) = mpsc::channel();
+        let (speed_test_sender, speed_test_receiver) = mpsc::channel();
+        let (path_mtu_sender, path_mtu_receiver) = mpsc::channel();
+        let (report_sender, report_receiver) = mpsc::channel();
+        let (xlsx_export_sender, xlsx_export_receiver) = mpsc::channel();
+        let (pdf_report_sender, pdf_report_receiver) = mpsc::channel();
+        let icmp_access = IcmpAccess::detect();
+        let (pdf_report_from, pdf_report_to) = pdf_report::default_range();
+        let (baseline_sender, baseline_receiver) = mpsc::channel();
+        let (compare_sender, compare_receiver) = mpsc::channel();
+        let (gateway_sender, gateway_receiver) = mpsc::channel();
+        let (gateway_probe_sender, gateway_probe_receiver) = mpsc::channel();
+        let (sweep_sender, sweep_receiver) = mpsc::channel();
+        let (dns_benchmark_sender, dns_benchmark_receiver) = mpsc::channel();
+        let (mdns_sender, mdns_receiver) = mpsc::channel();
+        let (stress_sender, stress_receiver) = mpsc::channel();
         Self {
+            probe: ProbeType::Icmp,
             target: "8.8.8.8".to_string(),
+            command: String::new(),
             is_monitoring: false,
             ping_results: VecDeque::new(),
             circles: [CircleColor::Gray; NUM_CIRCLES],
             circle_timestamps: [None; NUM_CIRCLES],
-            last_ping_second: None,
+            slots: Default::default(),
+            next_ping_at: None,
             ping_statistics: PingStatistics::default(),
             ping_receiver: None,
             ping_sender: None,
@@ -59,21 +541,241 @@ impl Default for PingMonitorApp {
             dns_cache: DnsCache::new(),
             green_threshold: 100,
             yellow_threshold: 200,
+            very_slow_threshold: 1000,
+            interval_secs: 5,
+            ntp_offset_threshold_ms: 100,
+            snmp: SnmpSettings::default(),
+            tcp_expect: String::new(),
+            tcp_proxy: String::new(),
+            http_proxy: HttpProxySettings::default(),
+            slot_aggregate: SlotAggregate::Worst,
+            fade_timings: None,
+            maintenance_windows: Vec::new(),
+            toggle_hotkey: "CmdOrCtrl+Shift+Alt+P".to_string(),
+            show_hotkey: "CmdOrCtrl+Shift+Alt+M".to_string(),
+            // Registering is a process-wide side effect; only the real app (`new`) does it
+            hotkeys: Hotkeys::register("", "").0,
+            wake_sent_at: None,
+            wake_status: None,
+            plugins: PluginHost::new(),
+            sonar_enabled: false,
+            sonar: Sonar::new(),
+            announce_changes: false,
+            log_level: LogLevel::Info,
+            log_view_level: LogLevel::Info,
+            remote_agent: String::new(),
+            agent_token: String::new(),
+            agent_port: remote_agent::DEFAULT_AGENT_PORT,
+            remote_viewer: None,
+            agents: Vec::new(),
+            compare_target: String::new(),
+            fallback_chains: FallbackChains::new(),
+            fallback_prober: FallbackProber::new(),
+            probe_interfaces: Vec::new(),
+            interface_streams: InterfaceStreams::new(),
+            replay: None,
+            replay_day: None,
+            live_view: None,
+            recent_targets: Vec::new(),
+            target_list: Vec::new(),
+            target_settings: Vec::new(),
+            target_table_view: false,
+            target_sort: TargetColumn::Target,
+            target_sort_ascending: true,
+            target_styles: TargetStyles::new(),
+            sla_percent: pdf_report::DEFAULT_SLA_PERCENT,
+            battery_saver: BatterySaver::default(),
+            metered_saver: MeteredSaver::default(),
+            fullscreen_saver: FullscreenSaver::default(),
+            rediscover_gateway: true,
+            alert_on_dns_change: false,
+            timestamp_format: TimestampFormat::Local,
+            main_view: MainView::Clock,
+            focus_mode: false,
+            auto_stop_enabled: false,
+            auto_stop_mins: 120,
+            session_started: None,
+            session_summary: None,
+            session_sent: 0,
+            session_replies: 0,
+            alerts_silenced_until: None,
+            attention: AttentionSettings::default(),
+            attention_requester: AttentionRequester::default(),
+            grafana: GrafanaSettings::default(),
+            paging: PagingSettings::default(),
+            ntfy: NtfySettings::default(),
+            pushover: PushoverSettings::default(),
+            web_dashboard_enabled: false,
+            web_dashboard_port: web_dashboard::DEFAULT_DASHBOARD_PORT,
+            web_dashboard_lan: false,
+            web_dashboard_token: String::new(),
+            web_dashboard_tls_cert: String::new(),
+            web_dashboard_tls_key: String::new(),
+            web_dashboard: None,
+            web_dashboard_error: None,
+            target_watch: TargetWatch::new(),
+            probe_limiter: Arc::new(Mutex::new(ProbeLimiter::new(32, 5))),
+            import_path: String::new(),
+            import_status: None,
+            agent_hub: AgentHub::new(),
+            new_agent: AgentEndpoint::default(),
             last_response_time: None,
+            config_errors: Vec::new(),
+            config_saver: ConfigSaver::default(),
+            resolve_preview: None,
+            previewed_target: String::new(),
+            resolve_preview_due: None,
+            resolve_preview_sender,
+            resolve_preview_receiver,
+            sleep_detector: SleepDetector::new(),
+            sleep_gaps: VecDeque::new(),
+            statistics_since: None,
+            network_watcher: NetworkWatcher::new(),
+            last_recovery: None,
+            network_events: VecDeque::new(),
+            outage_tracker: OutageTracker::new(),
+            traceroute_sender,
+            traceroute_receiver,
+            route_monitor: RouteMonitor::new(),
+            health_report: None,
+            health_check_running: false,
+            health_sender,
+            health_receiver,
+            wifi_monitor: WifiMonitor::new(),
+            bufferbloat_phase: None,
+            bufferbloat_result: None,
+            bufferbloat_sender,
+            bufferbloat_receiver,
+            speed_tests: VecDeque::new(),
+            speed_test_running: false,
+            speed_test_error: None,
+            speed_test_sender,
+            speed_test_receiver,
+            packet_capture: PacketCapture::new(),
+            capture_window_mins: CAPTURE_WINDOWS_MINS[0],
+            capture_export_status: None,
+            path_mtu_running: false,
+            path_mtu_result: None,
+            path_mtu_sender,
+            path_mtu_receiver,
+            tab: Tab::Monitor,
+            overlay_reference: OverlayReference::default(),
+            history: HistoryStore::open(),
+            history_error: None,
+            annotations: AnnotationStore::open(),
+            note_text: String::new(),
+            annotation_error: None,
+            event_log: EventLog::default(),
+            toasts: Toasts::default(),
+            toast_history_open: false,
+            icmp_access,
+            power_monitor: power::monitor(),
+            metered_monitor: metered::monitor(),
+            fullscreen_monitor: fullscreen::monitor(),
+            animations_off: false,
+            event_export_status: None,
+            report: None,
+            report_running: false,
+            xlsx_export_running: false,
+            xlsx_export_status: None,
+            xlsx_export_sender,
+            xlsx_export_receiver,
+            pdf_report_from,
+            pdf_report_to,
+            pdf_report_running: false,
+            pdf_report_status: None,
+            pdf_report_sender,
+            pdf_report_receiver,
+            report_sender,
+            report_receiver,
+            baseline: None,
+            baseline_running: false,
+            baseline_sender,
+            baseline_receiver,
+            compare_results: VecDeque::new(),
+            compare_sender,
+            compare_receiver,
+            gateway: None,
+            dns_servers: Vec::new(),
+            gateway_lookup_due: true,
+            gateway_sender,
+            gateway_receiver,
+            gateway_probe_sender,
+            gateway_probe_receiver,
+            sweep_range: String::new(),
+            sweep_hosts: Vec::new(),
+            sweep_progress: None,
+            sweep_error: None,
+            sweep_sender,
+            sweep_receiver,
+            dns_benchmark_extra: String::new(),
+            dns_scores: Vec::new(),
+            dns_benchmark_progress: None,
+            dns_benchmark_status: None,
+            dns_benchmark_sender,
+            dns_benchmark_receiver,
+            mdns_devices: Vec::new(),
+            mdns_running: false,
+            mdns_error: None,
+            mdns_sender,
+            mdns_receiver,
+            stress_rate: STRESS_DEFAULT_RATE,
+            stress_duration_secs: STRESS_DEFAULT_SECS,
+            stress_running: false,
+            stress_result: None,
+            stress_sender,
+            stress_receiver,
+            load_window: None,
+            geoip: GeoIp::load(),
         }
     }
 }
 
 impl PingMonitorApp {
     pub fn new() -> Self {
-        let config = AppConfig::load();
+        let (mut config, mut config_errors) = AppConfig::load();
+        config_errors.extend(config.apply_env_overrides());
+        app_log::set_level(config.log_level);
+        let (hotkeys, hotkey_errors) = Hotkeys::register(&config.toggle_hotkey, &config.show_hotkey);
+        config_errors.extend(hotkey_errors);
+        let (plugins, plugin_errors) = PluginHost::load();
+        config_errors.extend(plugin_errors);
+        for error in &config_errors {
+            log::warn!("{error}");
+        }
+        Compactor::spawn(HistoryStore::open());
+        let icmp_access = IcmpAccess::detect();
+        if let IcmpAccess::Denied(e) = &icmp_access {
+            icmp_access::warn_fallback(e);
+        }
+        let (traceroute_sender, traceroute_receiver) = mpsc::channel();
+        let (resolve_preview_sender, resolve_preview_receiver) = mpsc::channel();
+        let (health_sender, health_receiver) = mpsc::channel();
+        let (bufferbloat_sender, bufferbloat_receiver) = mpsc::channel();
+        let (speed_test_sender, speed_test_receiver) = mpsc::channel();
+        let (path_mtu_sender, path_mtu_receiver) = mpsc::channel();
+        let (report_sender, report_receiver) = mpsc::channel();
+        let (xlsx_export_sender, xlsx_export_receiver) = mpsc::channel();
+        let (pdf_report_sender, pdf_report_receiver) = mpsc::channel();
+        let (pdf_report_from, pdf_report_to) = pdf_report::default_range();
+        let (baseline_sender, baseline_receiver) = mpsc::channel();
+        let (compare_sender, compare_receiver) = mpsc::channel();
+        let (gateway_sender, gateway_receiver) = mpsc::channel();
+        let (gateway_probe_sender, gateway_probe_receiver) = mpsc::channel();
+        let (sweep_sender, sweep_receiver) = mpsc::channel();
+        let (dns_benchmark_sender, dns_benchmark_receiver) = mpsc::channel();
+        let (mdns_sender, mdns_receiver) = mpsc::channel();
+        let (stress_sender, stress_receiver) = mpsc::channel();
         Self {
+            probe: config.probe,
             target: config.target,
+            command: config.command,
             is_monitoring: false,
             ping_results: VecDeque::new(),
             circles: [CircleColor::Gray; NUM_CIRCLES],
             circle_timestamps: [None; NUM_CIRCLES],
-            last_ping_second: None,
+            slots: Default::default(),
+            next_ping_at: None,
             ping_statistics: PingStatistics::default(),
             ping_receiver: None,
             ping_sender: None,
@@ -81,47 +783,443 @@ impl PingMonitorApp {
             dns_cache: DnsCache::new(),
             green_threshold: config.green_threshold,
             yellow_threshold: config.yellow_threshold,
+            very_slow_threshold: config.very_slow_threshold,
+            interval_secs: config.interval_secs,
+            ntp_offset_threshold_ms: config.ntp_offset_threshold_ms,
+            snmp: config.snmp,
+            tcp_expect: config.tcp_expect,
+            tcp_proxy: config.tcp_proxy,
+            http_proxy: config.http_proxy,
+            slot_aggregate: config.slot_aggregate,
+            fade_timings: config.fade_timings,
+            maintenance_windows: config.maintenance_windows,
+            toggle_hotkey: config.toggle_hotkey,
+            show_hotkey: config.show_hotkey,
+            hotkeys,
+            wake_sent_at: None,
+            wake_status: None,
+            plugins,
+            sonar_enabled: config.sonar_enabled,
+            sonar: Sonar::new(),
+            announce_changes: config.announce_changes,
+            log_level: config.log_level,
+            log_view_level: config.log_level,
+            remote_agent: config.remote_agent,
+            agent_token: config.agent_token,
+            agent_port: config.agent_port,
+            remote_viewer: None,
+            agents: config.agents,
+            compare_target: config.compare_target,
+            fallback_chains: config.fallback_chains,
+            fallback_prober: FallbackProber::new(),
+            probe_interfaces: config.probe_interfaces,
+            interface_streams: InterfaceStreams::new(),
+            replay: None,
+            replay_day: None,
+            live_view: None,
+            recent_targets: config.recent_targets,
+            target_list: config.target_list,
+            target_settings: config.target_settings,
+            target_table_view: config.target_table_view,
+            target_sort: TargetColumn::Target,
+            target_sort_ascending: true,
+            target_styles: config.target_styles,
+            sla_percent: config.sla_percent,
+            battery_saver: config.battery_saver,
+            metered_saver: config.metered_saver,
+            fullscreen_saver: config.fullscreen_saver,
+            rediscover_gateway: config.rediscover_gateway,
+            alert_on_dns_change: config.alert_on_dns_change,
+            timestamp_format: config.timestamp_format,
+            main_view: config.main_view,
+            focus_mode: config.focus_mode,
+            auto_stop_enabled: config.auto_stop_enabled,
+            auto_stop_mins: config.auto_stop_mins,
+            session_started: None,
+            session_summary: None,
+            session_sent: 0,
+            session_replies: 0,
+            alerts_silenced_until: None,
+            attention: config.attention,
+            attention_requester: AttentionRequester::default(),
+            grafana: config.grafana,
+            paging: config.paging,
+            ntfy: config.ntfy,
+            pushover: config.pushover,
+            web_dashboard_enabled: config.web_dashboard_enabled,
+            web_dashboard_port: config.web_dashboard_port,
+            web_dashboard_lan: config.web_dashboard_lan,
+            web_dashboard_token: config.web_dashboard_token,
+            web_dashboard_tls_cert: config.web_dashboard_tls_cert,
+            web_dashboard_tls_key: config.web_dashboard_tls_key,
+            web_dashboard: None,
+            web_dashboard_error: None,
+            target_watch: TargetWatch::new(),
+            probe_limiter: Arc::new(Mutex::new(ProbeLimiter::new(config.max_in_flight_probes, config.probe_rate_limit))),
+            import_path: String::new(),
+            import_status: None,
+            agent_hub: AgentHub::new(),
+            new_agent: AgentEndpoint::default(),
             last_response_time: None,
+            config_errors,
+            config_saver: ConfigSaver::default(),
+            resolve_preview: None,
+            previewed_target: String::new(),
+            resolve_preview_due: None,
+            resolve_preview_sender,
+            resolve_preview_receiver,
+            sleep_detector: SleepDetector::new(),
+            sleep_gaps: VecDeque::new(),
+            statistics_since: None,
+            network_watcher: NetworkWatcher::new(),
+            last_recovery: None,
+            network_events: VecDeque::new(),
+            outage_tracker: OutageTracker::new(),
+            traceroute_sender,
+            traceroute_receiver,
+            route_monitor: RouteMonitor::new(),
+            health_report: None,
+            health_check_running: false,
+            health_sender,
+            health_receiver,
+            wifi_monitor: WifiMonitor::new(),
+            bufferbloat_phase: None,
+            bufferbloat_result: None,
+            bufferbloat_sender,
+            bufferbloat_receiver,
+            speed_tests: HistoryStore::open().load_speed_tests(MAX_SPEED_TESTS).into(),
+            speed_test_running: false,
+            speed_test_error: None,
+            speed_test_sender,
+            speed_test_receiver,
+            packet_capture: PacketCapture::new(),
+            capture_window_mins: CAPTURE_WINDOWS_MINS[0],
+            capture_export_status: None,
+            path_mtu_running: false,
+            path_mtu_result: None,
+            path_mtu_sender,
+            path_mtu_receiver,
+            tab: Tab::Monitor,
+            overlay_reference: OverlayReference::default(),
+            history: HistoryStore::open(),
+            history_error: None,
+            annotations: AnnotationStore::open(),
+            note_text: String::new(),
+            annotation_error: None,
+            event_log: EventLog::default(),
+            toasts: Toasts::default(),
+            toast_history_open: false,
+            icmp_access,
+            power_monitor: power::monitor(),
+            metered_monitor: metered::monitor(),
+            fullscreen_monitor: fullscreen::monitor(),
+            animations_off: false,
+            event_export_status: None,
+            report: None,
+            report_running: false,
+            xlsx_export_running: false,
+            xlsx_export_status: None,
+            xlsx_export_sender,
+            xlsx_export_receiver,
+            pdf_report_from,
+            pdf_report_to,
+            pdf_report_running: false,
+            pdf_report_status: None,
+            pdf_report_sender,
+            pdf_report_receiver,
+            report_sender,
+            report_receiver,
+            baseline: None,
+            baseline_running: false,
+            baseline_sender,
+            baseline_receiver,
+            compare_results: VecDeque::new(),
+            compare_sender,
+            compare_receiver,
+            gateway: None,
+            dns_servers: Vec::new(),
+            gateway_lookup_due: true,
+            gateway_sender,
+            gateway_receiver,
+            gateway_probe_sender,
+            gateway_probe_receiver,
+            sweep_range: String::new(),
+            sweep_hosts: Vec::new(),
+            sweep_progress: None,
+            sweep_error: None,
+            sweep_sender,
+            sweep_receiver,
+            dns_benchmark_extra: String::new(),
+            dns_scores: Vec::new(),
+            dns_benchmark_progress: None,
+            dns_benchmark_status: None,
+            dns_benchmark_sender,
+            dns_benchmark_receiver,
+            mdns_devices: Vec::new(),
+            mdns_running: false,
+            mdns_error: None,
+            mdns_sender,
+            mdns_receiver,
+            stress_rate: STRESS_DEFAULT_RATE,
+            stress_duration_secs: STRESS_DEFAULT_SECS,
+            stress_running: false,
+            stress_result: None,
+            stress_sender,
+            stress_receiver,
+            load_window: None,
+            geoip: GeoIp::load(),
         }
     }
 
-    fn save_config(&self) {
-        let config = AppConfig {
+    fn current_config(&self) -> AppConfig {
+        AppConfig {
+            version: config::CONFIG_VERSION,
+            probe: self.probe,
             target: self.target.clone(),
+            command: self.command.clone(),
             green_threshold: self.green_threshold,
             yellow_threshold: self.yellow_threshold,
-        };
+            very_slow_threshold: self.very_slow_threshold,
+            interval_secs: self.interval_secs,
+            ntp_offset_threshold_ms: self.ntp_offset_threshold_ms,
+            snmp: self.snmp.clone(),
+            tcp_expect: self.tcp_expect.clone(),
+            tcp_proxy: self.tcp_proxy.clone(),
+            http_proxy: self.http_proxy.clone(),
+            slot_aggregate: self.slot_aggregate,
+            fade_timings: self.fade_timings,
+            max_in_flight_probes: probe_limiter::lock(&self.probe_limiter).max_in_flight,
+            probe_rate_limit: probe_limiter::lock(&self.probe_limiter).max_per_second,
+            maintenance_windows: self.maintenance_windows.clone(),
+            toggle_hotkey: self.toggle_hotkey.clone(),
+            show_hotkey: self.show_hotkey.clone(),
+            sonar_enabled: self.sonar_enabled,
+            announce_changes: self.announce_changes,
+            log_level: self.log_level,
+            remote_agent: self.remote_agent.clone(),
+            agent_token: self.agent_token.clone(),
+            agent_port: self.agent_port,
+            agents: self.agents.clone(),
+            compare_target: self.compare_target.clone(),
+            fallback_chains: self.fallback_chains.clone(),
+            probe_interfaces: self.probe_interfaces.clone(),
+            recent_targets: self.recent_targets.clone(),
+            target_list: self.target_list.clone(),
+            target_settings: self.target_settings.clone(),
+            target_table_view: self.target_table_view,
+            target_styles: self.target_styles.clone(),
+            sla_percent: self.sla_percent,
+            battery_saver: self.battery_saver.clone(),
+            metered_saver: self.metered_saver.clone(),
+            fullscreen_saver: self.fullscreen_saver.clone(),
+            rediscover_gateway: self.rediscover_gateway,
+            alert_on_dns_change: self.alert_on_dns_change,
+            timestamp_format: self.timestamp_format,
+            main_view: self.main_view,
+            focus_mode: self.focus_mode,
+            auto_stop_enabled: self.auto_stop_enabled,
+            auto_stop_mins: self.auto_stop_mins,
+            attention: self.attention,
+            grafana: self.grafana.clone(),
+            paging: self.paging.clone(),
+            ntfy: self.ntfy.clone(),
+            pushover: self.pushover.clone(),
+            web_dashboard_enabled: self.web_dashboard_enabled,
+            web_dashboard_port: self.web_dashboard_port,
+            web_dashboard_lan: self.web_dashboard_lan,
+            web_dashboard_token: self.web_dashboard_token.clone(),
+            web_dashboard_tls_cert: self.web_dashboard_tls_cert.clone(),
+            web_dashboard_tls_key: self.web_dashboard_tls_key.clone(),
+        }
+    }
+
+    fn save_config(&mut self) {
+        let config = self.current_config();
 
+        // Never replace a good file with values the user is still fixing
+        if !config.validate().is_empty() {
+            return;
+        }
+
+        // Saving happens on its own after every change, so only a failure is worth a toast
+        // (logged errors become one)
         if let Err(e) = config.save() {
-            eprintln!("Failed to save config: {e}");
+            log::error!("Failed to save config: {e}");
+            let message = format!("Failed to save config: {e}");
+            if !self.config_errors.contains(&message) {
+                self.config_errors.push(message);
+            }
         }
     }
 
+    /// Logs an alert and shows it as a toast
+    fn alert(&mut self, severity: AlertSeverity, message: String) {
+        self.alert_with_actions(severity, message, Vec::new());
+    }
+
+    /// Like `alert`, with buttons on the toast for following up
+    fn alert_with_actions(&mut self, severity: AlertSeverity, message: String, actions: Vec<ToastAction>) {
+        if !self.alerts_silenced() {
+            self.attention_requester.request(self.attention.level(severity));
+            self.ntfy.notify(severity, message.clone(), self.web_dashboard_url());
+            self.pushover.notify(severity, message.clone());
+        }
+        self.toasts.push_with_actions(ToastLevel::Warning, message.clone(), actions);
+        self.event_log.push(EventKind::Alert, message);
+    }
+
+    fn alerts_silenced(&self) -> bool {
+        self.alerts_silenced_until.is_some_and(|until| SystemTime::now() < until)
+    }
+
+    fn run_toast_action(&mut self, ctx: &egui::Context, action: ToastAction) {
+        match action {
+            ToastAction::RunTraceroute(outage_id) => self.capture_outage_traceroute(ctx, outage_id),
+            ToastAction::OpenMonitor => {
+                self.focus_mode = false;
+                self.tab = Tab::Monitor;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            ToastAction::Silence => {
+                let until = SystemTime::now() + ALERT_SILENCE;
+                self.alerts_silenced_until = Some(until);
+                let message = format!("Alerts silenced until {}", chrono::DateTime::<chrono::Local>::from(until).format("%H:%M"));
+                self.event_log.push(EventKind::Alert, message.clone());
+                self.toasts.push(ToastLevel::Info, message);
+            }
+        }
+    }
+
+    /// Turns logged warnings and errors into toasts, since stderr isn't visible in GUI builds
+    fn collect_log_notices(&mut self) {
+        for notice in app_log::take_notices() {
+            let level = if notice.level == log::Level::Error { ToastLevel::Error } else { ToastLevel::Warning };
+            self.toasts.push(level, notice.message);
+        }
+    }
+
+    /// Current toasts stacked in the bottom-right corner; clicking one dismisses it
+    fn render_toasts(&mut self, ctx: &egui::Context) {
+        let visible: Vec<(u64, ToastLevel, String, Vec<ToastAction>)> = self
+            .toasts
+            .visible()
+            .into_iter()
+            .map(|toast| (toast.id, toast.level, toast.message.clone(), toast.actions.clone()))
+            .collect();
+        if visible.is_empty() {
+            return;
+        }
+        let mut dismissed = None;
+        let mut chosen = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, Vec2::new(-8.0, -8.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(280.0);
+                for (id, level, message, actions) in visible {
+                    let frame = egui::Frame::popup(ui.style()).stroke(Stroke::new(1.0, level.color()));
+                    let response = frame
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.colored_label(level.color(), level.icon());
+                                ui.label(message);
+                            });
+                            if !actions.is_empty() {
+                                ui.horizontal_wrapped(|ui| {
+                                    for action in actions {
+                                        if ui.small_button(action.label()).clicked() {
+                                            chosen = Some((id, action));
+                                        }
+                                    }
+                                });
+                            }
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismissed = Some(id);
+                    }
+                }
+            });
+        if let Some(id) = dismissed {
+            self.toasts.dismiss(id);
+        }
+        if let Some((id, action)) = chosen {
+            self.toasts.dismiss(id);
+            self.run_toast_action(ctx, action);
+        }
+    }
+
+    /// Drawer listing recent toasts, newest first
+    fn render_toast_history(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("toast_history").resizable(true).max_height(200.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong("Notifications");
+                if ui.small_button("Clear").clicked() {
+                    self.toasts.clear();
+                }
+                if ui.small_button("Close").clicked() {
+                    self.toast_history_open = false;
+                }
+            });
+            egui::ScrollArea::vertical().id_salt("toast_history_scroll").show(ui, |ui| {
+                for toast in self.toasts.history() {
+                    let time = chrono::DateTime::<chrono::Local>::from(toast.time).format("%H:%M:%S");
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(toast.level.color(), toast.level.icon());
+                        ui.weak(time.to_string());
+                        ui.label(&toast.message);
+                    });
+                }
+            });
+        });
+    }
+
     fn get_circle_color(&self, ping_result: &PingResult) -> CircleColor {
-        if !ping_result.success {
-            return CircleColor::Red;
+        match ping_result.failure_cause {
+            Some(FailureCause::LinkDown) => return CircleColor::LinkDown,
+            Some(FailureCause::ProxyFailed) => return CircleColor::Gray,
+            Some(_) => return CircleColor::Red,
+            None => {}
+        }
+        if ping_result.degraded {
+            return CircleColor::Degraded;
         }
         
         CircleColor::from_ping_response(
             ping_result.response_time,
             self.green_threshold,
-            self.yellow_threshold
+            self.yellow_threshold,
+            self.very_slow_threshold,
         )
     }
 
     fn update_statistics(&mut self) {
-        let now = SystemTime::now();
-        let cutoff_time = now - Duration::from_secs(STATISTICS_WINDOW_SECS);
+        let cutoff_time = self.clock_now().checked_sub(Duration::from_secs(STATISTICS_WINDOW_SECS));
         
         // Filter ping results to only include those from the last 60 seconds
-        let recent_results: Vec<&PingResult> = self.ping_results
+        let in_window = |r: &PingResult| {
+            cutoff_time.is_none_or(|cutoff| r.sent_at >= cutoff) && self.statistics_since.is_none_or(|since| r.sent_at >= since)
+        };
+        let window_results: Vec<&PingResult> = self.ping_results.iter().filter(|r| in_window(r)).collect();
+        // Spikes are judged against all kept results so the first ones in the window have a baseline
+        let spike_flags = spike::spike_flags(self.ping_results.iter().map(|r| r.response_time));
+        let spikes = self.ping_results
             .iter()
-            .filter(|r| r.timestamp >= cutoff_time)
-            .collect();
+            .zip(spike_flags)
+            .filter(|(r, spike)| *spike && in_window(r) && !r.in_maintenance)
+            .count() as u64;
+        let maintenance = window_results.iter().filter(|r| r.in_maintenance).count() as u64;
+        let recent_results: Vec<&PingResult> = window_results.into_iter().filter(|r| !r.in_maintenance).collect();
         
         let total = recent_results.len() as u64;
         let successful = recent_results.iter().filter(|r| r.success).count() as u64;
         let failed = total - successful;
+        let mut failure_causes = std::collections::BTreeMap::new();
+        for cause in recent_results.iter().filter_map(|r| r.failure_cause) {
+            *failure_causes.entry(cause).or_insert(0) += 1;
+        }
         
         let total_response_time: f64 = recent_results
             .iter()
@@ -132,28 +1230,240 @@ impl PingMonitorApp {
             total_pings: total,
             successful_pings: successful,
             failed_pings: failed,
+            failure_causes,
+            maintenance_pings: maintenance,
             total_response_time,
             loss_rate: if total > 0 { (failed as f64 / total as f64) * 100.0 } else { 0.0 },
             mean_response_time: if successful > 0 { total_response_time / successful as f64 } else { 0.0 },
+            spikes,
+        };
+    }
+
+    /// Seconds each circle of the dial covers at the interval in use, so a saver's longer
+    /// interval gets one probe per circle rather than leaving gaps
+    fn slot_secs(&self) -> u64 {
+        clock_slot::slot_secs(self.effective_interval_secs())
+    }
+
+    /// Time the hand takes around the dial: a minute at intervals up to 5 s, longer beyond
+    fn dial_period(&self) -> Duration {
+        Duration::from_secs(self.slot_secs() * NUM_CIRCLES as u64)
+    }
+
+    fn circle_index_for_time(&self, time: SystemTime) -> usize {
+        clock_slot::circle_of(time, self.slot_secs(), NUM_CIRCLES)
+    }
+
+    /// Coarse state that only changes on transitions, so announcing it doesn't repeat every probe
+    fn status_state(&self) -> &'static str {
+        if !self.is_monitoring {
+            return "stopped";
+        }
+        if self.outside_schedule() {
+            return "paused outside monitoring hours";
+        }
+        if self.fullscreen_action() == FullscreenAction::Pause {
+            return "paused while a full-screen app is active";
+        }
+        match self.ping_results.back() {
+            Some(result) => self.get_circle_color(result).description(),
+            None => "waiting for first reply",
+        }
+    }
+
+    /// Full status for screen readers: state, last RTT and loss rate
+    fn status_summary(&self) -> String {
+        let style = self.target_style(&self.history_target());
+        let target = if self.probe == ProbeType::Command && style.label.trim().is_empty() {
+            "command".to_string()
+        } else {
+            style.name(&self.history_target())
+        };
+        let last_reply = match self.last_response_time {
+            Some(time) if time < 1.0 => format!("last reply {:.0} microseconds", time * 1000.0),
+            Some(time) => format!("last reply {time:.0} milliseconds"),
+            None => "no recent reply".to_string(),
+        };
+        format!(
+            "{target} {}, {last_reply}, loss {:.1} percent",
+            self.status_state(),
+            self.ping_statistics.loss_rate,
+        )
+    }
+
+    fn render_status(&mut self, ui: &mut egui::Ui) {
+        let response = ui.label(format!("Status: {}", self.status_state()));
+        if self.announce_changes {
+            // A live region makes screen readers speak the label whenever its text changes
+            ui.ctx().accesskit_node_builder(response.id, |node| node.set_live(egui::accesskit::Live::Polite));
+        }
+        if self.probe == ProbeType::Ntp {
+            self.render_clock_offset(ui);
+        }
+        if let Some(summary) = &self.session_summary {
+            ui.label(format!("⏱ {summary}"));
+        }
+        if let Some(summary) = self.outage_tracker.ongoing().and_then(|outage| outage.loss_summary.as_ref()) {
+            ui.colored_label(Color32::from_rgb(255, 165, 0), format!("📍 {summary}"));
+        }
+        if let Some(replay) = &self.replay {
+            let mut exit = false;
+            ui.horizontal(|ui| {
+                let position = self.timestamp_format.display(replay.position());
+                ui.colored_label(Color32::from_rgb(255, 200, 0), format!("⏪ Replay of {} at {position}", replay.target));
+                exit = ui.small_button("Back to live").clicked();
+            });
+            if exit {
+                self.stop_replay();
+            }
+        }
+        if let Some(until) = self.alerts_silenced_until.filter(|_| self.alerts_silenced()) {
+            ui.horizontal(|ui| {
+                ui.label(format!("🔕 Alerts silenced until {}", chrono::DateTime::<chrono::Local>::from(until).format("%H:%M")));
+                if ui.small_button("Unsilence").clicked() {
+                    self.alerts_silenced_until = None;
+                }
+            });
+        }
+    }
+
+    /// Latest offset from the NTP server, flagged when it's beyond the configured threshold
+    fn render_clock_offset(&self, ui: &mut egui::Ui) {
+        let Some(offset) = self.ping_results.iter().rev().find_map(|result| result.clock_offset) else {
+            return;
         };
+        let threshold = self.ntp_offset_threshold_ms as f64;
+        let response = if offset.abs() > threshold {
+            ui.colored_label(
+                Color32::from_rgb(255, 100, 100),
+                format!("⚠ Clock offset {offset:+.1} ms exceeds ±{threshold:.0} ms"),
+            )
+        } else {
+            ui.label(format!("Clock offset: {offset:+.1} ms"))
+        };
+        if self.announce_changes && offset.abs() > threshold {
+            ui.ctx().accesskit_node_builder(response.id, |node| node.set_live(egui::accesskit::Live::Polite));
+        }
     }
 
-    fn get_circle_index_for_time(time: SystemTime) -> usize {
-        let duration = time.duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
-        let seconds = duration.as_secs();
-        ((seconds % 60) / 5) as usize
+    /// Recent probes newest first, with the exact figures the clock face only hints at by color
+    fn render_probe_list(&self, ui: &mut egui::Ui) {
+        if self.ping_results.is_empty() {
+            ui.label("No probes yet");
+            return;
+        }
+        egui::ScrollArea::vertical().id_salt("probe_list_scroll").max_height(PROBE_LIST_HEIGHT).show(ui, |ui| {
+            egui::Grid::new("probe_list").striped(true).show(ui, |ui| {
+                for heading in ["Time", "RTT", "Outcome", "IP"] {
+                    ui.strong(heading);
+                }
+                ui.end_row();
+                for result in self.ping_results.iter().rev() {
+                    let color = self.get_circle_color(result);
+                    let outcome = match result.failure_cause {
+                        Some(cause) => cause.label(),
+                        None if result.in_maintenance => "maintenance",
+                        None => color.description(),
+                    };
+                    ui.label(self.timestamp_format.display(result.timestamp));
+                    ui.label(result.response_time.filter(|_| result.success).map_or("–".to_string(), ping::format_ms));
+                    ui.colored_label(color.to_color32(), format!("● {outcome}"));
+                    ui.label(result.target_ip.map_or("–".to_string(), |ip| ip.to_string()));
+                    ui.end_row();
+                }
+            });
+        });
     }
 
-    fn draw_clock_face(&self, ui: &mut egui::Ui) {
-        let available_rect = ui.available_rect_before_wrap();
-        let center = available_rect.center();
+    /// Returns true when the face was double-clicked, which toggles focus mode
+    fn draw_clock_face(&self, ui: &mut egui::Ui) -> bool {
+        let clock_height = 240.0;
+        let (clock_rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), clock_height), egui::Sense::click());
+        let double_clicked = response.double_clicked();
+        let summary = self.status_summary();
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, true, &summary));
+        let center = clock_rect.center();
         let radius = 100.0;
         let circle_radius = 10.0;
         
         let painter = ui.painter();
         
         self.draw_circles(center, radius, circle_radius, painter, ui);
+        self.draw_route_change_marks(center, radius + circle_radius + 6.0, painter);
+        self.draw_annotation_marks(center, radius + circle_radius + 12.0, painter);
+        self.draw_spike_marks(center, radius + circle_radius + 19.0, painter);
+        self.draw_dns_change_marks(center, radius + circle_radius + 26.0, painter);
         self.draw_second_hand(center, radius, painter);
+        if let Some(pointer) = response.hover_pos()
+            && let Some(text) = self.circle_tooltip(center, radius, circle_radius, pointer)
+        {
+            response.on_hover_text(text);
+        }
+
+        if self.is_monitoring && self.outside_schedule() {
+            painter.text(
+                center + Vec2::new(0.0, radius * 0.45),
+                egui::Align2::CENTER_CENTER,
+                "Outside schedule",
+                egui::FontId::proportional(14.0),
+                ui.visuals().strong_text_color(),
+            );
+        }
+        double_clicked
+    }
+
+    /// The monitored target's per-target settings: its target list entry, or else its entry in
+    /// `target_settings`
+    fn target_entry(&self) -> Option<&TargetEntry> {
+        let target = self.target.trim();
+        self.target_list.iter().chain(&self.target_settings).find(|entry| entry.host == target)
+    }
+
+    /// Changes the monitored target's settings. A target that isn't in the list keeps them in
+    /// `target_settings`, so setting them doesn't watch it in the list as well; that entry is
+    /// dropped again once nothing is set. A new entry takes the DNS probe when the target is
+    /// monitored as a resolver, matching how it's probed, rather than the default ICMP.
+    fn edit_target_entry(&mut self, edit: impl FnOnce(&mut TargetEntry)) {
+        let target = self.target.trim().to_string();
+        if let Some(entry) = self.target_list.iter_mut().find(|entry| entry.host == target) {
+            edit(entry);
+            return;
+        }
+        let index = match self.target_settings.iter().position(|entry| entry.host == target) {
+            Some(index) => index,
+            None => {
+                let probe = if self.probe == ProbeType::Dns { ProbeType::Dns } else { ProbeType::Icmp };
+                self.target_settings.push(TargetEntry { host: target, probe, ..TargetEntry::default() });
+                self.target_settings.len() - 1
+            }
+        };
+        edit(&mut self.target_settings[index]);
+        self.target_settings.retain(TargetEntry::has_settings);
+    }
+
+    /// True when the target's monitoring hours are configured and none of them covers the current time
+    fn outside_schedule(&self) -> bool {
+        self.target_entry().is_some_and(|entry| entry.outside_schedule(chrono::Local::now()))
+    }
+
+    /// The latest result on the circle under `pointer`, with why it failed if it did
+    fn circle_tooltip(&self, center: Pos2, radius: f32, circle_radius: f32, pointer: Pos2) -> Option<String> {
+        let index = (0..NUM_CIRCLES).find(|&i| {
+            let angle = (i as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
+            Self::place_in_circle(center, radius, angle).distance(pointer) <= circle_radius
+        })?;
+        let cutoff = self.clock_now().checked_sub(self.dial_period());
+        let result = self.ping_results
+            .iter()
+            .rev()
+            .take_while(|r| cutoff.is_none_or(|cutoff| r.sent_at >= cutoff))
+            .find(|r| self.circle_index_for_time(r.timestamp) == index)?;
+        let outcome = match (result.failure_cause, result.response_time) {
+            (Some(cause), _) => cause.label().to_string(),
+            (None, Some(ms)) => ping::format_ms(ms),
+            (None, None) => "no reply".to_string(),
+        };
+        Some(format!("{} {outcome}", self.timestamp_format.display(result.timestamp)))
     }
 
     fn draw_circles(&self, center: Pos2, radius: f32, circle_radius: f32, painter: &egui::Painter, ui: &egui::Ui) {
@@ -161,7 +1471,11 @@ impl PingMonitorApp {
             let angle = (i as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
             let pos = Self::place_in_circle(center, radius, angle);
             
-            let color = self.get_circle_color_with_age(i);
+            let color = if self.is_monitoring && self.outside_schedule() {
+                OUTSIDE_SCHEDULE_COLOR
+            } else {
+                self.get_circle_color_with_age(i)
+            };
             painter.circle_filled(pos, circle_radius, color);
             
             let stroke_color = if self.pending_pings.contains_key(&i) {
@@ -170,33 +1484,138 @@ impl PingMonitorApp {
                 Color32::BLACK
             };
             painter.circle_stroke(pos, circle_radius, Stroke::new(2.0, stroke_color));
+            if let (Some(window), Some(sent_at)) = (&self.load_window, self.circle_timestamps[i])
+                && window.contains(sent_at)
+            {
+                painter.circle_stroke(pos, circle_radius + 3.0, Stroke::new(2.0, LOAD_WINDOW_COLOR));
+            }
             
             self.draw_circle_label(center, radius, angle, i, painter, ui);
         }
     }
 
+    /// Small dots outside the dial at the slot where a route change was detected in the last turn of the dial
+    fn draw_route_change_marks(&self, center: Pos2, radius: f32, painter: &egui::Painter) {
+        let recent_changes = self.route_monitor.changes
+            .iter()
+            .filter(|change| change.detected_at.elapsed() < self.dial_period());
+        for change in recent_changes {
+            let index = self.circle_index_for_time(change.detected);
+            let angle = (index as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
+            painter.circle_filled(Self::place_in_circle(center, radius, angle), 3.0, Color32::LIGHT_BLUE);
+        }
+    }
+
+    /// Small squares outside the dial at the slot of each note dropped in the last turn of the dial
+    fn draw_annotation_marks(&self, center: Pos2, radius: f32, painter: &egui::Painter) {
+        let Some(cutoff) = SystemTime::now().checked_sub(self.dial_period()) else {
+            return;
+        };
+        for note in self.annotations.since(cutoff) {
+            let index = self.circle_index_for_time(note.timestamp());
+            let angle = (index as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
+            let mark = egui::Rect::from_center_size(Self::place_in_circle(center, radius, angle), Vec2::splat(5.0));
+            painter.rect_filled(mark, 0.0, ANNOTATION_COLOR);
+        }
+    }
+
+    /// Small diamonds outside the dial at the slot where the target's address changed in the last turn of the dial
+    fn draw_dns_change_marks(&self, center: Pos2, radius: f32, painter: &egui::Painter) {
+        let recent_changes = self.network_events.iter().filter(|(time, event)| {
+            matches!(event, NetworkEvent::TargetAddressChanged { .. }) && time.elapsed().is_ok_and(|age| age < self.dial_period())
+        });
+        for (time, _) in recent_changes {
+            let index = self.circle_index_for_time(*time);
+            let angle = (index as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
+            let at = Self::place_in_circle(center, radius, angle);
+            let points = vec![at + Vec2::new(0.0, -4.0), at + Vec2::new(4.0, 0.0), at + Vec2::new(0.0, 4.0), at + Vec2::new(-4.0, 0.0)];
+            painter.add(egui::Shape::convex_polygon(points, NETWORK_CHANGE_COLOR, Stroke::NONE));
+        }
+    }
+
+    /// Small triangles outside the dial at the slot of each latency spike in the last turn of the dial
+    fn draw_spike_marks(&self, center: Pos2, radius: f32, painter: &egui::Painter) {
+        let cutoff = self.clock_now().checked_sub(self.dial_period());
+        let flags = spike::spike_flags(self.ping_results.iter().map(|result| result.response_time));
+        for (result, _) in self.ping_results.iter().zip(flags).filter(|(_, spike)| *spike) {
+            if cutoff.is_some_and(|cutoff| result.sent_at < cutoff) {
+                continue;
+            }
+            let index = self.circle_index_for_time(result.timestamp);
+            let angle = (index as f32 * 30.0 - 90.0) * std::f32::consts::PI / 180.0;
+            Self::draw_spike_glyph(painter, Self::place_in_circle(center, radius, angle), SPIKE_COLOR);
+        }
+    }
+
+    /// Upward triangle centred on `at`, the mark for a latency spike
+    fn draw_spike_glyph(painter: &egui::Painter, at: Pos2, color: Color32) {
+        let points = vec![at + Vec2::new(0.0, -4.0), at + Vec2::new(4.0, 3.0), at + Vec2::new(-4.0, 3.0)];
+        painter.add(egui::Shape::convex_polygon(points, color, Stroke::NONE));
+    }
+
+    /// Drops a note at the current time with the typed text, or a plain marker when none was typed
+    fn add_note(&mut self) {
+        let result = self.annotations.add(SystemTime::now(), &self.note_text);
+        if let Some(note) = self.annotations.notes.last() {
+            self.grafana.annotate("note", note.timestamp(), None, note.text.clone());
+        }
+        self.annotation_error = result.err().map(|e| format!("Note kept for this session only, saving failed: {e}"));
+        self.note_text.clear();
+    }
+
+    /// Writes the notes and events from within `window` next to an exported file, returning a suffix
+    /// for the status line
+    fn export_notes_beside(&self, path: &std::path::Path, window: Duration) -> String {
+        let cutoff = SystemTime::now().checked_sub(window).unwrap_or(UNIX_EPOCH);
+        let mut suffix = String::new();
+        if self.annotations.since(cutoff).next().is_some() {
+            let notes_path = path.with_extension("notes.csv");
+            suffix += &match self.annotations.export_csv(&notes_path, cutoff, self.timestamp_format) {
+                Ok(count) => format!(", {count} notes to {}", notes_path.display()),
+                Err(e) => format!(" (notes could not be exported: {e})"),
+            };
+        }
+        if self.event_log.since(cutoff).next().is_some() {
+            let events_path = path.with_extension("events.csv");
+            suffix += &match self.event_log.export_csv(&events_path, cutoff, self.timestamp_format) {
+                Ok(count) => format!(", {count} events to {}", events_path.display()),
+                Err(e) => format!(" (events could not be exported: {e})"),
+            };
+        }
+        suffix
+    }
+
+    fn effective_fade_timings(&self) -> FadeTimings {
+        self.fade_timings
+            .unwrap_or_else(|| FadeTimings::derived(self.effective_interval_secs(), self.slot_secs(), NUM_CIRCLES))
+    }
+
     fn get_circle_color_with_age(&self, circle_index: usize) -> Color32 {
-        if let Some(timestamp) = self.circle_timestamps[circle_index] {
-            if let Ok(elapsed) = SystemTime::now().duration_since(timestamp) {
-                let elapsed_seconds = elapsed.as_secs_f64();
-                return self.circles[circle_index].to_color32_with_age(elapsed_seconds);
+        match self.circle_timestamps[circle_index] {
+            Some(timestamp) => {
+                let elapsed_seconds = self.clock_now().saturating_duration_since(timestamp).as_secs_f64();
+                self.circles[circle_index].to_color32_with_age(elapsed_seconds, self.effective_fade_timings())
             }
+            None => self.circles[circle_index].to_color32(),
         }
-        self.circles[circle_index].to_color32()
     }
 
     fn draw_circle_label(&self, center: Pos2, radius: f32, angle: f32, index: usize, painter: &egui::Painter, ui: &egui::Ui) {
-        let text = format!("{}", index * 5);
+        let text = format!("{}", index as u64 * self.slot_secs());
         let text_pos = Self::place_in_circle(center, radius - 25.0, angle);
         let font = egui::FontId::monospace(12.0);
         painter.text(text_pos, egui::Align2::CENTER_CENTER, text, font, ui.visuals().text_color());
     }
 
     fn draw_second_hand(&self, center: Pos2, radius: f32, painter: &egui::Painter) {
-        let now = SystemTime::now();
+        let now = self.replay.as_ref().map_or_else(SystemTime::now, Replay::position);
         let duration = now.duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
-        let total_ms = duration.as_millis() % 60000;
-        let second_angle = (total_ms as f32 * 6.0 / 1000.0 - 90.0) * std::f32::consts::PI / 180.0;
+        let period_ms = self.dial_period().as_millis();
+        let mut total_ms = duration.as_millis() % period_ms;
+        if self.saving_power() && self.battery_saver.no_animations {
+            total_ms -= total_ms % 1000;
+        }
+        let second_angle = (total_ms as f32 * 360.0 / period_ms as f32 - 90.0) * std::f32::consts::PI / 180.0;
         let hand_length = radius * 0.8;
         let hand_end = Pos2::new(
             center.x + hand_length * second_angle.cos(),
@@ -217,35 +1636,94 @@ impl PingMonitorApp {
 
 impl eframe::App for PingMonitorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let previous_target = self.target.clone();
-        let previous_green = self.green_threshold;
-        let previous_yellow = self.yellow_threshold;
+        let previous_config = self.current_config();
         
-        // Process incoming ping results
-        self.process_ping_results();
+        self.detect_sleep_gap();
+        self.detect_network_change(ctx);
+        self.handle_hotkeys(ctx);
+        self.update_resolve_preview(ctx);
+        if ctx.input_mut(|input| input.consume_shortcut(&NOTE_SHORTCUT)) {
+            self.add_note();
+        }
+        if ctx.input_mut(|input| input.consume_shortcut(&FOCUS_SHORTCUT)) {
+            self.focus_mode = !self.focus_mode;
+        }
+
+        // Process incoming ping results, gateway ones first so an outage starting this frame is judged with them
+        self.process_gateway_probe_results();
+        self.process_fallback_results();
+        self.process_ping_results(ctx);
+        self.plugins.poll();
+        self.process_replay();
+        self.check_auto_stop();
+        self.process_traceroute_results();
+        self.process_health_reports();
+        self.process_bufferbloat_updates();
+        self.process_speed_test_results();
+        self.process_path_mtu_results();
+        self.process_reports();
+        self.process_compare_results();
+        self.update_gateway(ctx);
+        self.process_sweep_updates();
+        self.process_dns_benchmark_updates();
+        self.process_mdns_updates();
+        self.process_stress_updates();
+        self.handle_dropped_files(ctx);
+        self.target_watch.sync(&self.target_list);
+        self.target_watch.poll(&mut probe_limiter::lock(&self.probe_limiter));
+        self.interface_streams.sync(&self.probe_interfaces);
+        self.interface_streams.poll(&mut probe_limiter::lock(&self.probe_limiter));
+        self.refresh_baseline(ctx);
+        self.update_power_source(ctx);
+        self.update_metered(ctx);
+        self.update_fullscreen(ctx);
+        self.agent_hub.sync(&self.agents, ctx);
+        self.agent_hub.poll();
+        self.sync_web_dashboard();
         
         // Clean up old pending pings
         self.cleanup_pending_pings();
         
         // Handle periodic pinging
-        if self.is_monitoring {
-            self.handle_periodic_ping();
+        if self.is_monitoring && !self.outside_schedule() && self.fullscreen_action() != FullscreenAction::Pause {
+            if self.probe == ProbeType::Remote {
+                self.handle_remote_agent(ctx);
+            } else {
+                self.handle_periodic_ping(ctx);
+                self.handle_route_check(ctx);
+            }
+        } else {
+            // Re-align to the next boundary when monitoring resumes
+            self.next_ping_at = None;
+            self.remote_viewer = None;
         }
 
         // Render UI
+        self.collect_log_notices();
         self.render_ui(ctx);
+        self.render_toasts(ctx);
+        self.attention_requester.poll(ctx);
         
-        // Save config if changed
-        if previous_target != self.target || previous_green != self.green_threshold || previous_yellow != self.yellow_threshold {
+        // Save config a moment after the last change
+        if self.current_config() != previous_config {
+            self.config_saver.mark_changed();
+        }
+        if self.config_saver.take_due() {
             self.save_config();
         }
         
-        ctx.request_repaint_after(Duration::from_millis(100));
+        self.schedule_repaint(ctx);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if self.config_saver.take_pending() {
+            self.save_config();
+        }
     }
 }
 
 impl PingMonitorApp {
-    fn process_ping_results(&mut self) {
+    fn process_ping_results(&mut self, ctx: &egui::Context) {
         let mut ping_results_to_process = Vec::new();
         if let Some(receiver) = &self.ping_receiver {
             while let Ok(ping_result) = receiver.try_recv() {
@@ -253,17 +1731,116 @@ impl PingMonitorApp {
             }
         }
         
-        for ping_result in ping_results_to_process {
-            let circle_index = Self::get_circle_index_for_time(ping_result.timestamp);
-            self.circles[circle_index] = self.get_circle_color(&ping_result);
-            self.circle_timestamps[circle_index] = Some(ping_result.timestamp);
+        let target = self.history_target();
+        for mut ping_result in ping_results_to_process {
+            probe_limiter::lock(&self.probe_limiter).finished(&target);
+            // The agent probes on its own schedule, so its results are all this end sees
+            if self.probe == ProbeType::Remote {
+                self.session_sent += 1;
+            }
+            if ping_result.success {
+                self.session_replies += 1;
+            }
+            if !ping_result.success && self.network_watcher.link_down() {
+                ping_result.failure_cause = Some(FailureCause::LinkDown);
+            }
+            ping_result.in_maintenance = schedule::active_window(&self.maintenance_windows, ping_result.timestamp.into()).is_some();
+            ping_result.vpn = self.vpn_for(ping_result.target_ip);
+            let circle_index = self.circle_index_for_time(ping_result.timestamp);
+            let color = self.get_circle_color(&ping_result);
+            self.slots[circle_index].record(ping_result.timestamp, self.slot_secs(), color, ping_result.response_time.filter(|_| ping_result.success));
+            self.circles[circle_index] = self.slots[circle_index].aggregate(
+                self.slot_aggregate,
+                self.green_threshold,
+                self.yellow_threshold,
+                self.very_slow_threshold,
+            );
+            self.circle_timestamps[circle_index] = Some(ping_result.sent_at);
             
             self.last_response_time = ping_result.response_time;
             
             // Update DNS cache if we have resolution info
-            if let Some((hostname, ip)) = &ping_result.resolved_ip {
-                if hostname != &ip.to_string() {
-                    self.dns_cache.insert(hostname.clone(), DnsCacheEntry::new(*ip, DNS_CACHE_TTL_SECS));
+            if let Some((hostname, ip)) = &ping_result.resolved_ip
+                && hostname != &ip.to_string()
+            {
+                match self.dns_cache.insert(hostname.clone(), DnsCacheEntry::new(*ip, DNS_CACHE_TTL_SECS)) {
+                    AnswerChange::First => self.event_log.push(EventKind::Dns, format!("{hostname} resolved to {ip}")),
+                    AnswerChange::Changed(previous) => {
+                        let event = NetworkEvent::TargetAddressChanged { hostname: hostname.clone(), previous, current: *ip };
+                        if self.alert_on_dns_change {
+                            self.alert(AlertSeverity::Warning, format!("DNS answer changed: {event}"));
+                        }
+                        self.record_network_event(event);
+                    }
+                    AnswerChange::Unchanged => {}
+                }
+            }
+            
+            self.packet_capture.record(&ping_result);
+            let record = HistoryRecord::from_result(&ping_result, &target);
+            match self.history.append(&record) {
+                Ok(()) => self.history_error = None,
+                Err(e) => {
+                    if self.history_error.is_none() {
+                        log::error!("Could not save history: {e}");
+                    }
+                    self.history_error = Some(format!("Could not save history: {e}"));
+                }
+            }
+            if ping_result.success && let Some(sent_at) = self.wake_sent_at.take() {
+                self.wake_status = Some(format!("Target woke up {:.0} s after the wake packet", sent_at.elapsed().as_secs_f64()));
+            }
+            let outage_event = self.outage_tracker.record(&ping_result);
+            if let Some(OutageEvent::Started(outage_id)) = outage_event
+                && self.probe == ProbeType::Icmp
+            {
+                self.capture_outage_traceroute(ctx, outage_id);
+            }
+            self.plugins.on_result(&ping_result, &self.target);
+            if let Some(window) = &mut self.load_window {
+                window.record(&ping_result);
+            }
+            if self.sonar_enabled {
+                self.sonar.play(ping_result.response_time);
+            }
+            if let Some(event) = outage_event
+                && let Some(outage) = self.outage_tracker.outages.back()
+            {
+                match event {
+                    OutageEvent::Started(_) => {
+                        self.plugins.on_outage_start(outage);
+                        let mut message = format!("Outage started ({} stopped answering)", self.target_style(&target).name(&target));
+                        if let Some(location) = outage.fault_location() {
+                            message += &format!("; likely {}", location.label());
+                        }
+                        if let Some(fallbacks) = outage.fallback_summary() {
+                            message += &format!("; fallbacks: {fallbacks}");
+                        }
+                        self.grafana.annotate("outage", outage.started, None, message.clone());
+                        self.paging.trigger(&target, outage.started, message.clone());
+                        let actions = vec![ToastAction::RunTraceroute(outage.id), ToastAction::OpenMonitor, ToastAction::Silence];
+                        self.alert_with_actions(AlertSeverity::Critical, message, actions);
+                    }
+                    OutageEvent::Ended(_) => {
+                        self.plugins.on_outage_end(outage);
+                        let mut message = format!("Outage ended after {:.1} min, {} failed probes", outage.duration().as_secs_f64() / 60.0, outage.failed_pings);
+                        if let Some(location) = outage.fault_location() {
+                            message += &format!("; {}", location.label());
+                        }
+                        if let Some(fallbacks) = outage.fallback_summary() {
+                            message += &format!("; fallbacks: {fallbacks}");
+                        }
+                        self.grafana.annotate("outage", outage.started, outage.ended, format!("{message} ({target})"));
+                        self.paging.resolve(&target, outage.started, message.clone());
+                        self.alert(AlertSeverity::Info, message);
+                    }
+                }
+            }
+            if let Some(offset) = ping_result.clock_offset {
+                let threshold = self.ntp_offset_threshold_ms as f64;
+                let previous = self.ping_results.iter().rev().find_map(|result| result.clock_offset);
+                if offset.abs() > threshold && previous.is_none_or(|previous| previous.abs() <= threshold) {
+                    self.alert(AlertSeverity::Warning, format!("Clock offset {offset:+.1} ms exceeds ±{threshold:.0} ms"));
                 }
             }
             
@@ -278,124 +1855,3505 @@ impl PingMonitorApp {
         }
     }
 
-    fn cleanup_pending_pings(&mut self) {
-        let now = SystemTime::now();
-        let timeout_duration = Duration::from_secs(PENDING_PING_TIMEOUT_SECS);
-        self.pending_pings.retain(|_, &mut timestamp| {
-            now.duration_since(timestamp).unwrap_or(Duration::from_secs(0)) < timeout_duration
-        });
+    /// Now on the monotonic clock, or the replay position while a replay is shown
+    fn clock_now(&self) -> Instant {
+        self.replay.as_ref().map_or_else(Instant::now, Replay::clock)
     }
 
-    fn handle_periodic_ping(&mut self) {
-        let now = SystemTime::now();
-        let duration = now.duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
-        let current_second = duration.as_secs();
-        let current_5sec_boundary = (current_second / PING_INTERVAL_SECS) * PING_INTERVAL_SECS;
-        
-        let should_ping = match self.last_ping_second {
-            Some(last) => current_5sec_boundary > last,
-            None => current_second % PING_INTERVAL_SECS == 0,
-        };
+    /// Puts `view` on screen and returns what was shown before
+    fn swap_view(&mut self, mut view: LiveView) -> LiveView {
+        std::mem::swap(&mut self.circles, &mut view.circles);
+        std::mem::swap(&mut self.circle_timestamps, &mut view.circle_timestamps);
+        std::mem::swap(&mut self.slots, &mut view.slots);
+        std::mem::swap(&mut self.ping_results, &mut view.ping_results);
+        std::mem::swap(&mut self.last_response_time, &mut view.last_response_time);
+        std::mem::swap(&mut self.outage_tracker, &mut view.outage_tracker);
+        std::mem::swap(&mut self.event_log, &mut view.event_log);
+        self.update_statistics();
+        view
+    }
 
-        if should_ping {
-            self.initiate_ping(now, current_5sec_boundary);
+    fn start_replay(&mut self, day: chrono::NaiveDate) {
+        let target = self.history_target();
+        let Some(replay) = Replay::load(&self.history, &target, day) else {
+            self.toasts.push(ToastLevel::Info, format!("No history for {} on {day}", self.target_style(&target).name(&target)));
+            return;
+        };
+        let shown = self.swap_view(LiveView::empty());
+        match (&mut self.live_view, &self.replay) {
+            // Starting over from another replay keeps the live view stashed the first time,
+            // along with the live events logged during the previous replay
+            (Some(live_view), Some(previous)) => live_view.event_log.append_since(shown.event_log, previous.started),
+            (Some(_), None) => {}
+            (None, _) => self.live_view = Some(shown),
         }
+        self.replay = Some(replay);
     }
 
-    fn initiate_ping(&mut self, now: SystemTime, current_5sec_boundary: u64) {
-        let circle_index = Self::get_circle_index_for_time(now);
-        
-        // Only start a new ping if we're not already pinging this circle
-        if self.pending_pings.contains_key(&circle_index) {
+    fn stop_replay(&mut self) {
+        let Some(replay) = self.replay.take() else {
             return;
+        };
+        if let Some(live_view) = self.live_view.take() {
+            // Events logged while the replay was shown are live ones; replayed events are dated before it started
+            let replayed = self.swap_view(live_view);
+            self.event_log.append_since(replayed.event_log, replay.started);
         }
+    }
 
-        // Initialize channel if needed
-        if self.ping_receiver.is_none() {
-            let (sender, receiver) = mpsc::channel();
-            self.ping_receiver = Some(receiver);
-            self.ping_sender = Some(sender);
-        }
-        
-        if let Some(sender) = &self.ping_sender {
-            let target = self.target.clone();
-            let sender_clone = sender.clone();
-            
-            // Check for valid cached IP
-            if let Some(cached_ip) = self.dns_cache.get_valid_ip(&target) {
-                PingExecutor::ping_with_ip(cached_ip, sender_clone);
-            } else {
-                // Clean expired cache and resolve
-                self.dns_cache.clean_expired(&target);
-                PingExecutor::resolve_and_ping(target, sender_clone);
+    /// Feeds replayed results to the dial, charts, outages and events only; nothing is saved,
+    /// alerted or sent anywhere
+    fn process_replay(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        for result in replay.tick() {
+            let circle_index = self.circle_index_for_time(result.timestamp);
+            let color = self.get_circle_color(&result);
+            self.slots[circle_index].record(result.timestamp, self.slot_secs(), color, result.response_time.filter(|_| result.success));
+            self.circles[circle_index] = self.slots[circle_index].aggregate(
+                self.slot_aggregate,
+                self.green_threshold,
+                self.yellow_threshold,
+                self.very_slow_threshold,
+            );
+            self.circle_timestamps[circle_index] = Some(result.sent_at);
+            self.last_response_time = result.response_time;
+
+            let outage_event = self.outage_tracker.record(&result);
+            if let Some(event) = outage_event
+                && let Some(outage) = self.outage_tracker.outages.back()
+            {
+                let message = match event {
+                    OutageEvent::Started(_) => "Outage started".to_string(),
+                    OutageEvent::Ended(_) => format!("Outage ended after {:.1} min, {} failed probes", outage.duration().as_secs_f64() / 60.0, outage.failed_pings),
+                };
+                self.event_log.push_at(result.timestamp, EventKind::Alert, message);
             }
-            
-            self.pending_pings.insert(circle_index, now);
-            self.last_ping_second = Some(current_5sec_boundary);
+
+            self.ping_results.push_back(result);
+            if self.ping_results.len() > MAX_PING_RESULTS {
+                self.ping_results.pop_front();
+            }
+            self.update_statistics();
         }
     }
 
-    fn render_ui(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Ping Monitor");
-            
-            self.render_target_input(ui);
-            self.render_threshold_controls(ui);
-            self.render_control_buttons(ui);
-            
-            ui.separator();
-            
-            self.render_statistics(ui);
-            
-            ui.separator();
-            
-            let clock_height = 240.0;
-            ui.allocate_ui(Vec2::new(ui.available_width(), clock_height), |ui| {
-                self.draw_clock_face(ui);
-            });
-        });
+    fn capture_outage_traceroute(&mut self, ctx: &egui::Context, outage_id: u64) {
+        if let Some(outage) = self.outage_tracker.get_mut(outage_id) {
+            outage.traceroute = Some(TraceSnapshot::Running);
+        }
+        Traceroute::spawn(self.trace_target(), TracePurpose::Outage(outage_id), self.traceroute_sender.clone(), ctx.clone());
     }
 
-    fn render_target_input(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label("Target (IP or hostname):");
-            ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target));
-        });
+    fn handle_route_check(&mut self, ctx: &egui::Context) {
+        let mean_latency = (self.ping_statistics.successful_pings > 0)
+            .then_some(self.ping_statistics.mean_response_time);
+        if self.probe == ProbeType::Icmp && self.route_monitor.should_check(mean_latency) {
+            self.route_monitor.check_started(mean_latency);
+            Traceroute::spawn(self.trace_target(), TracePurpose::RouteCheck, self.traceroute_sender.clone(), ctx.clone());
+        }
     }
 
-    fn render_threshold_controls(&mut self, ui: &mut egui::Ui) {
-        ui.label("Time Thresholds:");
-        ui.horizontal(|ui| {
-            ui.label("Green < ");
-            ui.add(egui::DragValue::new(&mut self.green_threshold).range(1..=1000));
-            ui.label("[ms]");
-            ui.label("≤ Yellow <");
-            ui.add(egui::DragValue::new(&mut self.yellow_threshold).range(1..=2000));
-            ui.label("[ms]");
-            ui.label("≤ Orange");
-        });
+    /// Prefer the last known address so the trace doesn't depend on DNS during an outage
+    fn trace_target(&self) -> String {
+        if self.target.parse::<IpAddr>().is_ok() {
+            return self.target.clone();
+        }
+        match self.dns_cache.get(&self.target) {
+            Some(entry) => entry.ip_address().to_string(),
+            None => self.target.split(':').next().unwrap_or(&self.target).to_string(),
+        }
     }
 
-    fn render_control_buttons(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            if ui.button(if self.is_monitoring { "Stop" } else { "Start" }).clicked() {
-                self.is_monitoring = !self.is_monitoring;
-                if self.is_monitoring {
-                    self.last_ping_second = None;
+    fn process_health_reports(&mut self) {
+        while let Ok(report) = self.health_receiver.try_recv() {
+            self.health_report = Some(report);
+            self.health_check_running = false;
+        }
+    }
+
+    fn process_bufferbloat_updates(&mut self) {
+        while let Ok(update) = self.bufferbloat_receiver.try_recv() {
+            match update {
+                BufferbloatUpdate::Phase(phase) => self.bufferbloat_phase = Some(phase),
+                BufferbloatUpdate::Finished(result) => {
+                    self.bufferbloat_phase = None;
+                    self.bufferbloat_result = Some(result);
                 }
             }
-        });
+        }
     }
 
-    fn render_statistics(&self, ui: &mut egui::Ui) {
-        ui.label(format!("Success Rate: {:.1}%", 100.0 - self.ping_statistics.loss_rate));
-        ui.label(format!("Loss Rate: {:.1}%", self.ping_statistics.loss_rate));
-        ui.label(format!("Mean Response Time: {:.1}ms", self.ping_statistics.mean_response_time));
+    fn process_speed_test_results(&mut self) {
+        while let Ok(result) = self.speed_test_receiver.try_recv() {
+            self.speed_test_running = false;
+            match result {
+                Ok(mut result) => {
+                    result.latency_ms = (self.ping_statistics.successful_pings > 0)
+                        .then_some(self.ping_statistics.mean_response_time);
+                    self.speed_test_error = None;
+                    if let Err(e) = self.history.append_speed_test(&result) {
+                        log::error!("Could not save the speed test: {e}");
+                    }
+                    self.speed_tests.push_back(result);
+                    if self.speed_tests.len() > MAX_SPEED_TESTS {
+                        self.speed_tests.pop_front();
+                    }
+                }
+                Err(e) => self.speed_test_error = Some(e),
+            }
+        }
+    }
+
+    /// Looks up the default gateway off the UI thread, since some platforms shell out for it
+    fn update_gateway(&mut self, ctx: &egui::Context) {
+        while let Ok((gateway, dns_servers)) = self.gateway_receiver.try_recv() {
+            let previous = std::mem::replace(&mut self.gateway, gateway);
+            if previous.is_some() && previous != gateway {
+                self.record_network_event(NetworkEvent::GatewayChanged { previous, current: gateway });
+            }
+            self.dns_servers = dns_servers;
+        }
+        if self.gateway_lookup_due {
+            self.gateway_lookup_due = false;
+            let sender = self.gateway_sender.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send((network_info::default_gateway(), network_info::dns_servers()));
+                ctx.request_repaint();
+            });
+        }
+    }
+
+    /// Switches to pinging the router and keeps the previous target in the comparison chart,
+    /// so gateway and internet latency can be compared right away
+    fn monitor_gateway(&mut self, gateway: IpAddr) {
+        let previous = self.target.clone();
+        self.probe = ProbeType::Icmp;
+        self.target = gateway.to_string();
+        if self.compare_target.trim().is_empty() && previous != self.target {
+            self.compare_target = previous;
+            self.compare_results.clear();
+        }
+    }
+
+    fn process_sweep_updates(&mut self) {
+        while let Ok(update) = self.sweep_receiver.try_recv() {
+            match update {
+                SweepUpdate::Found(host) => {
+                    let position = self.sweep_hosts.partition_point(|found| found.address < host.address);
+                    self.sweep_hosts.insert(position, host);
+                }
+                SweepUpdate::Progress { done, total } => self.sweep_progress = Some((done, total)),
+                SweepUpdate::Failed(e) => self.sweep_error = Some(e),
+                SweepUpdate::Finished => self.sweep_progress = None,
+            }
+        }
+    }
+
+    fn process_dns_benchmark_updates(&mut self) {
+        while let Ok(update) = self.dns_benchmark_receiver.try_recv() {
+            match update {
+                BenchmarkUpdate::Scored(score) => {
+                    self.dns_scores.push(score);
+                    dns_benchmark::rank(&mut self.dns_scores);
+                    if let Some((scored, _)) = &mut self.dns_benchmark_progress {
+                        *scored += 1;
+                    }
+                }
+                BenchmarkUpdate::Finished => self.dns_benchmark_progress = None,
+            }
+        }
+    }
+
+    fn process_stress_updates(&mut self) {
+        while let Ok(update) = self.stress_receiver.try_recv() {
+            match update {
+                StressUpdate::Progress(summary) => self.stress_result = Some(Ok(summary)),
+                StressUpdate::Finished(result) => {
+                    self.stress_running = false;
+                    self.stress_result = Some(result);
+                }
+            }
+        }
+    }
+
+    fn process_mdns_updates(&mut self) {
+        while let Ok(update) = self.mdns_receiver.try_recv() {
+            match update {
+                MdnsUpdate::Found(device) => {
+                    match self.mdns_devices.iter_mut().find(|known| known.hostname == device.hostname) {
+                        Some(known) => known.merge(device),
+                        None => {
+                            self.mdns_devices.push(device);
+                            self.mdns_devices.sort_by_key(|device| device.name.to_lowercase());
+                        }
+                    }
+                }
+                MdnsUpdate::Finished(result) => {
+                    self.mdns_running = false;
+                    self.mdns_error = result.err();
+                }
+            }
+        }
+    }
+
+    /// A target list file dropped onto the window is imported like one picked by path
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<_> = ctx.input(|input| input.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        for path in dropped {
+            self.import_targets(&path);
+        }
+    }
+
+    /// Adds the hosts from an inventory file to the target list, skipping ones already present.
+    /// Shared monitoring profiles (.json) are applied as a whole instead.
+    fn import_targets(&mut self, path: &std::path::Path) {
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case(profile::PROFILE_EXTENSION)) {
+            self.import_profile(path);
+            return;
+        }
+        let (entries, errors) = match targets::import_file(path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.import_status = Some(e);
+                return;
+            }
+        };
+        let mut added = 0;
+        for entry in entries {
+            if !self.target_list.iter().any(|existing| existing.host == entry.host) {
+                self.target_list.push(entry);
+                added += 1;
+            }
+        }
+        let mut status = format!("Imported {added} target(s) from {}", path.display());
+        if !errors.is_empty() {
+            status.push_str(&format!("; skipped {} line(s): {}", errors.len(), errors.join("; ")));
+        }
+        self.import_status = Some(status);
+    }
+
+    /// Adds each DNS server the system resolver uses to the target list with the DNS probe,
+    /// since a flaky resolver looks just like "the internet is down"
+    fn monitor_system_dns(&mut self) {
+        let mut added = 0;
+        for (index, server) in self.dns_servers.iter().enumerate() {
+            let host = server.to_string();
+            if self.target_list.iter().any(|entry| entry.host == host && entry.probe == ProbeType::Dns) {
+                continue;
+            }
+            self.target_list.push(TargetEntry {
+                host,
+                label: format!("System DNS {}", index + 1),
+                group: "DNS".to_string(),
+                probe: ProbeType::Dns,
+                ..TargetEntry::default()
+            });
+            added += 1;
+        }
+        self.import_status = Some(format!("Added {added} of {} system DNS server(s)", self.dns_servers.len()));
+    }
+
+    fn import_profile(&mut self, path: &std::path::Path) {
+        if self.is_monitoring {
+            self.import_status = Some("Stop monitoring before importing a profile".to_string());
+            return;
+        }
+        let profile = match MonitoringProfile::load(path) {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.import_status = Some(e);
+                return;
+            }
+        };
+        let mut config = self.current_config();
+        profile.apply_to(&mut config);
+        let errors = config.validate();
+        if !errors.is_empty() {
+            self.import_status = Some(format!("Profile not applied: {}", errors.join("; ")));
+            return;
+        }
+        self.target = config.target;
+        self.green_threshold = config.green_threshold;
+        self.yellow_threshold = config.yellow_threshold;
+        self.very_slow_threshold = config.very_slow_threshold;
+        self.interval_secs = config.interval_secs;
+        self.compare_target = config.compare_target;
+        self.maintenance_windows = config.maintenance_windows;
+        self.target_list = config.target_list;
+        self.target_settings = config.target_settings;
+        self.target_styles = config.target_styles;
+        self.compare_results.clear();
+        self.import_status = Some(format!("Applied profile from {}", path.display()));
+    }
+
+    fn export_profile(&mut self) {
+        let path = config::export_path("ping-monitor-profile", profile::PROFILE_EXTENSION);
+        let status = match MonitoringProfile::from_config(&self.current_config()).export(&path) {
+            Ok(()) => Ok(format!("Exported the monitoring set to {}", path.display())),
+            Err(e) => Err(format!("Failed to export profile: {e}")),
+        };
+        self.toasts.push_result(&status);
+        self.import_status = Some(status.unwrap_or_else(|e| e));
+    }
+
+    fn process_gateway_probe_results(&mut self) {
+        while let Ok(result) = self.gateway_probe_receiver.try_recv() {
+            if let Some(ip) = result.target_ip {
+                probe_limiter::lock(&self.probe_limiter).finished(&ip.to_string());
+            }
+            self.outage_tracker.record_gateway(result.success);
+        }
+    }
+
+    fn process_fallback_results(&mut self) {
+        let chain = self.fallback_chain();
+        self.fallback_prober.sync(&chain);
+        for (host, success) in self.fallback_prober.poll(&mut probe_limiter::lock(&self.probe_limiter)) {
+            self.outage_tracker.record_fallback(&host, success);
+        }
+    }
+
+    /// Hosts to probe as well while the main target fails
+    fn fallback_chain(&self) -> Vec<String> {
+        self.fallback_chains.get(self.target.trim()).map(|chain| fallback_chain::parse_chain(chain)).unwrap_or_default()
+    }
+
+    fn process_compare_results(&mut self) {
+        while let Ok(result) = self.compare_receiver.try_recv() {
+            probe_limiter::lock(&self.probe_limiter).finished(self.compare_target.trim());
+            self.compare_results.push_back(result);
+            if self.compare_results.len() > MAX_PING_RESULTS {
+                self.compare_results.pop_front();
+            }
+        }
+    }
+
+    fn process_reports(&mut self) {
+        while let Ok(report) = self.report_receiver.try_recv() {
+            self.report_running = false;
+            self.report = Some(report);
+        }
+        while let Ok(status) = self.xlsx_export_receiver.try_recv() {
+            self.xlsx_export_running = false;
+            self.toasts.push_result(&status);
+            self.xlsx_export_status = Some(status);
+        }
+        while let Ok(status) = self.pdf_report_receiver.try_recv() {
+            self.pdf_report_running = false;
+            self.toasts.push_result(&status);
+            self.pdf_report_status = Some(status);
+        }
+    }
+
+    /// Keeps yesterday's figures for the current target reasonably fresh while monitoring
+    fn refresh_baseline(&mut self, ctx: &egui::Context) {
+        while let Ok(baseline) = self.baseline_receiver.try_recv() {
+            self.baseline_running = false;
+            self.baseline = Some(baseline);
+        }
+        if !self.is_monitoring || self.baseline_running {
+            return;
+        }
+        let target = self.history_target();
+        let via_vpn = self.network_watcher.vpn.is_some();
+        let stale = self.baseline.as_ref().is_none_or(|baseline| {
+            baseline.target != target || baseline.via_vpn != via_vpn || baseline.computed.elapsed() > Duration::from_secs(BASELINE_REFRESH_SECS)
+        });
+        if stale {
+            self.baseline_running = true;
+            BaselineLoader::spawn(self.history.clone(), target, via_vpn, self.baseline_sender.clone(), ctx.clone());
+        }
+    }
+
+    /// Arrow and delta versus yesterday, colored red when worse; None without a baseline
+    fn trend_label(current: f64, baseline: Option<f64>, steady: f64, unit: &str) -> Option<egui::RichText> {
+        let delta = current - baseline?;
+        let (arrow, color) = if delta.abs() < steady {
+            ("▶", Color32::GRAY)
+        } else if delta > 0.0 {
+            ("▲", Color32::from_rgb(255, 100, 100))
+        } else {
+            ("▼", Color32::from_rgb(100, 200, 100))
+        };
+        Some(egui::RichText::new(format!("{arrow} {delta:+.1} {unit} vs yesterday")).color(color))
+    }
+
+    /// What history records are filed under: the thing the current probe measures
+    fn history_target(&self) -> String {
+        match self.probe {
+            ProbeType::Icmp | ProbeType::Ntp | ProbeType::Snmp | ProbeType::Tcp | ProbeType::Dns | ProbeType::Http => self.target.clone(),
+            ProbeType::Command => self.command.clone(),
+            ProbeType::Remote => self.remote_agent.clone(),
+        }
+    }
+
+    fn refresh_report(&mut self, ctx: &egui::Context) {
+        self.report_running = true;
+        ReportBuilder::spawn(self.history.clone(), self.history_target(), self.report_sender.clone(), ctx.clone());
+    }
+
+    fn process_path_mtu_results(&mut self) {
+        while let Ok(result) = self.path_mtu_receiver.try_recv() {
+            self.path_mtu_running = false;
+            self.path_mtu_result = Some(result);
+        }
+    }
+
+    fn process_traceroute_results(&mut self) {
+        while let Ok((purpose, result)) = self.traceroute_receiver.try_recv() {
+            match purpose {
+                TracePurpose::Outage(outage_id) => {
+                    // Read while the watched hops' loss still describes this outage
+                    let loss_summary = result.as_ref().ok().and_then(|hops| self.loss_summary(hops));
+                    if let Some(outage) = self.outage_tracker.get_mut(outage_id) {
+                        outage.loss_summary = loss_summary;
+                        outage.traceroute = Some(match result {
+                            Ok(hops) => TraceSnapshot::Captured(hops),
+                            Err(e) => TraceSnapshot::Failed(e),
+                        });
+                    }
+                }
+                TracePurpose::RouteCheck => match result {
+                    Ok(hops) => {
+                        if let Some(change) = self.route_monitor.record(hops) {
+                            let message = format!("Route changed (path {:016x} → {:016x})", change.previous_hash, change.current_hash);
+                            self.grafana.annotate("route", SystemTime::now(), None, format!("{message} to {}", self.target));
+                            self.event_log.push(EventKind::Route, message);
+                        }
+                    }
+                    Err(e) => {
+                        log::debug!("Route check traceroute failed: {e}");
+                        self.route_monitor.check_failed();
+                    }
+                },
+            }
+        }
+    }
+
+    fn toggle_monitoring(&mut self) {
+        if !self.is_monitoring && self.replay.is_some() {
+            self.stop_replay();
+        }
+        self.is_monitoring = !self.is_monitoring;
+        if self.probe == ProbeType::Icmp {
+            // Stats are taken when a session ends, so the dropdown shows how the target last looked
+            let statistics = (!self.is_monitoring).then_some(&self.ping_statistics);
+            recent_targets::remember(&mut self.recent_targets, &self.target, statistics);
+        }
+        self.session_started = self.is_monitoring.then(SystemTime::now);
+        if self.is_monitoring {
+            self.session_summary = None;
+            self.session_sent = 0;
+            self.session_replies = 0;
+            self.next_ping_at = None;
+            self.route_monitor.reset();
+            self.event_log.push(EventKind::Monitoring, format!("Started monitoring {} ({})", self.target_style(&self.history_target()).name(&self.history_target()), self.probe.label()));
+        } else {
+            self.event_log.push(EventKind::Monitoring, "Stopped monitoring");
+        }
+    }
+
+    /// Time left in a session that stops on its own
+    fn auto_stop_remaining(&self) -> Option<Duration> {
+        let started = self.session_started.filter(|_| self.is_monitoring && self.auto_stop_enabled)?;
+        let elapsed = started.elapsed().unwrap_or_default();
+        Some(Duration::from_secs(self.auto_stop_mins * 60).saturating_sub(elapsed))
+    }
+
+    /// Stops a timed session once it has run its course and reports how it went
+    fn check_auto_stop(&mut self) {
+        let Some(started) = self.session_started else {
+            return;
+        };
+        if self.auto_stop_remaining() != Some(Duration::ZERO) {
+            return;
+        }
+        self.toggle_monitoring();
+
+        let target = self.history_target();
+        let records = self.history.load_days(chrono::DateTime::<chrono::Local>::from(started).date_naive(), chrono::Local::now().date_naive());
+        let session: Vec<&HistoryRecord> = records
+            .iter()
+            .filter(|record| record.target == target && record.timestamp() >= started)
+            .collect();
+        let summary = PeriodSummary::from_records("Session".to_string(), &session);
+        let format_ms = |ms: Option<f64>| ms.map_or("N/A".to_string(), ping::format_ms);
+        let message = format!(
+            "{}-minute session on {} finished: {} probes, {:.2}% up, mean {}, p95 {}, {} outage(s) totalling {:.1} min",
+            self.auto_stop_mins,
+            self.target_style(&target).name(&target),
+            summary.probes,
+            summary.uptime,
+            format_ms(summary.mean_ms),
+            format_ms(summary.p95_ms),
+            summary.outages,
+            summary.outage_duration.as_secs_f64() / 60.0,
+        );
+        self.toasts.push(ToastLevel::Info, message.clone());
+        self.event_log.push(EventKind::Monitoring, message.clone());
+        if !self.alerts_silenced() {
+            self.attention_requester.request(self.attention.level(AlertSeverity::Info));
+        }
+        self.session_summary = Some(message);
+    }
+
+    fn handle_hotkeys(&mut self, ctx: &egui::Context) {
+        for action in self.hotkeys.poll(ctx) {
+            match action {
+                HotkeyAction::ToggleMonitoring => self.toggle_monitoring(),
+                HotkeyAction::ShowWindow => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+            }
+        }
+    }
+
+    fn saving_power(&self) -> bool {
+        self.battery_saver.enabled && self.power_monitor.current == Some(PowerSource::Battery)
+    }
+
+    fn saving_data(&self) -> bool {
+        self.metered_saver.slow_probing && self.metered_monitor.current == Some(true)
+    }
+
+    fn heavy_tests_paused(&self) -> bool {
+        self.metered_saver.pause_heavy_tests && self.metered_monitor.current == Some(true)
+    }
+
+    /// The saver's action while a full-screen app is in front, Ignore otherwise
+    fn fullscreen_action(&self) -> FullscreenAction {
+        if self.fullscreen_monitor.current == Some(true) { self.fullscreen_saver.action } else { FullscreenAction::Ignore }
+    }
+
+    /// The probe interval in use, lengthened while on battery, a metered connection or a full-screen app
+    fn effective_interval_secs(&self) -> u64 {
+        let mut interval = self.interval_secs;
+        if self.saving_power() {
+            interval = interval.max(self.battery_saver.interval_secs);
+        }
+        if self.saving_data() {
+            interval = interval.max(self.metered_saver.interval_secs);
+        }
+        if self.fullscreen_action() == FullscreenAction::Throttle {
+            interval = interval.max(self.fullscreen_saver.interval_secs);
+        }
+        interval
+    }
+
+    fn update_metered(&mut self, ctx: &egui::Context) {
+        let previous = self.metered_monitor.current;
+        // Starting on an unmetered connection is the normal case and not worth an entry
+        if let Some(metered) = self.metered_monitor.poll(ctx)
+            && (previous.is_some() || metered)
+        {
+            let message = if metered {
+                let mut changes = Vec::new();
+                if self.saving_data() {
+                    changes.push(format!("probing every {} s", self.effective_interval_secs()));
+                }
+                if self.heavy_tests_paused() {
+                    changes.push("speed tests paused".to_string());
+                }
+                if changes.is_empty() {
+                    "Connection is metered".to_string()
+                } else {
+                    format!("Connection is metered, {}", changes.join(", "))
+                }
+            } else {
+                "Connection is no longer metered".to_string()
+            };
+            log::info!("{message}");
+            self.event_log.push(EventKind::Network, message.clone());
+            self.toasts.push(ToastLevel::Info, message);
+            self.next_ping_at = None;
+        }
+    }
+
+    /// Only checks while the saver would act on the answer
+    fn update_fullscreen(&mut self, ctx: &egui::Context) {
+        if self.fullscreen_saver.action == FullscreenAction::Ignore {
+            return;
+        }
+        let previous = self.fullscreen_monitor.current;
+        if let Some(fullscreen) = self.fullscreen_monitor.poll(ctx)
+            && (previous.is_some() || fullscreen)
+        {
+            // No toast: nobody sees it behind the full-screen app, and it would be stale afterwards
+            let message = match self.fullscreen_action() {
+                FullscreenAction::Pause => "Full-screen app in front, probing paused".to_string(),
+                FullscreenAction::Throttle => format!("Full-screen app in front, probing every {} s", self.effective_interval_secs()),
+                FullscreenAction::Ignore => "Full-screen app closed, probing resumed".to_string(),
+            };
+            log::info!("{message}");
+            self.event_log.push(EventKind::Monitoring, message);
+            self.next_ping_at = None;
+        }
+    }
+
+    fn update_power_source(&mut self, ctx: &egui::Context) {
+        let previous = self.power_monitor.current;
+        // Starting on AC is the normal case and not worth an entry
+        if let Some(source) = self.power_monitor.poll(ctx)
+            && !(previous.is_none() && source == PowerSource::Ac)
+        {
+            let message = match source {
+                PowerSource::Battery if self.battery_saver.enabled => format!("Running on battery, probing every {} s", self.effective_interval_secs()),
+                PowerSource::Battery => "Running on battery".to_string(),
+                PowerSource::Ac => "Running on AC power".to_string(),
+            };
+            log::info!("{message}");
+            self.event_log.push(EventKind::Monitoring, message.clone());
+            if self.battery_saver.enabled {
+                self.toasts.push(ToastLevel::Info, message);
+            }
+            self.next_ping_at = None;
+        }
+
+        let animations_off = self.saving_power() && self.battery_saver.no_animations;
+        if animations_off != self.animations_off {
+            self.animations_off = animations_off;
+            let animation_time = if animations_off { 0.0 } else { egui::Style::default().animation_time };
+            ctx.style_mut(|style| style.animation_time = animation_time);
+        }
+    }
+
+    fn detect_sleep_gap(&mut self) {
+        let expected_frame_gap = self.is_monitoring.then(|| Duration::from_secs(self.effective_interval_secs().max(1)));
+        if let Some(gap) = self.sleep_detector.check(expected_frame_gap) {
+            self.handle_sleep_gap(gap);
+        }
+    }
+
+    /// Everything shown from before the gap is stale and its monotonic age is unreliable,
+    /// so grey out the dial, restart statistics and the schedule, and keep a marker of the gap
+    fn handle_sleep_gap(&mut self, gap: SleepGap) {
+        self.circles = [CircleColor::Gray; NUM_CIRCLES];
+        self.circle_timestamps = [None; NUM_CIRCLES];
+        self.slots.iter_mut().for_each(ClockSlot::clear);
+        self.statistics_since = Some(Instant::now());
+        self.update_statistics();
+
+        self.event_log.push(EventKind::Sleep, format!("Computer slept for {:.1} min", gap.duration.as_secs_f64() / 60.0));
+        self.sleep_gaps.push_back(gap);
+        if self.sleep_gaps.len() > MAX_SLEEP_GAPS {
+            self.sleep_gaps.pop_front();
+        }
+
+        self.recover_monitoring("wake from sleep".to_string());
+    }
+
+    fn detect_network_change(&mut self, ctx: &egui::Context) {
+        self.network_watcher.subscribe(ctx);
+        // Route changes don't always change the local address, so the gateway is looked up again on any notification
+        if self.network_watcher.take_notification() && self.rediscover_gateway {
+            self.gateway_lookup_due = true;
+        }
+        let was_link_down = self.network_watcher.link_down();
+        let mut events = self.network_watcher.poll();
+        let link_restored = was_link_down && !self.network_watcher.link_down();
+        events.extend(self.detect_ssid_change(ctx));
+
+        if !events.is_empty() && self.rediscover_gateway {
+            self.gateway_lookup_due = true;
+        }
+        let mut recovery_reason = None;
+        for event in events {
+            if matches!(event, NetworkEvent::PrimaryAddressChanged { .. } | NetworkEvent::SsidChanged { .. })
+                || (link_restored && recovery_reason.is_none())
+            {
+                recovery_reason = Some(event.to_string());
+            }
+            self.record_network_event(event);
+        }
+        if let Some(reason) = recovery_reason {
+            self.recover_monitoring(reason);
+        }
+    }
+
+    /// Roaming to another Wi-Fi network can keep the same address, so the SSID is compared separately
+    fn detect_ssid_change(&mut self, ctx: &egui::Context) -> Option<NetworkEvent> {
+        let previous = self.wifi_monitor.current.as_ref().map(|wifi| wifi.ssid.clone());
+        self.wifi_monitor.poll(ctx);
+        let current = self.wifi_monitor.current.as_ref().map(|wifi| wifi.ssid.clone());
+        match (previous, current) {
+            (Some(previous), Some(current)) if previous != current => Some(NetworkEvent::SsidChanged { previous, current }),
+            _ => None,
+        }
+    }
+
+    fn record_network_event(&mut self, event: NetworkEvent) {
+        // Latency from before the change isn't comparable, so don't average across it; cached
+        // answers may point somewhere else on the new network
+        if event.splits_history() {
+            self.dns_cache.clear();
+            self.statistics_since = Some(Instant::now());
+            self.update_statistics();
+        }
+        let kind = if matches!(event, NetworkEvent::TargetAddressChanged { .. }) { EventKind::Dns } else { EventKind::Network };
+        self.event_log.push(kind, event.to_string());
+        self.network_events.push_back((SystemTime::now(), event));
+        if self.network_events.len() > MAX_NETWORK_EVENTS {
+            self.network_events.pop_front();
+        }
+    }
+
+    /// Starts or stops the web dashboard to match the setting and feeds it the current state
+    fn sync_web_dashboard(&mut self) {
+        if !self.web_dashboard_enabled || self.web_dashboard_needs_token() {
+            self.web_dashboard = None;
+            self.web_dashboard_error = None;
+            return;
+        }
+        // This computer talks to itself without crossing the network, so HTTPS is only for the LAN
+        let uses_tls = self.web_dashboard_lan
+            && !(self.web_dashboard_tls_cert.trim().is_empty() && self.web_dashboard_tls_key.trim().is_empty());
+        let binding = Binding {
+            port: self.web_dashboard_port,
+            lan: self.web_dashboard_lan,
+            tls: uses_tls.then(|| TlsFiles { cert: self.web_dashboard_tls_cert.clone(), key: self.web_dashboard_tls_key.clone() }),
+        };
+        // Dropping the old listener waits until its port is free again
+        if self.web_dashboard.as_ref().is_some_and(|dashboard| dashboard.binding != binding) {
+            self.web_dashboard = None;
+        }
+        // A port or certificate that didn't work isn't retried until it's changed or the dashboard is switched off and on
+        if self.web_dashboard.is_none() && self.web_dashboard_error.as_ref().is_none_or(|(failed, _)| *failed != binding) {
+            self.web_dashboard_error = None;
+            match WebDashboard::start(binding.clone(), &self.web_dashboard_token) {
+                Ok(dashboard) => self.web_dashboard = Some(dashboard),
+                Err(e) => {
+                    log::warn!("Web dashboard not started: {e}");
+                    self.web_dashboard_error = Some((binding, e));
+                }
+            }
+        }
+        if let Some(mut dashboard) = self.web_dashboard.take() {
+            dashboard.set_token(&self.web_dashboard_token);
+            dashboard.publish(|| self.dashboard_snapshot());
+            self.web_dashboard = Some(dashboard);
+        }
+    }
+
+    fn dashboard_snapshot(&self) -> DashboardSnapshot {
+        let target = self.history_target();
+        DashboardSnapshot {
+            target: self.target_style(&target).decorated(&target),
+            monitoring: self.is_monitoring,
+            status: self.status_state().to_string(),
+            last_ms: self.last_response_time,
+            mean_ms: self.ping_statistics.mean_response_time,
+            loss_rate: self.ping_statistics.loss_rate,
+            interval_secs: self.effective_interval_secs(),
+            circles: (0..NUM_CIRCLES)
+                .map(|index| {
+                    let color = self.get_circle_color_with_age(index);
+                    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+                })
+                .collect(),
+            results: self.ping_results
+                .iter()
+                .map(|result| {
+                    let timestamp_ms = result.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+                    (timestamp_ms, result.response_time.filter(|_| result.success))
+                })
+                .collect(),
+        }
+    }
+
+    /// Opening the dashboard to the LAN without a token would let anyone there in
+    fn web_dashboard_needs_token(&self) -> bool {
+        self.web_dashboard_lan && self.web_dashboard_token.trim().is_empty()
+    }
+
+    /// The dashboard's address on `host`, with the token when one is set
+    fn web_dashboard_address(&self, host: &str, binding: &Binding) -> String {
+        web_dashboard::address(host, binding.port, binding.tls.is_some(), &self.web_dashboard_token)
+    }
+
+    /// Address phones on the LAN can open, for notifications; the dashboard only listens on IPv4.
+    /// The token is left out since notifications pass through a third-party server.
+    fn web_dashboard_url(&self) -> Option<String> {
+        let dashboard = self.web_dashboard.as_ref().filter(|dashboard| dashboard.binding.lan)?;
+        let ip = network_watcher::primary_local_ip().filter(IpAddr::is_ipv4)?;
+        Some(web_dashboard::address(&ip.to_string(), dashboard.binding.port, dashboard.binding.tls.is_some(), ""))
+    }
+
+    fn render_web_dashboard(&mut self, ui: &mut egui::Ui) {
+        if !self.web_dashboard_enabled {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Dashboard port:");
+            ui.add(egui::DragValue::new(&mut self.web_dashboard_port).range(1..=u16::MAX).speed(0.0).update_while_editing(false));
+            ui.checkbox(&mut self.web_dashboard_lan, "Open to the LAN")
+                .on_hover_text("Let phones and other computers on the local network open the dashboard; otherwise only this computer can");
+            ui.label("Token:");
+            ui.add(egui::TextEdit::singleline(&mut self.web_dashboard_token).password(true).desired_width(120.0))
+                .on_hover_text("Part of the dashboard's address; letters and digits only");
+        });
+        if self.web_dashboard_lan {
+            ui.horizontal(|ui| {
+                ui.label("HTTPS certificate:");
+                ui.add(egui::TextEdit::singleline(&mut self.web_dashboard_tls_cert).hint_text("optional, cert.pem").desired_width(160.0))
+                    .on_hover_text("PEM certificate chain, e.g. from your own CA; leave both empty to serve plain HTTP");
+                ui.label("Key:");
+                ui.add(egui::TextEdit::singleline(&mut self.web_dashboard_tls_key).hint_text("key.pem").desired_width(160.0))
+                    .on_hover_text("PEM private key of the certificate");
+            });
+        }
+        if self.web_dashboard_needs_token() {
+            ui.colored_label(Color32::from_rgb(255, 165, 0), "Set a token to open the web dashboard to the LAN");
+        } else if let Some((_, e)) = &self.web_dashboard_error {
+            ui.colored_label(Color32::RED, format!("Web dashboard: {e}"));
+        } else if let Some(dashboard) = &self.web_dashboard {
+            let host = if dashboard.binding.lan {
+                network_watcher::primary_local_ip().filter(IpAddr::is_ipv4).map_or("this-computer".to_string(), |ip| ip.to_string())
+            } else {
+                Ipv4Addr::LOCALHOST.to_string()
+            };
+            let url = self.web_dashboard_address(&host, &dashboard.binding);
+            ui.horizontal(|ui| {
+                ui.label("Web dashboard:");
+                ui.hyperlink(&url);
+            });
+        }
+    }
+
+    /// Tunnel the probe to `target_ip` is routed through; with split tunneling only some targets use the VPN
+    fn vpn_for(&self, target_ip: Option<IpAddr>) -> Option<String> {
+        match target_ip.and_then(network_watcher::local_ip_for) {
+            Some(local_ip) => vpn::tunnel_carrying(&self.network_watcher.interfaces, local_ip),
+            None => self.network_watcher.vpn.clone(),
+        }
+    }
+
+    /// Drops everything tied to the previous network session: cached DNS answers, in-flight pings
+    /// and their result channel (so replies from dead sockets are discarded), and the schedule.
+    /// Each ping opens a fresh ICMP socket, so the next one starts on the new network.
+    fn recover_monitoring(&mut self, reason: String) {
+        self.dns_cache.clear();
+        self.pending_pings.clear();
+        self.ping_receiver = None;
+        self.ping_sender = None;
+        self.next_ping_at = None;
+        log::info!("Restarting probes after {reason}");
+        self.event_log.push(EventKind::Monitoring, format!("Restarted probing after {reason}"));
+        self.last_recovery = Some((SystemTime::now(), reason));
+    }
+
+    fn cleanup_pending_pings(&mut self) {
+        let timeout_duration = Duration::from_secs(PENDING_PING_TIMEOUT_SECS);
+        self.pending_pings.retain(|_, started| started.elapsed() < timeout_duration);
+    }
+
+    /// Ping results wake the UI themselves, so frames are only scheduled to move the second hand
+    /// (smoothly while monitoring, once a second when idle or saving battery) or, while minimized, to hit the next ping
+    fn schedule_repaint(&self, ctx: &egui::Context) {
+        let minimized = ctx.input(|i| i.viewport().minimized.unwrap_or(false));
+        if minimized {
+            if self.is_monitoring {
+                let until_next = self.next_ping_at
+                    .map_or_else(|| self.time_until_next_ping(), |next| next.saturating_duration_since(Instant::now()));
+                ctx.request_repaint_after(until_next);
+            }
+            if let Some(remaining) = self.config_saver.remaining() {
+                ctx.request_repaint_after(remaining);
+            }
+            return;
+        }
+
+        let interval_ms = if (self.saving_power() && self.battery_saver.slow_repaint) || self.fullscreen_action() != FullscreenAction::Ignore {
+            IDLE_REPAINT_INTERVAL_MS
+        } else if self.is_monitoring || !self.pending_pings.is_empty() || self.replay.as_ref().is_some_and(|replay| !replay.paused) {
+            ACTIVE_REPAINT_INTERVAL_MS
+        } else {
+            IDLE_REPAINT_INTERVAL_MS
+        };
+        ctx.request_repaint_after(Duration::from_millis(interval_ms));
+    }
+
+    fn time_until_next_ping(&self) -> Duration {
+        let interval_ms = self.effective_interval_secs().max(1) * 1000;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::from_secs(0))
+            .as_millis() as u64;
+        Duration::from_millis(interval_ms - now_ms % interval_ms)
+    }
+
+    /// Pings are aligned to wall-clock interval boundaries, but the wait itself is measured
+    /// on the monotonic clock so clock steps can neither stall nor storm the schedule
+    fn handle_periodic_ping(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        let due = match self.next_ping_at {
+            Some(next) => now >= next,
+            None => {
+                self.next_ping_at = Some(now + self.time_until_next_ping());
+                false
+            }
+        };
+
+        if due {
+            self.initiate_ping(ctx, SystemTime::now());
+            self.next_ping_at = Some(now + self.next_ping_delay());
+        }
+    }
+
+    fn next_ping_delay(&self) -> Duration {
+        let delay = self.time_until_next_ping();
+        if delay < Duration::from_millis(MIN_PING_SPACING_MS) {
+            delay + Duration::from_secs(self.effective_interval_secs().max(1))
+        } else {
+            delay
+        }
+    }
+
+    /// Sender for the ping result channel, creating the channel on first use
+    fn ping_sender(&mut self) -> mpsc::Sender<PingResult> {
+        if let Some(sender) = &self.ping_sender {
+            return sender.clone();
+        }
+        let (sender, receiver) = mpsc::channel();
+        self.ping_receiver = Some(receiver);
+        self.ping_sender = Some(sender.clone());
+        sender
+    }
+
+    /// Agent results arrive on the agent's own schedule; this only keeps the connection open
+    fn handle_remote_agent(&mut self, ctx: &egui::Context) {
+        if self.remote_viewer.is_none() {
+            let sender = self.ping_sender();
+            self.remote_viewer = Some(RemoteViewer::connect(
+                self.remote_agent.clone(),
+                self.agent_token.clone(),
+                sender,
+                ctx.clone(),
+            ));
+        }
+        if let Some(viewer) = &mut self.remote_viewer {
+            viewer.poll();
+        }
+    }
+
+    fn initiate_ping(&mut self, ctx: &egui::Context, now: SystemTime) {
+        let circle_index = self.circle_index_for_time(now);
+        
+        // Only start a new ping if we're not already pinging this circle
+        if self.pending_pings.contains_key(&circle_index) {
+            return;
+        }
+
+        if !probe_limiter::lock(&self.probe_limiter).try_start(&self.history_target()) {
+            return;
+        }
+        let sender = self.ping_sender();
+        let target = self.target.clone();
+        
+        if self.probe == ProbeType::Command {
+            CommandProbe::spawn(self.command.clone(), sender, ctx.clone());
+        } else if self.probe == ProbeType::Ntp {
+            NtpProbe::spawn(target, sender, ctx.clone());
+        } else if self.probe == ProbeType::Snmp {
+            SnmpProbe::spawn(target, self.snmp.clone(), sender, ctx.clone());
+        } else if self.probe == ProbeType::Tcp {
+            TcpProbe::spawn(target, self.tcp_expect.clone(), self.tcp_proxy.clone(), sender, ctx.clone());
+        } else if self.probe == ProbeType::Dns {
+            let query_name = self.target_entry().map_or(dns_probe::DEFAULT_QUERY_NAME, TargetEntry::lookup_name).to_string();
+            DnsProbe::spawn(target, query_name, sender, ctx.clone());
+        } else if self.probe == ProbeType::Http {
+            HttpProbe::spawn(target, self.http_proxy.clone(), sender, ctx.clone());
+        } else if let Some(cached_ip) = self.dns_cache.get_valid_ip(&target) {
+            // Use the cached IP while it is valid
+            PingExecutor::ping_with_ip(cached_ip, sender, ctx.clone());
+        } else {
+            // Clean expired cache and resolve
+            self.dns_cache.clean_expired(&target);
+            PingExecutor::resolve_and_ping(target, sender, ctx.clone());
+        }
+        self.session_sent += 1;
+        self.target_watch.probe_all(&mut probe_limiter::lock(&self.probe_limiter), ctx);
+        if self.probe == ProbeType::Icmp {
+            self.interface_streams.probe_all(&self.target, &self.network_watcher.interfaces, &mut probe_limiter::lock(&self.probe_limiter), ctx);
+        }
+        if !self.compare_target.trim().is_empty() && probe_limiter::lock(&self.probe_limiter).try_start(self.compare_target.trim()) {
+            PingExecutor::resolve_and_ping(self.compare_target.trim().to_string(), self.compare_sender.clone(), ctx.clone());
+        }
+        // A remote agent fails for reasons our own gateway can't explain
+        if self.outage_tracker.failing()
+            && self.probe != ProbeType::Remote
+            && let Some(gateway) = self.gateway
+            && gateway.to_string() != self.target
+            && probe_limiter::lock(&self.probe_limiter).try_start(&gateway.to_string())
+        {
+            PingExecutor::ping_with_ip(gateway, self.gateway_probe_sender.clone(), ctx.clone());
+        }
+        if self.outage_tracker.failing() {
+            self.fallback_prober.probe_all(&mut probe_limiter::lock(&self.probe_limiter), ctx);
+        }
+        
+        self.pending_pings.insert(circle_index, Instant::now());
+    }
+
+    fn render_ui(&mut self, ctx: &egui::Context) {
+        if self.toast_history_open {
+            self.toasts.unread = 0;
+            self.render_toast_history(ctx);
+        }
+        if self.focus_mode {
+            self.render_focus_mode(ctx);
+            return;
+        }
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Ping Monitor");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Monitor, "Monitor");
+                    ui.selectable_value(&mut self.tab, Tab::Reports, "Reports");
+                    let shortcut = ctx.format_shortcut(&FOCUS_SHORTCUT);
+                    if ui.button("⛶").on_hover_text(format!("Focus mode: only the clock and last reply ({shortcut} or double-click the clock)")).clicked() {
+                        self.focus_mode = true;
+                    }
+                    let bell = if self.toasts.unread > 0 { format!("🔔 {}", self.toasts.unread) } else { "🔔".to_string() };
+                    if ui.selectable_label(self.toast_history_open, bell).on_hover_text("Recent notifications").clicked() {
+                        self.toast_history_open = !self.toast_history_open;
+                    }
+                });
+                if self.tab == Tab::Reports {
+                    self.render_reports(ui);
+                    return;
+                }
+                
+                self.render_config_errors(ui);
+                self.render_icmp_access(ui);
+                self.render_target_input(ui);
+                self.render_threshold_controls(ui);
+                self.render_control_buttons(ui);
+                self.render_replay(ui);
+                
+                ui.separator();
+                
+                self.render_status(ui);
+                self.render_statistics(ui);
+                let pinned = [self.render_outages(ui), self.render_route_changes(ui)];
+                if let Some(hop) = pinned.into_iter().flatten().next() {
+                    self.pin_hop(&hop);
+                }
+                self.render_annotations(ui);
+                self.render_event_log(ui);
+                self.render_target_list(ui);
+                self.render_comparison(ui);
+                self.render_fallback_chain(ui);
+                self.render_interface_streams(ui);
+                self.render_subnet_sweep(ui);
+                self.render_dns_benchmark(ui);
+                self.render_speed_tests(ui);
+                self.render_stress_test(ui);
+                self.render_packet_capture(ui);
+                self.render_plugins(ui);
+                self.render_agents(ui);
+                self.render_probe_limits(ui);
+                self.render_network_status(ui);
+                self.render_log_console(ui);
+                
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.main_view, MainView::Clock, "🕐 Clock");
+                    ui.selectable_value(&mut self.main_view, MainView::List, "☰ List");
+                });
+                match self.main_view {
+                    MainView::Clock => {
+                        if self.draw_clock_face(ui) {
+                            self.focus_mode = true;
+                        }
+                    }
+                    MainView::List => self.render_probe_list(ui),
+                }
+            });
+        });
+    }
+
+    /// Just the clock face and the last reply, for a monitor tucked in a corner of the screen
+    fn render_focus_mode(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.draw_clock_face(ui) {
+                self.focus_mode = false;
+            }
+            let last = match (self.last_response_time, self.ping_results.back()) {
+                (_, Some(result)) if !result.success => "no reply".to_string(),
+                (Some(time), _) => ping::format_ms(time),
+                (None, _) => "–".to_string(),
+            };
+            ui.vertical_centered(|ui| {
+                ui.heading(last);
+                let shortcut = ctx.format_shortcut(&FOCUS_SHORTCUT);
+                ui.weak(format!("Double-click or {shortcut} to show everything"));
+            });
+        });
+    }
+
+    /// Date range, agreed availability and the button that writes the PDF report
+    fn render_pdf_report_controls(&mut self, ui: &mut egui::Ui, target: &str) {
+        ui.horizontal(|ui| {
+            ui.label("PDF report from");
+            ui.add(egui::TextEdit::singleline(&mut self.pdf_report_from).desired_width(80.0));
+            ui.label("to");
+            ui.add(egui::TextEdit::singleline(&mut self.pdf_report_to).desired_width(80.0));
+            ui.label("agreed uptime");
+            ui.add(egui::DragValue::new(&mut self.sla_percent).range(0.0..=100.0).speed(0.01).max_decimals(3).suffix(" %"));
+            let label = if self.pdf_report_running { "Writing PDF…" } else { "Export PDF" };
+            let export = ui.add_enabled(!self.pdf_report_running, egui::Button::new(label))
+                .on_hover_text("Availability against the agreed uptime, daily charts and every outage in the range, for a complaint or contract review");
+            if export.clicked() {
+                match pdf_report::parse_range(&self.pdf_report_from, &self.pdf_report_to) {
+                    Ok((first, last)) => {
+                        self.pdf_report_running = true;
+                        let path = config::export_path("ping-monitor-report", "pdf");
+                        let name = self.target_style(target).export_name(target);
+                        PdfReport::spawn(self.history.clone(), target.to_string(), name, first, last, self.sla_percent, path,
+                            self.pdf_report_sender.clone(), ui.ctx().clone());
+                    }
+                    Err(e) => self.pdf_report_status = Some(Err(e)),
+                }
+            }
+        });
+        match &self.pdf_report_status {
+            Some(Ok(message)) => { ui.label(message); }
+            Some(Err(e)) => { ui.colored_label(Color32::RED, e); }
+            None => {}
+        }
+    }
+
+    fn render_reports(&mut self, ui: &mut egui::Ui) {
+        let target = self.history_target();
+        let stale = self.report.as_ref().is_none_or(|report| {
+            report.target != target
+                || report.generated.elapsed().unwrap_or_default() > Duration::from_secs(REPORT_REFRESH_SECS)
+        });
+        if stale && !self.report_running {
+            self.refresh_report(ui.ctx());
+        }
+
+        if let Some(e) = &self.history_error {
+            ui.colored_label(Color32::RED, format!("⚠ {e}"));
+        }
+        ui.horizontal(|ui| {
+            let refresh_label = if self.report_running { "Reading history…" } else { "Refresh" };
+            if ui.add_enabled(!self.report_running, egui::Button::new(refresh_label)).clicked() {
+                self.refresh_report(ui.ctx());
+            }
+            let export_label = if self.xlsx_export_running { "Exporting…" } else { "Export .xlsx" };
+            let export = ui.add_enabled(!self.xlsx_export_running, egui::Button::new(export_label))
+                .on_hover_text(format!("Summary, hourly figures and every sample of the last {} days as an Excel workbook", xlsx_export::EXPORT_DAYS));
+            if export.clicked() {
+                self.xlsx_export_running = true;
+                let path = config::export_path("ping-monitor-report", "xlsx");
+                let name = self.target_style(&target).export_name(&target);
+                XlsxExport::spawn(self.history.clone(), target.clone(), name, path, self.xlsx_export_sender.clone(), ui.ctx().clone());
+            }
+            if let Some(report) = &self.report {
+                let generated = chrono::DateTime::<chrono::Local>::from(report.generated).format("%H:%M");
+                ui.label(format!("{} · as of {generated}", self.target_style(&report.target).decorated(&report.target)));
+            }
+        });
+        match &self.xlsx_export_status {
+            Some(Ok(message)) => { ui.label(message); }
+            Some(Err(e)) => { ui.colored_label(Color32::RED, e); }
+            None => {}
+        }
+        self.render_pdf_report_controls(ui, &target);
+
+        let Some(report) = &self.report else {
+            return;
+        };
+        if report.days.is_empty() {
+            ui.label("No history for this target yet; results are saved while monitoring.");
+            return;
+        }
+        ui.strong("By time of day (last 4 weeks)");
+        self.draw_time_of_day_chart(ui, &report.hour_of_day);
+        let windows = report::congested_windows(&report.hour_of_day);
+        if windows.is_empty() {
+            ui.label("No recurring congestion");
+        } else {
+            let windows: Vec<String> = windows
+                .iter()
+                .map(|(start, end)| format!("{start:02}:00–{:02}:00", end % 24))
+                .collect();
+            ui.colored_label(Color32::from_rgb(255, 165, 0), format!("Recurring congestion: {}", windows.join(", ")));
+        }
+        Self::render_loss_pattern(ui, &report.loss_pattern);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("Today vs");
+            for reference in [OverlayReference::Yesterday, OverlayReference::LastWeek] {
+                ui.selectable_value(&mut self.overlay_reference, reference, reference.label());
+            }
+        });
+        let reference_curve = report.reference_curve(self.overlay_reference);
+        ui.horizontal(|ui| {
+            ui.colored_label(COMPARE_MAIN_COLOR, "━ Today");
+            ui.colored_label(COMPARE_OTHER_COLOR, format!("━ {}", self.overlay_reference.label()));
+        });
+        Self::draw_day_overlay_chart(ui, &report.today_curve, reference_curve);
+        match report::curve_difference(&report.today_curve, reference_curve) {
+            Some(difference) if difference.abs() < TREND_STEADY_MS => {
+                ui.label("Today is about as fast as the same hours then");
+            }
+            Some(difference) => {
+                let comparison = if difference > 0.0 { "slower" } else { "faster" };
+                ui.label(format!("Today is {:.1} ms {comparison} on average over the same hours", difference.abs()));
+            }
+            None => {
+                ui.label("No overlapping hours to compare yet");
+            }
+        }
+        ui.separator();
+        ui.strong("Daily");
+        self.render_report_table(ui, "daily_report", &report.days);
+        ui.separator();
+        ui.strong("Weekly");
+        self.render_report_table(ui, "weekly_report", &report.weeks);
+        if !report.long_term.is_empty() {
+            ui.separator();
+            ui.strong(format!("Last {} days", report::LONG_TERM_DAYS));
+            Self::draw_long_term_chart(ui, &report.long_term);
+        }
+    }
+
+    /// Hourly mean latency over the long-term range, with lossy hours marked along the top
+    fn draw_long_term_chart(ui: &mut egui::Ui, rollups: &[Rollup]) {
+        let width = ui.available_width();
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(width, REPORT_CHART_HEIGHT * 6.0), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_stroke(rect, 2.0, Stroke::new(1.0, ui.visuals().weak_text_color()), egui::StrokeKind::Inside);
+        let (Some(first), Some(last)) = (rollups.first(), rollups.last()) else {
+            return;
+        };
+        let hour_ms = rollup::Resolution::Hour.secs() * 1000;
+        let span_ms = (last.start_ms - first.start_ms + hour_ms) as f32;
+        let x_for = |start_ms: u64| rect.left() + rect.width() * (start_ms - first.start_ms) as f32 / span_ms;
+        let slowest = rollups.iter().filter_map(|rollup| rollup.mean_ms).fold(1.0, f64::max);
+
+        let mut line: Vec<Pos2> = Vec::new();
+        let mut previous_start = None;
+        for rollup in rollups {
+            // Hours without data (app closed) break the line
+            if previous_start.is_some_and(|previous| rollup.start_ms - previous > hour_ms) {
+                if line.len() > 1 {
+                    painter.add(egui::Shape::line(std::mem::take(&mut line), Stroke::new(1.0, COMPARE_MAIN_COLOR)));
+                }
+                line.clear();
+            }
+            previous_start = Some(rollup.start_ms);
+            let x = x_for(rollup.start_ms);
+            if rollup.failed > 0 {
+                let height = (rollup.loss_rate() as f32 / 100.0 * rect.height()).max(3.0);
+                painter.line_segment([Pos2::new(x, rect.top()), Pos2::new(x, rect.top() + height)], Stroke::new(1.0, Color32::RED));
+            }
+            if let Some(ms) = rollup.mean_ms {
+                line.push(Pos2::new(x, rect.bottom() - rect.height() * (ms / slowest) as f32));
+            }
+        }
+        if line.len() > 1 {
+            painter.add(egui::Shape::line(line, Stroke::new(1.0, COMPARE_MAIN_COLOR)));
+        }
+
+        let date = |start_ms: u64| chrono::DateTime::<chrono::Local>::from(UNIX_EPOCH + Duration::from_millis(start_ms)).format("%m-%d").to_string();
+        painter.text(rect.left_bottom() + Vec2::new(2.0, 0.0), egui::Align2::LEFT_BOTTOM, date(first.start_ms),
+            egui::FontId::proportional(10.0), ui.visuals().weak_text_color());
+        painter.text(rect.right_bottom() - Vec2::new(2.0, 0.0), egui::Align2::RIGHT_BOTTOM, date(last.start_ms),
+            egui::FontId::proportional(10.0), ui.visuals().weak_text_color());
+        painter.text(rect.left_top() + Vec2::new(4.0, 2.0), egui::Align2::LEFT_TOP, format!("{slowest:.0} ms"),
+            egui::FontId::proportional(10.0), ui.visuals().weak_text_color());
+        response.on_hover_text("Mean response time per hour; red marks along the top show lost probes, taller for more loss");
+    }
+
+    /// Today's latency curve over an earlier day's, midnight to midnight
+    fn draw_day_overlay_chart(ui: &mut egui::Ui, today: &DayCurve, reference: &DayCurve) {
+        let width = ui.available_width().min(24.0 * 14.0);
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(width, REPORT_CHART_HEIGHT * 6.0), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_stroke(rect, 2.0, Stroke::new(1.0, ui.visuals().weak_text_color()), egui::StrokeKind::Inside);
+        let slowest = today.iter().chain(reference).flatten().copied().fold(1.0, f64::max);
+        let step = rect.width() / report::CURVE_POINTS as f32;
+
+        for (curve, color) in [(reference, COMPARE_OTHER_COLOR), (today, COMPARE_MAIN_COLOR)] {
+            // Gaps without data break the line
+            let mut line = Vec::new();
+            for (index, mean) in curve.iter().enumerate() {
+                match mean {
+                    Some(ms) => line.push(Pos2::new(
+                        rect.left() + (index as f32 + 0.5) * step,
+                        rect.bottom() - rect.height() * (ms / slowest) as f32,
+                    )),
+                    None if line.len() > 1 => {
+                        painter.add(egui::Shape::line(std::mem::take(&mut line), Stroke::new(1.5, color)));
+                    }
+                    None => line.clear(),
+                }
+            }
+            if line.len() > 1 {
+                painter.add(egui::Shape::line(line, Stroke::new(1.5, color)));
+            }
+        }
+        for hour in (0..24).step_by(3) {
+            painter.text(
+                Pos2::new(rect.left() + rect.width() * hour as f32 / 24.0 + 2.0, rect.bottom()),
+                egui::Align2::LEFT_BOTTOM,
+                format!("{hour:02}"),
+                egui::FontId::proportional(10.0),
+                ui.visuals().weak_text_color(),
+            );
+        }
+        painter.text(
+            rect.left_top() + Vec2::new(4.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            format!("{slowest:.0} ms"),
+            egui::FontId::proportional(10.0),
+            ui.visuals().weak_text_color(),
+        );
+        response.on_hover_text("Mean response time in 10-minute steps, local time");
+    }
+
+    /// Random vs bursty loss with the burst-length distribution
+    fn render_loss_pattern(ui: &mut egui::Ui, pattern: &LossPattern) {
+        if pattern.losses == 0 {
+            ui.label("No packet loss");
+            return;
+        }
+        let kind = pattern.kind();
+        let label = ui.label(format!("Packet loss pattern: {} ({} lost of {})", kind.label(), pattern.losses, pattern.probes));
+        if !kind.hint().is_empty() {
+            label.on_hover_text(kind.hint());
+        }
+        let buckets: Vec<String> = pattern.burst_buckets().iter().map(|(length, count)| format!("{length}: {count}×")).collect();
+        let mean = pattern.mean_burst().map_or(String::new(), |mean| format!(" · mean {mean:.1} in a row"));
+        ui.label(format!("Burst lengths {}{mean}", buckets.join(", ")));
+        if let (Some(after_success), Some(after_loss)) = (pattern.loss_after_success, pattern.loss_after_loss) {
+            ui.weak(format!("Lost after a success {after_success:.2}% · after a loss {after_loss:.1}%"));
+        }
+    }
+
+    fn render_report_table(&self, ui: &mut egui::Ui, id: &str, periods: &[PeriodSummary]) {
+        let format_ms = |ms: Option<f64>| ms.map_or("N/A".to_string(), ping::format_ms);
+        for period in periods {
+            egui::CollapsingHeader::new(format!("{} · {:.2}% up", period.label, period.uptime))
+                .id_salt((id, &period.label))
+                .show(ui, |ui| {
+                    egui::Grid::new((id, &period.label, "grid")).show(ui, |ui| {
+                        ui.label("Probes");
+                        ui.label(period.probes.to_string());
+                        ui.end_row();
+                        ui.label("Uptime");
+                        ui.label(format!("{:.2}%", period.uptime));
+                        ui.end_row();
+                        ui.label("Mean / p95");
+                        ui.label(format!("{} / {}", format_ms(period.mean_ms), format_ms(period.p95_ms)));
+                        ui.end_row();
+                        ui.label("Outages");
+                        ui.label(format!("{} ({:.1} min)", period.outages, period.outage_duration.as_secs_f64() / 60.0));
+                        ui.end_row();
+                        if let Some(hour) = period.worst_hour() {
+                            let summary = &period.hours[hour];
+                            ui.label("Worst hour");
+                            ui.label(format!("{hour:02}:00–{:02}:00 ({:.1}% loss, {})",
+                                (hour + 1) % 24,
+                                summary.loss_rate(),
+                                format_ms(summary.mean_ms),
+                            ));
+                            ui.end_row();
+                        }
+                    });
+                    self.draw_hourly_chart(ui, period);
+                });
+        }
+    }
+
+    /// 24 bars, one per hour of the day: height follows the mean response time, red where probes were lost
+    fn draw_hourly_chart(&self, ui: &mut egui::Ui, period: &PeriodSummary) {
+        let width = ui.available_width().min(24.0 * 12.0);
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(width, REPORT_CHART_HEIGHT * 2.0), egui::Sense::hover());
+        let painter = ui.painter();
+        let bar_width = rect.width() / 24.0;
+        let slowest = period.hours.iter().filter_map(|hour| hour.mean_ms).fold(1.0, f64::max);
+        for (index, hour) in period.hours.iter().enumerate() {
+            if hour.probes == 0 {
+                continue;
+            }
+            let color = if hour.failed > 0 {
+                CircleColor::Red.to_color32()
+            } else {
+                CircleColor::from_ping_response(hour.mean_ms, self.green_threshold, self.yellow_threshold, self.very_slow_threshold).to_color32()
+            };
+            let fraction = (hour.mean_ms.unwrap_or(slowest) / slowest).clamp(0.1, 1.0) as f32;
+            let left = rect.left() + index as f32 * bar_width;
+            let bar = egui::Rect::from_min_max(
+                Pos2::new(left + 1.0, rect.bottom() - rect.height() * fraction),
+                Pos2::new(left + bar_width - 1.0, rect.bottom()),
+            );
+            painter.rect_filled(bar, 1.0, color);
+        }
+        response.on_hover_text("Mean response time by hour of day (00–23); red hours had lost probes");
+    }
+
+    /// Mean latency (upper bars) and loss (lower bars, red) for each hour of the day, with values on hover
+    fn draw_time_of_day_chart(&self, ui: &mut egui::Ui, hours: &[HourSummary; 24]) {
+        let width = ui.available_width().min(24.0 * 14.0);
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(width, REPORT_CHART_HEIGHT * 6.0), egui::Sense::hover());
+        let painter = ui.painter();
+        let bar_width = rect.width() / 24.0;
+        let latency_height = REPORT_CHART_HEIGHT * 3.0;
+        let loss_top = rect.top() + latency_height + 2.0;
+        let loss_height = REPORT_CHART_HEIGHT * 2.0;
+        let slowest = hours.iter().filter_map(|hour| hour.mean_ms).fold(1.0, f64::max);
+        let lossiest = hours.iter().map(HourSummary::loss_rate).fold(1.0, f64::max);
+
+        for (index, hour) in hours.iter().enumerate() {
+            let left = rect.left() + index as f32 * bar_width;
+            if let Some(mean) = hour.mean_ms {
+                let color = CircleColor::from_ping_response(Some(mean), self.green_threshold, self.yellow_threshold, self.very_slow_threshold)
+                    .to_color32();
+                let height = latency_height * (mean / slowest).clamp(0.05, 1.0) as f32;
+                painter.rect_filled(
+                    egui::Rect::from_min_max(
+                        Pos2::new(left + 1.0, rect.top() + latency_height - height),
+                        Pos2::new(left + bar_width - 1.0, rect.top() + latency_height),
+                    ),
+                    1.0,
+                    color,
+                );
+            }
+            if hour.failed > 0 {
+                let height = loss_height * (hour.loss_rate() / lossiest).clamp(0.05, 1.0) as f32;
+                painter.rect_filled(
+                    egui::Rect::from_min_max(Pos2::new(left + 1.0, loss_top), Pos2::new(left + bar_width - 1.0, loss_top + height)),
+                    1.0,
+                    Color32::RED,
+                );
+            }
+            if index % 3 == 0 {
+                painter.text(
+                    Pos2::new(left + bar_width / 2.0, rect.bottom()),
+                    egui::Align2::CENTER_BOTTOM,
+                    format!("{index:02}"),
+                    egui::FontId::proportional(10.0),
+                    ui.visuals().weak_text_color(),
+                );
+            }
+        }
+
+        if let Some(pointer) = response.hover_pos() {
+            let index = (((pointer.x - rect.left()) / bar_width) as usize).min(23);
+            let hour = &hours[index];
+            let text = match hour.mean_ms {
+                _ if hour.probes == 0 => format!("{index:02}:00 no data"),
+                Some(mean) => format!("{index:02}:00 mean {}, {:.2}% loss ({} probes)", ping::format_ms(mean), hour.loss_rate(), hour.probes),
+                None => format!("{index:02}:00 no replies ({} probes)", hour.probes),
+            };
+            response.on_hover_text(text);
+        }
+    }
+
+    fn render_config_errors(&mut self, ui: &mut egui::Ui) {
+        let validation_errors = self.current_config().validate();
+        if self.config_errors.is_empty() && validation_errors.is_empty() {
+            return;
+        }
+
+        for error in self.config_errors.iter().chain(validation_errors.iter()) {
+            ui.colored_label(Color32::RED, format!("⚠ {error}"));
+        }
+
+        if !self.config_errors.is_empty() && ui.small_button("Dismiss").clicked() {
+            self.config_errors.clear();
+        }
+    }
+
+    /// Explains why ICMP pings go through the system ping command and how to allow them
+    fn render_icmp_access(&self, ui: &mut egui::Ui) {
+        let IcmpAccess::Denied(e) = &self.icmp_access else {
+            return;
+        };
+        if self.probe != ProbeType::Icmp {
+            return;
+        }
+        ui.colored_label(Color32::from_rgb(255, 165, 0), format!("⚠ This app may not send ICMP ({e}); pinging with the system ping command instead"))
+            .on_hover_text("That works, but starts a process per probe. The TCP probe needs no special rights.");
+        egui::CollapsingHeader::new("How to allow ICMP")
+            .id_salt("icmp_access_help")
+            .show(ui, |ui| {
+                for (explanation, command) in icmp_access::fix_steps() {
+                    ui.label(explanation);
+                    if !command.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.monospace(&command);
+                            if ui.small_button("Copy").clicked() {
+                                ui.ctx().copy_text(command.clone());
+                            }
+                        });
+                    }
+                }
+                ui.weak("Restart Ping Monitor afterwards.");
+            });
+    }
+
+    /// Reduces a pasted URL or padded "host:port" to what the current probe takes: the host for
+    /// ICMP, host:port for TCP (using the URL scheme's port when none is given), and for NTP and
+    /// SNMP the host with a port only when it was typed as host:port rather than part of a URL
+    fn clean_up_pasted_target(&mut self, edit: &egui::Response) {
+        let pasted = edit.ctx.input(|input| input.events.iter().any(|event| matches!(event, egui::Event::Paste(_))));
+        // The HTTP probe takes the whole URL
+        if !edit.changed() || !pasted || self.probe == ProbeType::Http {
+            return;
+        }
+        let Some(parsed) = target_parser::parse_target(&self.target) else {
+            return;
+        };
+        self.target = match self.probe {
+            ProbeType::Icmp => parsed.host,
+            ProbeType::Tcp => parsed.with_port(),
+            _ if self.target.contains("://") => ParsedTarget { port: None, ..parsed }.with_port(),
+            _ => parsed.with_port(),
+        };
+    }
+
+    fn uses_target_field(&self) -> bool {
+        matches!(self.probe, ProbeType::Icmp | ProbeType::Ntp | ProbeType::Snmp | ProbeType::Tcp | ProbeType::Dns | ProbeType::Http)
+    }
+
+    /// Resolves the target shortly after it was last edited, before monitoring starts, so a
+    /// typo shows up as an error next to the field rather than as a dial of red circles
+    fn update_resolve_preview(&mut self, ctx: &egui::Context) {
+        while let Ok(preview) = self.resolve_preview_receiver.try_recv() {
+            if preview.target == self.previewed_target {
+                self.resolve_preview = Some(preview);
+            }
+        }
+        if self.is_monitoring || !self.uses_target_field() {
+            self.resolve_preview_due = None;
+            return;
+        }
+
+        let target = self.target.trim();
+        if target != self.previewed_target {
+            self.previewed_target = target.to_string();
+            self.resolve_preview = None;
+            self.resolve_preview_due = Some(Instant::now() + Duration::from_millis(RESOLVE_PREVIEW_DELAY_MS));
+        }
+        let Some(due) = self.resolve_preview_due else {
+            return;
+        };
+        if Instant::now() < due {
+            ctx.request_repaint_after(due - Instant::now());
+            return;
+        }
+        self.resolve_preview_due = None;
+        let valid = match self.probe {
+            ProbeType::Tcp => tcp_probe::validate_address(target).and_then(|()| config::validate_target(target)),
+            ProbeType::Http => http_probe::validate_url(target),
+            _ => config::validate_target(target),
+        };
+        match valid {
+            Ok(()) => ResolvePreview::spawn(target.to_string(), self.resolve_preview_sender.clone(), ctx.clone()),
+            Err(e) => self.resolve_preview = Some(ResolvePreview::failed(target, e)),
+        }
+    }
+
+    fn render_resolve_preview(&self, ui: &mut egui::Ui) {
+        if self.is_monitoring || !self.uses_target_field() || self.target.trim().is_empty() {
+            return;
+        }
+        match &self.resolve_preview {
+            None => { ui.weak("Resolving…"); }
+            Some(ResolvePreview { result: Ok(addresses), .. }) => {
+                let described: Vec<String> = addresses
+                    .iter()
+                    .take(MAX_PREVIEW_ADDRESSES)
+                    .map(|ip| format!("{ip} ({})", if ip.is_ipv4() { "IPv4" } else { "IPv6" }))
+                    .collect();
+                let more = addresses.len().saturating_sub(MAX_PREVIEW_ADDRESSES);
+                let more = if more > 0 { format!(" and {more} more") } else { String::new() };
+                ui.weak(format!("→ {}{more}", described.join(", ")));
+            }
+            Some(ResolvePreview { result: Err(e), .. }) => { ui.colored_label(Color32::RED, format!("⚠ {e}")); }
+        }
+    }
+
+    fn render_target_input(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Probe:");
+            ui.add_enabled_ui(!self.is_monitoring, |ui| {
+                egui::ComboBox::from_id_salt("probe_type")
+                    .selected_text(self.probe.label())
+                    .show_ui(ui, |ui| {
+                        for probe in ProbeType::ALL {
+                            ui.selectable_value(&mut self.probe, probe, probe.label());
+                        }
+                    });
+            });
+        });
+        ui.horizontal(|ui| match self.probe {
+            ProbeType::Icmp => {
+                ui.label("Target (IP or hostname):");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target));
+                self.clean_up_pasted_target(&edit);
+                ui.add_enabled_ui(!self.is_monitoring && !self.recent_targets.is_empty(), |ui| {
+                    ui.menu_button("▾", |ui| {
+                        for recent in &self.recent_targets {
+                            let button = ui.button(format!("{}  ({})", recent.target, recent.summary()));
+                            if button.clicked() {
+                                self.target = recent.target.clone();
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Recent targets");
+                });
+                ui.add_enabled_ui(!self.is_monitoring, |ui| {
+                    ui.menu_button("★", |ui| {
+                        for pick in quick_picks::quick_picks(self.gateway, self.dns_servers.first().copied()) {
+                            let button = ui.button(format!("{}  ({})", pick.name, pick.target));
+                            ui.weak(&pick.description);
+                            if button.clicked() {
+                                self.target = pick.target;
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Well-known targets, with what each one tells you");
+                });
+            }
+            ProbeType::Command => {
+                ui.label("Command:");
+                ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.command)
+                    .hint_text("exit 0 = up; last number printed = ms"));
+            }
+            ProbeType::Ntp => {
+                ui.label("NTP server:");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target).hint_text("e.g. pool.ntp.org"));
+                self.clean_up_pasted_target(&edit);
+                ui.label("Alert above ±");
+                ui.add(egui::DragValue::new(&mut self.ntp_offset_threshold_ms).range(1..=60_000));
+                ui.label("[ms] offset");
+            }
+            ProbeType::Tcp => {
+                ui.label("Host:port:");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target).hint_text("e.g. example.com:22"));
+                self.clean_up_pasted_target(&edit);
+                ui.label("Expect:");
+                ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.tcp_expect)
+                    .hint_text("optional regex, e.g. ^SSH-2\\.0"))
+                    .on_hover_text("Matched against the first bytes the server sends; a mismatch is shown as degraded");
+                ui.label("Via SOCKS5:");
+                ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.tcp_proxy)
+                    .hint_text("optional, e.g. localhost:1080"))
+                    .on_hover_text("Connect from the proxy's vantage point, e.g. an ssh -D 1080 forward; host names are resolved by the proxy");
+            }
+            ProbeType::Dns => {
+                ui.label("DNS server:");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target)
+                    .hint_text(format!("host or host:{}", dns_probe::DNS_PORT)));
+                self.clean_up_pasted_target(&edit);
+                ui.label("Look up:");
+                let mut query_name = self.target_entry().map(|entry| entry.query_name.clone()).unwrap_or_default();
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut query_name)
+                    .hint_text(dns_probe::DEFAULT_QUERY_NAME))
+                    .on_hover_text("Kept with this server");
+                if edit.changed() {
+                    self.edit_target_entry(|entry| entry.query_name = query_name);
+                }
+            }
+            ProbeType::Http => {
+                ui.label("URL:");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target).hint_text("e.g. https://example.com/"));
+                self.clean_up_pasted_target(&edit);
+            }
+            ProbeType::Snmp => {
+                ui.label("Device:");
+                let edit = ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.target)
+                    .hint_text(format!("host or host:{}", snmp_probe::SNMP_PORT)));
+                self.clean_up_pasted_target(&edit);
+            }
+            ProbeType::Remote => {
+                ui.label("Agent:");
+                ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.remote_agent)
+                    .hint_text(format!("host or host:{}", remote_agent::DEFAULT_AGENT_PORT)));
+                ui.label("Token:");
+                ui.add_enabled(!self.is_monitoring, egui::TextEdit::singleline(&mut self.agent_token).password(true));
+            }
+        });
+        self.render_resolve_preview(ui);
+        self.render_target_style(ui);
+        if self.uses_target_field() {
+            // Internationalized names are resolved in punycode and shown in Unicode
+            let (unicode, ascii) = (target_parser::to_unicode(&self.target), target_parser::to_ascii(&self.target));
+            if unicode != ascii {
+                ui.weak(format!("{unicode} ({ascii})"));
+            }
+        }
+        if self.probe == ProbeType::Snmp {
+            ui.add_enabled_ui(!self.is_monitoring, |ui| self.render_snmp_settings(ui));
+        }
+        if self.probe == ProbeType::Http {
+            ui.add_enabled_ui(!self.is_monitoring, |ui| self.render_http_proxy_settings(ui));
+        }
+        if let Some(viewer) = &self.remote_viewer {
+            ui.label(viewer.status.to_string());
+        }
+        if let Some(gateway) = self.gateway
+            && (self.probe != ProbeType::Icmp || self.target != gateway.to_string())
+        {
+            let button = ui.add_enabled(!self.is_monitoring, egui::Button::new(format!("Monitor my router ({gateway})")))
+                .on_hover_text("Ping the default gateway and compare it with the current target");
+            if button.clicked() {
+                self.monitor_gateway(gateway);
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("MAC (Wake-on-LAN):");
+            let mut mac = self.target_entry().map(|entry| entry.mac.clone()).unwrap_or_default();
+            let edit = ui.add(egui::TextEdit::singleline(&mut mac).hint_text("optional, aa:bb:cc:dd:ee:ff"))
+                .on_hover_text("Kept with this target");
+            if edit.changed() {
+                self.edit_target_entry(|entry| entry.mac = mac);
+            }
+        });
+        self.render_wake_on_lan(ui);
+
+        if let Some(ip) = self.ping_results.back().and_then(|result| result.target_ip) {
+            match self.geoip.lookup(ip) {
+                Some(info) => ui.label(format!("Resolved: {ip} · {info}")),
+                None => ui.label(format!("Resolved: {ip}")),
+            };
+        }
+    }
+
+    /// Offers a Wake button while a LAN target with a configured MAC is not answering,
+    /// then watches for it to come back
+    fn render_wake_on_lan(&mut self, ui: &mut egui::Ui) {
+        if let Some(sent_at) = self.wake_sent_at
+            && sent_at.elapsed() >= Duration::from_secs(WAKE_WATCH_SECS)
+        {
+            self.wake_sent_at = None;
+            self.wake_status = Some(format!("No reply within {} min of the wake packet", WAKE_WATCH_SECS / 60));
+        }
+
+        let last_result = self.ping_results.back();
+        let target_down = last_result.is_some_and(|result| !result.success);
+        let on_lan = last_result
+            .and_then(|result| result.target_ip)
+            .is_some_and(|ip| match ip {
+                IpAddr::V4(v4) => v4.is_private() || v4.is_link_local(),
+                IpAddr::V6(v6) => v6.segments()[0] & 0xfe00 == 0xfc00 || v6.segments()[0] & 0xffc0 == 0xfe80,
+            });
+        let mac = self.target_entry().and_then(|entry| wake_on_lan::parse_mac(&entry.mac).ok());
+
+        ui.horizontal(|ui| {
+            if let Some(mac) = mac
+                && target_down
+                && on_lan
+                && self.wake_sent_at.is_none()
+                && ui.button("Wake").on_hover_text("Send a Wake-on-LAN magic packet to the target's MAC").clicked()
+            {
+                match wake_on_lan::send_magic_packet(mac) {
+                    Ok(()) => {
+                        self.wake_sent_at = Some(Instant::now());
+                        self.wake_status = None;
+                        if !self.is_monitoring {
+                            self.toggle_monitoring();
+                        }
+                    }
+                    Err(e) => self.wake_status = Some(format!("Could not send wake packet: {e}")),
+                }
+            }
+            if let Some(sent_at) = self.wake_sent_at {
+                ui.spinner();
+                ui.label(format!("Waiting for the target to wake… {:.0} s", sent_at.elapsed().as_secs_f64()));
+            } else if let Some(status) = &self.wake_status {
+                ui.label(status);
+            }
+        });
+    }
+
+    /// Where along the path loss starts, from the outage's traceroute; hops pinned to the target
+    /// list contribute their measured loss instead of the trace's single answer, as in MTR. A hop
+    /// that stayed silent in the trace is matched to the router pinned at its position.
+    fn loss_summary(&self, hops: &[TraceHop]) -> Option<String> {
+        let target_ip = self.ping_results.iter().rev().find_map(|result| result.target_ip);
+        let mut losses: Vec<HopLoss> = hops
+            .iter()
+            .map(|hop| {
+                let entry = self.target_list.iter().find(|entry| match hop.address {
+                    Some(address) => entry.host == address.to_string(),
+                    None => entry.hop == Some(hop.number),
+                });
+                let watched = entry.and_then(|entry| {
+                    self.target_watch.status(entry, self.green_threshold, self.yellow_threshold, self.very_slow_threshold)
+                });
+                let loss_rate = match watched {
+                    Some(status) => status.loss_rate,
+                    None if hop.rtt_ms.is_some() => 0.0,
+                    None => 100.0,
+                };
+                let address = hop.address.or_else(|| entry.and_then(|entry| entry.host.parse().ok()));
+                HopLoss { number: hop.number, address, loss_rate }
+            })
+            .collect();
+        // Outage traces rarely reach the target, which wasn't answering while they ran
+        if let Some(last) = hops.last()
+            && (target_ip.is_none() || last.address != target_ip)
+        {
+            losses.push(HopLoss { number: last.number + 1, address: target_ip, loss_rate: 100.0 });
+        }
+        loss_localization::summarize(&losses, self.gateway, target_ip, &self.geoip)
+    }
+
+    /// Hop line with the owning network appended when a GeoIP database knows it
+    fn describe_hop(&self, hop: &TraceHop) -> String {
+        match hop.address.and_then(|address| self.geoip.lookup(address)) {
+            Some(info) => format!("{hop}  [{info}]"),
+            None => hop.to_string(),
+        }
+    }
+
+    /// Hop line with a button to watch the router; true when the button was clicked
+    fn render_hop(&self, ui: &mut egui::Ui, hop: &TraceHop) -> bool {
+        ui.horizontal(|ui| {
+            ui.monospace(self.describe_hop(hop));
+            hop.address.is_some()
+                && ui.small_button("📌").on_hover_text("Keep pinging this router from the target list").clicked()
+        })
+        .inner
+    }
+
+    /// Adds a traceroute hop to the target list, so loss that starts at one router can be watched
+    fn pin_hop(&mut self, hop: &TraceHop) {
+        let Some(address) = hop.address else {
+            return;
+        };
+        let host = address.to_string();
+        if self.target_list.iter().any(|entry| entry.host == host) {
+            self.toasts.push(ToastLevel::Info, format!("{host} is already in the target list"));
+            return;
+        }
+        self.target_list.push(TargetEntry {
+            host: host.clone(),
+            label: format!("Hop {} ({host})", hop.number),
+            group: "Hops".to_string(),
+            hop: Some(hop.number),
+            ..TargetEntry::default()
+        });
+        self.toasts.push(ToastLevel::Info, format!("Watching hop {} ({host})", hop.number));
+    }
+
+    fn render_snmp_settings(&mut self, ui: &mut egui::Ui) {
+        let snmp = &mut self.snmp;
+        ui.horizontal(|ui| {
+            ui.label("SNMP");
+            egui::ComboBox::from_id_salt("snmp_version")
+                .selected_text(snmp.version.label())
+                .show_ui(ui, |ui| {
+                    for version in SnmpVersion::ALL {
+                        ui.selectable_value(&mut snmp.version, version, version.label());
+                    }
+                });
+            ui.label("OID:");
+            ui.add(egui::TextEdit::singleline(&mut snmp.oid).hint_text(snmp_probe::SYS_UPTIME_OID));
+        });
+        ui.horizontal(|ui| match snmp.version {
+            SnmpVersion::V2c => {
+                ui.label("Community:");
+                ui.add(egui::TextEdit::singleline(&mut snmp.community).password(true));
+            }
+            SnmpVersion::V3 => {
+                ui.label("User:");
+                ui.add(egui::TextEdit::singleline(&mut snmp.username).desired_width(100.0));
+                egui::ComboBox::from_id_salt("snmp_auth_protocol")
+                    .selected_text(snmp.auth_protocol.label())
+                    .show_ui(ui, |ui| {
+                        for protocol in SnmpAuthProtocol::ALL {
+                            ui.selectable_value(&mut snmp.auth_protocol, protocol, protocol.label());
+                        }
+                    });
+                ui.label("Auth:");
+                ui.add(egui::TextEdit::singleline(&mut snmp.auth_password).password(true).desired_width(100.0))
+                    .on_hover_text("Leave empty for noAuthNoPriv");
+                ui.label("Privacy (AES):");
+                ui.add(egui::TextEdit::singleline(&mut snmp.privacy_password).password(true).desired_width(100.0))
+                    .on_hover_text("Leave empty for authNoPriv");
+            }
+        });
+    }
+
+    fn render_http_proxy_settings(&mut self, ui: &mut egui::Ui) {
+        let proxy = &mut self.http_proxy;
+        ui.horizontal(|ui| {
+            ui.label("Proxy:");
+            ui.add(egui::TextEdit::singleline(&mut proxy.url).hint_text("optional, e.g. http://proxy:8080"));
+            ui.add_enabled(proxy.url.trim().is_empty(), egui::Checkbox::new(&mut proxy.use_system, "Else use the system proxy"))
+                .on_hover_text("HTTPS_PROXY, HTTP_PROXY or ALL_PROXY (skipping NO_PROXY hosts) or, on Windows, the Internet Options");
+        });
+        if !proxy.url.trim().is_empty() {
+            ui.horizontal(|ui| {
+                ui.label("User:");
+                ui.add(egui::TextEdit::singleline(&mut proxy.username).desired_width(100.0))
+                    .on_hover_text("Leave empty if the proxy needs no login");
+                ui.label("Password:");
+                ui.add(egui::TextEdit::singleline(&mut proxy.password).password(true).desired_width(100.0));
+            });
+        }
+    }
+
+    fn render_threshold_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("Time Thresholds:");
+        ui.horizontal(|ui| {
+            ui.label("Green < ");
+            ui.add(egui::DragValue::new(&mut self.green_threshold).range(1..=1000));
+            ui.label("[ms]");
+            ui.label("≤ Yellow <");
+            ui.add(egui::DragValue::new(&mut self.yellow_threshold).range(1..=2000));
+            ui.label("[ms]");
+            ui.label("≤ Orange <");
+            ui.add(egui::DragValue::new(&mut self.very_slow_threshold).range(1..=10_000));
+            ui.label("[ms]");
+            ui.label("≤ Dark red");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Several results per circle:");
+            egui::ComboBox::from_id_salt("slot_aggregate")
+                .selected_text(self.slot_aggregate.label())
+                .show_ui(ui, |ui| {
+                    for aggregate in SlotAggregate::ALL {
+                        ui.selectable_value(&mut self.slot_aggregate, aggregate, aggregate.label());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            let mut automatic = self.fade_timings.is_none();
+            ui.label("Circles fade:");
+            if ui.checkbox(&mut automatic, "with the interval").changed() {
+                self.fade_timings = (!automatic).then(|| self.effective_fade_timings());
+            }
+            match &mut self.fade_timings {
+                Some(fade) => {
+                    ui.label("from");
+                    ui.add(egui::DragValue::new(&mut fade.start_secs).range(1..=86_400));
+                    ui.label("to gray at");
+                    ui.add(egui::DragValue::new(&mut fade.end_secs).range(2..=86_400));
+                    ui.label("[s]");
+                    fade.end_secs = fade.end_secs.max(fade.start_secs + 1);
+                }
+                None => {
+                    let fade = self.effective_fade_timings();
+                    ui.weak(format!("from {} s to gray at {} s", fade.start_secs, fade.end_secs));
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            let saving = self.saving_power();
+            let saver = &mut self.battery_saver;
+            let mut changed = ui.checkbox(&mut saver.enabled, "On battery:")
+                .on_hover_text("Save power while the laptop isn't plugged in; normal behavior returns on AC")
+                .changed();
+            ui.add_enabled_ui(saver.enabled, |ui| {
+                ui.label("probe every");
+                changed |= ui.add(egui::DragValue::new(&mut saver.interval_secs).range(1..=config::MAX_INTERVAL_SECS)).changed();
+                ui.label("[s]");
+                ui.checkbox(&mut saver.slow_repaint, "fewer redraws");
+                ui.checkbox(&mut saver.no_animations, "no animations");
+            });
+            match self.power_monitor.current {
+                Some(PowerSource::Battery) if saving => ui.weak("🔋 saving power"),
+                Some(PowerSource::Battery) => ui.weak("🔋 on battery"),
+                Some(PowerSource::Ac) => ui.weak("🔌 on AC"),
+                None => ui.weak("power source unknown"),
+            };
+            if changed {
+                self.next_ping_at = None;
+            }
+        });
+        ui.horizontal(|ui| {
+            let saver = &mut self.metered_saver;
+            ui.label("On metered connections:");
+            let mut changed = ui.checkbox(&mut saver.slow_probing, "probe every").changed();
+            ui.add_enabled_ui(saver.slow_probing, |ui| {
+                changed |= ui.add(egui::DragValue::new(&mut saver.interval_secs).range(1..=config::MAX_INTERVAL_SECS)).changed();
+                ui.label("[s]");
+            });
+            ui.checkbox(&mut saver.pause_heavy_tests, "pause speed tests")
+                .on_hover_text("The speed and bufferbloat tests transfer tens of megabytes");
+            match self.metered_monitor.current {
+                Some(true) => ui.weak("📶 metered now"),
+                Some(false) => ui.weak("not metered now"),
+                None => ui.weak("metered status unknown"),
+            };
+            if changed {
+                self.next_ping_at = None;
+            }
+        });
+        ui.horizontal(|ui| {
+            let saver = &mut self.fullscreen_saver;
+            ui.label("While a full-screen app is in front:");
+            let mut changed = false;
+            egui::ComboBox::from_id_salt("fullscreen_action")
+                .selected_text(saver.action.label())
+                .show_ui(ui, |ui| {
+                    for action in FullscreenAction::ALL {
+                        changed |= ui.selectable_value(&mut saver.action, action, action.label()).changed();
+                    }
+                })
+                .response
+                .on_hover_text("Games and videos get the machine to themselves; redraws also slow to once a second");
+            if saver.action == FullscreenAction::Throttle {
+                changed |= ui.add(egui::DragValue::new(&mut saver.interval_secs).range(1..=config::MAX_INTERVAL_SECS)).changed();
+                ui.label("[s]");
+            }
+            if saver.action != FullscreenAction::Ignore {
+                match self.fullscreen_monitor.current {
+                    Some(true) => ui.weak("🎮 full screen now"),
+                    Some(false) => ui.weak("nothing full screen now"),
+                    None => ui.weak("full-screen detection unavailable").on_hover_text("Needs Windows, or X11 with xprop on Linux"),
+                };
+            }
+            if changed {
+                self.next_ping_at = None;
+            }
+        });
+        ui.checkbox(&mut self.alert_on_dns_change, "Alert when the target's DNS answer changes")
+            .on_hover_text("Changes are always logged and marked on the dial; names that rotate between addresses would alert constantly");
+        ui.checkbox(&mut self.rediscover_gateway, "Look up the gateway and DNS servers again after network changes")
+            .on_hover_text("Keeps \"Monitor my router\" and the quick picks pointing at the current network");
+        ui.horizontal(|ui| {
+            ui.label("Timestamps:");
+            egui::ComboBox::from_id_salt("timestamp_format")
+                .selected_text(self.timestamp_format.label())
+                .show_ui(ui, |ui| {
+                    for format in TimestampFormat::ALL {
+                        ui.selectable_value(&mut self.timestamp_format, format, format.label());
+                    }
+                });
+        })
+        .response
+        .on_hover_text("Used in the event, outage and note lists and in CSV exports");
+        ui.horizontal(|ui| {
+            ui.label("When in the background, flash the taskbar or bounce the dock for");
+            let attention = &mut self.attention;
+            for (name, level) in [("outages", &mut attention.critical), ("warnings", &mut attention.warning), ("recoveries", &mut attention.info)] {
+                ui.label(name);
+                egui::ComboBox::from_id_salt(("attention", name))
+                    .selected_text(level.label())
+                    .show_ui(ui, |ui| {
+                        for choice in AttentionLevel::ALL {
+                            ui.selectable_value(level, choice, choice.label());
+                        }
+                    });
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.grafana.enabled, "Send annotations to Grafana")
+                .on_hover_text("Outages, route changes and notes are posted to the server's annotations API");
+            ui.add_enabled_ui(self.grafana.enabled, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.grafana.url).hint_text("https://grafana.example.com").desired_width(180.0));
+                ui.label("Token:");
+                ui.add(egui::TextEdit::singleline(&mut self.grafana.api_token).password(true).desired_width(100.0));
+                ui.label("Tags:");
+                ui.add(egui::TextEdit::singleline(&mut self.grafana.tags).hint_text("ping-monitor, home").desired_width(120.0))
+                    .on_hover_text("Comma-separated; \"outage\", \"route\" or \"note\" is added to each annotation");
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Page on-call during outages:");
+            egui::ComboBox::from_id_salt("paging_service")
+                .selected_text(self.paging.service.label())
+                .show_ui(ui, |ui| {
+                    for service in PagingService::ALL {
+                        ui.selectable_value(&mut self.paging.service, service, service.label());
+                    }
+                });
+            if self.paging.service != PagingService::Off {
+                let hint = if self.paging.service == PagingService::PagerDuty { "routing key" } else { "API key" };
+                ui.add(egui::TextEdit::singleline(&mut self.paging.key).hint_text(hint).password(true).desired_width(140.0));
+            }
+            if self.paging.service == PagingService::Opsgenie {
+                ui.checkbox(&mut self.paging.opsgenie_eu, "EU account");
+            }
+        })
+        .response
+        .on_hover_text("Opens a critical incident when an outage starts and resolves it when the target answers again");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.ntfy.enabled, "Push alerts with ntfy")
+                .on_hover_text("Subscribe to the topic in the ntfy app to get outage and recovery alerts on your phone");
+            ui.add_enabled_ui(self.ntfy.enabled, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.ntfy.server).hint_text(ntfy::DEFAULT_NTFY_SERVER).desired_width(140.0));
+                ui.label("Topic:");
+                ui.add(egui::TextEdit::singleline(&mut self.ntfy.topic).desired_width(120.0))
+                    .on_hover_text("Anyone who knows a topic on a public server can read it, so pick something hard to guess");
+                ui.label("Token:");
+                ui.add(egui::TextEdit::singleline(&mut self.ntfy.access_token).hint_text("optional").password(true).desired_width(100.0));
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.pushover.enabled, "Send alerts with Pushover");
+            ui.add_enabled_ui(self.pushover.enabled, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.pushover.app_token).hint_text("app token").password(true).desired_width(100.0));
+                ui.add(egui::TextEdit::singleline(&mut self.pushover.user_key).hint_text("user key").password(true).desired_width(100.0));
+            });
+        });
+        if self.pushover.enabled {
+            ui.horizontal(|ui| {
+                ui.label("Pushover priority for");
+                let pushover = &mut self.pushover;
+                for (name, priority) in [("outages", &mut pushover.critical), ("warnings", &mut pushover.warning), ("recoveries", &mut pushover.info)] {
+                    ui.label(name);
+                    egui::ComboBox::from_id_salt(("pushover", name))
+                        .selected_text(priority.label())
+                        .show_ui(ui, |ui| {
+                            for choice in PushoverPriority::ALL {
+                                ui.selectable_value(priority, choice, choice.label());
+                            }
+                        });
+                }
+            })
+            .response
+            .on_hover_text("Emergency repeats every minute for up to an hour until acknowledged in the app");
+        }
+    }
+
+    fn render_control_buttons(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.button(if self.is_monitoring { "Stop" } else { "Start" }).clicked() {
+                self.toggle_monitoring();
+            }
+            ui.checkbox(&mut self.auto_stop_enabled, "Stop after")
+                .on_hover_text("End the session after a set time and summarize it, e.g. to watch a backup window");
+            ui.add_enabled(
+                self.auto_stop_enabled,
+                egui::DragValue::new(&mut self.auto_stop_mins).range(1..=config::MAX_AUTO_STOP_MINS).suffix(" min"),
+            );
+            if let Some(remaining) = self.auto_stop_remaining() {
+                let mins = remaining.as_secs().div_ceil(60);
+                ui.weak(if mins >= 60 { format!("{} h {} min left", mins / 60, mins % 60) } else { format!("{mins} min left") });
+            }
+
+            let diagnose_label = if self.health_check_running { "Diagnosing…" } else { "Diagnose" };
+            let diagnose = ui.add_enabled(!self.health_check_running, egui::Button::new(diagnose_label))
+                .on_hover_text("Ping the default gateway, DNS server and an internet anchor at once");
+            if diagnose.clicked() {
+                self.health_check_running = true;
+                HealthCheck::spawn(self.health_sender.clone(), ui.ctx().clone());
+            }
+
+            ui.add_enabled(Sonar::AVAILABLE, egui::Checkbox::new(&mut self.sonar_enabled, "Sonar"))
+                .on_hover_text("Beep on every reply: higher pitch for faster replies, silence on loss")
+                .on_disabled_hover_text("This build has no sound support (build with --features sound)");
+
+            ui.checkbox(&mut self.announce_changes, "Announce changes")
+                .on_hover_text("Have screen readers announce when the target goes up, slow or down");
+
+            ui.checkbox(&mut self.web_dashboard_enabled, "Web dashboard")
+                .on_hover_text("Serve a read-only status page to browsers, on this computer or the local network");
+
+            let heavy_paused = self.heavy_tests_paused();
+            let bufferbloat = ui.add_enabled(self.bufferbloat_phase.is_none() && !heavy_paused, egui::Button::new("Bufferbloat test"))
+                .on_hover_text("Compare latency while idle and while saturating the link (about 25 seconds)")
+                .on_disabled_hover_text(METERED_PAUSE_HINT);
+            if bufferbloat.clicked() {
+                self.bufferbloat_phase = Some(BufferbloatPhase::Idle);
+                self.bufferbloat_result = None;
+                BufferbloatTest::spawn(self.target.clone(), self.bufferbloat_sender.clone(), ui.ctx().clone());
+            }
+
+            let loading = self.load_window.as_ref().is_some_and(LoadWindow::is_open);
+            let load_label = if loading { "End loaded window" } else { "Measure under my load" };
+            let load = ui.add_enabled(self.is_monitoring || loading, egui::Button::new(load_label))
+                .on_hover_text("Start, then run your own large transfer; ending the window compares latency with the minute before");
+            if load.clicked() {
+                match &mut self.load_window {
+                    Some(window) if window.is_open() => window.stop(),
+                    _ => self.load_window = Some(LoadWindow::start(&self.ping_results)),
+                }
+            }
+
+            let speed_label = if self.speed_test_running { "Testing speed…" } else { "Speed test" };
+            let speed_test = ui.add_enabled(!self.speed_test_running && !heavy_paused, egui::Button::new(speed_label))
+                .on_hover_text("Measure download and upload throughput against Cloudflare's speed test servers")
+                .on_disabled_hover_text(METERED_PAUSE_HINT);
+            if speed_test.clicked() {
+                self.speed_test_running = true;
+                SpeedTest::spawn(self.speed_test_sender.clone(), ui.ctx().clone());
+            }
+
+            let mtu_label = if self.path_mtu_running { "Finding MTU…" } else { "Path MTU" };
+            let path_mtu = ui.add_enabled(!self.path_mtu_running, egui::Button::new(mtu_label))
+                .on_hover_text("Find the largest unfragmented packet that reaches the target");
+            if path_mtu.clicked() {
+                self.path_mtu_running = true;
+                PathMtu::spawn(self.target.clone(), self.path_mtu_sender.clone(), ui.ctx().clone());
+            }
+        });
+
+        self.render_web_dashboard(ui);
+        self.render_health_report(ui);
+        self.render_load_window(ui);
+        self.render_bufferbloat(ui);
+        self.render_path_mtu(ui);
+    }
+
+    fn render_path_mtu(&self, ui: &mut egui::Ui) {
+        match &self.path_mtu_result {
+            Some(Ok(report)) => {
+                let bound = if report.at_limit { "at least " } else { "" };
+                ui.label(format!("Path MTU to {}: {bound}{} bytes", report.target, report.mtu));
+                if report.black_hole {
+                    ui.colored_label(Color32::from_rgb(255, 165, 0),
+                        "⚠ Oversized packets are dropped silently (PMTU black hole): large transfers may stall");
+                }
+            }
+            Some(Err(e)) => { ui.colored_label(Color32::RED, format!("Path MTU discovery failed: {e}")); }
+            None => {}
+        }
+    }
+
+    fn render_load_window(&mut self, ui: &mut egui::Ui) {
+        let Some(window) = &self.load_window else {
+            return;
+        };
+        let describe = |mean: Option<f64>| mean.map_or("no replies".to_string(), |mean| format!("{mean:.1}ms"));
+        let state = if window.is_open() { "so far" } else { "measured" };
+        ui.horizontal(|ui| {
+            ui.colored_label(LOAD_WINDOW_COLOR, "◯");
+            ui.label(format!(
+                "Under load ({:.0} s {state}): idle {} ({:.1}% loss) → loaded {} ({:.1}% loss)",
+                window.duration().as_secs_f64(),
+                describe(window.idle.mean_ms()),
+                window.idle.loss_rate(),
+                describe(window.loaded.mean_ms()),
+                window.loaded.loss_rate(),
+            ));
+        });
+        match window.latency_increase_ms() {
+            Some(increase) => { ui.label(format!("Latency change under load: {increase:+.1}ms")); }
+            None if window.idle.probes == 0 => { ui.weak("No idle baseline: monitor for a while before starting the load"); }
+            None => {}
+        }
+        if !window.is_open() && ui.small_button("Clear").clicked() {
+            self.load_window = None;
+        }
+    }
+
+    fn render_bufferbloat(&self, ui: &mut egui::Ui) {
+        if let Some(phase) = self.bufferbloat_phase {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(phase.description());
+            });
+        }
+        match &self.bufferbloat_result {
+            Some(Ok(report)) => {
+                let describe = |loaded: Option<f64>| loaded.map_or("all pings lost".to_string(), |loaded| format!("{loaded:.1}ms"));
+                ui.label(format!("Bufferbloat grade: {} (to {})", report.grade(), report.target));
+                ui.label(format!("Idle {:.1}ms, download {} at {:.1} Mbps, upload {} at {:.1} Mbps",
+                    report.idle_ms,
+                    describe(report.download_ms),
+                    report.download_mbps,
+                    describe(report.upload_ms),
+                    report.upload_mbps,
+                ));
+            }
+            Some(Err(e)) => { ui.colored_label(Color32::RED, format!("Bufferbloat test failed: {e}")); }
+            None => {}
+        }
+    }
+
+    fn render_health_report(&self, ui: &mut egui::Ui) {
+        let Some(report) = &self.health_report else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            for (name, status) in [("Gateway", &report.gateway), ("DNS", &report.dns), ("Internet", &report.internet)] {
+                let (color, detail) = match status {
+                    CheckStatus::NotDetected => (Color32::GRAY, "not detected".to_string()),
+                    CheckStatus::Reachable { address, response_time } => (Color32::GREEN, format!("{address}: {}", ping::format_ms(*response_time))),
+                    CheckStatus::Unreachable { address } => (Color32::RED, format!("{address}: no reply")),
+                };
+                let (rect, response) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+                ui.painter().circle_filled(rect.center(), 6.0, color);
+                response.on_hover_text(detail);
+                ui.label(name);
+            }
+        });
+        ui.label(report.summary());
+    }
+
+    /// Limits on concurrent probes, with the probes they've skipped
+    fn render_probe_limits(&mut self, ui: &mut egui::Ui) {
+        let limiter = &mut probe_limiter::lock(&self.probe_limiter);
+        egui::CollapsingHeader::new(format!("Probe limits ({} in flight, {} dropped)", limiter.in_flight(), limiter.total_dropped()))
+            .id_salt("probe_limits")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("At most");
+                    ui.add(egui::DragValue::new(&mut limiter.max_in_flight).range(1..=1024));
+                    ui.label("probes in flight, and");
+                    ui.add(egui::DragValue::new(&mut limiter.max_per_second).range(1..=100));
+                    ui.label("per target per second");
+                });
+                if limiter.dropped.is_empty() {
+                    ui.weak("No probes have been dropped");
+                    return;
+                }
+                let mut dropped: Vec<_> = limiter.dropped.iter().collect();
+                dropped.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+                for (target, counts) in dropped {
+                    ui.label(format!(
+                        "{target}: {} dropped at the in-flight limit, {} rate limited",
+                        counts.saturated, counts.rate_limited,
+                    ));
+                }
+                if ui.small_button("Reset counters").clicked() {
+                    limiter.dropped.clear();
+                }
+            });
+    }
+
+    fn render_network_status(&self, ui: &mut egui::Ui) {
+        if self.network_watcher.link_down() {
+            ui.colored_label(CircleColor::LinkDown.to_color32(), "⚠ Network: link down (no physical interface has a usable address)");
+        }
+        if self.network_events.is_empty() {
+            return;
+        }
+
+        egui::CollapsingHeader::new(format!("Network events ({})", self.network_events.len()))
+            .id_salt("network_events")
+            .show(ui, |ui| {
+                for (time, event) in self.network_events.iter().rev() {
+                    let time = self.timestamp_format.display(*time);
+                    ui.label(format!("{time} {event}"));
+                }
+                ui.separator();
+                for interface in &self.network_watcher.interfaces {
+                    let addresses: Vec<String> = interface.addresses.iter().map(|ip| ip.to_string()).collect();
+                    let state = if interface.up { "up" } else { "down" };
+                    ui.monospace(format!("{} {state} {}", interface.name, addresses.join(", ")));
+                }
+            });
+    }
+
+    /// Returns a hop the user chose to watch
+    fn render_outages(&self, ui: &mut egui::Ui) -> Option<TraceHop> {
+        if self.outage_tracker.outages.is_empty() {
+            return None;
+        }
+
+        let mut pinned = None;
+        egui::CollapsingHeader::new(format!("Outages ({})", self.outage_tracker.outages.len()))
+            .id_salt("outages")
+            .show(ui, |ui| {
+                for outage in self.outage_tracker.outages.iter().rev() {
+                    let started = self.timestamp_format.display(outage.started);
+                    let status = if outage.is_ongoing() { " (ongoing)" } else { "" };
+                    let mut summary = format!("{started}, {:.1} min, {} failed pings{status}",
+                        outage.duration().as_secs_f64() / 60.0,
+                        outage.failed_pings,
+                    );
+                    if let Some(location) = outage.fault_location() {
+                        summary += &format!(", {}", location.label());
+                    }
+
+                    egui::CollapsingHeader::new(summary)
+                        .id_salt(("outage", outage.id))
+                        .show(ui, |ui| {
+                            if let Some(fallbacks) = outage.fallback_summary() {
+                                ui.label(format!("Fallbacks: {fallbacks}"));
+                            }
+                            match &outage.traceroute {
+                                Some(TraceSnapshot::Running) => { ui.label("Running traceroute…"); }
+                                Some(TraceSnapshot::Captured(hops)) => {
+                                    if let Some(summary) = &outage.loss_summary {
+                                        ui.label(format!("📍 {summary}"));
+                                    }
+                                    for hop in hops {
+                                        if self.render_hop(ui, hop) {
+                                            pinned = Some(hop.clone());
+                                        }
+                                    }
+                                }
+                                Some(TraceSnapshot::Failed(e)) => { ui.colored_label(Color32::RED, e); }
+                                None => { ui.label("No traceroute captured"); }
+                            }
+                        });
+                }
+            });
+        pinned
+    }
+
+    /// The user's style for `target`, falling back to its label in the target list
+    fn target_style(&self, target: &str) -> TargetStyle {
+        let target = target.trim();
+        let mut style = self.target_styles.get(target).cloned().unwrap_or_default();
+        if style.label.trim().is_empty()
+            && let Some(entry) = self.target_list.iter().find(|entry| entry.host == target)
+        {
+            style.label = entry.label.clone();
+        }
+        style
+    }
+
+    fn set_target_style(&mut self, target: &str, style: TargetStyle) {
+        let target = target.trim().to_string();
+        if style.is_empty() {
+            self.target_styles.remove(&target);
+        } else {
+            self.target_styles.insert(target, style);
+        }
+    }
+
+    /// Chart colors of the main and comparison targets: their accents, or the defaults
+    fn compare_colors(&self) -> (Color32, Color32) {
+        let main = self.target_style(&self.history_target()).accent().unwrap_or(COMPARE_MAIN_COLOR);
+        let other = self.target_style(&self.compare_target).accent().unwrap_or(COMPARE_OTHER_COLOR);
+        (main, other)
+    }
+
+    /// The target's icon and name in its accent color
+    fn style_label(ui: &mut egui::Ui, style: &TargetStyle, target: &str) -> egui::Response {
+        match style.accent() {
+            Some(color) => ui.colored_label(color, style.decorated(target)),
+            None => ui.label(style.decorated(target)),
+        }
+    }
+
+    /// 🎨 menu editing a target's name, icon and accent; true when something changed
+    fn style_menu(ui: &mut egui::Ui, style: &mut TargetStyle) -> bool {
+        let menu = ui.menu_button("🎨", |ui| {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                changed |= ui.add(egui::TextEdit::singleline(&mut style.label).hint_text("e.g. Office VPN").desired_width(140.0)).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Icon:");
+                changed |= ui.add(egui::TextEdit::singleline(&mut style.icon).desired_width(30.0)).changed();
+                for icon in target_style::SUGGESTED_ICONS {
+                    if ui.selectable_label(style.icon == icon, icon).clicked() {
+                        style.icon = if style.icon == icon { String::new() } else { icon.to_string() };
+                        changed = true;
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Color:");
+                if ui.selectable_label(style.color.is_none(), "none").clicked() {
+                    style.color = None;
+                    changed = true;
+                }
+                for color in target_style::ACCENT_COLORS {
+                    let [r, g, b] = color;
+                    let mark = if style.color == Some(color) { "✔" } else { "  " };
+                    if ui.add(egui::Button::new(mark).fill(Color32::from_rgb(r, g, b))).clicked() {
+                        style.color = Some(color);
+                        changed = true;
+                    }
+                }
+            });
+            if ui.button("Reset").clicked() {
+                *style = TargetStyle::default();
+                changed = true;
+            }
+            changed
+        });
+        menu.response.on_hover_text("Name, icon and color used for this target everywhere it is shown");
+        menu.inner == Some(true)
+    }
+
+    /// Style editor for the current target, with its styled name once it has one
+    fn render_target_style(&mut self, ui: &mut egui::Ui) {
+        let target = self.history_target();
+        if target.trim().is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            let mut style = self.target_style(&target);
+            if Self::style_menu(ui, &mut style) {
+                self.set_target_style(&target, style);
+            }
+            let style = self.target_style(&target);
+            if style.is_empty() {
+                ui.weak("Give this target a name, icon and color");
+            } else {
+                Self::style_label(ui, &style, &target);
+            }
+        });
+    }
+
+    fn render_target_list(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        let mut chosen = None;
+        let mut restyled = None;
+        egui::CollapsingHeader::new(format!("Targets ({})", self.target_list.len()))
+            .id_salt("target_list")
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.target_table_view, "Table view")
+                    .on_hover_text("Figures for every target in columns you can sort by; handy for long lists");
+                if self.target_table_view {
+                    self.render_target_table(ui, &mut removed, &mut chosen);
+                } else {
+                    // Ungrouped entries first, then each group under its own heading in list order
+                    let mut groups: Vec<&str> = Vec::new();
+                    for entry in &self.target_list {
+                        if !groups.contains(&entry.group.as_str()) {
+                            groups.push(&entry.group);
+                        }
+                    }
+                    groups.sort_by_key(|group| !group.is_empty());
+                    for group in groups {
+                        if !group.is_empty() {
+                            ui.strong(group);
+                        }
+                        for (index, entry) in self.target_list.iter().enumerate().filter(|(_, entry)| entry.group == group) {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").on_hover_text("Remove from the list").clicked() {
+                                    removed = Some(index);
+                                }
+                                match self.target_watch.status(entry, self.green_threshold, self.yellow_threshold, self.very_slow_threshold) {
+                                    Some(status) => {
+                                        let last = status.last_response_ms.map_or("no reply".to_string(), ping::format_ms);
+                                        ui.colored_label(status.color.to_color32(), "●");
+                                        Self::style_label(ui, &self.target_style(&entry.host), &entry.host).on_hover_text(&entry.host);
+                                        ui.label(format!("{last} · {:.0}% loss", status.loss_rate));
+                                    }
+                                    None => {
+                                        ui.colored_label(Color32::GRAY, "●");
+                                        Self::style_label(ui, &self.target_style(&entry.host), &entry.host).on_hover_text(&entry.host);
+                                    }
+                                }
+                                if ui.add_enabled(!self.is_monitoring, egui::Button::new("Monitor").small()).clicked() {
+                                    chosen = Some((entry.host.clone(), entry.probe));
+                                }
+                                let mut style = self.target_style(&entry.host);
+                                if Self::style_menu(ui, &mut style) {
+                                    restyled = Some((entry.host.clone(), style));
+                                }
+                            });
+                        }
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Import:");
+                    ui.add(egui::TextEdit::singleline(&mut self.import_path).hint_text("path to .txt, .csv or profile .json"));
+                    if ui.add_enabled(!self.import_path.trim().is_empty(), egui::Button::new("Import")).clicked() {
+                        let path = std::path::PathBuf::from(self.import_path.trim());
+                        self.import_targets(&path);
+                    }
+                    if ui.button("Export profile")
+                        .on_hover_text("Save the target, thresholds, schedules and this list (no tokens or commands) for teammates to import")
+                        .clicked()
+                    {
+                        self.export_profile();
+                    }
+                });
+                let dns_button = ui.add_enabled(!self.dns_servers.is_empty(), egui::Button::new("Monitor system DNS servers"))
+                    .on_hover_text("Query each DNS server this computer uses, so a failing resolver shows up next to your other targets");
+                if dns_button.clicked() {
+                    self.monitor_system_dns();
+                }
+                ui.weak("One host per line: host[,label[,green_ms[,yellow_ms[,group]]]]. You can also drop the file onto the window.");
+                if let Some(status) = &self.import_status {
+                    ui.label(status);
+                }
+            });
+        if let Some(index) = removed {
+            self.target_list.remove(index);
+        }
+        if let Some((host, style)) = restyled {
+            self.set_target_style(&host, style);
+        }
+        if let Some((host, probe)) = chosen {
+            self.probe = if probe == ProbeType::Dns { ProbeType::Dns } else { ProbeType::Icmp };
+            self.target = host;
+        }
+    }
+
+    /// The target list with the last minute's figures in columns; clicking a header sorts by it,
+    /// clicking it again reverses the order
+    fn render_target_table(&mut self, ui: &mut egui::Ui, removed: &mut Option<usize>, chosen: &mut Option<(String, ProbeType)>) {
+        let statuses: Vec<_> = self.target_list
+            .iter()
+            .map(|entry| self.target_watch.status(entry, self.green_threshold, self.yellow_threshold, self.very_slow_threshold))
+            .collect();
+        let names: Vec<String> = self.target_list.iter().map(|entry| self.target_style(&entry.host).name(&entry.host)).collect();
+        let mut order: Vec<usize> = (0..self.target_list.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.target_sort.compare((&names[a], statuses[a].as_ref()), (&names[b], statuses[b].as_ref()), self.target_sort_ascending)
+        });
+
+        let format_ms = |ms: Option<f64>| ms.map_or("–".to_string(), ping::format_ms);
+        egui::Grid::new("target_table").striped(true).show(ui, |ui| {
+            for column in TargetColumn::ALL {
+                let sorted = column == self.target_sort;
+                let arrow = match (sorted, self.target_sort_ascending) {
+                    (false, _) => "",
+                    (true, true) => " ▲",
+                    (true, false) => " ▼",
+                };
+                if ui.selectable_label(sorted, format!("{}{arrow}", column.label())).clicked() {
+                    self.target_sort_ascending = !sorted || !self.target_sort_ascending;
+                    self.target_sort = column;
+                }
+            }
+            ui.end_row();
+            for index in order {
+                let entry = &self.target_list[index];
+                Self::style_label(ui, &self.target_style(&entry.host), &entry.host).on_hover_text(&entry.host);
+                match &statuses[index] {
+                    Some(status) => {
+                        ui.label(format_ms(status.last_response_ms));
+                        ui.label(format_ms(status.mean_ms));
+                        ui.label(format_ms(status.p95_ms));
+                        ui.label(format!("{:.1}%", status.loss_rate));
+                        ui.label(if status.streak >= 0 { format!("✔ {}", status.streak) } else { format!("✖ {}", -status.streak) })
+                            .on_hover_text("Replies (✔) or losses (✖) in a row");
+                        ui.colored_label(status.color.to_color32(), format!("● {}", status.color.description()));
+                    }
+                    None => {
+                        for _ in 0..5 {
+                            ui.label("–");
+                        }
+                        ui.colored_label(Color32::GRAY, "● no results yet");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.is_monitoring, egui::Button::new("Monitor").small()).clicked() {
+                        *chosen = Some((entry.host.clone(), entry.probe));
+                    }
+                    if ui.small_button("✖").on_hover_text("Remove from the list").clicked() {
+                        *removed = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    }
+
+    /// Hosts probed alongside the current target only while it fails, e.g. the VPN gateway and
+    /// a public anchor behind a VPN endpoint
+    fn render_fallback_chain(&mut self, ui: &mut egui::Ui) {
+        let target = self.target.trim().to_string();
+        if target.is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("If {target} fails, also probe:"));
+            let chain = self.fallback_chains.entry(target.clone()).or_default();
+            ui.add(egui::TextEdit::singleline(chain).hint_text("e.g. 10.8.0.1, 1.1.1.1").desired_width(200.0))
+                .on_hover_text("Comma-separated; nothing extra is sent while the target answers, and outages list how each fallback fared. Only the main target probes its chain, not the watch list.");
+            if chain.trim().is_empty() {
+                self.fallback_chains.remove(&target);
+            }
+        });
+    }
+
+    /// The main target pinged out of each chosen interface, side by side, for multi-homed hosts
+    /// and Wi-Fi against wired comparisons
+    fn render_replay(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Replay")
+            .id_salt("replay")
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let day_text = self.replay_day.map_or("Pick a day".to_string(), |day| day.to_string());
+                    egui::ComboBox::from_id_salt("replay_day").selected_text(day_text).show_ui(ui, |ui| {
+                        for day in self.history.recorded_days().into_iter().rev() {
+                            ui.selectable_value(&mut self.replay_day, Some(day), day.to_string());
+                        }
+                    });
+                    let target = self.history_target();
+                    let load = ui.add_enabled(!self.is_monitoring && self.replay_day.is_some(), egui::Button::new("Load"))
+                        .on_hover_text(format!("Play that day of {} back on the dial, charts and event log", self.target_style(&target).name(&target)))
+                        .on_disabled_hover_text("Stop monitoring and pick a day first");
+                    if load.clicked() && let Some(day) = self.replay_day {
+                        self.start_replay(day);
+                    }
+                });
+                let Some(replay) = &mut self.replay else {
+                    return;
+                };
+                ui.horizontal_wrapped(|ui| {
+                    let play_label = if replay.paused || replay.finished() { "▶ Play" } else { "⏸ Pause" };
+                    if ui.add_enabled(!replay.finished(), egui::Button::new(play_label)).clicked() {
+                        replay.paused = !replay.paused;
+                    }
+                    ui.label("Speed:");
+                    for speed in REPLAY_SPEEDS {
+                        ui.selectable_value(&mut replay.speed, speed, format!("{speed}×"));
+                    }
+                });
+                let position = self.timestamp_format.display(replay.position());
+                let text = if replay.finished() { format!("{position} · end of {}", replay.day) } else { position };
+                ui.add(egui::ProgressBar::new(replay.progress()).text(text));
+            });
+    }
+
+    fn render_interface_streams(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Compare interfaces")
+            .id_salt("interface_streams")
+            .show(ui, |ui| {
+                if !ping_executor::CAN_PIN_INTERFACE {
+                    ui.colored_label(Color32::YELLOW, "This system can't pin pings to an interface, so interfaces can't be compared here");
+                    return;
+                }
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Also ping the target through:");
+                    let names: Vec<String> = self.network_watcher.interfaces
+                        .iter()
+                        .filter(|interface| interface.is_usable() || self.probe_interfaces.contains(&interface.name))
+                        .map(|interface| interface.name.clone())
+                        .collect();
+                    for name in names {
+                        let mut chosen = self.probe_interfaces.contains(&name);
+                        if ui.checkbox(&mut chosen, &name).changed() {
+                            if chosen {
+                                self.probe_interfaces.push(name);
+                            } else {
+                                self.probe_interfaces.retain(|interface| interface != &name);
+                            }
+                        }
+                    }
+                });
+                if self.probe_interfaces.is_empty() {
+                    return;
+                }
+                if self.probe != ProbeType::Icmp {
+                    ui.weak("Interfaces are only compared with the ICMP probe");
+                    return;
+                }
+                let format_ms = |ms: Option<f64>| ms.map_or("–".to_string(), ping::format_ms);
+                egui::Grid::new("interface_streams_table").striped(true).show(ui, |ui| {
+                    for heading in ["Interface", "Last RTT", "Mean", "p95", "Loss"] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+                    for (interface, results) in self.interface_streams.streams() {
+                        let status = TargetStatus::from_results(results, self.green_threshold, self.yellow_threshold, self.very_slow_threshold);
+                        match status {
+                            Some(status) => {
+                                ui.colored_label(status.color.to_color32(), format!("● {interface}"));
+                                ui.label(format_ms(status.last_response_ms));
+                                ui.label(format_ms(status.mean_ms));
+                                ui.label(format_ms(status.p95_ms));
+                                ui.label(format!("{:.1}%", status.loss_rate));
+                            }
+                            None => {
+                                ui.label(interface);
+                                for _ in 0..4 {
+                                    ui.label("–");
+                                }
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Overlays the latency of the main target and a second target so a slowdown can be
+    /// told apart as local to one destination or affecting everything
+    fn render_comparison(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Compare targets")
+            .id_salt("compare_targets")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Compare with:");
+                    let edit = ui.add(egui::TextEdit::singleline(&mut self.compare_target).hint_text("e.g. 1.1.1.1"));
+                    if edit.changed() {
+                        self.compare_results.clear();
+                    }
+                });
+                if self.compare_target.trim().is_empty() {
+                    return;
+                }
+                let (main_color, other_color) = self.compare_colors();
+                let main = self.history_target();
+                let other = self.compare_target.trim();
+                ui.horizontal(|ui| {
+                    ui.colored_label(main_color, format!("━ {}", self.target_style(&main).decorated(&main)));
+                    ui.colored_label(other_color, format!("━ {}", self.target_style(other).decorated(other)));
+                });
+                self.draw_comparison_chart(ui);
+            });
+    }
+
+    fn draw_comparison_chart(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), COMPARE_CHART_HEIGHT), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_stroke(rect, 2.0, Stroke::new(1.0, ui.visuals().weak_text_color()), egui::StrokeKind::Inside);
+
+        let span = Duration::from_secs(self.effective_interval_secs().max(1) * MAX_PING_RESULTS as u64);
+        let now = SystemTime::now();
+        let slowest = self.ping_results
+            .iter()
+            .chain(&self.compare_results)
+            .filter_map(|result| result.response_time)
+            .fold(1.0, f64::max);
+        painter.text(
+            rect.left_top() + Vec2::new(4.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            format!("{slowest:.0} ms"),
+            egui::FontId::proportional(10.0),
+            ui.visuals().weak_text_color(),
+        );
+
+        let x_for = |time: SystemTime| {
+            let age = now.duration_since(time).unwrap_or_default().as_secs_f32();
+            rect.right() - rect.width() * (age / span.as_secs_f32()).min(1.0)
+        };
+        if let Some(window) = &self.load_window {
+            let end = window.ended.map_or(now, |(_, ended_at)| ended_at);
+            let band = egui::Rect::from_x_y_ranges(x_for(window.started_at)..=x_for(end), rect.y_range());
+            painter.rect_filled(band, 0.0, LOAD_WINDOW_COLOR.gamma_multiply(0.15));
+        }
+        if let Some(cutoff) = now.checked_sub(span) {
+            for note in self.annotations.since(cutoff) {
+                let x = x_for(note.timestamp());
+                painter.line_segment([Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())], Stroke::new(1.0, ANNOTATION_COLOR));
+                painter.text(
+                    Pos2::new(x + 2.0, rect.bottom() - 2.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    &note.text,
+                    egui::FontId::proportional(10.0),
+                    ANNOTATION_COLOR,
+                );
+            }
+        }
+        let changes: Vec<SystemTime> = self.network_events
+            .iter()
+            .filter(|(time, event)| event.splits_history() && now.duration_since(*time).is_ok_and(|age| age < span))
+            .map(|(time, _)| *time)
+            .collect();
+        for time in &changes {
+            let x = x_for(*time);
+            let dashes = egui::Shape::dashed_line(&[Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())], Stroke::new(1.0, NETWORK_CHANGE_COLOR), 4.0, 3.0);
+            painter.extend(dashes);
+        }
+        let y_for = |ms: f64| rect.bottom() - rect.height() * (ms / slowest) as f32;
+        let slot_secs = self.slot_secs();
+        let slot_width = (rect.width() * slot_secs as f32 / span.as_secs_f32()).max(2.0);
+        let (main_color, other_color) = self.compare_colors();
+        for (results, color) in [(&self.ping_results, main_color), (&self.compare_results, other_color)] {
+            // Results are drawn per slot: with intervals under 5 s several share one, and their
+            // min–max spread is shaded around the median the way Smokeping shows variance
+            let mut slots: Vec<(f32, Vec<f64>, bool)> = Vec::new();
+            let mut current_window = None;
+            let mut segment = 0;
+            let spike_flags = spike::spike_flags(results.iter().map(|result| result.response_time));
+            for (result, spike) in results.iter().zip(spike_flags) {
+                let window = clock_slot::window_of(result.timestamp, slot_secs);
+                let x = x_for(UNIX_EPOCH + Duration::from_secs_f64((window as f64 + 0.5) * slot_secs as f64));
+                // The line isn't drawn across a network change, since before and after aren't comparable
+                let result_segment = changes.iter().filter(|time| **time <= result.timestamp).count();
+                if current_window != Some(window) || result_segment != segment {
+                    current_window = Some(window);
+                    slots.push((x, Vec::new(), result_segment != segment));
+                    segment = result_segment;
+                }
+                match result.response_time {
+                    Some(ms) => {
+                        if spike {
+                            Self::draw_spike_glyph(painter, Pos2::new(x, y_for(ms) - 7.0), color);
+                        }
+                        if let Some((_, replies, _)) = slots.last_mut() {
+                            replies.push(ms);
+                        }
+                    }
+                    // Lost probes are marked along the top edge
+                    None => {
+                        painter.line_segment([Pos2::new(x, rect.top()), Pos2::new(x, rect.top() + 6.0)], Stroke::new(2.0, color));
+                    }
+                }
+            }
+
+            let mut line = Vec::new();
+            for (x, replies, after_change) in &slots {
+                if *after_change {
+                    if line.len() > 1 {
+                        painter.add(egui::Shape::line(std::mem::take(&mut line), Stroke::new(1.5, color)));
+                    }
+                    line.clear();
+                }
+                let Some(spread) = SlotSpread::of(replies) else {
+                    // A slot where nothing answered breaks the line
+                    if line.len() > 1 {
+                        painter.add(egui::Shape::line(std::mem::take(&mut line), Stroke::new(1.5, color)));
+                    }
+                    line.clear();
+                    continue;
+                };
+                if spread.max > spread.min {
+                    let band = egui::Rect::from_x_y_ranges(x - slot_width / 2.0..=x + slot_width / 2.0, y_for(spread.max)..=y_for(spread.min));
+                    painter.rect_filled(band, 0.0, color.gamma_multiply(0.3));
+                }
+                line.push(Pos2::new(*x, y_for(spread.median)));
+            }
+            if line.len() > 1 {
+                painter.add(egui::Shape::line(line, Stroke::new(1.5, color)));
+            }
+        }
+    }
+
+    /// Pings a CIDR range and lists the hosts that answered, each of which can become the target
+    fn render_subnet_sweep(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Discover hosts")
+            .id_salt("subnet_sweep")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Range:");
+                    ui.add(egui::TextEdit::singleline(&mut self.sweep_range).hint_text("192.168.1.0/24"));
+                    let running = self.sweep_progress.is_some();
+                    if ui.add_enabled(!running, egui::Button::new("Sweep")).clicked() {
+                        match subnet_sweep::parse_cidr(&self.sweep_range) {
+                            Ok(hosts) => {
+                                self.sweep_error = None;
+                                self.sweep_hosts.clear();
+                                self.sweep_progress = Some((0, hosts.len()));
+                                SubnetSweep::spawn(hosts, self.probe_limiter.clone(), self.sweep_sender.clone(), ui.ctx().clone());
+                            }
+                            Err(e) => self.sweep_error = Some(e),
+                        }
+                    }
+                });
+                if let Some(e) = &self.sweep_error {
+                    ui.colored_label(Color32::RED, e);
+                }
+                if let Some((done, total)) = self.sweep_progress {
+                    ui.add(egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .text(format!("{done}/{total} · {} found", self.sweep_hosts.len())));
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let label = if self.mdns_running { "Searching…" } else { "Find devices by name (mDNS)" };
+                    if ui.add_enabled(!self.mdns_running, egui::Button::new(label)).clicked() {
+                        self.mdns_running = true;
+                        self.mdns_error = None;
+                        self.mdns_devices.clear();
+                        MdnsDiscovery::spawn(self.mdns_sender.clone(), ui.ctx().clone());
+                    }
+                });
+                if let Some(e) = &self.mdns_error {
+                    ui.colored_label(Color32::RED, e);
+                }
+
+                let mut chosen = None;
+                for device in &self.mdns_devices {
+                    let Some(address) = device.preferred_address() else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(&device.name)
+                            .on_hover_text(format!("{} · {address}\n{}", device.hostname, device.services.join(", ")));
+                        if ui.add_enabled(!self.is_monitoring, egui::Button::new("Monitor").small()).clicked() {
+                            chosen = Some(address);
+                        }
+                    });
+                }
+                for host in &self.sweep_hosts {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{:<15} {:>7.1}ms", host.address, host.response_time));
+                        if ui.add_enabled(!self.is_monitoring, egui::Button::new("Monitor").small()).clicked() {
+                            chosen = Some(host.address);
+                        }
+                    });
+                }
+                if let Some(address) = chosen {
+                    self.probe = ProbeType::Icmp;
+                    self.target = address.to_string();
+                }
+            });
+    }
+
+    /// Ranks the system's and well-known public DNS resolvers by how quickly they answer
+    fn render_dns_benchmark(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("DNS resolver benchmark")
+            .id_salt("dns_benchmark")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Also test:");
+                    ui.add(egui::TextEdit::singleline(&mut self.dns_benchmark_extra).hint_text("192.168.1.53, 2606:4700::1111"));
+                    let running = self.dns_benchmark_progress.is_some();
+                    if ui.add_enabled(!running, egui::Button::new("Benchmark")).clicked() {
+                        match dns_benchmark::resolvers_to_test(&self.dns_benchmark_extra) {
+                            Ok(resolvers) => {
+                                self.dns_benchmark_status = None;
+                                self.dns_scores.clear();
+                                self.dns_benchmark_progress = Some((0, resolvers.len()));
+                                DnsBenchmark::spawn(resolvers, self.dns_benchmark_sender.clone(), ui.ctx().clone());
+                            }
+                            Err(e) => self.dns_benchmark_status = Some(Err(e)),
+                        }
+                    }
+                    let export = ui.add_enabled(!running && !self.dns_scores.is_empty(), egui::Button::new("Export .csv"));
+                    if export.clicked() {
+                        let path = config::export_path("ping-monitor-dns-benchmark", "csv");
+                        let status = match dns_benchmark::export_csv(&path, &self.dns_scores) {
+                            Ok(()) => Ok(format!("Exported to {}", path.display())),
+                            Err(e) => Err(format!("Export failed: {e}")),
+                        };
+                        self.toasts.push_result(&status);
+                        self.dns_benchmark_status = Some(status);
+                    }
+                });
+                match &self.dns_benchmark_status {
+                    Some(Ok(message)) => { ui.label(message); }
+                    Some(Err(e)) => { ui.colored_label(Color32::RED, e); }
+                    None => {}
+                }
+                if let Some((scored, total)) = self.dns_benchmark_progress {
+                    ui.add(egui::ProgressBar::new(scored as f32 / total.max(1) as f32).text(format!("{scored}/{total} resolvers")));
+                }
+                for (index, score) in self.dns_scores.iter().enumerate() {
+                    let median = score.median().map_or("no answer".to_string(), |median| format!("{median:.1}ms"));
+                    let text = format!("{:>2}. {:<10} {:<39} {median:>9}", index + 1, score.resolver.name, score.resolver.address.to_string());
+                    let label = ui.monospace(text);
+                    if score.failures > 0 {
+                        label.on_hover_text(format!("{} of {} queries failed ({:.0}%)", score.failures, score.queries(), score.failure_percent()));
+                    }
+                }
+            });
+    }
+
+    /// Timestamped notes ("rebooted router") for correlating your actions with the data later
+    fn render_annotations(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("Notes ({})", self.annotations.notes.len()))
+            .id_salt("annotations")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let edit = ui.add(egui::TextEdit::singleline(&mut self.note_text).hint_text("e.g. rebooted router"));
+                    let submitted = edit.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    let shortcut = ui.ctx().format_shortcut(&NOTE_SHORTCUT);
+                    if ui.button("Add note").on_hover_text(format!("Or press {shortcut} anywhere in the window")).clicked() || submitted {
+                        self.add_note();
+                    }
+                });
+                if let Some(e) = &self.annotation_error {
+                    ui.colored_label(Color32::RED, e);
+                }
+                for note in self.annotations.notes.iter().rev().take(MAX_LISTED_NOTES) {
+                    let time = self.timestamp_format.display(note.timestamp());
+                    ui.label(format!("{time}  {}", note.text));
+                }
+            });
+    }
+
+    /// Everything that happened besides probe results, newest first
+    fn render_event_log(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("Event log ({})", self.event_log.events.len()))
+            .id_salt("event_log")
+            .show(ui, |ui| {
+                let export = ui.add_enabled(!self.event_log.events.is_empty(), egui::Button::new("Export .csv"));
+                if export.clicked() {
+                    let path = config::export_path("ping-monitor-events", "csv");
+                    let status = match self.event_log.export_csv(&path, UNIX_EPOCH, self.timestamp_format) {
+                        Ok(count) => Ok(format!("Exported {count} events to {}", path.display())),
+                        Err(e) => Err(format!("Export failed: {e}")),
+                    };
+                    self.toasts.push_result(&status);
+                    self.event_export_status = Some(status);
+                }
+                match &self.event_export_status {
+                    Some(Ok(message)) => { ui.label(message); }
+                    Some(Err(e)) => { ui.colored_label(Color32::RED, e); }
+                    None => {}
+                }
+                egui::ScrollArea::vertical().id_salt("event_log_scroll").max_height(200.0).show(ui, |ui| {
+                    for event in self.event_log.events.iter().rev() {
+                        let time = self.timestamp_format.display(event.time);
+                        ui.label(format!("{time} [{}] {}", event.kind.label(), event.message));
+                    }
+                });
+            });
+    }
+
+    /// Recent diagnostics from the log crate, since stderr is lost in the Windows release build
+    fn render_log_console(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Log")
+            .id_salt("log_console")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Record:");
+                    egui::ComboBox::from_id_salt("log_level")
+                        .selected_text(self.log_level.label())
+                        .show_ui(ui, |ui| {
+                            for level in LogLevel::ALL {
+                                if ui.selectable_value(&mut self.log_level, level, level.label()).changed() {
+                                    app_log::set_level(level);
+                                }
+                            }
+                        });
+                    ui.label("Show:");
+                    egui::ComboBox::from_id_salt("log_view_level")
+                        .selected_text(self.log_view_level.label())
+                        .show_ui(ui, |ui| {
+                            for level in LogLevel::ALL {
+                                ui.selectable_value(&mut self.log_view_level, level, level.label());
+                            }
+                        });
+                    if ui.small_button("Clear").clicked() {
+                        app_log::clear();
+                    }
+                });
+                egui::ScrollArea::vertical().id_salt("log_console_scroll").max_height(200.0).stick_to_bottom(true).show(ui, |ui| {
+                    for line in app_log::recent_lines(self.log_view_level) {
+                        let time = chrono::DateTime::<chrono::Local>::from(line.time).format("%H:%M:%S");
+                        let color = match line.level {
+                            log::Level::Error => Color32::RED,
+                            log::Level::Warn => Color32::from_rgb(255, 165, 0),
+                            _ => ui.visuals().text_color(),
+                        };
+                        ui.colored_label(color, format!("{time} {:<5} {}: {}", line.level, line.target, line.message));
+                    }
+                });
+            });
+    }
+
+    /// Returns a hop the user chose to watch
+    fn render_route_changes(&self, ui: &mut egui::Ui) -> Option<TraceHop> {
+        if self.route_monitor.changes.is_empty() {
+            return None;
+        }
+
+        let mut pinned = None;
+        egui::CollapsingHeader::new(format!("Route changes ({})", self.route_monitor.changes.len()))
+            .id_salt("route_changes")
+            .show(ui, |ui| {
+                for change in self.route_monitor.changes.iter().rev() {
+                    let detected = self.timestamp_format.display(change.detected);
+                    egui::CollapsingHeader::new(format!("{detected}, path {:016x} → {:016x}", change.previous_hash, change.current_hash))
+                        .id_salt(("route_change", change.current_hash, change.detected))
+                        .show(ui, |ui| {
+                            for hop in &change.hops {
+                                if self.render_hop(ui, hop) {
+                                    pinned = Some(hop.clone());
+                                }
+                            }
+                        });
+                }
+            });
+        pinned
+    }
+
+    /// Short burst of rapid pings for catching sub-second loss the normal cadence misses
+    fn render_stress_test(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Rapid ping")
+            .id_salt("stress_test")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(!self.stress_running, |ui| {
+                        ui.add(egui::DragValue::new(&mut self.stress_rate).range(stress_test::MIN_RATE..=stress_test::MAX_RATE));
+                        ui.label("pings/s for");
+                        ui.add(egui::DragValue::new(&mut self.stress_duration_secs).range(1..=stress_test::MAX_DURATION_SECS));
+                        ui.label("s");
+                    });
+                    let label = if self.stress_running { "Running…" } else { "Start" };
+                    let start = ui.add_enabled(!self.stress_running, egui::Button::new(label))
+                        .on_hover_text(format!("Ping {} as fast as configured, without waiting for replies", self.target));
+                    if start.clicked() {
+                        self.stress_running = true;
+                        self.stress_result = None;
+                        StressTest::spawn(
+                            self.target.clone(),
+                            self.stress_rate,
+                            self.stress_duration_secs,
+                            self.probe_limiter.clone(),
+                            self.stress_sender.clone(),
+                            ui.ctx().clone(),
+                        );
+                    }
+                });
+                match &self.stress_result {
+                    Some(Ok(summary)) => {
+                        if self.stress_running {
+                            ui.add(egui::ProgressBar::new(summary.progress()).text(format!("{}/{} sent", summary.sent, summary.total)));
+                        }
+                        let describe = |value: Option<f64>| value.map_or("-".to_string(), |value| format!("{value:.1}"));
+                        ui.label(format!(
+                            "{} at {}/s: {} answered, {} lost ({:.1}%), longest loss burst {}",
+                            summary.target,
+                            summary.rate,
+                            summary.answered,
+                            summary.lost,
+                            summary.loss_rate(),
+                            summary.longest_loss_burst,
+                        ));
+                        ui.label(format!(
+                            "min/mean/max {}/{}/{} ms, jitter {} ms",
+                            describe(summary.min_ms),
+                            describe(summary.mean_ms),
+                            describe(summary.max_ms),
+                            describe(summary.jitter_ms),
+                        ));
+                    }
+                    Some(Err(e)) => { ui.colored_label(Color32::RED, format!("Rapid ping failed: {e}")); }
+                    None => {}
+                }
+            });
+    }
+
+    fn render_speed_tests(&self, ui: &mut egui::Ui) {
+        if let Some(e) = &self.speed_test_error {
+            ui.colored_label(Color32::RED, format!("Speed test failed: {e}"));
+        }
+        if self.speed_tests.is_empty() {
+            return;
+        }
+
+        egui::CollapsingHeader::new(format!("Speed tests ({})", self.speed_tests.len()))
+            .id_salt("speed_tests")
+            .show(ui, |ui| {
+                for result in self.speed_tests.iter().rev() {
+                    let finished = self.timestamp_format.display(result.finished);
+                    let latency = result.latency_ms.map_or("N/A".to_string(), |latency| format!("{latency:.1}ms"));
+                    ui.label(format!("{finished} ↓ {:.1} Mbps ↑ {:.1} Mbps, latency {latency}",
+                        result.download_mbps,
+                        result.upload_mbps,
+                    ));
+                }
+            });
+    }
+
+    /// Matrix of agents (rows) by the targets they probe (columns), plus a form to add agents
+    fn render_agents(&mut self, ui: &mut egui::Ui) {
+        let view = self.agent_hub.view(self.green_threshold, self.yellow_threshold, self.very_slow_threshold);
+        let mut removed = None;
+        egui::CollapsingHeader::new(format!("Agents ({})", self.agents.len()))
+            .id_salt("agents")
+            .show(ui, |ui| {
+                if !view.rows.is_empty() {
+                    egui::Grid::new("agent_matrix").striped(true).show(ui, |ui| {
+                        ui.strong("Agent");
+                        for target in &view.targets {
+                            ui.strong(target);
+                        }
+                        ui.end_row();
+
+                        for (index, row) in view.rows.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").on_hover_text("Remove this agent").clicked() {
+                                    removed = Some(index);
+                                }
+                                ui.label(&row.name).on_hover_text(row.status.to_string());
+                            });
+                            if row.cells.iter().all(Option::is_none) {
+                                // Not connected yet: say why instead of leaving the row blank
+                                ui.label(row.status.to_string());
+                            } else {
+                                for cell in &row.cells {
+                                    let Some(cell) = cell else {
+                                        ui.label("");
+                                        continue;
+                                    };
+                                    let text = match (cell.samples, cell.last_response_ms) {
+                                        (0, _) => "● waiting".to_string(),
+                                        (_, Some(ms)) => format!("● {ms:.0} ms · {:.0}% loss", cell.loss_rate),
+                                        (_, None) => format!("● no reply · {:.0}% loss", cell.loss_rate),
+                                    };
+                                    ui.colored_label(cell.color.to_color32(), text);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_agent.name).hint_text("name").desired_width(70.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.new_agent.address).hint_text("host:port").desired_width(110.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.new_agent.token).hint_text("token").password(true).desired_width(70.0));
+                    let complete = [&self.new_agent.name, &self.new_agent.address, &self.new_agent.token]
+                        .iter()
+                        .all(|field| !field.trim().is_empty());
+                    if ui.add_enabled(complete, egui::Button::new("Add agent")).clicked() {
+                        self.agents.push(std::mem::take(&mut self.new_agent));
+                    }
+                });
+            });
+        if let Some(index) = removed {
+            self.agents.remove(index);
+        }
+    }
+
+    fn render_plugins(&self, ui: &mut egui::Ui) {
+        let names: Vec<&str> = self.plugins.plugin_names().collect();
+        if names.is_empty() {
+            return;
+        }
+
+        for (name, value) in &self.plugins.metrics {
+            ui.label(format!("{name}: {value}"));
+        }
+        egui::CollapsingHeader::new(format!("Plugins ({})", names.join(", ")))
+            .id_salt("plugins")
+            .show(ui, |ui| {
+                if self.plugins.log.is_empty() {
+                    ui.label("No plugin messages yet");
+                }
+                for (time, message) in self.plugins.log.iter().rev() {
+                    let time = chrono::DateTime::<chrono::Local>::from(*time).format("%H:%M:%S");
+                    ui.label(format!("{time} {message}"));
+                }
+            });
+    }
+
+    fn render_packet_capture(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Packet capture")
+            .id_salt("packet_capture")
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.packet_capture.enabled, "Capture probe packets")
+                    .on_hover_text("Keep each ICMP probe's addresses, echo identifier, sequence number and timing so its requests and replies can be rebuilt into a .pcap file");
+                ui.horizontal(|ui| {
+                    ui.label("Export last");
+                    let describe = |mins: u64| if mins >= 60 { format!("{} h", mins / 60) } else { format!("{mins} min") };
+                    egui::ComboBox::from_id_salt("capture_window")
+                        .selected_text(describe(self.capture_window_mins))
+                        .show_ui(ui, |ui| {
+                            for mins in CAPTURE_WINDOWS_MINS {
+                                ui.selectable_value(&mut self.capture_window_mins, mins, describe(mins));
+                            }
+                        });
+                    let export = ui.add_enabled(self.packet_capture.len() > 0, egui::Button::new("Export .pcap"));
+                    if export.clicked() {
+                        let path = pcap::default_export_path();
+                        let window = Duration::from_secs(self.capture_window_mins * 60);
+                        let status = match self.packet_capture.export(&path, window) {
+                            Ok(count) => Ok(format!("Exported {count} probes to {}{}", path.display(), self.export_notes_beside(&path, window))),
+                            Err(e) => Err(format!("Export failed: {e}")),
+                        };
+                        self.toasts.push_result(&status);
+                        self.capture_export_status = Some(status);
+                    }
+                    if ui.small_button("Clear").clicked() {
+                        self.packet_capture.clear();
+                    }
+                });
+                ui.label(format!("{} probes captured", self.packet_capture.len()));
+                match &self.capture_export_status {
+                    Some(Ok(message)) => { ui.label(message); }
+                    Some(Err(e)) => { ui.colored_label(Color32::RED, e); }
+                    None => {}
+                }
+            });
+    }
+
+    fn render_statistics(&self, ui: &mut egui::Ui) {
+        if let Some(started) = self.session_started {
+            let secs = started.elapsed().unwrap_or_default().as_secs();
+            let replied = if self.session_sent > 0 { self.session_replies as f64 / self.session_sent as f64 * 100.0 } else { 0.0 };
+            ui.label(format!(
+                "Session: {:02}:{:02}:{:02} · {} sent, {} replied ({replied:.1}%)",
+                secs / 3600,
+                secs % 3600 / 60,
+                secs % 60,
+                self.session_sent,
+                self.session_replies,
+            ))
+            .on_hover_text("Since Start was pressed; the figures below cover only the last minute");
+        }
+        ui.label(format!("Success Rate: {:.1}%", 100.0 - self.ping_statistics.loss_rate));
+        let baseline = self.baseline.as_ref().filter(|baseline| baseline.target == self.history_target());
+        ui.horizontal(|ui| {
+            ui.label(format!("Loss Rate: {:.1}%", self.ping_statistics.loss_rate));
+            let trend = (self.ping_statistics.total_pings > 0).then(|| {
+                Self::trend_label(self.ping_statistics.loss_rate, baseline.and_then(|b| b.loss_rate), TREND_STEADY_LOSS, "pts")
+            });
+            if let Some(trend) = trend.flatten() {
+                ui.label(trend);
+            }
+        });
+        if !self.ping_statistics.failure_causes.is_empty() {
+            let causes: Vec<String> = self.ping_statistics.failure_causes
+                .iter()
+                .map(|(cause, count)| format!("{count} {}", cause.label()))
+                .collect();
+            ui.label(format!("Failures: {}", causes.join(" · ")));
+        }
+        if self.is_monitoring && self.outside_schedule() {
+            let hours: Vec<String> = self.target_entry().map_or(Vec::new(), |entry| entry.monitoring_hours.iter().map(|window| window.to_string()).collect());
+            ui.label(format!("Paused outside monitoring hours ({})", hours.join("; ")));
+        }
+        if let Some(window) = schedule::active_window(&self.maintenance_windows, chrono::Local::now()) {
+            ui.label(format!("🔧 Maintenance window ({window}): failures are not counted"));
+        } else if self.ping_statistics.maintenance_pings > 0 {
+            ui.label(format!("Excluded (maintenance): {}", self.ping_statistics.maintenance_pings));
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("Mean Response Time: {}", ping::format_ms(self.ping_statistics.mean_response_time)));
+            let trend = (self.ping_statistics.successful_pings > 0).then(|| {
+                Self::trend_label(self.ping_statistics.mean_response_time, baseline.and_then(|b| b.mean_ms), TREND_STEADY_MS, "ms")
+            });
+            if let Some(trend) = trend.flatten() {
+                ui.label(trend);
+            }
+        });
+        ui.label(format!("Spikes: {}", self.ping_statistics.spikes))
+            .on_hover_text("One or two samples at least 3× the recent median (marked ▲); sustained slowdowns aren't counted");
+        let gaps = ping::sequence_gaps(&self.ping_results);
+        if !gaps.is_empty() {
+            let recent: Vec<String> = gaps
+                .iter()
+                .rev()
+                .take(MAX_LISTED_SEQUENCE_GAPS)
+                .rev()
+                .map(|&(first, last)| if first == last { format!("#{first}") } else { format!("#{first}–#{last}") })
+                .collect();
+            ui.label(format!("Sequence gaps: {} ({})", gaps.len(), recent.join(", ")))
+                .on_hover_text("Echo requests that got no reply although later ones did; the most recent are listed");
+        }
         ui.label(format!("Last Response Time: {}", 
             match self.last_response_time {
-                Some(time) => format!("{time:.1}ms"),
+                Some(time) => ping::format_ms(time),
                 None => "N/A".to_string(),
             }
         ));
+        if let Some(wifi) = &self.wifi_monitor.current {
+            let ssid = wifi.ssid.as_deref().unwrap_or("(hidden)");
+            let signal = match (wifi.rssi_dbm, wifi.quality()) {
+                (Some(rssi), Some(quality)) => format!("{rssi} dBm, {quality}"),
+                _ => "signal unknown".to_string(),
+            };
+            ui.label(format!("Wi-Fi: {ssid} ({signal})"));
+        }
+        if let Some(vpn) = self.ping_results.back().and_then(|result| result.vpn.as_ref()) {
+            ui.label(format!("🔒 Through VPN ({vpn})"));
+        }
+        if self.metered_monitor.current == Some(true) {
+            let mut text = "📶 Metered connection".to_string();
+            if self.saving_data() {
+                text += &format!(", probing every {} s", self.effective_interval_secs());
+            }
+            if self.heavy_tests_paused() {
+                text += ", speed tests paused";
+            }
+            ui.label(text);
+        }
+        let format_time = |time: SystemTime| chrono::DateTime::<chrono::Local>::from(time).format("%H:%M:%S").to_string();
+        if let Some(gap) = self.sleep_gaps.back() {
+            ui.label(format!("Sleep gap: {} – {} ({:.1} min)",
+                format_time(gap.started),
+                format_time(gap.ended()),
+                gap.duration.as_secs_f64() / 60.0,
+            ));
+        }
+        if let Some((time, reason)) = &self.last_recovery {
+            ui.label(format!("Recovered after {reason} at {}", format_time(*time)));
+        }
     }
 }
\ No newline at end of file
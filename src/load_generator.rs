@@ -0,0 +1,126 @@
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Cloudflare's speed test endpoints accept anonymous downloads of a requested size and discard uploads
+const DOWNLOAD_URL: &str = "https://speed.cloudflare.com/__down?bytes=";
+const UPLOAD_URL: &str = "https://speed.cloudflare.com/__up";
+const REQUEST_BYTES: u64 = 100_000_000;
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Download,
+    Upload,
+}
+
+/// Parallel HTTP transfers that keep the link saturated until stopped
+pub struct LoadGenerator {
+    stop: Arc<AtomicBool>,
+    bytes: Arc<AtomicU64>,
+    started: Instant,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// What a load run moved, for throughput figures
+#[derive(Debug, Clone, Copy)]
+pub struct LoadSummary {
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl LoadSummary {
+    pub fn mbps(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.bytes as f64 * 8.0 / secs / 1_000_000.0 } else { 0.0 }
+    }
+}
+
+impl LoadGenerator {
+    pub fn start(direction: Direction, streams: usize) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let bytes = Arc::new(AtomicU64::new(0));
+        let workers = (0..streams)
+            .map(|_| {
+                let (stop, bytes) = (stop.clone(), bytes.clone());
+                thread::spawn(move || {
+                    let agent: ureq::Agent = ureq::Agent::config_builder()
+                        .timeout_global(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+                        .build()
+                        .into();
+                    while !stop.load(Ordering::Relaxed) {
+                        let result = match direction {
+                            Direction::Download => Self::download(&agent, &stop, &bytes),
+                            Direction::Upload => Self::upload(&agent, &stop, &bytes),
+                        };
+                        if result.is_err() {
+                            // Don't hammer the server if it is refusing us
+                            thread::sleep(Duration::from_millis(500));
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self { stop, bytes, started: Instant::now(), workers }
+    }
+
+    /// Bytes transferred so far across all streams
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Signals every stream to stop and waits for them to wind down
+    pub fn stop(self) -> LoadSummary {
+        let elapsed = self.started.elapsed();
+        let bytes = self.bytes();
+        self.stop.store(true, Ordering::Relaxed);
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+        LoadSummary { bytes, elapsed }
+    }
+
+    fn download(agent: &ureq::Agent, stop: &AtomicBool, bytes: &AtomicU64) -> Result<(), ureq::Error> {
+        let response = agent.get(format!("{DOWNLOAD_URL}{REQUEST_BYTES}")).call()?;
+        let mut reader = response.into_body().into_reader();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        while !stop.load(Ordering::Relaxed) {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            bytes.fetch_add(read as u64, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn upload(agent: &ureq::Agent, stop: &AtomicBool, bytes: &AtomicU64) -> Result<(), ureq::Error> {
+        let mut body = UploadBody { remaining: REQUEST_BYTES, stop, bytes };
+        agent.post(UPLOAD_URL).send(ureq::SendBody::from_reader(&mut body))?;
+        Ok(())
+    }
+}
+
+/// Zero-filled request body that ends early once the stop flag is raised
+struct UploadBody<'a> {
+    remaining: u64,
+    stop: &'a AtomicBool,
+    bytes: &'a AtomicU64,
+}
+
+impl Read for UploadBody<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || self.stop.load(Ordering::Relaxed) {
+            return Ok(0);
+        }
+        let len = buf.len().min(CHUNK_SIZE).min(self.remaining as usize);
+        buf[..len].fill(0);
+        self.remaining -= len as u64;
+        self.bytes.fetch_add(len as u64, Ordering::Relaxed);
+        Ok(len)
+    }
+}
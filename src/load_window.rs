@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+use crate::ping::PingResult;
+
+/// Idle latency is taken from the results of this long before the window opened
+const IDLE_LOOKBACK_SECS: u64 = 60;
+
+/// Running latency and loss over a set of probes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencySample {
+    pub probes: usize,
+    pub lost: usize,
+    total_ms: f64,
+}
+
+impl LatencySample {
+    fn record(&mut self, result: &PingResult) {
+        if result.in_maintenance {
+            return;
+        }
+        self.probes += 1;
+        match result.response_time.filter(|_| result.success) {
+            Some(response_time) => self.total_ms += response_time,
+            None => self.lost += 1,
+        }
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        let answered = self.probes - self.lost;
+        (answered > 0).then(|| self.total_ms / answered as f64)
+    }
+
+    pub fn loss_rate(&self) -> f64 {
+        if self.probes == 0 { 0.0 } else { self.lost as f64 / self.probes as f64 * 100.0 }
+    }
+}
+
+/// A stretch of time during which the user is generating load (a large download, a video
+/// call…), compared against the idle latency just before it
+#[derive(Debug, Clone)]
+pub struct LoadWindow {
+    pub started: Instant,
+    pub started_at: SystemTime,
+    pub ended: Option<(Instant, SystemTime)>,
+    pub idle: LatencySample,
+    pub loaded: LatencySample,
+}
+
+impl LoadWindow {
+    pub fn start(recent: &VecDeque<PingResult>) -> Self {
+        let started = Instant::now();
+        let mut idle = LatencySample::default();
+        let lookback = started.checked_sub(Duration::from_secs(IDLE_LOOKBACK_SECS));
+        for result in recent.iter().filter(|result| lookback.is_none_or(|lookback| result.sent_at >= lookback)) {
+            idle.record(result);
+        }
+        Self { started, started_at: SystemTime::now(), ended: None, idle, loaded: LatencySample::default() }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.ended.is_none()
+    }
+
+    pub fn stop(&mut self) {
+        self.ended.get_or_insert((Instant::now(), SystemTime::now()));
+    }
+
+    /// Counts results sent while the window is open
+    pub fn record(&mut self, result: &PingResult) {
+        if self.contains(result.sent_at) {
+            self.loaded.record(result);
+        }
+    }
+
+    pub fn contains(&self, sent_at: Instant) -> bool {
+        sent_at >= self.started && self.ended.is_none_or(|(ended, _)| sent_at < ended)
+    }
+
+    pub fn latency_increase_ms(&self) -> Option<f64> {
+        Some(self.loaded.mean_ms()? - self.idle.mean_ms()?)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.ended.map_or(self.started.elapsed(), |(ended, _)| ended - self.started)
+    }
+}
@@ -0,0 +1,112 @@
+use std::net::IpAddr;
+use crate::geoip::GeoIp;
+
+/// Share of probes (percent) a hop has to lose to count as losing them
+const LOSS_THRESHOLD: f64 = 20.0;
+
+/// Loss seen at one hop of the path; a hop from a single traceroute pass is 0 or 100%, a hop
+/// watched from the target list has a measured rate
+#[derive(Debug, Clone)]
+pub struct HopLoss {
+    pub number: u32,
+    pub address: Option<IpAddr>,
+    pub loss_rate: f64,
+}
+
+impl HopLoss {
+    fn lossy(&self) -> bool {
+        self.loss_rate >= LOSS_THRESHOLD
+    }
+}
+
+/// Plain-language reading of the hops, like "Loss begins at hop 4 (203.0.113.9), an ISP core router".
+/// Loss only counts where it carries on to every later hop: a router that ignores probes while the
+/// ones behind it answer is rate-limiting, not dropping traffic. None when no hop loses anything.
+pub fn summarize(hops: &[HopLoss], gateway: Option<IpAddr>, target: Option<IpAddr>, geoip: &GeoIp) -> Option<String> {
+    let Some(origin) = (0..hops.len()).find(|&i| hops[i..].iter().all(HopLoss::lossy)) else {
+        return hops
+            .iter()
+            .any(HopLoss::lossy)
+            .then(|| "Some routers skip probes but the hops after them answer, which is rate limiting rather than loss".to_string());
+    };
+    let hop = &hops[origin];
+    let isp_asn = hops
+        .iter()
+        .filter_map(|hop| hop.address.filter(|address| scope(*address) == Scope::Public))
+        .find_map(|address| geoip.lookup(address).and_then(|info| info.asn));
+    let first_public = hops.iter().position(|hop| hop.address.is_some_and(|address| scope(address) == Scope::Public));
+
+    let role = match hop.address {
+        None => "which doesn't identify itself".to_string(),
+        Some(address) if Some(address) == target => "the target itself".to_string(),
+        Some(address) if Some(address) == gateway => "your router".to_string(),
+        Some(address) => match scope(address) {
+            Scope::Local => "a router on your local network".to_string(),
+            Scope::CarrierNat => "your ISP's carrier-grade NAT".to_string(),
+            Scope::Public if first_public == Some(origin) => "your ISP's first router".to_string(),
+            Scope::Public => match geoip.lookup(address).and_then(|info| Some((info.asn?, info.organization))) {
+                Some((asn, _)) if Some(asn) == isp_asn => "an ISP core router".to_string(),
+                Some((asn, Some(organization))) => format!("a router of AS{asn} {organization}, past your ISP"),
+                Some((asn, None)) => format!("a router of AS{asn}, past your ISP"),
+                None => "a router past your ISP's first hop".to_string(),
+            },
+        },
+    };
+    let address = hop.address.map_or("no reply".to_string(), |address| address.to_string());
+    let mut summary = format!("Loss begins at hop {} ({address}), {role}", hop.number);
+    if origin > 0 {
+        summary += &format!("; hops up to {} answer", hops[origin - 1].number);
+    }
+    Some(summary)
+}
+
+#[derive(PartialEq, Eq)]
+enum Scope {
+    Local,
+    /// 100.64.0.0/10, shared by an ISP's customers behind carrier-grade NAT
+    CarrierNat,
+    Public,
+}
+
+fn scope(address: IpAddr) -> Scope {
+    match address {
+        IpAddr::V4(v4) if v4.octets()[0] == 100 && v4.octets()[1] & 0xc0 == 64 => Scope::CarrierNat,
+        IpAddr::V4(v4) if v4.is_private() || v4.is_link_local() || v4.is_loopback() => Scope::Local,
+        IpAddr::V6(v6) if v6.is_loopback() || v6.segments()[0] & 0xfe00 == 0xfc00 || v6.segments()[0] & 0xffc0 == 0xfe80 => Scope::Local,
+        _ => Scope::Public,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hop(number: u32, address: &str, loss_rate: f64) -> HopLoss {
+        HopLoss { number, address: address.parse().ok(), loss_rate }
+    }
+
+    #[test]
+    fn loss_that_recovers_later_is_rate_limiting() {
+        let hops = [hop(1, "192.168.1.1", 0.0), hop(2, "203.0.113.1", 100.0), hop(3, "198.51.100.7", 0.0)];
+        let summary = summarize(&hops, None, None, &GeoIp::default()).unwrap();
+        assert!(summary.contains("rate limiting"), "{summary}");
+        assert_eq!(summarize(&hops[..1], None, None, &GeoIp::default()), None);
+    }
+
+    #[test]
+    fn names_carrier_grade_nat() {
+        let hops = [hop(1, "192.168.1.1", 0.0), hop(2, "100.64.12.1", 60.0), hop(3, "203.0.113.1", 100.0)];
+        let summary = summarize(&hops, None, None, &GeoIp::default()).unwrap();
+        assert_eq!(summary, "Loss begins at hop 2 (100.64.12.1), your ISP's carrier-grade NAT; hops up to 1 answer");
+        // 100.128.0.0 is past the /10 and public
+        assert!(scope("100.128.0.1".parse().unwrap()) == Scope::Public);
+    }
+
+    #[test]
+    fn loss_from_the_first_hop_is_the_router() {
+        let gateway = "192.168.1.1".parse().ok();
+        let hops = [hop(1, "192.168.1.1", 100.0), hop(2, "", 100.0)];
+        let summary = summarize(&hops, gateway, None, &GeoIp::default()).unwrap();
+        assert_eq!(summary, "Loss begins at hop 1 (192.168.1.1), your router");
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+/// Fewer lost probes than this can't tell random loss from bursts
+const MIN_LOSSES: usize = 5;
+/// Loss is bursty when a probe right after a lost one is lost this many times more often
+/// than one after a success; for independent (random) loss both are about the same
+const BURSTY_FACTOR: f64 = 3.0;
+/// Upper bounds of the burst-length buckets shown in reports; longer bursts share the last one
+const BURST_BUCKETS: [(usize, &str); 5] = [(1, "1"), (2, "2"), (4, "3–4"), (9, "5–9"), (usize::MAX, "10+")];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossKind {
+    TooFewLosses,
+    /// Losses are independent of each other
+    Random,
+    /// Losses cluster into runs
+    Bursty,
+}
+
+impl LossKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LossKind::TooFewLosses => "too few losses to tell",
+            LossKind::Random => "random",
+            LossKind::Bursty => "bursty",
+        }
+    }
+
+    /// What this kind of loss usually points to
+    pub fn hint(self) -> &'static str {
+        match self {
+            LossKind::TooFewLosses => "",
+            LossKind::Random => "Scattered single losses point to a weak Wi-Fi signal, a bad cable or a link that drops packets under load",
+            LossKind::Bursty => "Runs of losses point to brief drop-outs: Wi-Fi roaming or interference, congested buffers overflowing, or a flapping route",
+        }
+    }
+}
+
+/// Run-length statistics of lost probes plus the two transition rates of a Gilbert–Elliott
+/// (two-state) loss model fitted to them
+#[derive(Debug, Clone, Default)]
+pub struct LossPattern {
+    pub probes: usize,
+    pub losses: usize,
+    /// Number of runs of consecutive losses by run length
+    pub burst_lengths: BTreeMap<usize, usize>,
+    /// Share of probes lost right after a success (the model's good → bad rate), percent
+    pub loss_after_success: Option<f64>,
+    /// Share of probes lost right after a loss (1 − the model's bad → good rate), percent
+    pub loss_after_loss: Option<f64>,
+}
+
+impl LossPattern {
+    /// `outcomes` are probe successes in the order they were sent
+    pub fn from_outcomes(outcomes: impl IntoIterator<Item = bool>) -> Self {
+        let mut pattern = Self::default();
+        // [after success, after loss] × [next succeeded, next lost]
+        let mut transitions = [[0usize; 2]; 2];
+        let mut previous: Option<bool> = None;
+        let mut run = 0;
+        for success in outcomes {
+            pattern.probes += 1;
+            if let Some(previous_success) = previous {
+                transitions[usize::from(!previous_success)][usize::from(!success)] += 1;
+            }
+            if success {
+                if run > 0 {
+                    *pattern.burst_lengths.entry(run).or_default() += 1;
+                }
+                run = 0;
+            } else {
+                pattern.losses += 1;
+                run += 1;
+            }
+            previous = Some(success);
+        }
+        if run > 0 {
+            *pattern.burst_lengths.entry(run).or_default() += 1;
+        }
+
+        let rate = |[kept, lost]: [usize; 2]| (kept + lost > 0).then(|| lost as f64 / (kept + lost) as f64 * 100.0);
+        pattern.loss_after_success = rate(transitions[0]);
+        pattern.loss_after_loss = rate(transitions[1]);
+        pattern
+    }
+
+    pub fn bursts(&self) -> usize {
+        self.burst_lengths.values().sum()
+    }
+
+    /// Mean number of probes lost in a row
+    pub fn mean_burst(&self) -> Option<f64> {
+        let bursts = self.bursts();
+        (bursts > 0).then(|| self.losses as f64 / bursts as f64)
+    }
+
+    pub fn kind(&self) -> LossKind {
+        if self.losses < MIN_LOSSES {
+            return LossKind::TooFewLosses;
+        }
+        match (self.loss_after_success, self.loss_after_loss) {
+            (Some(after_success), Some(after_loss)) if after_loss > after_success * BURSTY_FACTOR => LossKind::Bursty,
+            _ => LossKind::Random,
+        }
+    }
+
+    /// Burst counts grouped into length ranges, e.g. ("3–4", 2); empty ranges are left out
+    pub fn burst_buckets(&self) -> Vec<(&'static str, usize)> {
+        let mut buckets = Vec::new();
+        let mut lower = 1;
+        for (upper, label) in BURST_BUCKETS {
+            let count: usize = self.burst_lengths.range(lower..=upper).map(|(_, count)| count).sum();
+            if count > 0 {
+                buckets.push((label, count));
+            }
+            lower = upper.saturating_add(1);
+        }
+        buckets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(outcomes: &str) -> LossPattern {
+        LossPattern::from_outcomes(outcomes.chars().map(|outcome| outcome == '.'))
+    }
+
+    #[test]
+    fn runs_and_transition_rates() {
+        let pattern = pattern(".xx.x..xxx");
+        assert_eq!((pattern.probes, pattern.losses, pattern.bursts()), (10, 6, 3));
+        assert_eq!(pattern.burst_lengths, BTreeMap::from([(1, 1), (2, 1), (3, 1)]));
+        assert_eq!(pattern.mean_burst(), Some(2.0));
+        assert_eq!(pattern.loss_after_success, Some(75.0));
+        assert_eq!(pattern.loss_after_loss, Some(60.0));
+    }
+
+    #[test]
+    fn one_long_run_is_bursty() {
+        let pattern = pattern(&format!("{}{}{}", ".".repeat(50), "x".repeat(10), ".".repeat(50)));
+        assert_eq!(pattern.kind(), LossKind::Bursty);
+    }
+
+    #[test]
+    fn scattered_losses_are_random() {
+        assert_eq!(pattern(&".........x".repeat(10)).kind(), LossKind::Random);
+        assert_eq!(pattern("x.x.x.x.").kind(), LossKind::TooFewLosses);
+        assert_eq!(pattern("....").mean_burst(), None);
+    }
+
+    #[test]
+    fn bursts_are_grouped_by_length() {
+        let pattern = pattern(&format!("x.x.xxx.{}", "x".repeat(12)));
+        assert_eq!(pattern.burst_buckets(), vec![("1", 2), ("3–4", 1), ("10+", 1)]);
+    }
+}
@@ -1,7 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use egui::IconData;
-use ping_monitor::PingMonitorApp;
+use ping_monitor::{AGENT_FLAG, PingMonitorApp};
 use eframe::egui;
 
 fn load_icon() -> IconData {
@@ -19,7 +19,15 @@ fn load_icon_rgba(icon_bytes: &[u8]) -> Vec<u8> {
 }
 
 fn main() -> eframe::Result {
-    env_logger::init();
+    ping_monitor::init_logging();
+
+    if std::env::args().any(|arg| arg == AGENT_FLAG) {
+        if let Err(e) = ping_monitor::run_agent() {
+            log::error!("Agent stopped: {e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     let app = PingMonitorApp::new();
 
@@ -0,0 +1,108 @@
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use eframe::egui;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+
+/// RFC 6763 meta-query: answers name every service type advertised on the LAN
+const SERVICE_TYPES_QUERY: &str = "_services._dns-sd._udp.local.";
+/// Most devices answer within a couple of seconds; sleepy ones get a little longer
+const DISCOVERY_SECS: u64 = 8;
+const POLL_INTERVAL_MS: u64 = 50;
+
+/// A device found via mDNS, merged across the services it advertises
+#[derive(Debug, Clone)]
+pub struct MdnsDevice {
+    /// Instance name as shown to users, e.g. "Living Room TV"
+    pub name: String,
+    /// e.g. "living-room-tv.local."
+    pub hostname: String,
+    pub addresses: Vec<IpAddr>,
+    /// Service types such as "_airplay._tcp"
+    pub services: Vec<String>,
+}
+
+impl MdnsDevice {
+    /// IPv4 is preferred since link-local IPv6 addresses need a zone to be pinged
+    pub fn preferred_address(&self) -> Option<IpAddr> {
+        self.addresses.iter().find(|address| address.is_ipv4()).or(self.addresses.first()).copied()
+    }
+
+    /// Folds in another service advertised by the same host
+    pub fn merge(&mut self, other: MdnsDevice) {
+        for address in other.addresses {
+            if !self.addresses.contains(&address) {
+                self.addresses.push(address);
+            }
+        }
+        for service in other.services {
+            if !self.services.contains(&service) {
+                self.services.push(service);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MdnsUpdate {
+    Found(MdnsDevice),
+    Finished(Result<(), String>),
+}
+
+pub struct MdnsDiscovery;
+
+impl MdnsDiscovery {
+    /// Asks which service types exist, browses each one for a few seconds and reports every
+    /// resolved instance; devices advertising several services are reported once per service
+    pub fn spawn(sender: mpsc::Sender<MdnsUpdate>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let result = Self::discover(&sender, &repaint);
+            let _ = sender.send(MdnsUpdate::Finished(result));
+            repaint.request_repaint();
+        });
+    }
+
+    fn discover(sender: &mpsc::Sender<MdnsUpdate>, repaint: &egui::Context) -> Result<(), String> {
+        let daemon = ServiceDaemon::new().map_err(|e| format!("mDNS is unavailable: {e}"))?;
+        let types = daemon.browse(SERVICE_TYPES_QUERY).map_err(|e| e.to_string())?;
+        let mut browsing = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(DISCOVERY_SECS);
+
+        while Instant::now() < deadline {
+            while let Ok(event) = types.try_recv() {
+                if let ServiceEvent::ServiceFound(_, service_type) = event
+                    && !browsing.iter().any(|(known, _)| known == &service_type)
+                    && let Ok(receiver) = daemon.browse(&service_type)
+                {
+                    browsing.push((service_type, receiver));
+                }
+            }
+            for (service_type, receiver) in &browsing {
+                while let Ok(event) = receiver.try_recv() {
+                    if let ServiceEvent::ServiceResolved(info) = event {
+                        let service = service_type.trim_end_matches(".local.").to_string();
+                        let name = info
+                            .get_fullname()
+                            .strip_suffix(service_type.as_str())
+                            .unwrap_or(info.get_fullname())
+                            .trim_end_matches('.')
+                            .replace("\\032", " ");
+                        let device = MdnsDevice {
+                            name,
+                            hostname: info.get_hostname().to_string(),
+                            addresses: info.get_addresses().iter().copied().collect(),
+                            services: vec![service],
+                        };
+                        let _ = sender.send(MdnsUpdate::Found(device));
+                        repaint.request_repaint();
+                    }
+                }
+            }
+            thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        }
+
+        let _ = daemon.shutdown();
+        Ok(())
+    }
+}
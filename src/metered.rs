@@ -0,0 +1,75 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::periodic_query::PeriodicQuery;
+
+const METERED_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// What changes while the connection is metered (mobile hotspot, capped plan). Probes already
+/// carry no payload, so only their frequency can be reduced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeteredSaver {
+    /// Probe less often; a longer normal interval is kept
+    pub slow_probing: bool,
+    pub interval_secs: u64,
+    /// Disable the speed and bufferbloat tests, which transfer tens of megabytes
+    pub pause_heavy_tests: bool,
+}
+
+impl Default for MeteredSaver {
+    fn default() -> Self {
+        Self { slow_probing: false, interval_secs: 30, pause_heavy_tests: true }
+    }
+}
+
+/// Polls the metered flag of the current connection
+pub type MeteredMonitor = PeriodicQuery<bool>;
+
+pub fn monitor() -> MeteredMonitor {
+    PeriodicQuery::new(Duration::from_secs(METERED_CHECK_INTERVAL_SECS), platform::query)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::system_command;
+
+    pub fn query() -> Option<bool> {
+        // NetworkManager's Metered property for the primary connection, printed as "u 4";
+        // 1 = yes, 2 = no, 3 = guessed yes, 4 = guessed no, 0 = unknown
+        let output = system_command::run("busctl", &[
+            "--system", "get-property", "org.freedesktop.NetworkManager", "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager", "Metered",
+        ]).ok()?;
+        match output.split_whitespace().nth(1)? {
+            "1" | "3" => Some(true),
+            "2" | "4" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use crate::system_command;
+
+    const SCRIPT: &str = "[void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+        $connection = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+        if ($connection) { $connection.GetConnectionCost().NetworkCostType }";
+
+    pub fn query() -> Option<bool> {
+        // NetworkCostType is Unrestricted, Fixed (data cap), Variable (charged per byte) or Unknown
+        let output = system_command::run("powershell", &["-NoProfile", "-Command", SCRIPT]).ok()?;
+        match output.trim() {
+            "Fixed" | "Variable" => Some(true),
+            "Unrestricted" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod platform {
+    pub fn query() -> Option<bool> {
+        None
+    }
+}
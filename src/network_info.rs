@@ -0,0 +1,113 @@
+use std::net::IpAddr;
+
+/// Returns the IPv4 default gateway of the system, if one is configured
+pub fn default_gateway() -> Option<IpAddr> {
+    platform::default_gateway()
+}
+
+/// Returns the DNS servers the system resolver is configured to use
+pub fn dns_servers() -> Vec<IpAddr> {
+    platform::dns_servers()
+}
+
+/// Parses `nameserver` lines from a resolv.conf-style file
+#[cfg(unix)]
+fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| rest.split_whitespace().next())
+        // Scoped IPv6 servers ("fe80::1%en0") can't be pinged without the zone; skip them
+        .filter_map(|address| address.parse::<IpAddr>().ok())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    /// systemd-resolved keeps the real upstream servers here; /etc/resolv.conf only points at its stub
+    const RESOLVED_UPSTREAM_CONF: &str = "/run/systemd/resolve/resolv.conf";
+    const RESOLV_CONF: &str = "/etc/resolv.conf";
+    const ROUTE_TABLE: &str = "/proc/net/route";
+    const RTF_GATEWAY: u32 = 0x2;
+
+    pub fn default_gateway() -> Option<IpAddr> {
+        let table = std::fs::read_to_string(ROUTE_TABLE).ok()?;
+        // Columns: Iface Destination Gateway Flags ... with addresses in little-endian hex
+        table.lines().skip(1).find_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let destination = u32::from_str_radix(columns.get(1)?, 16).ok()?;
+            let gateway = u32::from_str_radix(columns.get(2)?, 16).ok()?;
+            let flags = u32::from_str_radix(columns.get(3)?, 16).ok()?;
+            (destination == 0 && flags & RTF_GATEWAY != 0)
+                .then(|| IpAddr::V4(Ipv4Addr::from(gateway.swap_bytes())))
+        })
+    }
+
+    pub fn dns_servers() -> Vec<IpAddr> {
+        [RESOLVED_UPSTREAM_CONF, RESOLV_CONF]
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|content| parse_resolv_conf(&content))
+            .find(|servers| !servers.is_empty())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+    pub fn default_gateway() -> Option<IpAddr> {
+        // "route -n get default" prints "gateway: 192.168.1.1" on macOS and the BSDs
+        let output = system_command::run("route", &["-n", "get", "default"]).ok()?;
+        output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("gateway:"))
+            .find_map(|gateway| gateway.trim().parse::<IpAddr>().ok())
+    }
+
+    pub fn dns_servers() -> Vec<IpAddr> {
+        std::fs::read_to_string(RESOLV_CONF)
+            .map(|content| parse_resolv_conf(&content))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    pub fn default_gateway() -> Option<IpAddr> {
+        // Active route rows look like "0.0.0.0  0.0.0.0  192.168.1.1  192.168.1.20  25"
+        let output = system_command::run("route", &["print", "-4", "0.0.0.0"]).ok()?;
+        output.lines().find_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            match columns.as_slice() {
+                ["0.0.0.0", "0.0.0.0", gateway, ..] => gateway.parse::<IpAddr>().ok(),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn dns_servers() -> Vec<IpAddr> {
+        // ipconfig output is localized; the PowerShell cmdlet prints bare addresses
+        let script = "(Get-DnsClientServerAddress -AddressFamily IPv4).ServerAddresses";
+        system_command::run("powershell", &["-NoProfile", "-Command", script])
+            .map(|output| {
+                let mut servers: Vec<IpAddr> = output
+                    .lines()
+                    .filter_map(|line| line.trim().parse::<IpAddr>().ok())
+                    .collect();
+                servers.dedup();
+                servers
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use eframe::egui;
+use crate::system_command;
+
+/// The OS's own network monitor, which prints a line for every link, address or route change
+#[cfg(target_os = "linux")]
+const MONITOR_COMMAND: Option<(&str, &[&str])> = Some(("ip", &["monitor", "link", "address", "route"]));
+#[cfg(target_os = "macos")]
+const MONITOR_COMMAND: Option<(&str, &[&str])> = Some(("route", &["-n", "monitor"]));
+/// Windows has no such command; changes are found by polling alone
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const MONITOR_COMMAND: Option<(&str, &[&str])> = None;
+
+/// Runs the monitor command for as long as it is kept
+pub struct NetworkNotifier {
+    child: Child,
+    receiver: mpsc::Receiver<()>,
+}
+
+impl NetworkNotifier {
+    /// Whether a change was reported since the last call
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while self.receiver.try_recv().is_ok() {
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl Drop for NetworkNotifier {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Starts the monitor command and signals on every change it reports. Returns None when the
+/// command is unavailable, in which case changes are only noticed by polling.
+pub fn spawn(repaint: egui::Context) -> Option<NetworkNotifier> {
+    let (program, args) = MONITOR_COMMAND?;
+    let mut child = match system_command::command(program).args(args).stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            log::info!("Network change notifications unavailable ({program}: {e}); polling instead");
+            return None;
+        }
+    };
+    let stdout = child.stdout.take()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(()).is_err() {
+                break;
+            }
+            repaint.request_repaint();
+        }
+    });
+    Some(NetworkNotifier { child, receiver })
+}
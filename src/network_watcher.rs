@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket};
+use std::time::{Duration, Instant};
+use eframe::egui;
+use crate::network_notifier::{self, NetworkNotifier};
+use crate::target_parser::ParsedTarget;
+use crate::vpn;
+
+const NETWORK_CHECK_INTERVAL_SECS: u64 = 5;
+/// Changes arrive in bursts (link, then address, then routes); checks triggered by them are spaced this far apart
+const NOTIFIED_CHECK_SPACING_MS: u64 = 500;
+
+/// Well-known addresses used only to ask the OS which local address it would route from.
+/// Connecting a UDP socket sends no packets.
+const ROUTE_PROBE_V4: &str = "8.8.8.8:53";
+const ROUTE_PROBE_V6: &str = "[2001:4860:4860::8888]:53";
+
+/// Interface name prefixes of container and VM networking (Docker, Podman, libvirt, LXC/LXD,
+/// Kubernetes CNI, VMware, VirtualBox); they stay up with addresses when the real link is gone
+const VIRTUAL_PREFIXES: [&str; 11] = ["docker", "veth", "br-", "virbr", "podman", "lxcbr", "lxdbr", "cni", "flannel", "vmnet", "vboxnet"];
+/// Words in Windows adapter names of Hyper-V and WSL switches ("vEthernet (WSL)")
+const VIRTUAL_WORDS: [&str; 2] = ["vethernet", "hyper-v"];
+
+/// Returns the local address the OS would use for outbound traffic, or None without a route
+pub fn primary_local_ip() -> Option<IpAddr> {
+    local_ip_towards("0.0.0.0:0", ROUTE_PROBE_V4)
+        .or_else(|| local_ip_towards("[::]:0", ROUTE_PROBE_V6))
+}
+
+/// Returns the local address the OS would use to reach `target`
+pub fn local_ip_for(target: IpAddr) -> Option<IpAddr> {
+    let bind_addr = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind_addr).ok()?;
+    socket.connect((target, 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn local_ip_towards(bind_addr: &str, remote: &str) -> Option<IpAddr> {
+    let socket = UdpSocket::bind(bind_addr).ok()?;
+    socket.connect(remote).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceStatus {
+    pub name: String,
+    pub up: bool,
+    pub addresses: Vec<IpAddr>,
+}
+
+impl InterfaceStatus {
+    /// Up with an address that can reach beyond the link (link-local alone means no DHCP lease)
+    pub fn is_usable(&self) -> bool {
+        self.up && self.addresses.iter().any(|ip| !is_link_local(ip))
+    }
+}
+
+/// Whether `name` is a container, VM or tunnel interface rather than a physical link
+pub fn is_virtual(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    VIRTUAL_PREFIXES.iter().any(|prefix| lower.starts_with(prefix))
+        || VIRTUAL_WORDS.iter().any(|word| lower.contains(word))
+        || vpn::is_tunnel(name)
+}
+
+pub fn is_link_local(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => v6.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+/// Lists non-loopback interfaces with their operational state and addresses
+pub fn list_interfaces() -> Vec<InterfaceStatus> {
+    let mut interfaces: BTreeMap<String, InterfaceStatus> = BTreeMap::new();
+    for interface in if_addrs::get_if_addrs().unwrap_or_default() {
+        if interface.is_loopback() {
+            continue;
+        }
+        let status = interfaces.entry(interface.name.clone()).or_insert_with(|| InterfaceStatus {
+            name: interface.name.clone(),
+            up: false,
+            addresses: Vec::new(),
+        });
+        status.up |= interface.is_oper_up();
+        status.addresses.push(interface.ip());
+    }
+    interfaces.into_values().collect()
+}
+
+/// Index of the interface named by an IPv6 zone ID: a number as used on Windows (`%12`) or an
+/// interface name (`%eth0`)
+pub fn interface_index(zone: &str) -> Option<u32> {
+    if let Ok(index) = zone.parse::<u32>() {
+        return Some(index);
+    }
+    if_addrs::get_if_addrs()
+        .ok()?
+        .into_iter()
+        .find(|interface| interface.name == zone)
+        .and_then(|interface| interface.index)
+}
+
+/// Socket address for a link-local target with a zone ID, e.g. `[fe80::1%eth0]:22`
+pub fn scoped_socket_addr(target: &ParsedTarget) -> Option<SocketAddr> {
+    let ip = target.host.parse::<Ipv6Addr>().ok()?;
+    let scope_id = interface_index(target.zone.as_deref()?)?;
+    Some(SocketAddr::V6(SocketAddrV6::new(ip, target.port?, 0, scope_id)))
+}
+
+#[derive(Debug, Clone)]
+pub enum NetworkEvent {
+    PrimaryAddressChanged { previous: Option<IpAddr>, current: Option<IpAddr> },
+    InterfaceUp(String),
+    InterfaceDown(String),
+    AddressesChanged { name: String, addresses: Vec<IpAddr> },
+    VpnConnected(String),
+    VpnDisconnected(String),
+    SsidChanged { previous: Option<String>, current: Option<String> },
+    GatewayChanged { previous: Option<IpAddr>, current: Option<IpAddr> },
+    /// The target's name now resolves elsewhere, which often moves latency with it
+    TargetAddressChanged { hostname: String, previous: IpAddr, current: IpAddr },
+}
+
+impl NetworkEvent {
+    /// Whether measurements before and after the event aren't comparable, so charts break there
+    pub fn splits_history(&self) -> bool {
+        matches!(
+            self,
+            NetworkEvent::PrimaryAddressChanged { .. }
+                | NetworkEvent::VpnConnected(_)
+                | NetworkEvent::VpnDisconnected(_)
+                | NetworkEvent::SsidChanged { .. }
+                | NetworkEvent::GatewayChanged { .. }
+                | NetworkEvent::TargetAddressChanged { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for NetworkEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |ip: &Option<IpAddr>| ip.map_or("no network".to_string(), |ip| ip.to_string());
+        match self {
+            NetworkEvent::PrimaryAddressChanged { previous, current } => {
+                write!(f, "network change ({} → {})", describe(previous), describe(current))
+            }
+            NetworkEvent::InterfaceUp(name) => write!(f, "{name} is up"),
+            NetworkEvent::InterfaceDown(name) => write!(f, "{name} is down"),
+            NetworkEvent::AddressesChanged { name, addresses } => {
+                let addresses: Vec<String> = addresses.iter().map(|ip| ip.to_string()).collect();
+                write!(f, "{name} addresses changed ({})", addresses.join(", "))
+            }
+            NetworkEvent::VpnConnected(name) => write!(f, "VPN connected ({name})"),
+            NetworkEvent::VpnDisconnected(name) => write!(f, "VPN disconnected ({name})"),
+            NetworkEvent::SsidChanged { previous, current } => {
+                let describe = |ssid: &Option<String>| ssid.clone().unwrap_or_else(|| "hidden".to_string());
+                write!(f, "Wi-Fi network change ({} → {})", describe(previous), describe(current))
+            }
+            NetworkEvent::GatewayChanged { previous, current } => {
+                write!(f, "default gateway change ({} → {})", describe(previous), describe(current))
+            }
+            NetworkEvent::TargetAddressChanged { hostname, previous, current } => {
+                write!(f, "{hostname} now resolves to {current} (was {previous})")
+            }
+        }
+    }
+}
+
+/// Polls the primary local address and interface states and reports what changed
+/// (interface switch, new lease, cable unplugged, Wi-Fi dropped)
+pub struct NetworkWatcher {
+    last_address: Option<Option<IpAddr>>,
+    last_check: Option<Instant>,
+    pub interfaces: Vec<InterfaceStatus>,
+    /// Tunnel interface carrying the default route, if any
+    pub vpn: Option<String>,
+    notifier: Option<NetworkNotifier>,
+    subscribed: bool,
+    notified: bool,
+}
+
+impl NetworkWatcher {
+    pub fn new() -> Self {
+        Self {
+            last_address: None,
+            last_check: None,
+            interfaces: Vec::new(),
+            vpn: None,
+            notifier: None,
+            subscribed: false,
+            notified: false,
+        }
+    }
+
+    /// Subscribes to the OS's change notifications, so changes are checked right away instead
+    /// of at the next poll; only the first call does anything
+    pub fn subscribe(&mut self, repaint: &egui::Context) {
+        if !self.subscribed {
+            self.subscribed = true;
+            self.notifier = network_notifier::spawn(repaint.clone());
+        }
+    }
+
+    /// Whether the OS reported a change since the last call
+    pub fn take_notification(&mut self) -> bool {
+        let notified = self.notifier.as_ref().is_some_and(NetworkNotifier::changed);
+        self.notified |= notified;
+        notified
+    }
+
+    /// Cheap to call every frame; only queries the OS once per check interval or soon after a notification
+    pub fn poll(&mut self) -> Vec<NetworkEvent> {
+        let check_interval = if self.notified {
+            Duration::from_millis(NOTIFIED_CHECK_SPACING_MS)
+        } else {
+            Duration::from_secs(NETWORK_CHECK_INTERVAL_SECS)
+        };
+        if self.last_check.is_some_and(|last| last.elapsed() < check_interval) {
+            return Vec::new();
+        }
+        let first_check = self.last_check.is_none();
+        self.last_check = Some(Instant::now());
+        self.notified = false;
+
+        let mut events = Vec::new();
+        let interfaces = list_interfaces();
+        if !first_check {
+            events.extend(Self::diff_interfaces(&self.interfaces, &interfaces));
+        }
+        self.interfaces = interfaces;
+
+        let current = primary_local_ip();
+        if let Some(previous) = self.last_address.replace(current)
+            && previous != current
+        {
+            events.push(NetworkEvent::PrimaryAddressChanged { previous, current });
+        }
+
+        let vpn = current.and_then(|ip| vpn::tunnel_carrying(&self.interfaces, ip));
+        if !first_check && vpn != self.vpn {
+            events.extend(self.vpn.take().map(NetworkEvent::VpnDisconnected));
+            events.extend(vpn.clone().map(NetworkEvent::VpnConnected));
+        }
+        self.vpn = vpn;
+        events
+    }
+
+    fn diff_interfaces(before: &[InterfaceStatus], after: &[InterfaceStatus]) -> Vec<NetworkEvent> {
+        let mut events = Vec::new();
+        for old in before {
+            match after.iter().find(|new| new.name == old.name) {
+                // Interfaces without addresses drop out of the OS listing entirely
+                None if old.up => events.push(NetworkEvent::InterfaceDown(old.name.clone())),
+                None => {}
+                Some(new) if old.up && !new.up => events.push(NetworkEvent::InterfaceDown(new.name.clone())),
+                Some(new) if !old.up && new.up => events.push(NetworkEvent::InterfaceUp(new.name.clone())),
+                Some(new) if old.addresses != new.addresses => events.push(NetworkEvent::AddressesChanged {
+                    name: new.name.clone(),
+                    addresses: new.addresses.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for new in after.iter().filter(|new| new.up && !before.iter().any(|old| old.name == new.name)) {
+            events.push(NetworkEvent::InterfaceUp(new.name.clone()));
+        }
+        events
+    }
+
+    /// True when no physical interface is up with a routable address, so failures are local
+    /// rather than remote. Docker bridges, veths and tunnels keep their addresses without a link.
+    pub fn link_down(&self) -> bool {
+        self.last_check.is_some()
+            && !self.interfaces.iter().any(|interface| !is_virtual(&interface.name) && interface.is_usable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(name: &str, address: &str) -> InterfaceStatus {
+        InterfaceStatus { name: name.to_string(), up: true, addresses: vec![address.parse().unwrap()] }
+    }
+
+    fn watcher(interfaces: Vec<InterfaceStatus>) -> NetworkWatcher {
+        NetworkWatcher { last_check: Some(Instant::now()), interfaces, ..NetworkWatcher::new() }
+    }
+
+    #[test]
+    fn container_and_tunnel_interfaces_are_virtual() {
+        for name in ["docker0", "veth3f2a1b", "br-5c1d2e", "virbr0", "tun0", "wg0", "vEthernet (WSL)"] {
+            assert!(is_virtual(name), "{name}");
+        }
+        for name in ["eth0", "enp3s0", "wlan0", "en0", "Wi-Fi", "Ethernet"] {
+            assert!(!is_virtual(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn virtual_interfaces_alone_mean_link_down() {
+        assert!(watcher(vec![interface("docker0", "172.17.0.1"), interface("veth1", "169.254.3.4"), interface("tun0", "10.8.0.2")]).link_down());
+        assert!(!watcher(vec![interface("docker0", "172.17.0.1"), interface("eth0", "192.168.1.20")]).link_down());
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::attention::AlertSeverity;
+use crate::http_post::http_post_json;
+
+pub const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+/// Push notifications through an ntfy server, the public one or a self-hosted instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NtfySettings {
+    pub enabled: bool,
+    pub server: String,
+    /// Anyone who knows a topic on a public server can read it, so pick something hard to guess
+    pub topic: String,
+    /// Access token for servers or topics that require one; empty to publish anonymously
+    pub access_token: String,
+}
+
+impl Default for NtfySettings {
+    fn default() -> Self {
+        Self { enabled: false, server: DEFAULT_NTFY_SERVER.to_string(), topic: String::new(), access_token: String::new() }
+    }
+}
+
+impl NtfySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let server = self.server.trim();
+        if !server.starts_with("http://") && !server.starts_with("https://") {
+            return Err("ntfy server must start with http:// or https://".to_string());
+        }
+        let topic = self.topic.trim();
+        if topic.is_empty() || !topic.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err("ntfy topic must be letters, digits, '-' or '_'".to_string());
+        }
+        Ok(())
+    }
+
+    /// Publishes in the background with a priority and emoji tag matching the severity; `open_url`
+    /// adds an "Open monitor" button to the notification
+    pub fn notify(&self, severity: AlertSeverity, message: String, open_url: Option<String>) {
+        let body = self.payload(severity, message, open_url);
+        let url = self.server.trim().trim_end_matches('/').to_string();
+        let authorization = (!self.access_token.trim().is_empty()).then(|| format!("Bearer {}", self.access_token.trim()));
+        http_post_json(self.enabled, self.validate(), "ntfy", url, authorization, &body);
+    }
+
+    fn payload(&self, severity: AlertSeverity, message: String, open_url: Option<String>) -> serde_json::Value {
+        // Priorities 5 (urgent), 4 (high) and 3 (default)
+        let (priority, tag) = match severity {
+            AlertSeverity::Critical => (5, "rotating_light"),
+            AlertSeverity::Warning => (4, "warning"),
+            AlertSeverity::Info => (3, "white_check_mark"),
+        };
+        // JSON messages are published to the server root and name their topic
+        let mut body = json!({
+            "topic": self.topic.trim(),
+            "title": "Ping Monitor",
+            "message": message,
+            "priority": priority,
+            "tags": [tag],
+        });
+        if let Some(url) = open_url {
+            body["actions"] = json!([{ "action": "view", "label": "Open monitor", "url": url }]);
+        }
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web_dashboard;
+
+    #[test]
+    fn dashboard_button_carries_no_token() {
+        let settings = NtfySettings { enabled: true, topic: "pings".to_string(), ..NtfySettings::default() };
+        let open_url = web_dashboard::address("192.168.1.20", web_dashboard::DEFAULT_DASHBOARD_PORT, false, "");
+        let body = settings.payload(AlertSeverity::Critical, "Target is down".to_string(), Some(open_url));
+        assert_eq!(body["actions"][0]["url"], "http://192.168.1.20:7879/");
+        assert!(!body.to_string().contains("token="));
+    }
+}
@@ -0,0 +1,147 @@
+use std::io::{Error, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use eframe::egui;
+use crate::config;
+use crate::ping::PingResult;
+
+pub const NTP_PORT: u16 = 123;
+const NTP_TIMEOUT_SECS: u64 = 5;
+const PACKET_LEN: usize = 48;
+/// Seconds from the NTP epoch (1900) to the Unix epoch (1970)
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+/// Leap indicator 0, version 4, mode 3 (client)
+const CLIENT_HEADER: u8 = 0x23;
+const MODE_SERVER: u8 = 4;
+
+/// One SNTP exchange, both in ms
+struct NtpSample {
+    /// Round trip with the server's own processing time taken out
+    delay: f64,
+    /// How far the server's clock is ahead of ours; negative when the local clock runs fast
+    offset: f64,
+}
+
+impl NtpSample {
+    /// From our send time and the server's receive and send times (ms on their clocks) plus the
+    /// round trip as our monotonic clock measured it
+    fn from_exchange(client_sent: f64, server_received: f64, server_sent: f64, elapsed: f64) -> Self {
+        // Measured on the monotonic clock so a clock step mid-exchange can't skew the delay
+        let client_received = client_sent + elapsed;
+        Self {
+            delay: (elapsed - (server_sent - server_received)).max(0.0),
+            offset: ((server_received - client_sent) + (server_sent - client_received)) / 2.0,
+        }
+    }
+}
+
+pub struct NtpProbe;
+
+impl NtpProbe {
+    /// Queries `server` (host or host:port) once. The response time is the network delay and
+    /// the clock offset is attached to the result.
+    pub fn spawn(server: String, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let result = match Self::query(&server) {
+                Ok((sample, address)) => PingResult::success(timestamp, sent_at, sample.delay, None)
+                    .with_target(address)
+                    .with_clock_offset(sample.offset),
+                Err(_) => PingResult::failure(timestamp, sent_at),
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn query(server: &str) -> std::io::Result<(NtpSample, IpAddr)> {
+        let address = config::with_default_port(server, NTP_PORT)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("{server} has no address")))?;
+        let local: IpAddr = if address.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+        let socket = UdpSocket::bind((local, 0))?;
+        socket.set_read_timeout(Some(Duration::from_secs(NTP_TIMEOUT_SECS)))?;
+        socket.connect(address)?;
+
+        let mut request = [0u8; PACKET_LEN];
+        request[0] = CLIENT_HEADER;
+        let sent = SystemTime::now();
+        let started = Instant::now();
+        request[40..48].copy_from_slice(&to_ntp(sent).to_be_bytes());
+        socket.send(&request)?;
+
+        let mut response = [0u8; PACKET_LEN];
+        let length = socket.recv(&mut response)?;
+        let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        // The server echoes our transmit time as its origin time, which rules out stray packets
+        if length < PACKET_LEN || response[0] & 0x07 != MODE_SERVER || response[24..32] != request[40..48] {
+            return Err(Error::new(ErrorKind::InvalidData, "not a reply to this request"));
+        }
+        // Stratum 0 is a "kiss-o'-death": the server refuses to give us the time
+        if response[1] == 0 {
+            return Err(Error::new(ErrorKind::ConnectionRefused, "server sent kiss-o'-death"));
+        }
+
+        let sample = NtpSample::from_exchange(
+            ntp_ms(to_ntp(sent)),
+            ntp_ms(read_timestamp(&response[32..40])),
+            ntp_ms(read_timestamp(&response[40..48])),
+            elapsed,
+        );
+        Ok((sample, address.ip()))
+    }
+}
+
+/// 32.32 fixed-point seconds since 1900
+fn to_ntp(time: SystemTime) -> u64 {
+    let since_unix = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_unix.as_secs() + NTP_UNIX_OFFSET_SECS;
+    let fraction = (u64::from(since_unix.subsec_nanos()) << 32) / 1_000_000_000;
+    (seconds << 32) | fraction
+}
+
+fn read_timestamp(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().unwrap_or_default())
+}
+
+fn ntp_ms(timestamp: u64) -> f64 {
+    (timestamp >> 32) as f64 * 1000.0 + (timestamp & 0xffff_ffff) as f64 / 4_294_967_296.0 * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_and_delay_from_a_symmetric_exchange() {
+        // 10 ms each way, 2 ms at the server, whose clock is 500 ms ahead
+        let sample = NtpSample::from_exchange(1000.0, 1510.0, 1512.0, 22.0);
+        assert!((sample.delay - 20.0).abs() < 1e-9);
+        assert!((sample.offset - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fast_local_clock_gives_negative_offset() {
+        let sample = NtpSample::from_exchange(1000.0, 760.0, 761.0, 21.0);
+        assert!((sample.offset + 250.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn delay_is_never_negative() {
+        // A server claiming to have held the request longer than the whole round trip
+        assert_eq!(NtpSample::from_exchange(0.0, 5.0, 25.0, 10.0).delay, 0.0);
+    }
+
+    #[test]
+    fn ntp_timestamps_count_from_1900() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_250);
+        assert_eq!(to_ntp(UNIX_EPOCH) >> 32, NTP_UNIX_OFFSET_SECS);
+        let ms = ntp_ms(to_ntp(time)) - NTP_UNIX_OFFSET_SECS as f64 * 1000.0;
+        assert!((ms - 1_700_000_000_250.0).abs() < 0.001);
+    }
+}
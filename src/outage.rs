@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+use crate::ping::{FailureCause, PingResult};
+use crate::traceroute::TraceHop;
+
+/// Consecutive failed pings before an outage is declared
+const OUTAGE_FAILURE_THRESHOLD: u32 = 3;
+const MAX_OUTAGE_RECORDS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub enum TraceSnapshot {
+    Running,
+    Captured(Vec<TraceHop>),
+    Failed(String),
+}
+
+/// Where an outage most likely is, judged by whether the default gateway kept answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultLocation {
+    /// The gateway was unreachable too: Wi-Fi, cable or router
+    Local,
+    /// The gateway answered, so the problem is with the ISP or beyond
+    Upstream,
+}
+
+impl FaultLocation {
+    pub fn label(self) -> &'static str {
+        match self {
+            FaultLocation::Local => "local network (the gateway isn't answering either)",
+            FaultLocation::Upstream => "upstream (the gateway still answers)",
+        }
+    }
+}
+
+/// Outcome of probes to another host sent alongside the target's while it was failing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplyTally {
+    pub replies: u32,
+    pub losses: u32,
+}
+
+impl ReplyTally {
+    pub fn record(&mut self, success: bool) {
+        if success { self.replies += 1 } else { self.losses += 1 }
+    }
+}
+
+impl std::fmt::Display for ReplyTally {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} answered", self.replies, self.replies + self.losses)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutageRecord {
+    pub id: u64,
+    pub started: SystemTime,
+    pub ended: Option<SystemTime>,
+    pub failed_pings: u64,
+    pub traceroute: Option<TraceSnapshot>,
+    /// Where along the path loss began, read from the traceroute when it was captured
+    pub loss_summary: Option<String>,
+    pub gateway: ReplyTally,
+    /// Hosts of the target's fallback chain probed during the outage
+    pub fallbacks: Vec<(String, ReplyTally)>,
+}
+
+impl OutageRecord {
+    /// None until the gateway has been probed at least once
+    pub fn fault_location(&self) -> Option<FaultLocation> {
+        match (self.gateway.replies, self.gateway.losses) {
+            (0, 0) => None,
+            (replies, losses) if replies > losses => Some(FaultLocation::Upstream),
+            _ => Some(FaultLocation::Local),
+        }
+    }
+
+    /// "vpn-gw 0/3 answered, 1.1.1.1 3/3 answered", or None when no fallback was probed
+    pub fn fallback_summary(&self) -> Option<String> {
+        let parts: Vec<String> = self.fallbacks.iter().map(|(host, tally)| format!("{host} {tally}")).collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    pub fn duration(&self) -> Duration {
+        let end = self.ended.unwrap_or_else(SystemTime::now);
+        end.duration_since(self.started).unwrap_or(Duration::from_secs(0))
+    }
+
+    pub fn is_ongoing(&self) -> bool {
+        self.ended.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutageEvent {
+    Started(u64),
+    Ended(u64),
+}
+
+/// Turns the stream of ping results into outage records: an outage starts after
+/// several consecutive failures (dated from the first of them) and ends on the next success
+pub struct OutageTracker {
+    consecutive_failures: u32,
+    first_failure: Option<SystemTime>,
+    /// Gateway and fallback results from the failures before an outage is declared, handed to it when it is
+    pending_gateway: ReplyTally,
+    pending_fallbacks: Vec<(String, ReplyTally)>,
+    next_id: u64,
+    pub outages: VecDeque<OutageRecord>,
+}
+
+impl OutageTracker {
+    pub fn new() -> Self {
+        Self {
+            consecutive_failures: 0,
+            first_failure: None,
+            pending_gateway: ReplyTally::default(),
+            pending_fallbacks: Vec::new(),
+            next_id: 1,
+            outages: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, result: &PingResult) -> Option<OutageEvent> {
+        // A failing proxy leaves the target's state unknown, so it neither starts nor ends an outage
+        if (result.in_maintenance && !result.success) || result.failure_cause == Some(FailureCause::ProxyFailed) {
+            return None;
+        }
+
+        if result.success {
+            self.consecutive_failures = 0;
+            self.first_failure = None;
+            self.pending_gateway = ReplyTally::default();
+            self.pending_fallbacks.clear();
+            let ongoing = self.ongoing_mut()?;
+            ongoing.ended = Some(result.timestamp);
+            return Some(OutageEvent::Ended(ongoing.id));
+        }
+
+        self.consecutive_failures += 1;
+        self.first_failure.get_or_insert(result.timestamp);
+
+        if let Some(ongoing) = self.ongoing_mut() {
+            ongoing.failed_pings += 1;
+            return None;
+        }
+
+        if self.consecutive_failures < OUTAGE_FAILURE_THRESHOLD {
+            return None;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.outages.push_back(OutageRecord {
+            id,
+            started: self.first_failure.unwrap_or(result.timestamp),
+            ended: None,
+            failed_pings: self.consecutive_failures as u64,
+            traceroute: None,
+            loss_summary: None,
+            gateway: std::mem::take(&mut self.pending_gateway),
+            fallbacks: std::mem::take(&mut self.pending_fallbacks),
+        });
+        if self.outages.len() > MAX_OUTAGE_RECORDS {
+            self.outages.pop_front();
+        }
+        Some(OutageEvent::Started(id))
+    }
+
+    /// Whether the last result failed, i.e. an outage is ongoing or may be about to start
+    pub fn failing(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+
+    /// Counts a gateway probe towards the ongoing outage, or the one that may be starting
+    pub fn record_gateway(&mut self, success: bool) {
+        if let Some(ongoing) = self.ongoing_mut() {
+            ongoing.gateway.record(success);
+        } else if self.failing() {
+            self.pending_gateway.record(success);
+        }
+    }
+
+    /// Counts a probe of a fallback host like `record_gateway`
+    pub fn record_fallback(&mut self, host: &str, success: bool) {
+        let fallbacks = match self.outages.back_mut().filter(|outage| outage.is_ongoing()) {
+            Some(ongoing) => &mut ongoing.fallbacks,
+            None if self.consecutive_failures > 0 => &mut self.pending_fallbacks,
+            None => return,
+        };
+        match fallbacks.iter_mut().find(|(fallback, _)| fallback == host) {
+            Some((_, tally)) => tally.record(success),
+            None => {
+                let mut tally = ReplyTally::default();
+                tally.record(success);
+                fallbacks.push((host.to_string(), tally));
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut OutageRecord> {
+        self.outages.iter_mut().find(|outage| outage.id == id)
+    }
+
+    pub fn ongoing(&self) -> Option<&OutageRecord> {
+        self.outages.back().filter(|outage| outage.is_ongoing())
+    }
+
+    fn ongoing_mut(&mut self) -> Option<&mut OutageRecord> {
+        self.outages.back_mut().filter(|outage| outage.is_ongoing())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+    use super::*;
+
+    fn fail(tracker: &mut OutageTracker) -> Option<OutageEvent> {
+        tracker.record(&PingResult::failure(SystemTime::now(), Instant::now()))
+    }
+
+    #[test]
+    fn fallbacks_probed_before_the_outage_is_declared_are_handed_over() {
+        let mut tracker = OutageTracker::new();
+        tracker.record_fallback("vpn-gw", false);
+        assert!(tracker.pending_fallbacks.is_empty(), "nothing is failing yet");
+
+        fail(&mut tracker);
+        tracker.record_fallback("vpn-gw", false);
+        tracker.record_fallback("1.1.1.1", true);
+        while !matches!(fail(&mut tracker), Some(OutageEvent::Started(_))) {}
+        tracker.record_fallback("1.1.1.1", true);
+
+        let outage = tracker.ongoing().unwrap();
+        assert_eq!(outage.fallback_summary().as_deref(), Some("vpn-gw 0/1 answered, 1.1.1.1 2/2 answered"));
+        assert!(tracker.pending_fallbacks.is_empty());
+    }
+
+    #[test]
+    fn pending_fallbacks_are_dropped_when_the_target_recovers() {
+        let mut tracker = OutageTracker::new();
+        fail(&mut tracker);
+        tracker.record_fallback("vpn-gw", true);
+        tracker.record(&PingResult::success(SystemTime::now(), Instant::now(), 12.0, None));
+        while !matches!(fail(&mut tracker), Some(OutageEvent::Started(_))) {}
+        assert_eq!(tracker.ongoing().unwrap().fallback_summary(), None);
+    }
+
+    #[test]
+    fn reply_tally_counts_and_displays() {
+        let mut tally = ReplyTally::default();
+        tally.record(true);
+        tally.record(false);
+        tally.record(true);
+        assert_eq!(tally, ReplyTally { replies: 2, losses: 1 });
+        assert_eq!(tally.to_string(), "2/3 answered");
+    }
+
+    #[test]
+    fn fault_location_follows_the_gateway() {
+        let mut tracker = OutageTracker::new();
+        tracker.record_gateway(false);
+        fail(&mut tracker);
+        tracker.record_gateway(false);
+        while !matches!(fail(&mut tracker), Some(OutageEvent::Started(_))) {}
+        assert_eq!(tracker.ongoing().unwrap().fault_location(), Some(FaultLocation::Local));
+
+        let mut outage = tracker.ongoing().unwrap().clone();
+        outage.gateway = ReplyTally::default();
+        assert_eq!(outage.fault_location(), None);
+        outage.gateway = ReplyTally { replies: 1, losses: 1 };
+        assert_eq!(outage.fault_location(), Some(FaultLocation::Local));
+        outage.gateway = ReplyTally { replies: 2, losses: 1 };
+        assert_eq!(outage.fault_location(), Some(FaultLocation::Upstream));
+    }
+}
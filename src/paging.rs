@@ -0,0 +1,105 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::http_post::http_post_json;
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const OPSGENIE_US_URL: &str = "https://api.opsgenie.com/v2/alerts";
+const OPSGENIE_EU_URL: &str = "https://api.eu.opsgenie.com/v2/alerts";
+
+/// On-call service paged when an outage starts and resolved when it ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PagingService {
+    #[default]
+    Off,
+    /// Events API v2 with an integration (routing) key
+    PagerDuty,
+    /// Alert API with an API integration key
+    Opsgenie,
+}
+
+impl PagingService {
+    pub const ALL: [PagingService; 3] = [PagingService::Off, PagingService::PagerDuty, PagingService::Opsgenie];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PagingService::Off => "Off",
+            PagingService::PagerDuty => "PagerDuty",
+            PagingService::Opsgenie => "Opsgenie",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PagingSettings {
+    pub service: PagingService,
+    /// PagerDuty routing key or Opsgenie API key
+    pub key: String,
+    /// Opsgenie accounts hosted in the EU use a separate API domain
+    pub opsgenie_eu: bool,
+}
+
+impl PagingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.service != PagingService::Off && self.key.trim().is_empty() {
+            return Err(format!("{} paging needs an integration key", self.service.label()));
+        }
+        Ok(())
+    }
+
+    /// Opens an incident for the outage that started at `started`; `resolve` with the same
+    /// arguments closes it, since both derive the same deduplication key
+    pub fn trigger(&self, target: &str, started: SystemTime, summary: String) {
+        let dedup_key = dedup_key(target, started);
+        let (url, body) = match self.service {
+            PagingService::Off => return,
+            PagingService::PagerDuty => (
+                PAGERDUTY_EVENTS_URL.to_string(),
+                json!({
+                    "routing_key": self.key.trim(),
+                    "event_action": "trigger",
+                    "dedup_key": dedup_key,
+                    "payload": { "summary": summary, "source": target, "severity": "critical", "component": "ping-monitor" },
+                }),
+            ),
+            PagingService::Opsgenie => (
+                self.opsgenie_url().to_string(),
+                json!({ "message": summary, "alias": dedup_key, "entity": target, "priority": "P1", "tags": ["ping-monitor"] }),
+            ),
+        };
+        self.send(url, body);
+    }
+
+    pub fn resolve(&self, target: &str, started: SystemTime, summary: String) {
+        let dedup_key = dedup_key(target, started);
+        let (url, body) = match self.service {
+            PagingService::Off => return,
+            PagingService::PagerDuty => (
+                PAGERDUTY_EVENTS_URL.to_string(),
+                json!({ "routing_key": self.key.trim(), "event_action": "resolve", "dedup_key": dedup_key }),
+            ),
+            PagingService::Opsgenie => (
+                format!("{}/{dedup_key}/close?identifierType=alias", self.opsgenie_url()),
+                json!({ "note": summary, "source": "ping-monitor" }),
+            ),
+        };
+        self.send(url, body);
+    }
+
+    fn opsgenie_url(&self) -> &'static str {
+        if self.opsgenie_eu { OPSGENIE_EU_URL } else { OPSGENIE_US_URL }
+    }
+
+    fn send(&self, url: String, body: serde_json::Value) {
+        let authorization = (self.service == PagingService::Opsgenie).then(|| format!("GenieKey {}", self.key.trim()));
+        http_post_json(self.service != PagingService::Off, self.validate(), self.service.label(), url, authorization, &body);
+    }
+}
+
+/// Derived from the outage rather than stored, so the trigger and the resolve address one incident
+fn dedup_key(target: &str, started: SystemTime) -> String {
+    let started_ms = started.duration_since(UNIX_EPOCH).map(|since| since.as_millis()).unwrap_or(0);
+    format!("ping-monitor:{target}:{started_ms}")
+}
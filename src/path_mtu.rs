@@ -0,0 +1,119 @@
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use eframe::egui;
+use crate::system_command;
+use crate::target_parser;
+
+/// IPv4 header (20) plus ICMP header (8)
+const HEADER_BYTES: u32 = 28;
+/// Largest payload that fits a standard 1500-byte Ethernet MTU
+const MAX_PAYLOAD: u32 = 1500 - HEADER_BYTES;
+const MIN_PAYLOAD: u32 = 0;
+
+/// Fragments of ping output that mean the packet was refused for size, locally or by a router
+/// that sent back "fragmentation needed"
+const TOO_BIG_MARKERS: [&str; 4] = ["frag needed", "message too long", "needs to be fragmented", "mtu ="];
+
+#[derive(Debug, Clone)]
+pub struct PathMtuReport {
+    pub target: IpAddr,
+    pub mtu: u32,
+    /// True when even the largest standard payload got through, so the real MTU may be higher
+    pub at_limit: bool,
+    /// Oversized packets vanished without a "fragmentation needed" reply, which breaks
+    /// TCP's own path MTU discovery (large transfers stall while small requests work)
+    pub black_hole: bool,
+}
+
+enum ProbeOutcome {
+    Delivered,
+    TooBig,
+    Lost,
+}
+
+pub struct PathMtu;
+
+impl PathMtu {
+    /// Binary-searches the largest don't-fragment ping that reaches `target` using the system ping
+    pub fn spawn(target: String, sender: mpsc::Sender<Result<PathMtuReport, String>>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let _ = sender.send(Self::run(&target));
+            repaint.request_repaint();
+        });
+    }
+
+    fn run(target: &str) -> Result<PathMtuReport, String> {
+        let target = Self::resolve_ipv4(target)?;
+
+        if !matches!(Self::probe(target, MIN_PAYLOAD)?, ProbeOutcome::Delivered) {
+            return Err(format!("{target} does not answer pings"));
+        }
+        if matches!(Self::probe(target, MAX_PAYLOAD)?, ProbeOutcome::Delivered) {
+            return Ok(PathMtuReport { target, mtu: MAX_PAYLOAD + HEADER_BYTES, at_limit: true, black_hole: false });
+        }
+
+        // Invariant: `low` is delivered, `high` is not
+        let (mut low, mut high) = (MIN_PAYLOAD, MAX_PAYLOAD);
+        let mut saw_too_big = false;
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            match Self::probe(target, middle)? {
+                ProbeOutcome::Delivered => low = middle,
+                ProbeOutcome::TooBig => {
+                    saw_too_big = true;
+                    high = middle;
+                }
+                ProbeOutcome::Lost => high = middle,
+            }
+        }
+
+        // Confirm the boundary: a single lost probe could be ordinary packet loss
+        let black_hole = !saw_too_big && matches!(Self::probe(target, high)?, ProbeOutcome::Lost);
+        Ok(PathMtuReport { target, mtu: low + HEADER_BYTES, at_limit: false, black_hole })
+    }
+
+    fn probe(target: IpAddr, payload: u32) -> Result<ProbeOutcome, String> {
+        let target = target.to_string();
+        let payload = payload.to_string();
+        #[cfg(windows)]
+        let args = ["-n", "1", "-w", "2000", "-f", "-l", &payload, &target];
+        #[cfg(target_os = "linux")]
+        let args = ["-c", "1", "-W", "2", "-M", "do", "-s", &payload, &target];
+        #[cfg(all(unix, not(target_os = "linux")))]
+        let args = ["-c", "1", "-t", "2", "-D", "-s", &payload, &target];
+
+        let output = system_command::command("ping")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Could not run ping: {e}"))?;
+        let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+            .to_lowercase();
+
+        // Windows ping exits 0 when any reply came back, including "destination unreachable"
+        let delivered = output.status.success() && !text.contains("unreachable");
+        Ok(if delivered {
+            ProbeOutcome::Delivered
+        } else if TOO_BIG_MARKERS.iter().any(|marker| text.contains(marker)) {
+            ProbeOutcome::TooBig
+        } else {
+            ProbeOutcome::Lost
+        })
+    }
+
+    /// The don't-fragment flag only exists in IPv4; IPv6 routers never fragment
+    fn resolve_ipv4(target: &str) -> Result<IpAddr, String> {
+        if target.is_empty() || target.starts_with('-') {
+            return Err("Invalid target".to_string());
+        }
+        if let Ok(ip) = target.parse::<IpAddr>() {
+            return if ip.is_ipv4() { Ok(ip) } else { Err("Path MTU discovery needs an IPv4 target".to_string()) };
+        }
+        (target_parser::to_ascii(target).as_str(), 0)
+            .to_socket_addrs()
+            .map_err(|e| format!("Could not resolve {target}: {e}"))?
+            .map(|address| address.ip())
+            .find(IpAddr::is_ipv4)
+            .ok_or_else(|| format!("{target} has no IPv4 address"))
+    }
+}
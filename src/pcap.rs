@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::config;
+use crate::ping::PingResult;
+use crate::ping_executor::{ECHO_PAYLOAD, ECHO_TTL};
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+/// Raw IP packets without a link-layer header; Wireshark picks IPv4 or IPv6 from the version nibble
+const LINKTYPE_RAW: u32 = 101;
+const SNAPLEN: u32 = 65535;
+/// About a day at the default interval; oldest probes are dropped beyond this
+const MAX_CAPTURED_PROBES: usize = 20_000;
+
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+const PROTOCOL_ICMP: u8 = 1;
+const PROTOCOL_ICMPV6: u8 = 58;
+
+/// One echo exchange as seen by the monitor
+#[derive(Debug, Clone)]
+pub struct CapturedProbe {
+    pub sent: SystemTime,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub identifier: u16,
+    pub sequence: u16,
+    pub round_trip: Option<Duration>,
+    /// 0 when the OS didn't report it
+    pub reply_ttl: u8,
+}
+
+/// Records the echo requests and replies of each probe while enabled.
+/// The ICMP socket is not exposed by the ping library, so packets are rebuilt from the
+/// probe results: addresses, timing, identifier, sequence number, TTL and payload match what was
+/// on the wire, except reply TTLs the OS didn't report (all of IPv6), which are written as 0.
+/// Probes without an echo sequence (the system ping fallback, other probe types) are left out.
+pub struct PacketCapture {
+    pub enabled: bool,
+    probes: VecDeque<CapturedProbe>,
+}
+
+impl PacketCapture {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            probes: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.probes.len()
+    }
+
+    pub fn record(&mut self, result: &PingResult) {
+        if !self.enabled {
+            return;
+        }
+        let (Some(destination), Some(identifier), Some(sequence)) = (result.target_ip, result.identifier, result.sequence) else {
+            return;
+        };
+        let source = result.source_ip.unwrap_or(match destination {
+            IpAddr::V4(_) => IpAddr::from([0, 0, 0, 0]),
+            IpAddr::V6(_) => IpAddr::from([0u16; 8]),
+        });
+
+        self.probes.push_back(CapturedProbe {
+            sent: result.timestamp,
+            source,
+            destination,
+            identifier,
+            sequence,
+            round_trip: result.response_time.map(|ms| Duration::from_secs_f64(ms / 1000.0)),
+            reply_ttl: result.reply_ttl.unwrap_or(0),
+        });
+        if self.probes.len() > MAX_CAPTURED_PROBES {
+            self.probes.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.probes.clear();
+    }
+
+    /// Writes the probes sent within the last `window` to a pcap file and returns how many were written
+    pub fn export(&self, path: &Path, window: Duration) -> io::Result<usize> {
+        let cutoff = SystemTime::now().checked_sub(window).unwrap_or(UNIX_EPOCH);
+        let probes: Vec<&CapturedProbe> = self.probes.iter().filter(|probe| probe.sent >= cutoff).collect();
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_global_header(&mut writer)?;
+        for probe in &probes {
+            let request = echo_packet(probe.source, probe.destination, true, ECHO_TTL, probe.identifier, probe.sequence);
+            write_record(&mut writer, probe.sent, &request)?;
+            if let Some(round_trip) = probe.round_trip {
+                let reply = echo_packet(probe.destination, probe.source, false, probe.reply_ttl, probe.identifier, probe.sequence);
+                write_record(&mut writer, probe.sent + round_trip, &reply)?;
+            }
+        }
+        writer.flush()?;
+        Ok(probes.len())
+    }
+}
+
+pub fn default_export_path() -> PathBuf {
+    config::export_path("ping-monitor", "pcap")
+}
+
+fn write_global_header(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&PCAP_MAGIC.to_le_bytes())?;
+    writer.write_all(&2u16.to_le_bytes())?; // version major
+    writer.write_all(&4u16.to_le_bytes())?; // version minor
+    writer.write_all(&0i32.to_le_bytes())?; // timezone offset
+    writer.write_all(&0u32.to_le_bytes())?; // timestamp accuracy
+    writer.write_all(&SNAPLEN.to_le_bytes())?;
+    writer.write_all(&LINKTYPE_RAW.to_le_bytes())
+}
+
+fn write_record(writer: &mut impl Write, time: SystemTime, packet: &[u8]) -> io::Result<()> {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    writer.write_all(&(since_epoch.as_secs() as u32).to_le_bytes())?;
+    writer.write_all(&since_epoch.subsec_micros().to_le_bytes())?;
+    writer.write_all(&(packet.len() as u32).to_le_bytes())?;
+    writer.write_all(&(packet.len() as u32).to_le_bytes())?;
+    writer.write_all(packet)
+}
+
+/// Builds an IP packet carrying an ICMP or ICMPv6 echo request/reply with the monitor's payload
+fn echo_packet(source: IpAddr, destination: IpAddr, request: bool, ttl: u8, identifier: u16, sequence: u16) -> Vec<u8> {
+    match (source, destination) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            let mut icmp = echo_message(if request { ICMP_ECHO_REQUEST } else { ICMP_ECHO_REPLY }, identifier, sequence);
+            let checksum = internet_checksum(&icmp);
+            icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+            let mut packet = vec![0u8; 20];
+            packet[0] = 0x45; // version 4, 5-word header
+            packet[2..4].copy_from_slice(&((20 + icmp.len()) as u16).to_be_bytes());
+            packet[8] = ttl;
+            packet[9] = PROTOCOL_ICMP;
+            packet[12..16].copy_from_slice(&source.octets());
+            packet[16..20].copy_from_slice(&destination.octets());
+            let checksum = internet_checksum(&packet);
+            packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+            packet.extend_from_slice(&icmp);
+            packet
+        }
+        (source, destination) => {
+            let to_v6 = |ip: IpAddr| match ip {
+                IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+                IpAddr::V6(v6) => v6,
+            };
+            let (source, destination) = (to_v6(source), to_v6(destination));
+            let mut icmp = echo_message(if request { ICMPV6_ECHO_REQUEST } else { ICMPV6_ECHO_REPLY }, identifier, sequence);
+
+            // ICMPv6 checksums cover a pseudo-header of both addresses, the length and the next header
+            let mut pseudo = Vec::with_capacity(40 + icmp.len());
+            pseudo.extend_from_slice(&source.octets());
+            pseudo.extend_from_slice(&destination.octets());
+            pseudo.extend_from_slice(&(icmp.len() as u32).to_be_bytes());
+            pseudo.extend_from_slice(&[0, 0, 0, PROTOCOL_ICMPV6]);
+            pseudo.extend_from_slice(&icmp);
+            let checksum = internet_checksum(&pseudo);
+            icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+            let mut packet = vec![0u8; 40];
+            packet[0] = 0x60; // version 6
+            packet[4..6].copy_from_slice(&(icmp.len() as u16).to_be_bytes());
+            packet[6] = PROTOCOL_ICMPV6;
+            packet[7] = ttl;
+            packet[8..24].copy_from_slice(&source.octets());
+            packet[24..40].copy_from_slice(&destination.octets());
+            packet.extend_from_slice(&icmp);
+            packet
+        }
+    }
+}
+
+fn echo_message(message_type: u8, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut message = vec![message_type, 0, 0, 0];
+    message.extend_from_slice(&identifier.to_be_bytes());
+    message.extend_from_slice(&sequence.to_be_bytes());
+    message.extend_from_slice(ECHO_PAYLOAD);
+    message
+}
+
+/// RFC 1071 ones' complement sum
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -0,0 +1,500 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeZone};
+use eframe::egui;
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str, TextStr};
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::outage::{OutageEvent, OutageTracker};
+use crate::ping;
+use crate::report::PeriodSummary;
+
+/// Availability promised by a typical business internet contract, percent
+pub const DEFAULT_SLA_PERCENT: f64 = 99.9;
+/// Days covered by the date range the Reports tab starts with, today included
+pub const DEFAULT_RANGE_DAYS: u64 = 30;
+/// Longest range a report may cover, so a typo can't make it read years of history
+const MAX_RANGE_DAYS: i64 = 366;
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// A4 portrait in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const CHART_HEIGHT: f32 = 130.0;
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+type Rgb = (f32, f32, f32);
+const TEXT_COLOR: Rgb = (0.1, 0.1, 0.1);
+const MUTED_COLOR: Rgb = (0.45, 0.45, 0.45);
+const GRID_COLOR: Rgb = (0.85, 0.85, 0.85);
+const MET_COLOR: Rgb = (0.2, 0.6, 0.3);
+const MISSED_COLOR: Rgb = (0.8, 0.2, 0.2);
+const LATENCY_COLOR: Rgb = (0.3, 0.5, 0.85);
+
+/// Parses the report range typed as two YYYY-MM-DD dates
+pub fn parse_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let parse = |text: &str| NaiveDate::parse_from_str(text.trim(), DATE_FORMAT).map_err(|_| format!("'{}' is not a date like 2024-05-31", text.trim()));
+    let (first, last) = (parse(from)?, parse(to)?);
+    if first > last {
+        return Err("The report range ends before it starts".to_string());
+    }
+    if (last - first).num_days() >= MAX_RANGE_DAYS {
+        return Err(format!("A report covers at most {MAX_RANGE_DAYS} days"));
+    }
+    Ok((first, last))
+}
+
+/// The last `DEFAULT_RANGE_DAYS` days up to today, as text for the range fields
+pub fn default_range() -> (String, String) {
+    let today = Local::now().date_naive();
+    (format_date(today - Days::new(DEFAULT_RANGE_DAYS - 1)), format_date(today))
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// One outage as listed in the report
+struct OutageRow {
+    started: SystemTime,
+    /// None when the target was still down at the end of the range
+    ended: Option<SystemTime>,
+    duration: Duration,
+    failed_pings: u64,
+}
+
+pub struct PdfReport;
+
+impl PdfReport {
+    /// Writes a report on `target` over the local days `first..=last` to `path` in the background:
+    /// availability against `sla_percent`, daily charts, a table per day and every outage.
+    /// `name` is how the target is labelled in the report.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn(
+        store: HistoryStore,
+        target: String,
+        name: String,
+        first: NaiveDate,
+        last: NaiveDate,
+        sla_percent: f64,
+        path: PathBuf,
+        sender: mpsc::Sender<Result<String, String>>,
+        repaint: egui::Context,
+    ) {
+        thread::spawn(move || {
+            let records: Vec<HistoryRecord> = store.load_days(first, last).into_iter().filter(|record| record.target == target).collect();
+            let result = if records.is_empty() {
+                Err(format!("No history for {target} between {} and {}", format_date(first), format_date(last)))
+            } else {
+                let pdf = Self::render(&name, first, last, sla_percent, &records);
+                std::fs::write(&path, pdf)
+                    .map(|()| format!("Saved PDF report to {}", path.display()))
+                    .map_err(|e| format!("PDF export failed: {e}"))
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn render(target: &str, first: NaiveDate, last: NaiveDate, sla_percent: f64, records: &[HistoryRecord]) -> Vec<u8> {
+        let all: Vec<&HistoryRecord> = records.iter().collect();
+        let period = PeriodSummary::from_records(String::new(), &all);
+        let mut by_day: BTreeMap<NaiveDate, Vec<&HistoryRecord>> = BTreeMap::new();
+        for record in records {
+            by_day.entry(DateTime::<Local>::from(record.timestamp()).date_naive()).or_default().push(record);
+        }
+        let days: Vec<(NaiveDate, Option<PeriodSummary>)> = first
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .map(|day| (day, by_day.get(&day).map(|records| PeriodSummary::from_records(format_date(day), records))))
+            .collect();
+        let outages = outage_rows(&all);
+
+        let mut doc = Document::new();
+        doc.line(BOLD, 18.0, "Connection quality report");
+        doc.gap(4.0);
+        doc.line(REGULAR, 11.0, &format!("Target: {target}"));
+        doc.line(REGULAR, 11.0, &format!("Period: {} to {} (local time)", format_date(first), format_date(last)));
+        doc.muted_line(&format!("Generated {} by Ping Monitor from {} stored probes", Local::now().format("%Y-%m-%d %H:%M"), records.len()));
+        doc.gap(12.0);
+
+        Self::sla_section(&mut doc, first, last, sla_percent, &period, &outages);
+
+        doc.heading("Daily availability");
+        let low = days
+            .iter()
+            .filter_map(|(_, day)| day.as_ref().map(|day| day.uptime))
+            .fold(sla_percent, f64::min);
+        // Start the axis just below the worst day so the difference between 99.5 and 99.9 shows
+        let floor = ((low - 0.5).floor()).clamp(0.0, 99.0);
+        let bars: Vec<Bar> = days
+            .iter()
+            .map(|(day, summary)| Bar {
+                label: day.format("%m-%d").to_string(),
+                value: summary.as_ref().map(|summary| summary.uptime),
+                marker: None,
+                color: if summary.as_ref().is_some_and(|summary| summary.uptime < sla_percent) { MISSED_COLOR } else { MET_COLOR },
+            })
+            .collect();
+        doc.bar_chart(&bars, floor, 100.0, Some(sla_percent), |value| format!("{value:.1}%"));
+        doc.muted_line(&format!("Red days fall short of the agreed {sla_percent}%; the red line marks it. Days without data are left blank."));
+
+        doc.heading("Daily latency");
+        let high = days
+            .iter()
+            .filter_map(|(_, day)| day.as_ref().and_then(|day| day.p95_ms.or(day.mean_ms)))
+            .fold(1.0, f64::max);
+        let bars: Vec<Bar> = days
+            .iter()
+            .map(|(day, summary)| Bar {
+                label: day.format("%m-%d").to_string(),
+                value: summary.as_ref().and_then(|summary| summary.mean_ms),
+                marker: summary.as_ref().and_then(|summary| summary.p95_ms),
+                color: LATENCY_COLOR,
+            })
+            .collect();
+        doc.bar_chart(&bars, 0.0, nice_ceiling(high), None, |value| format!("{value:.0} ms"));
+        doc.muted_line("Bars show the mean response time, the dark tick the 95th percentile.");
+
+        doc.heading("By day");
+        let columns = [0.0, 80.0, 140.0, 205.0, 265.0, 330.0, 390.0];
+        doc.row(&columns, BOLD, &["Date", "Probes", "Uptime", "Mean", "95th pct", "Outages", "Outage time"]);
+        for (day, summary) in &days {
+            match summary {
+                Some(summary) => doc.row(&columns, REGULAR, &[
+                    &format_date(*day),
+                    &summary.probes.to_string(),
+                    &format!("{:.3}%", summary.uptime),
+                    &format_ms(summary.mean_ms),
+                    &format_ms(summary.p95_ms),
+                    &summary.outages.to_string(),
+                    &format_duration(summary.outage_duration),
+                ]),
+                None => doc.row(&columns, REGULAR, &[&format_date(*day), "no data"]),
+            }
+        }
+
+        doc.heading("Outages");
+        if outages.is_empty() {
+            doc.line(REGULAR, 10.0, "No outages in this period.");
+        } else {
+            let columns = [0.0, 130.0, 260.0, 350.0];
+            doc.row(&columns, BOLD, &["Started", "Ended", "Duration", "Failed probes"]);
+            for outage in &outages {
+                let ended = match outage.ended {
+                    Some(ended) => format_time(ended),
+                    None => "still down at end".to_string(),
+                };
+                doc.row(&columns, REGULAR, &[&format_time(outage.started), &ended, &format_duration(outage.duration), &outage.failed_pings.to_string()]);
+            }
+        }
+        doc.finish(target)
+    }
+
+    fn sla_section(doc: &mut Document, first: NaiveDate, last: NaiveDate, sla_percent: f64, period: &PeriodSummary, outages: &[OutageRow]) {
+        let met = period.uptime >= sla_percent;
+        doc.heading("Service level");
+        doc.line(REGULAR, 11.0, &format!("Agreed availability: {sla_percent}%"));
+        doc.colored_line(BOLD, 13.0, if met { MET_COLOR } else { MISSED_COLOR }, &format!(
+            "Measured availability: {:.3}% ({})",
+            period.uptime,
+            if met { "met" } else { "NOT met" }
+        ));
+        let allowed = period_length(first, last).mul_f64((100.0 - sla_percent).max(0.0) / 100.0);
+        doc.line(REGULAR, 11.0, &format!("Downtime allowed by the agreement: {}", format_duration(allowed)));
+        doc.line(REGULAR, 11.0, &format!("Downtime measured in outages: {} in {} outage(s)", format_duration(period.outage_duration), period.outages));
+        if let Some(longest) = outages.iter().max_by_key(|outage| outage.duration) {
+            doc.line(REGULAR, 11.0, &format!("Longest outage: {} starting {}", format_duration(longest.duration), format_time(longest.started)));
+        }
+        let lost: usize = period.hours.iter().map(|hour| hour.failed).sum();
+        doc.line(REGULAR, 11.0, &format!("Probes: {} sent, {lost} lost", period.probes));
+        doc.line(REGULAR, 11.0, &format!("Response time: {} mean, {} 95th percentile", format_ms(period.mean_ms), format_ms(period.p95_ms)));
+        doc.muted_line("Availability is the share of probes answered, maintenance windows excluded. An outage starts after 3 failed probes in a row.");
+    }
+}
+
+/// Every outage in `records`, replayed with the same rule as the live outage list
+fn outage_rows(records: &[&HistoryRecord]) -> Vec<OutageRow> {
+    let mut tracker = OutageTracker::new();
+    let mut rows = Vec::new();
+    for record in records {
+        if let Some(OutageEvent::Ended(_)) = tracker.record(&record.to_result(Instant::now()))
+            && let Some(outage) = tracker.outages.back()
+        {
+            rows.push(OutageRow { started: outage.started, ended: outage.ended, duration: outage.duration(), failed_pings: outage.failed_pings });
+        }
+    }
+    // Still down when the data ends: it lasted at least until the last failure
+    if let (Some(ongoing), Some(last)) = (tracker.ongoing(), records.last()) {
+        let duration = last.timestamp().duration_since(ongoing.started).unwrap_or_default();
+        rows.push(OutageRow { started: ongoing.started, ended: None, duration, failed_pings: ongoing.failed_pings });
+    }
+    rows
+}
+
+/// Time from the start of `first` to the end of `last`, or to now while `last` is still going on
+fn period_length(first: NaiveDate, last: NaiveDate) -> Duration {
+    let start = Local.from_local_datetime(&first.and_time(NaiveTime::MIN)).earliest();
+    let end = last.succ_opt().and_then(|next| Local.from_local_datetime(&next.and_time(NaiveTime::MIN)).earliest());
+    match (start, end) {
+        (Some(start), Some(end)) => (end.min(Local::now()) - start).to_std().unwrap_or_default(),
+        _ => Duration::from_secs(86_400 * ((last - first).num_days() as u64 + 1)),
+    }
+}
+
+/// Smallest of 1, 2 or 5 × a power of ten that is at least `value`, for a round axis maximum
+fn nice_ceiling(value: f64) -> f64 {
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|step| step * magnitude).find(|ceiling| *ceiling >= value).unwrap_or(value)
+}
+
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn format_ms(ms: Option<f64>) -> String {
+    ms.map(ping::format_ms).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{secs} s"),
+        60..3600 => format!("{} min {} s", secs / 60, secs % 60),
+        _ => format!("{} h {} min", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Text in the WinAnsi encoding of the built-in fonts; characters it lacks become '?'
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Rough width of `text` in Helvetica, enough to right-align axis labels
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.52
+}
+
+struct Bar {
+    label: String,
+    value: Option<f64>,
+    /// Drawn as a tick across the bar, e.g. a percentile
+    marker: Option<f64>,
+    color: Rgb,
+}
+
+/// Pages being laid out top to bottom, with `y` the baseline of the last line written
+struct Document {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl Document {
+    fn new() -> Self {
+        let mut doc = Self { pages: Vec::new(), y: 0.0 };
+        doc.new_page();
+        doc
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn content(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("a document always has a page")
+    }
+
+    /// Moves to a new page unless `height` more points fit on this one
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, x: f32, y: f32, font: Name, size: f32, color: Rgb, text: &str) {
+        let text = encode(text);
+        let content = self.content();
+        content.set_fill_rgb(color.0, color.1, color.2);
+        content.begin_text();
+        content.set_font(font, size);
+        content.next_line(x, y);
+        content.show(Str(&text));
+        content.end_text();
+    }
+
+    fn colored_line(&mut self, font: Name, size: f32, color: Rgb, text: &str) {
+        self.reserve(size * 1.4);
+        self.y -= size * 1.4;
+        self.text(MARGIN, self.y, font, size, color, text);
+    }
+
+    fn line(&mut self, font: Name, size: f32, text: &str) {
+        self.colored_line(font, size, TEXT_COLOR, text);
+    }
+
+    fn muted_line(&mut self, text: &str) {
+        self.colored_line(REGULAR, 8.5, MUTED_COLOR, text);
+    }
+
+    /// A section title, kept on the same page as at least a few lines of what follows
+    fn heading(&mut self, text: &str) {
+        self.gap(12.0);
+        self.reserve(80.0);
+        self.line(BOLD, 13.0, text);
+        self.gap(4.0);
+    }
+
+    /// One table row; `columns` are x offsets from the left margin
+    fn row(&mut self, columns: &[f32], font: Name, cells: &[&str]) {
+        self.reserve(14.0);
+        self.y -= 14.0;
+        for (x, cell) in columns.iter().zip(cells) {
+            self.text(MARGIN + x, self.y, font, 9.5, TEXT_COLOR, cell);
+        }
+    }
+
+    /// Vertical bars between `low` and `high` with gridlines, one day label per few bars and
+    /// an optional reference line (e.g. the agreed availability)
+    fn bar_chart(&mut self, bars: &[Bar], low: f64, high: f64, reference: Option<f64>, axis_label: impl Fn(f64) -> String) {
+        self.reserve(CHART_HEIGHT + 30.0);
+        let left = MARGIN + 45.0;
+        let width = PAGE_WIDTH - MARGIN - left;
+        let bottom = self.y - CHART_HEIGHT - 6.0;
+        let scale = |value: f64| bottom + ((value.clamp(low, high) - low) / (high - low).max(f64::EPSILON)) as f32 * CHART_HEIGHT;
+
+        for step in 0..=4 {
+            let value = low + (high - low) * step as f64 / 4.0;
+            let y = scale(value);
+            let label = axis_label(value);
+            self.text(left - 6.0 - text_width(&label, 8.0), y - 3.0, REGULAR, 8.0, MUTED_COLOR, &label);
+            let content = self.content();
+            content.set_stroke_rgb(GRID_COLOR.0, GRID_COLOR.1, GRID_COLOR.2);
+            content.set_line_width(0.5);
+            content.move_to(left, y);
+            content.line_to(left + width, y);
+            content.stroke();
+        }
+
+        let slot = width / bars.len().max(1) as f32;
+        let label_every = bars.len().div_ceil(12).max(1);
+        for (index, bar) in bars.iter().enumerate() {
+            let x = left + slot * index as f32;
+            let content = self.content();
+            if let Some(value) = bar.value {
+                content.set_fill_rgb(bar.color.0, bar.color.1, bar.color.2);
+                content.rect(x + slot * 0.15, bottom, slot * 0.7, scale(value) - bottom);
+                content.fill_nonzero();
+            }
+            if let Some(marker) = bar.marker {
+                content.set_stroke_rgb(TEXT_COLOR.0, TEXT_COLOR.1, TEXT_COLOR.2);
+                content.set_line_width(1.5);
+                content.move_to(x + slot * 0.1, scale(marker));
+                content.line_to(x + slot * 0.9, scale(marker));
+                content.stroke();
+            }
+            if index % label_every == 0 {
+                self.text(x + slot * 0.5 - text_width(&bar.label, 7.5) / 2.0, bottom - 11.0, REGULAR, 7.5, MUTED_COLOR, &bar.label);
+            }
+        }
+
+        if let Some(reference) = reference {
+            let y = scale(reference);
+            let content = self.content();
+            content.set_stroke_rgb(MISSED_COLOR.0, MISSED_COLOR.1, MISSED_COLOR.2);
+            content.set_line_width(1.0);
+            content.move_to(left, y);
+            content.line_to(left + width, y);
+            content.stroke();
+        }
+        self.y = bottom - 16.0;
+    }
+
+    /// Numbers the pages and assembles the file
+    fn finish(mut self, target: &str) -> Vec<u8> {
+        let page_count = self.pages.len();
+        for (index, content) in self.pages.iter_mut().enumerate() {
+            let footer = encode(&format!("{target} - page {} of {page_count}", index + 1));
+            content.set_fill_rgb(MUTED_COLOR.0, MUTED_COLOR.1, MUTED_COLOR.2);
+            content.begin_text();
+            content.set_font(REGULAR, 8.0);
+            content.next_line(MARGIN, MARGIN / 2.0);
+            content.show(Str(&footer));
+            content.end_text();
+        }
+
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let tree_id = Ref::new(2);
+        let regular_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let info_id = Ref::new(5);
+        let page_ids: Vec<Ref> = (0..page_count as i32).map(|index| Ref::new(6 + index * 2)).collect();
+
+        pdf.catalog(catalog_id).pages(tree_id);
+        pdf.pages(tree_id).kids(page_ids.iter().copied()).count(page_count as i32);
+        pdf.type1_font(regular_id).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.document_info(info_id)
+            .title(TextStr(&format!("Connection quality report: {target}")))
+            .producer(TextStr("Ping Monitor"));
+
+        for (page_id, content) in page_ids.iter().zip(self.pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(tree_id);
+            page.contents(content_id);
+            page.resources().fonts().pair(REGULAR, regular_id).pair(BOLD, bold_id);
+            // The page dictionary is closed when `page` is dropped
+            drop(page);
+            pdf.stream(content_id, &content.finish());
+        }
+        pdf.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_reads_two_dates() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        assert_eq!(parse_range(" 2024-05-01", "2024-05-31 "), Ok((day(1), day(31))));
+        assert_eq!(parse_range("2024-05-01", "2024-05-01"), Ok((day(1), day(1))));
+    }
+
+    #[test]
+    fn parse_range_rejects_bad_ranges() {
+        assert!(parse_range("2024-5-1x", "2024-05-31").is_err());
+        assert!(parse_range("2024-05-31", "2024-05-01").is_err());
+        // At most 366 days, counting both ends
+        assert!(parse_range("2023-01-01", "2024-01-01").is_ok());
+        assert!(parse_range("2023-01-01", "2024-01-02").is_err());
+    }
+
+    #[test]
+    fn nice_ceiling_rounds_up_to_1_2_or_5() {
+        assert_eq!(nice_ceiling(3.0), 5.0);
+        assert_eq!(nice_ceiling(100.0), 100.0);
+        assert_eq!(nice_ceiling(120.0), 200.0);
+        assert_eq!(nice_ceiling(600.0), 1000.0);
+        assert_eq!(nice_ceiling(0.3), 0.5);
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use eframe::egui;
+
+/// Asks the system something on a background thread at most once per interval, since the
+/// system tools can take a while to answer, and keeps the last answer
+pub struct PeriodicQuery<T> {
+    pub current: Option<T>,
+    interval: Duration,
+    /// Returns None when the platform doesn't say (or the tool is unavailable)
+    query: fn() -> Option<T>,
+    last_check: Option<Instant>,
+    check_running: bool,
+    sender: mpsc::Sender<Option<T>>,
+    receiver: mpsc::Receiver<Option<T>>,
+}
+
+impl<T: Copy + PartialEq + Send + 'static> PeriodicQuery<T> {
+    pub fn new(interval: Duration, query: fn() -> Option<T>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            current: None,
+            interval,
+            query,
+            last_check: None,
+            check_running: false,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Cheap to call every frame; starts a query once per interval. Returns the new answer
+    /// when it changed since the last one; "don't know" keeps the last answer.
+    pub fn poll(&mut self, repaint: &egui::Context) -> Option<T> {
+        let mut changed = None;
+        while let Ok(answer) = self.receiver.try_recv() {
+            self.check_running = false;
+            if answer.is_some() && answer != self.current {
+                self.current = answer;
+                changed = answer;
+            }
+        }
+
+        if self.check_running || self.last_check.is_some_and(|last| last.elapsed() < self.interval) {
+            return changed;
+        }
+        self.last_check = Some(Instant::now());
+        self.check_running = true;
+
+        let (sender, query) = (self.sender.clone(), self.query);
+        let repaint = repaint.clone();
+        thread::spawn(move || {
+            let _ = sender.send(query());
+            repaint.request_repaint();
+        });
+        changed
+    }
+}
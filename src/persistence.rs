@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+/// A change is written once the settings have been left alone this long
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Tracks unsaved config changes so the file is written shortly after the last change
+/// instead of on every frame, e.g. while a threshold is being dragged
+#[derive(Debug, Default)]
+pub struct ConfigSaver {
+    changed_at: Option<Instant>,
+}
+
+impl ConfigSaver {
+    pub fn mark_changed(&mut self) {
+        self.changed_at = Some(Instant::now());
+    }
+
+    /// Time until the pending save is due, or None when nothing is unsaved
+    pub fn remaining(&self) -> Option<Duration> {
+        self.changed_at.map(|changed_at| SAVE_DEBOUNCE.saturating_sub(changed_at.elapsed()))
+    }
+
+    /// True once when the debounce has passed; the caller then saves
+    pub fn take_due(&mut self) -> bool {
+        let due = self.remaining().is_some_and(|remaining| remaining.is_zero());
+        if due {
+            self.changed_at = None;
+        }
+        due
+    }
+
+    /// True when anything is unsaved, regardless of the debounce, e.g. on exit
+    pub fn take_pending(&mut self) -> bool {
+        self.changed_at.take().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_to_save_until_changed() {
+        let mut saver = ConfigSaver::default();
+        assert_eq!(saver.remaining(), None);
+        assert!(!saver.take_due());
+        assert!(!saver.take_pending());
+    }
+
+    #[test]
+    fn recent_change_waits_for_the_debounce() {
+        let mut saver = ConfigSaver::default();
+        saver.mark_changed();
+        assert!(saver.remaining().is_some_and(|remaining| remaining > Duration::ZERO));
+        assert!(!saver.take_due());
+        assert!(saver.take_pending());
+        assert!(!saver.take_pending());
+    }
+
+    #[test]
+    fn settled_change_is_due_once() {
+        let mut saver = ConfigSaver { changed_at: Instant::now().checked_sub(SAVE_DEBOUNCE) };
+        assert_eq!(saver.remaining(), Some(Duration::ZERO));
+        assert!(saver.take_due());
+        assert!(!saver.take_due());
+        assert_eq!(saver.remaining(), None);
+    }
+}
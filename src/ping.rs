@@ -1,32 +1,146 @@
-use std::time::SystemTime;
+use std::collections::BTreeMap;
+use std::time::{Instant, SystemTime};
 use std::net::IpAddr;
 
+/// Response time for display: µs below 1 ms, where "0.3ms" would hide the differences that
+/// matter on a LAN, and ms with one decimal above
+pub fn format_ms(ms: f64) -> String {
+    if ms.abs() < 1.0 { format!("{:.0} µs", ms * 1000.0) } else { format!("{ms:.1} ms") }
+}
+
+/// Why a ping failed, so local link problems aren't shown the same as an unreachable target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureCause {
+    /// Nothing came back before the timeout
+    NoReply,
+    LinkDown,
+    /// A router or the target's host answered with ICMP destination unreachable
+    Unreachable,
+    /// The TTL ran out on the way, e.g. in a routing loop
+    TimeExceeded,
+    ResolutionFailed,
+    /// The OS refused to send, e.g. no ICMP rights or a local firewall rule
+    PermissionDenied,
+    /// The proxy a TCP or HTTP probe goes through failed, which says nothing about the target
+    ProxyFailed,
+}
+
+impl FailureCause {
+    pub fn label(self) -> &'static str {
+        match self {
+            FailureCause::NoReply => "timeout waiting for reply",
+            FailureCause::LinkDown => "network link down",
+            FailureCause::Unreachable => "destination unreachable",
+            FailureCause::TimeExceeded => "time exceeded",
+            FailureCause::ResolutionFailed => "resolution failed",
+            FailureCause::PermissionDenied => "permission denied",
+            FailureCause::ProxyFailed => "proxy failed",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PingResult {
+    /// Wall-clock send time, used for clock-face placement and display
     pub timestamp: SystemTime,
+    /// Monotonic send time, used for ageing and statistics windows
+    pub sent_at: Instant,
     pub response_time: Option<f64>,
     pub success: bool,
     pub resolved_ip: Option<(String, IpAddr)>,
+    pub failure_cause: Option<FailureCause>,
+    /// Address the echo request was sent to; None when the target could not be resolved
+    pub target_ip: Option<IpAddr>,
+    /// Sent during a maintenance window: kept for display but left out of statistics and outages
+    pub in_maintenance: bool,
+    /// Server clock minus local clock in ms, measured by the NTP probe
+    pub clock_offset: Option<f64>,
+    /// Reachable but not answering as expected, e.g. a TCP banner that didn't match
+    pub degraded: bool,
+    /// Tunnel interface the probe was routed through; latency via a VPN isn't comparable with direct
+    pub vpn: Option<String>,
+    /// ICMP echo sequence number; None for other probes and the system ping fallback
+    pub sequence: Option<u16>,
+    /// ICMP echo identifier as sent, which the OS may have substituted for ours
+    pub identifier: Option<u16>,
+    /// Local address the echo request left from
+    pub source_ip: Option<IpAddr>,
+    /// TTL of the echo reply as received; only IPv4 replies report it
+    pub reply_ttl: Option<u8>,
 }
 
 impl PingResult {
-    pub fn success(timestamp: SystemTime, response_time_ms: f64, resolved_ip: Option<(String, IpAddr)>) -> Self {
+    pub fn success(timestamp: SystemTime, sent_at: Instant, response_time_ms: f64, resolved_ip: Option<(String, IpAddr)>) -> Self {
         Self {
             timestamp,
+            sent_at,
             response_time: Some(response_time_ms),
             success: true,
             resolved_ip,
+            failure_cause: None,
+            target_ip: None,
+            in_maintenance: false,
+            clock_offset: None,
+            degraded: false,
+            vpn: None,
+            sequence: None,
+            identifier: None,
+            source_ip: None,
+            reply_ttl: None,
         }
     }
 
-    pub fn failure(timestamp: SystemTime) -> Self {
+    pub fn failure(timestamp: SystemTime, sent_at: Instant) -> Self {
         Self {
             timestamp,
+            sent_at,
             response_time: None,
             success: false,
             resolved_ip: None,
+            failure_cause: Some(FailureCause::NoReply),
+            target_ip: None,
+            in_maintenance: false,
+            clock_offset: None,
+            degraded: false,
+            vpn: None,
+            sequence: None,
+            identifier: None,
+            source_ip: None,
+            reply_ttl: None,
         }
     }
+
+    pub fn with_target(mut self, target_ip: IpAddr) -> Self {
+        self.target_ip = Some(target_ip);
+        self
+    }
+
+    pub fn with_clock_offset(mut self, offset_ms: f64) -> Self {
+        self.clock_offset = Some(offset_ms);
+        self
+    }
+
+    pub fn with_cause(mut self, cause: FailureCause) -> Self {
+        self.failure_cause = Some(cause);
+        self
+    }
+
+    pub fn with_echo(mut self, identifier: u16, sequence: u16) -> Self {
+        self.identifier = Some(identifier);
+        self.sequence = Some(sequence);
+        self
+    }
+
+    pub fn with_route(mut self, source_ip: Option<IpAddr>, reply_ttl: Option<u8>) -> Self {
+        self.source_ip = source_ip;
+        self.reply_ttl = reply_ttl;
+        self
+    }
+
+    pub fn with_degraded(mut self) -> Self {
+        self.degraded = true;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -34,7 +148,66 @@ pub struct PingStatistics {
     pub total_pings: u64,
     pub successful_pings: u64,
     pub failed_pings: u64,
+    /// Failures in the window by cause; causes that didn't occur are left out
+    pub failure_causes: BTreeMap<FailureCause, u64>,
+    pub maintenance_pings: u64,
     pub total_response_time: f64,
     pub loss_rate: f64,
     pub mean_response_time: f64,
+    /// Isolated latency spikes, as opposed to sustained slowdowns
+    pub spikes: u64,
+}
+
+/// Runs of echo sequence numbers without a reply between two replies from the same address, as
+/// (first, last), oldest first. Probes after the latest reply don't count until one arrives.
+pub fn sequence_gaps<'a>(results: impl IntoIterator<Item = &'a PingResult>) -> Vec<(u16, u16)> {
+    let mut replies: Vec<(Instant, IpAddr, u16)> = results
+        .into_iter()
+        .filter(|result| result.success)
+        .filter_map(|result| Some((result.sent_at, result.target_ip?, result.sequence?)))
+        .collect();
+    // Replies can arrive out of order; what matters is the order they were sent in
+    replies.sort_by_key(|(sent_at, _, _)| *sent_at);
+    replies
+        .windows(2)
+        .filter(|pair| pair[0].1 == pair[1].1 && pair[1].2.wrapping_sub(pair[0].2) > 1)
+        .map(|pair| (pair[0].2.wrapping_add(1), pair[1].2.wrapping_sub(1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    fn reply(start: Instant, sent_secs: u64, sequence: u16, host: u8) -> PingResult {
+        PingResult::success(SystemTime::now(), start + Duration::from_secs(sent_secs), 10.0, None)
+            .with_target(Ipv4Addr::new(192, 0, 2, host).into())
+            .with_echo(1, sequence)
+    }
+
+    #[test]
+    fn gaps_between_replies_in_send_order() {
+        let start = Instant::now();
+        let results = [reply(start, 5, 5, 1), reply(start, 1, 1, 1), reply(start, 2, 2, 1), reply(start, 9, 9, 1)];
+        assert_eq!(sequence_gaps(&results), vec![(3, 4), (6, 8)]);
+    }
+
+    #[test]
+    fn failures_and_other_addresses_are_not_compared() {
+        let start = Instant::now();
+        let lost = PingResult::failure(SystemTime::now(), start + Duration::from_secs(2))
+            .with_target(Ipv4Addr::new(192, 0, 2, 1).into())
+            .with_echo(1, 2);
+        let results = [reply(start, 1, 1, 1), lost, reply(start, 4, 4, 2)];
+        assert!(sequence_gaps(&results).is_empty());
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        let start = Instant::now();
+        let results = [reply(start, 1, u16::MAX, 1), reply(start, 3, 1, 1)];
+        assert_eq!(sequence_gaps(&results), vec![(0, 0)]);
+    }
 }
@@ -1,102 +1,273 @@
-use std::net::IpAddr;
-use std::time::{Duration, SystemTime};
-use std::sync::mpsc;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::sync::{LazyLock, Mutex, mpsc};
 use std::thread;
-use surge_ping::{Client, Config, IcmpPacket, PingIdentifier, PingSequence};
-use crate::ping::PingResult;
+use eframe::egui;
+use surge_ping::{Client, Config, ICMP, IcmpPacket, PingIdentifier, PingSequence, SurgeError};
+use crate::icmp_access;
+use crate::network_watcher::{interface_index, is_link_local, local_ip_for};
+use crate::ping::{FailureCause, PingResult};
+use crate::system_ping;
+use crate::target_parser::{self, parse_target};
 
 const PING_TIMEOUT_SECS: u64 = 5;
+/// TTL (hop limit) set on every echo request
+pub const ECHO_TTL: u8 = 64;
+/// Payload of every echo request, echoed back in the reply
+pub const ECHO_PAYLOAD: &[u8] = b"ping-monitor echo payload 0123456789";
 
-/// Sanitize hostname by keeping only valid characters (alphanumeric, dots, hyphens)
-/// Returns None if the result is empty
-fn sanitize_hostname(hostname: &str) -> Option<String> {
-    // Also handle case where user included port like "example.com:8080"
-    let hostname = hostname.split(':').next().unwrap_or(hostname);
-
-    let sanitized: String = hostname
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '-')
-        .collect();
-
-    if sanitized.is_empty() {
-        None
-    } else {
-        Some(sanitized)
+/// Echo identifier for this run. Raw ICMP sockets see every echo reply on the host, so another
+/// instance (or another ping tool) using the same identifier could have its replies taken for ours.
+static IDENTIFIER: LazyLock<PingIdentifier> = LazyLock::new(|| {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+    PingIdentifier((std::process::id() ^ nanos) as u16)
+});
+/// Last sequence number sent to each address
+static SEQUENCES: LazyLock<Mutex<HashMap<IpAddr, u16>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Numbers each address's echo requests 1, 2, 3…, wrapping after 65535
+fn next_sequence(target_ip: IpAddr) -> u16 {
+    let mut sequences = SEQUENCES.lock().unwrap_or_else(|e| e.into_inner());
+    let sequence = sequences.entry(target_ip).or_insert(0);
+    *sequence = sequence.wrapping_add(1);
+    *sequence
+}
+
+/// Whether this platform can pin an ICMP socket to an interface (SO_BINDTOIFINDEX, IP_BOUND_IF);
+/// binding the source address alone still follows the routing table
+pub const CAN_PIN_INTERFACE: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "illumos",
+    target_os = "solaris",
+));
+
+/// Pings leave through this interface, from one of its addresses
+#[derive(Debug, Clone)]
+pub struct SourceBinding {
+    pub interface: String,
+    pub addresses: Vec<IpAddr>,
+}
+
+impl SourceBinding {
+    /// A routable address of the same family as `target_ip`
+    fn source_for(&self, target_ip: IpAddr) -> Option<IpAddr> {
+        self.addresses.iter().copied().find(|ip| ip.is_ipv4() == target_ip.is_ipv4() && !is_link_local(ip))
     }
 }
 
 pub struct PingExecutor;
 
 impl PingExecutor {
-    /// Resolves hostname (if needed) and executes ping asynchronously
-    pub fn resolve_and_ping(target: String, sender: mpsc::Sender<PingResult>) {
+    /// Resolves hostname (if needed) and executes ping asynchronously.
+    /// `repaint` is woken once the result has been sent so the UI can redraw immediately.
+    pub fn resolve_and_ping(target: String, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
         let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
         
         thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
             let result = rt.block_on(async {
-                let target_ip = match Self::resolve_target(&target).await {
-                    Some(ip) => ip,
-                    None => return PingResult::failure(timestamp),
+                let (target_ip, scope_id) = match Self::resolve_target(&target).await {
+                    Some(resolved) => resolved,
+                    None => return PingResult::failure(timestamp, sent_at).with_cause(FailureCause::ResolutionFailed),
                 };
 
-                Self::execute_ping(target_ip, timestamp, Some(target)).await
+                // A link-local address only means something on its interface, so it isn't cached
+                let hostname = scope_id.is_none().then_some(target);
+                Self::execute_scoped_ping(target_ip, scope_id, None, timestamp, sent_at, hostname).await
             });
             
             let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    /// Like `resolve_and_ping`, but out of `binding`'s interface whatever the routing table says.
+    /// Fails as link down when the interface has no address for the target's family.
+    pub fn resolve_and_ping_via(target: String, binding: SourceBinding, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let result = rt.block_on(async {
+                let Some((target_ip, _)) = Self::resolve_target(&target).await else {
+                    return PingResult::failure(timestamp, sent_at).with_cause(FailureCause::ResolutionFailed);
+                };
+                let Some(source) = binding.source_for(target_ip) else {
+                    return PingResult::failure(timestamp, sent_at).with_cause(FailureCause::LinkDown).with_target(target_ip);
+                };
+                Self::execute_scoped_ping(target_ip, None, Some((&binding.interface, source)), timestamp, sent_at, None).await
+            });
+
+            let _ = sender.send(result);
+            repaint.request_repaint();
         });
     }
 
     /// Executes ping with a pre-resolved IP address
-    pub fn ping_with_ip(target_ip: IpAddr, sender: mpsc::Sender<PingResult>) {
+    pub fn ping_with_ip(target_ip: IpAddr, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
         let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
         
         thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
-            let result = rt.block_on(Self::execute_ping(target_ip, timestamp, None));
+            let result = rt.block_on(Self::execute_ping(target_ip, timestamp, sent_at, None));
             let _ = sender.send(result);
+            repaint.request_repaint();
         });
     }
 
-    /// Resolve hostname to IP address
-    async fn resolve_target(target: &str) -> Option<IpAddr> {
+    /// Resolve hostname to IP address, plus the interface index of a zoned link-local address
+    async fn resolve_target(target: &str) -> Option<(IpAddr, Option<u32>)> {
+        // Accepts URLs and host:port as well; the port doesn't matter for ICMP
+        let parsed = parse_target(target)?;
+        let host = parsed.host;
+
         // Try parsing as IP address first
-        if let Ok(ip) = target.parse::<IpAddr>() {
-            return Some(ip);
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            let scope_id = match parsed.zone {
+                Some(zone) => Some(interface_index(&zone)?),
+                None => None,
+            };
+            return Some((ip, scope_id));
         }
 
-        // Sanitize hostname input
-        let sanitized = sanitize_hostname(target)?;
-
         // Try resolving as hostname
-        match tokio::net::lookup_host(&format!("{sanitized}:80")).await {
-            Ok(mut addrs) => addrs.next().map(|addr| addr.ip()),
+        match tokio::net::lookup_host(&format!("{}:80", target_parser::to_ascii(&host))).await {
+            Ok(mut addrs) => addrs.next().map(|addr| (addr.ip(), None)),
             Err(_) => None,
         }
     }
 
     /// Execute the actual ping operation
-    async fn execute_ping(
+    pub async fn execute_ping(
         target_ip: IpAddr, 
         timestamp: SystemTime,
+        sent_at: Instant,
+        hostname: Option<String>
+    ) -> PingResult {
+        Self::execute_scoped_ping(target_ip, None, None, timestamp, sent_at, hostname).await
+    }
+
+    /// Pings out of the interface `scope_id` when given, as link-local IPv6 addresses require,
+    /// and from `source` (interface name, address) when given
+    async fn execute_scoped_ping(
+        target_ip: IpAddr,
+        scope_id: Option<u32>,
+        source: Option<(&str, IpAddr)>,
+        timestamp: SystemTime,
+        sent_at: Instant,
         hostname: Option<String>
     ) -> PingResult {
-        let config = Config::default();
-        let client = match Client::new(&config) {
+        let mut builder = match target_ip {
+            IpAddr::V4(_) => Config::builder(),
+            IpAddr::V6(_) => Config::builder().kind(ICMP::V6),
+        }
+        .ttl(u32::from(ECHO_TTL));
+        if let Some((interface, address)) = source {
+            let Some(index) = interface_index(interface).and_then(NonZeroU32::new).filter(|_| CAN_PIN_INTERFACE) else {
+                return PingResult::failure(timestamp, sent_at).with_cause(FailureCause::LinkDown).with_target(target_ip);
+            };
+            builder = builder.bind(SocketAddr::new(address, 0)).interface_index(index);
+        }
+        let client = match Client::new(&builder.build()) {
             Ok(client) => client,
-            Err(_) => return PingResult::failure(timestamp),
+            // The system ping command can't be told which interface to use in a portable way
+            Err(_) if source.is_some() => {
+                return PingResult::failure(timestamp, sent_at).with_cause(FailureCause::PermissionDenied).with_target(target_ip);
+            }
+            Err(e) => {
+                icmp_access::warn_fallback(&e.to_string());
+                return Self::execute_system_ping(target_ip, timestamp, sent_at, hostname).await;
+            }
         };
         
-        let mut pinger = client.pinger(target_ip, PingIdentifier(1)).await;
+        let mut pinger = client.pinger(target_ip, *IDENTIFIER).await;
         pinger.timeout(Duration::from_secs(PING_TIMEOUT_SECS));
+        if let Some(scope_id) = scope_id {
+            pinger.scope_id(scope_id);
+        }
         
-        match pinger.ping(PingSequence(1), &[]).await {
-            Ok((IcmpPacket::V4(_), duration)) | Ok((IcmpPacket::V6(_), duration)) => {
-                let response_time_ms = duration.as_secs_f64() * 1000.0;
+        let sequence = next_sequence(target_ip);
+        let source_ip = source.map(|(_, address)| address).or_else(|| local_ip_for(target_ip));
+        let mut reply_ttl = None;
+        let result = match pinger.ping(PingSequence(sequence), ECHO_PAYLOAD).await {
+            Ok((packet, _)) if reply_sequence(&packet) != sequence => {
+                log::debug!("Echo reply from {target_ip} had sequence {} instead of {sequence}", reply_sequence(&packet));
+                PingResult::failure(timestamp, sent_at)
+            }
+            Ok((packet, duration)) => match error_cause(&packet) {
+                Some(cause) => PingResult::failure(timestamp, sent_at).with_cause(cause),
+                None => {
+                    reply_ttl = match &packet {
+                        IcmpPacket::V4(packet) => packet.get_ttl(),
+                        IcmpPacket::V6(_) => None,
+                    };
+                    let response_time_ms = duration.as_secs_f64() * 1000.0;
+                    let resolved_ip = hostname.map(|h| (h, target_ip));
+                    PingResult::success(timestamp, sent_at, response_time_ms, resolved_ip)
+                }
+            },
+            Err(SurgeError::IOError(e)) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                PingResult::failure(timestamp, sent_at).with_cause(FailureCause::PermissionDenied)
+            }
+            Err(_) => PingResult::failure(timestamp, sent_at),
+        };
+        result
+            .with_target(target_ip)
+            .with_echo(wire_identifier(&client), sequence)
+            .with_route(source_ip, reply_ttl)
+    }
+
+    /// Fallback for when this process may not open ICMP sockets: the system `ping` command may
+    async fn execute_system_ping(target_ip: IpAddr, timestamp: SystemTime, sent_at: Instant, hostname: Option<String>) -> PingResult {
+        match tokio::task::spawn_blocking(move || system_ping::ping(target_ip, PING_TIMEOUT_SECS)).await {
+            Ok(Ok(response_time_ms)) => {
                 let resolved_ip = hostname.map(|h| (h, target_ip));
-                PingResult::success(timestamp, response_time_ms, resolved_ip)
+                PingResult::success(timestamp, sent_at, response_time_ms, resolved_ip).with_target(target_ip)
             }
-            Err(_) => PingResult::failure(timestamp),
+            Ok(Err(cause)) => PingResult::failure(timestamp, sent_at).with_cause(cause).with_target(target_ip),
+            Err(_) => PingResult::failure(timestamp, sent_at).with_target(target_ip),
         }
     }
 }
+
+/// Unprivileged ICMP sockets on Linux send the socket's port as the identifier instead of ours
+fn wire_identifier(client: &Client) -> u16 {
+    match client.get_socket().local_addr() {
+        Ok(address) if address.port() != 0 => address.port(),
+        _ => IDENTIFIER.0,
+    }
+}
+
+/// The identifier isn't compared: unprivileged ICMP sockets on Linux replace it with their own
+fn reply_sequence(packet: &IcmpPacket) -> u16 {
+    match packet {
+        IcmpPacket::V4(packet) => packet.get_sequence().0,
+        IcmpPacket::V6(packet) => packet.get_sequence().0,
+    }
+}
+
+/// ICMP errors that quote our request arrive in its place; echo replies give None
+fn error_cause(packet: &IcmpPacket) -> Option<FailureCause> {
+    // Types from RFC 792 and RFC 4443
+    let cause = match packet {
+        IcmpPacket::V4(packet) => match packet.get_icmp_type().0 {
+            3 => FailureCause::Unreachable,
+            11 => FailureCause::TimeExceeded,
+            _ => return None,
+        },
+        IcmpPacket::V6(packet) => match packet.get_icmpv6_type().0 {
+            1 => FailureCause::Unreachable,
+            3 => FailureCause::TimeExceeded,
+            _ => return None,
+        },
+    };
+    Some(cause)
+}
@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::SystemTime;
+use rhai::{AST, CallFnOptions, Dynamic, Engine, Map, Scope};
+use crate::config::AppConfig;
+use crate::outage::{FaultLocation, OutageRecord};
+use crate::ping::PingResult;
+use crate::system_command;
+
+/// Scripts are loaded from this folder inside the config directory
+pub const PLUGINS_DIR_NAME: &str = "plugins";
+const SCRIPT_EXTENSION: &str = "rhai";
+/// Stops a runaway script so later hooks still run; plenty for per-result logic
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_LOG_ENTRIES: usize = 100;
+
+/// Something a script asked the app to show
+enum PluginOutput {
+    Message(String),
+    Metric(String, String),
+}
+
+/// A field handed to a hook; rhai values can't cross threads, so they are built on the script thread
+enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<Value> for Dynamic {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Unit => Dynamic::UNIT,
+            Value::Bool(value) => value.into(),
+            Value::Int(value) => value.into(),
+            Value::Float(value) => Dynamic::from_float(value),
+            Value::Text(value) => value.into(),
+        }
+    }
+}
+
+struct HookCall {
+    hook: &'static str,
+    fields: Vec<(&'static str, Value)>,
+}
+
+/// Runs user scripts (rhai) that react to ping results and outages.
+/// A script may define any of `on_result(result)`, `on_outage_start(outage)` and
+/// `on_outage_end(outage)`, and can call `notify(text)`, `metric(name, value)` and
+/// `exec(program, [args])`. Scripts run on a thread of their own, so a slow one never stalls
+/// the window; what they report shows up at the next `poll`.
+pub struct PluginHost {
+    /// None without scripts, so there is no thread to feed
+    calls: Option<mpsc::Sender<HookCall>>,
+    outputs: mpsc::Receiver<(String, PluginOutput)>,
+    names: Vec<String>,
+    pub metrics: BTreeMap<String, String>,
+    pub log: VecDeque<(SystemTime, String)>,
+}
+
+impl PluginHost {
+    /// Compiles every script in the plugins folder on the script thread and waits for that;
+    /// scripts that fail to compile are reported and skipped
+    pub fn load() -> (Self, Vec<String>) {
+        let Ok(dir) = AppConfig::get_config_dir() else {
+            return (Self::new(), Vec::new());
+        };
+        let dir = dir.join(PLUGINS_DIR_NAME);
+        let (calls, call_receiver) = mpsc::channel();
+        let (output_sender, outputs) = mpsc::channel();
+        let (loaded_sender, loaded) = mpsc::channel();
+        thread::spawn(move || {
+            let mut runner = ScriptRunner::new(output_sender);
+            let errors = runner.load_dir(&dir);
+            let names = runner.plugins.iter().map(|plugin| plugin.name.clone()).collect();
+            let _ = loaded_sender.send((names, errors));
+            // Ends when the host is dropped or has no scripts to call
+            for call in call_receiver {
+                runner.call(call);
+            }
+        });
+
+        let (names, errors): (Vec<String>, Vec<String>) = loaded.recv().unwrap_or_default();
+        let mut host = Self { calls: (!names.is_empty()).then_some(calls), outputs, names, ..Self::new() };
+        host.poll();
+        (host, errors)
+    }
+
+    pub fn new() -> Self {
+        Self {
+            calls: None,
+            outputs: mpsc::channel().1,
+            names: Vec::new(),
+            metrics: BTreeMap::new(),
+            log: VecDeque::new(),
+        }
+    }
+
+    pub fn plugin_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    pub fn on_result(&self, result: &PingResult, target: &str) {
+        let text = |value: Option<String>| value.map_or(Value::Unit, Value::Text);
+        self.send("on_result", vec![
+            ("time", Value::Text(timestamp(result.timestamp))),
+            ("target", Value::Text(target.to_string())),
+            ("ip", text(result.target_ip.map(|ip| ip.to_string()))),
+            ("success", Value::Bool(result.success)),
+            ("response_ms", result.response_time.map_or(Value::Unit, Value::Float)),
+            ("in_maintenance", Value::Bool(result.in_maintenance)),
+            ("vpn", text(result.vpn.clone())),
+        ]);
+    }
+
+    pub fn on_outage_start(&self, outage: &OutageRecord) {
+        self.send("on_outage_start", Self::outage_fields(outage));
+    }
+
+    pub fn on_outage_end(&self, outage: &OutageRecord) {
+        self.send("on_outage_end", Self::outage_fields(outage));
+    }
+
+    fn outage_fields(outage: &OutageRecord) -> Vec<(&'static str, Value)> {
+        let fault = match outage.fault_location() {
+            Some(FaultLocation::Local) => "local",
+            Some(FaultLocation::Upstream) => "upstream",
+            None => "",
+        };
+        vec![
+            ("id", Value::Int(outage.id as i64)),
+            ("started", Value::Text(timestamp(outage.started))),
+            ("duration_secs", Value::Float(outage.duration().as_secs_f64())),
+            ("failed_pings", Value::Int(outage.failed_pings as i64)),
+            ("fault", Value::Text(fault.to_string())),
+        ]
+    }
+
+    fn send(&self, hook: &'static str, fields: Vec<(&'static str, Value)>) {
+        if let Some(calls) = &self.calls {
+            let _ = calls.send(HookCall { hook, fields });
+        }
+    }
+
+    /// Takes in what the scripts reported since the last call
+    pub fn poll(&mut self) {
+        for (plugin, output) in self.outputs.try_iter() {
+            match output {
+                PluginOutput::Message(message) => {
+                    self.log.push_back((SystemTime::now(), format!("[{plugin}] {message}")));
+                    if self.log.len() > MAX_LOG_ENTRIES {
+                        self.log.pop_front();
+                    }
+                }
+                PluginOutput::Metric(name, value) => {
+                    self.metrics.insert(name, value);
+                }
+            }
+        }
+    }
+}
+
+struct Plugin {
+    name: String,
+    ast: AST,
+    /// Persists between calls as `this` inside hook functions, so scripts can keep counters
+    state: Dynamic,
+}
+
+/// The engine and compiled scripts, owned by the script thread
+struct ScriptRunner {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+    current: Rc<RefCell<String>>,
+    outputs: Rc<RefCell<Vec<(String, PluginOutput)>>>,
+    sender: mpsc::Sender<(String, PluginOutput)>,
+}
+
+impl ScriptRunner {
+    fn new(sender: mpsc::Sender<(String, PluginOutput)>) -> Self {
+        let current = Rc::new(RefCell::new(String::new()));
+        let outputs: Rc<RefCell<Vec<(String, PluginOutput)>>> = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let emit = {
+            let (current, outputs) = (current.clone(), outputs.clone());
+            move |output: PluginOutput| outputs.borrow_mut().push((current.borrow().clone(), output))
+        };
+        let notify = emit.clone();
+        engine.register_fn("notify", move |message: &str| notify(PluginOutput::Message(message.to_string())));
+        let print = emit.clone();
+        engine.on_print(move |message| print(PluginOutput::Message(message.to_string())));
+        let metric = emit.clone();
+        engine.register_fn("metric", move |name: &str, value: Dynamic| {
+            metric(PluginOutput::Metric(name.to_string(), value.to_string()))
+        });
+        engine.register_fn("exec", move |program: &str, args: rhai::Array| {
+            let args: Vec<String> = args.into_iter().map(|arg| arg.to_string()).collect();
+            if let Err(e) = system_command::command(program).args(&args).spawn() {
+                emit(PluginOutput::Message(format!("exec {program} failed: {e}")));
+            }
+        });
+
+        Self { engine, plugins: Vec::new(), current, outputs, sender }
+    }
+
+    fn load_dir(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == SCRIPT_EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+            *self.current.borrow_mut() = name.clone();
+            // Top-level statements run once at load time, e.g. to print a greeting
+            let loaded = self.engine.compile_file(path.clone()).and_then(|ast| {
+                self.engine.run_ast_with_scope(&mut Scope::new(), &ast)?;
+                Ok(ast)
+            });
+            match loaded {
+                Ok(ast) => self.plugins.push(Plugin { name, ast, state: Map::new().into() }),
+                Err(e) => errors.push(format!("Plugin {}: {e}", path.display())),
+            }
+        }
+        self.forward_outputs();
+        errors
+    }
+
+    /// Calls the hook in every plugin that defines it; a failing plugin doesn't stop the others
+    fn call(&mut self, HookCall { hook, fields }: HookCall) {
+        let argument: Map = fields.into_iter().map(|(name, value)| (name.into(), value.into())).collect();
+        for plugin in &mut self.plugins {
+            if !plugin.ast.iter_functions().any(|function| function.name == hook && function.params.len() == 1) {
+                continue;
+            }
+            *self.current.borrow_mut() = plugin.name.clone();
+            let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut plugin.state);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &plugin.ast,
+                hook,
+                (argument.clone(),),
+            );
+            if let Err(e) = result {
+                self.outputs.borrow_mut().push((plugin.name.clone(), PluginOutput::Message(format!("{hook} failed: {e}"))));
+            }
+        }
+        self.forward_outputs();
+    }
+
+    fn forward_outputs(&self) {
+        for output in self.outputs.borrow_mut().drain(..) {
+            let _ = self.sender.send(output);
+        }
+    }
+}
+
+fn timestamp(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::periodic_query::PeriodicQuery;
+
+const POWER_CHECK_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// What changes while running on battery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatterySaver {
+    pub enabled: bool,
+    /// Probe interval on battery; a longer normal interval is kept
+    pub interval_secs: u64,
+    /// Redraw once a second instead of moving the second hand smoothly
+    pub slow_repaint: bool,
+    /// Turn off egui's expand/collapse animations and let the second hand tick
+    pub no_animations: bool,
+}
+
+impl Default for BatterySaver {
+    fn default() -> Self {
+        Self { enabled: true, interval_secs: 30, slow_repaint: true, no_animations: true }
+    }
+}
+
+/// Polls the current power state; machines without a battery report AC
+pub type PowerMonitor = PeriodicQuery<PowerSource>;
+
+pub fn monitor() -> PowerMonitor {
+    PeriodicQuery::new(Duration::from_secs(POWER_CHECK_INTERVAL_SECS), platform::query)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::fs;
+
+    const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+    pub fn query() -> Option<PowerSource> {
+        // Each supply has a "type" (Mains, Battery, USB…); mains adapters report "online"
+        // and batteries "status" (Charging, Discharging, Full…)
+        let mut has_battery = false;
+        let mut discharging = false;
+        for entry in fs::read_dir(POWER_SUPPLY_DIR).ok()?.flatten() {
+            let read = |name: &str| fs::read_to_string(entry.path().join(name)).map(|value| value.trim().to_string()).unwrap_or_default();
+            match read("type").as_str() {
+                "Mains" | "USB" if read("online") == "1" => return Some(PowerSource::Ac),
+                "Battery" if read("scope") != "Device" => {
+                    has_battery = true;
+                    discharging |= read("status") == "Discharging";
+                }
+                _ => {}
+            }
+        }
+        Some(if has_battery && discharging { PowerSource::Battery } else { PowerSource::Ac })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    pub fn query() -> Option<PowerSource> {
+        // "pmset -g batt" starts with "Now drawing from 'Battery Power'" or "'AC Power'"
+        let output = system_command::run("pmset", &["-g", "batt"]).ok()?;
+        Some(if output.contains("'Battery Power'") { PowerSource::Battery } else { PowerSource::Ac })
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    /// Win32_Battery.BatteryStatus value for "discharging"
+    const DISCHARGING: &str = "1";
+
+    pub fn query() -> Option<PowerSource> {
+        // Prints nothing on machines without a battery
+        let output = system_command::run("powershell", &["-NoProfile", "-Command", "(Get-CimInstance Win32_Battery).BatteryStatus"]).ok()?;
+        Some(if output.lines().any(|line| line.trim() == DISCHARGING) { PowerSource::Battery } else { PowerSource::Ac })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    pub fn query() -> Option<PowerSource> {
+        None
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A probe whose result never came back (e.g. its channel was replaced) stops counting after this
+const IN_FLIGHT_EXPIRY_SECS: u64 = 30;
+/// How often a job waiting in `reserve` checks for a free slot
+const RESERVE_RETRY_MS: u64 = 20;
+
+/// Probes not started for one target, by reason
+#[derive(Debug, Clone, Default)]
+pub struct DropCounts {
+    /// The global in-flight limit was reached
+    pub saturated: u64,
+    /// The target already had its share of probes in the last second
+    pub rate_limited: u64,
+}
+
+impl DropCounts {
+    pub fn total(&self) -> u64 {
+        self.saturated + self.rate_limited
+    }
+}
+
+/// The app's limiter, shared with the background jobs (subnet sweep, rapid ping) that start probes
+pub type SharedLimiter = Arc<Mutex<ProbeLimiter>>;
+
+pub fn lock(limiter: &SharedLimiter) -> MutexGuard<'_, ProbeLimiter> {
+    limiter.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Bounds the probes the app has outstanding: at most `max_in_flight` overall, and at most
+/// `max_per_second` started towards any one target in a second. Probes that don't fit are
+/// dropped rather than queued so a slow network can't build up a backlog.
+pub struct ProbeLimiter {
+    pub max_in_flight: usize,
+    pub max_per_second: usize,
+    in_flight: HashMap<String, VecDeque<Instant>>,
+    recent_starts: HashMap<String, VecDeque<Instant>>,
+    pub dropped: HashMap<String, DropCounts>,
+}
+
+impl ProbeLimiter {
+    pub fn new(max_in_flight: usize, max_per_second: usize) -> Self {
+        Self {
+            max_in_flight,
+            max_per_second,
+            in_flight: HashMap::new(),
+            recent_starts: HashMap::new(),
+            dropped: HashMap::new(),
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.values().map(VecDeque::len).sum()
+    }
+
+    /// Reserves a slot for a probe to `target`, returning false (and counting the drop) when
+    /// there is none. Call `finished` when the probe's result arrives.
+    pub fn try_start(&mut self, target: &str) -> bool {
+        self.expire();
+        let now = Instant::now();
+        let saturated = self.in_flight() >= self.max_in_flight;
+        let starts = self.recent_starts.entry(target.to_string()).or_default();
+        while starts.front().is_some_and(|start| now.duration_since(*start) >= Duration::from_secs(1)) {
+            starts.pop_front();
+        }
+        if saturated {
+            self.dropped.entry(target.to_string()).or_default().saturated += 1;
+            return false;
+        }
+        if starts.len() >= self.max_per_second {
+            self.dropped.entry(target.to_string()).or_default().rate_limited += 1;
+            return false;
+        }
+        starts.push_back(now);
+        self.in_flight.entry(target.to_string()).or_default().push_back(now);
+        true
+    }
+
+    /// For jobs that pace themselves and wait for a slot instead of dropping the probe: only the
+    /// in-flight limit applies, and a refusal isn't counted as a drop. Call `finished` as usual.
+    pub fn try_reserve(&mut self, target: &str) -> bool {
+        self.expire();
+        if self.in_flight() >= self.max_in_flight {
+            return false;
+        }
+        self.in_flight.entry(target.to_string()).or_default().push_back(Instant::now());
+        true
+    }
+
+    /// Waits until `try_reserve` succeeds
+    pub async fn reserve(limiter: &SharedLimiter, target: &str) {
+        loop {
+            let reserved = lock(limiter).try_reserve(target);
+            if reserved {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(RESERVE_RETRY_MS)).await;
+        }
+    }
+
+    pub fn finished(&mut self, target: &str) {
+        if let Some(started) = self.in_flight.get_mut(target) {
+            started.pop_front();
+        }
+    }
+
+    pub fn total_dropped(&self) -> u64 {
+        self.dropped.values().map(DropCounts::total).sum()
+    }
+
+    fn expire(&mut self) {
+        let expiry = Duration::from_secs(IN_FLIGHT_EXPIRY_SECS);
+        for started in self.in_flight.values_mut() {
+            while started.front().is_some_and(|start| start.elapsed() > expiry) {
+                started.pop_front();
+            }
+        }
+        self.in_flight.retain(|_, started| !started.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_flight_limit_applies_across_targets() {
+        let mut limiter = ProbeLimiter::new(2, 10);
+        assert!(limiter.try_start("a"));
+        assert!(limiter.try_start("b"));
+        assert!(!limiter.try_start("c"));
+        assert_eq!(limiter.dropped["c"].saturated, 1);
+        limiter.finished("a");
+        assert!(limiter.try_start("c"));
+        assert_eq!(limiter.in_flight(), 2);
+    }
+
+    #[test]
+    fn per_second_limit_applies_to_each_target() {
+        let mut limiter = ProbeLimiter::new(10, 2);
+        assert!(limiter.try_start("a"));
+        limiter.finished("a");
+        assert!(limiter.try_start("a"));
+        limiter.finished("a");
+        assert!(!limiter.try_start("a"));
+        assert!(limiter.try_start("b"));
+        assert_eq!(limiter.dropped["a"].rate_limited, 1);
+        assert_eq!(limiter.total_dropped(), 1);
+    }
+
+    #[test]
+    fn reserve_ignores_the_rate_and_counts_no_drops() {
+        let mut limiter = ProbeLimiter::new(2, 1);
+        assert!(limiter.try_reserve("a"));
+        assert!(limiter.try_reserve("a"));
+        assert!(!limiter.try_reserve("a"));
+        assert_eq!(limiter.total_dropped(), 0);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+use crate::schedule::TimeWindow;
+use crate::targets::TargetEntry;
+use crate::target_style::TargetStyles;
+
+/// Marks a JSON file as a monitoring profile so other JSON files aren't imported by mistake
+const PROFILE_FORMAT: &str = "ping-monitor-profile";
+const PROFILE_VERSION: u32 = 2;
+pub const PROFILE_EXTENSION: &str = "json";
+
+/// The shareable part of the configuration: what is monitored and how it is judged.
+/// Secrets (agent tokens), machine-specific values (MAC addresses, hotkeys) and shell
+/// commands are never included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoringProfile {
+    pub format: String,
+    pub version: u32,
+    pub target: String,
+    pub green_threshold: u64,
+    pub yellow_threshold: u64,
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub very_slow_threshold: Option<u64>,
+    #[serde(default)]
+    pub compare_target: String,
+    #[serde(default)]
+    pub target_list: Vec<TargetEntry>,
+    #[serde(default)]
+    pub target_styles: TargetStyles,
+    #[serde(default)]
+    pub maintenance_windows: Vec<TimeWindow>,
+    /// Version 1 profiles kept the monitoring hours outside the target list; they are moved to
+    /// the entry of `target` on import
+    #[serde(default, skip_serializing)]
+    pub monitoring_hours: Vec<TimeWindow>,
+}
+
+impl MonitoringProfile {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            format: PROFILE_FORMAT.to_string(),
+            version: PROFILE_VERSION,
+            target: config.target.clone(),
+            green_threshold: config.green_threshold,
+            yellow_threshold: config.yellow_threshold,
+            interval_secs: config.interval_secs,
+            very_slow_threshold: Some(config.very_slow_threshold),
+            compare_target: config.compare_target.clone(),
+            // MAC addresses are only meaningful on this machine's network
+            target_list: config.target_list.iter().map(|entry| TargetEntry { mac: String::new(), ..entry.clone() }).collect(),
+            target_styles: config.target_styles.clone(),
+            maintenance_windows: config.maintenance_windows.clone(),
+            monitoring_hours: Vec::new(),
+        }
+    }
+
+    pub fn export(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let profile: Self = serde_json::from_str(&content).map_err(|e| format!("{} is not a monitoring profile: {e}", path.display()))?;
+        if profile.format != PROFILE_FORMAT {
+            return Err(format!("{} is not a monitoring profile", path.display()));
+        }
+        if profile.version > PROFILE_VERSION {
+            return Err(format!("{} was written by a newer version (profile version {})", path.display(), profile.version));
+        }
+        Ok(profile)
+    }
+
+    /// Copies the profile over `config`. Targets already in the list are updated in place
+    /// and local-only entries are kept, so importing never loses a teammate's own additions.
+    pub fn apply_to(self, config: &mut AppConfig) {
+        config.target = self.target;
+        config.green_threshold = self.green_threshold;
+        config.yellow_threshold = self.yellow_threshold;
+        config.interval_secs = self.interval_secs;
+        if let Some(very_slow_threshold) = self.very_slow_threshold {
+            config.very_slow_threshold = very_slow_threshold;
+        }
+        config.compare_target = self.compare_target;
+        config.maintenance_windows = self.maintenance_windows;
+        for entry in self.target_list {
+            match config.target_list.iter_mut().find(|existing| existing.host == entry.host) {
+                Some(existing) => *existing = TargetEntry { mac: std::mem::take(&mut existing.mac), ..entry },
+                None => config.target_list.push(entry),
+            }
+        }
+        if !self.monitoring_hours.is_empty() {
+            let host = config.target.trim().to_string();
+            match config.target_list.iter_mut().find(|entry| entry.host == host) {
+                Some(entry) => entry.monitoring_hours = self.monitoring_hours,
+                None => config.target_list.push(TargetEntry { host, monitoring_hours: self.monitoring_hours, ..TargetEntry::default() }),
+            }
+        }
+        config.target_styles.extend(self.target_styles);
+    }
+}
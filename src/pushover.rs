@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::attention::AlertSeverity;
+use crate::http_post::http_post_json;
+
+const MESSAGES_URL: &str = "https://api.pushover.net/1/messages.json";
+/// Emergency notifications repeat this often until acknowledged, at most for `EMERGENCY_EXPIRE_SECS`
+const EMERGENCY_RETRY_SECS: u32 = 60;
+const EMERGENCY_EXPIRE_SECS: u32 = 60 * 60;
+
+/// Pushover's priorities, from silent to repeating until acknowledged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushoverPriority {
+    /// Not sent at all
+    Off,
+    /// Delivered without sound or vibration
+    Quiet,
+    Normal,
+    /// Bypasses the user's quiet hours
+    High,
+    /// Repeats until acknowledged in the app
+    Emergency,
+}
+
+impl PushoverPriority {
+    pub const ALL: [PushoverPriority; 5] =
+        [PushoverPriority::Off, PushoverPriority::Quiet, PushoverPriority::Normal, PushoverPriority::High, PushoverPriority::Emergency];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PushoverPriority::Off => "off",
+            PushoverPriority::Quiet => "quiet",
+            PushoverPriority::Normal => "normal",
+            PushoverPriority::High => "high",
+            PushoverPriority::Emergency => "emergency",
+        }
+    }
+
+    fn value(self) -> Option<i8> {
+        match self {
+            PushoverPriority::Off => None,
+            PushoverPriority::Quiet => Some(-1),
+            PushoverPriority::Normal => Some(0),
+            PushoverPriority::High => Some(1),
+            PushoverPriority::Emergency => Some(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushoverSettings {
+    pub enabled: bool,
+    /// Token of the application registered for this monitor
+    pub app_token: String,
+    /// User or group key of whoever receives the notifications
+    pub user_key: String,
+    pub critical: PushoverPriority,
+    pub warning: PushoverPriority,
+    pub info: PushoverPriority,
+}
+
+impl Default for PushoverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            app_token: String::new(),
+            user_key: String::new(),
+            critical: PushoverPriority::High,
+            warning: PushoverPriority::Normal,
+            info: PushoverPriority::Quiet,
+        }
+    }
+}
+
+impl PushoverSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && (self.app_token.trim().is_empty() || self.user_key.trim().is_empty()) {
+            return Err("Pushover needs an application token and a user key".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn priority(&self, severity: AlertSeverity) -> PushoverPriority {
+        match severity {
+            AlertSeverity::Critical => self.critical,
+            AlertSeverity::Warning => self.warning,
+            AlertSeverity::Info => self.info,
+        }
+    }
+
+    /// Sends in the background at the priority chosen for `severity`
+    pub fn notify(&self, severity: AlertSeverity, message: String) {
+        let Some(priority) = self.priority(severity).value() else {
+            return;
+        };
+        let mut body = json!({
+            "token": self.app_token.trim(),
+            "user": self.user_key.trim(),
+            "title": "Ping Monitor",
+            "message": message,
+            "priority": priority,
+        });
+        if priority == 2 {
+            body["retry"] = json!(EMERGENCY_RETRY_SECS);
+            body["expire"] = json!(EMERGENCY_EXPIRE_SECS);
+        }
+        http_post_json(self.enabled, self.validate(), "Pushover", MESSAGES_URL.to_string(), None, &body);
+    }
+}
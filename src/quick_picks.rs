@@ -0,0 +1,39 @@
+use std::net::IpAddr;
+
+/// Well-known anchors: (address, name, what it tells you)
+const ANCHORS: [(&str, &str, &str); 4] = [
+    ("1.1.1.1", "Cloudflare DNS", "Cloudflare's public DNS server, close to most users: a good general test of your internet connection"),
+    ("8.8.8.8", "Google DNS", "Google's public DNS server, reachable from almost anywhere in the world"),
+    ("9.9.9.9", "Quad9 DNS", "A non-profit public DNS server; a second opinion when one of the others looks slow"),
+    ("2606:4700:4700::1111", "Cloudflare DNS (IPv6)", "The same Cloudflare server over IPv6, to check whether your IPv6 connection works"),
+];
+
+/// A ready-made target with a plain-language explanation for people who don't know what 8.8.8.8 is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickPick {
+    pub target: String,
+    pub name: String,
+    pub description: String,
+}
+
+impl QuickPick {
+    fn new(target: impl ToString, name: &str, description: &str) -> Self {
+        Self { target: target.to_string(), name: name.to_string(), description: description.to_string() }
+    }
+}
+
+/// The detected router and DNS server (when known) followed by the public anchors
+pub fn quick_picks(gateway: Option<IpAddr>, dns_server: Option<IpAddr>) -> Vec<QuickPick> {
+    let mut picks = Vec::new();
+    if let Some(gateway) = gateway {
+        let name = if dns_server == Some(gateway) { "Your router (also your DNS server)" } else { "Your router" };
+        picks.push(QuickPick::new(gateway, name,
+            "The box that connects your network to the internet; if it is slow, the problem is in your home or office"));
+    }
+    if let Some(dns_server) = dns_server.filter(|dns_server| Some(*dns_server) != gateway) {
+        picks.push(QuickPick::new(dns_server, "Your DNS server",
+            "Turns names like example.com into addresses; when it is slow, websites take long to start loading"));
+    }
+    picks.extend(ANCHORS.iter().map(|(address, name, description)| QuickPick::new(address, name, description)));
+    picks
+}
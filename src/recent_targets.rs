@@ -0,0 +1,58 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::ping::{self, PingStatistics};
+
+pub const MAX_RECENT_TARGETS: usize = 10;
+
+/// A previously monitored target with how it looked the last time it was used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentTarget {
+    pub target: String,
+    /// Seconds since the Unix epoch
+    pub last_used: u64,
+    pub mean_ms: Option<f64>,
+    pub loss_rate: Option<f64>,
+}
+
+impl RecentTarget {
+    pub fn last_used_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.last_used)
+    }
+
+    /// e.g. "3 h ago · 23.4 ms · 0.0% loss"
+    pub fn summary(&self) -> String {
+        let age = SystemTime::now().duration_since(self.last_used_time()).unwrap_or_default().as_secs();
+        let mut parts = vec![match age {
+            0..60 => "just now".to_string(),
+            60..3600 => format!("{} min ago", age / 60),
+            3600..86400 => format!("{} h ago", age / 3600),
+            _ => format!("{} d ago", age / 86400),
+        }];
+        if let Some(mean) = self.mean_ms {
+            parts.push(ping::format_ms(mean));
+        }
+        if let Some(loss) = self.loss_rate {
+            parts.push(format!("{loss:.1}% loss"));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Moves `target` to the front of the list, keeping its last stats unless new ones are given
+pub fn remember(recent: &mut Vec<RecentTarget>, target: &str, statistics: Option<&PingStatistics>) {
+    let target = target.trim();
+    if target.is_empty() {
+        return;
+    }
+    let mut entry = match recent.iter().position(|entry| entry.target == target) {
+        Some(index) => recent.remove(index),
+        None => RecentTarget { target: target.to_string(), last_used: 0, mean_ms: None, loss_rate: None },
+    };
+    entry.last_used = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if let Some(statistics) = statistics.filter(|statistics| statistics.total_pings > 0) {
+        entry.mean_ms = (statistics.successful_pings > 0).then_some(statistics.mean_response_time);
+        entry.loss_rate = Some(statistics.loss_rate);
+    }
+    recent.insert(0, entry);
+    recent.truncate(MAX_RECENT_TARGETS);
+}
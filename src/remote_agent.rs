@@ -0,0 +1,315 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::command_probe::CommandProbe;
+use crate::config::{self, AppConfig, ProbeType};
+use crate::dns_probe::{DEFAULT_QUERY_NAME, DnsProbe};
+use crate::http_probe::HttpProbe;
+use crate::ntp_probe::NtpProbe;
+use crate::ping::PingResult;
+use crate::ping_executor::PingExecutor;
+use crate::probe_limiter::ProbeLimiter;
+use crate::snmp_probe::SnmpProbe;
+use crate::targets::TargetEntry;
+use crate::tcp_probe::TcpProbe;
+
+/// Starts the headless agent instead of the window
+pub const AGENT_FLAG: &str = "--agent";
+pub const DEFAULT_AGENT_PORT: u16 = 7878;
+const HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const RECONNECT_DELAY_SECS: u64 = 5;
+/// How often the viewer thread wakes up to check whether it should stop
+const READ_POLL_MS: u64 = 500;
+/// Lines longer than this are not part of the protocol
+const MAX_LINE_BYTES: usize = 4096;
+
+/// One JSON object per line in both directions: the viewer says Hello, the agent answers
+/// Welcome or Denied and then streams a Result for every probe.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+    Hello { token: String },
+    Welcome { target: String, interval_secs: u64 },
+    Denied { reason: String },
+    Result { timestamp_ms: u64, success: bool, response_ms: Option<f64> },
+}
+
+impl Message {
+    fn from_result(result: &PingResult) -> Self {
+        let timestamp_ms = result.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        Message::Result { timestamp_ms, success: result.success, response_ms: result.response_time }
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())
+    }
+}
+
+/// Reads newline-delimited messages, tolerating read timeouts part-way through a line
+struct LineReader {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl LineReader {
+    fn new(stream: TcpStream) -> Self {
+        Self { stream, buffer: Vec::new() }
+    }
+
+    /// Ok(None) means the read timed out before a full line arrived
+    fn next_message(&mut self) -> Result<Option<Message>, String> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return serde_json::from_slice(&line).map(Some).map_err(|e| format!("Bad message: {e}"));
+            }
+            if self.buffer.len() > MAX_LINE_BYTES {
+                return Err("Message too long".to_string());
+            }
+            let mut chunk = [0u8; 1024];
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err("Connection closed".to_string()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Compares tokens without bailing out at the first differing byte
+pub fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Runs probes without a window and streams the results to every authenticated viewer.
+/// Uses the saved probe, target and interval; never returns unless the port can't be opened.
+pub fn run_agent(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if config.agent_token.trim().is_empty() {
+        return Err("Set agent_token in config.json before starting the agent".into());
+    }
+    let target = match config.probe {
+        ProbeType::Icmp | ProbeType::Ntp | ProbeType::Snmp | ProbeType::Tcp | ProbeType::Dns | ProbeType::Http => config.target.clone(),
+        ProbeType::Command => config.command.clone(),
+        ProbeType::Remote => return Err("The agent can't use the remote probe itself; choose another probe".into()),
+    };
+    let query_name = config
+        .target_list
+        .iter()
+        .find(|entry| entry.host == config.target.trim())
+        .map_or(DEFAULT_QUERY_NAME, TargetEntry::lookup_name)
+        .to_string();
+
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, config.agent_port))?;
+    log::info!("Agent listening on port {} and probing {target} every {} s", config.agent_port, config.interval_secs);
+
+    let viewers: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+    {
+        let viewers = viewers.clone();
+        let token = config.agent_token.clone();
+        let interval_secs = config.interval_secs;
+        let target = target.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (viewers, token, target) = (viewers.clone(), token.clone(), target.clone());
+                // Handshakes run on their own thread so a silent client can't block new ones
+                thread::spawn(move || {
+                    let peer = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                    match handshake(stream, &token, &target, interval_secs) {
+                        Ok(stream) => {
+                            log::info!("Viewer connected from {peer}");
+                            if let Ok(mut viewers) = viewers.lock() {
+                                viewers.push(stream);
+                            }
+                        }
+                        Err(e) => log::warn!("Rejected viewer {peer}: {e}"),
+                    }
+                });
+            }
+        });
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    // Probes wake a context after sending; headless there's nothing to repaint
+    let repaint = egui::Context::default();
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    let mut limiter = ProbeLimiter::new(config.max_in_flight_probes, config.probe_rate_limit);
+    let mut next_probe = Instant::now();
+    loop {
+        if Instant::now() >= next_probe && !limiter.try_start(&target) {
+            log::warn!("Skipped a probe to {target}: too many probes are still waiting for an answer");
+            next_probe += interval;
+        } else if Instant::now() >= next_probe {
+            match config.probe {
+                ProbeType::Command => CommandProbe::spawn(target.clone(), sender.clone(), repaint.clone()),
+                ProbeType::Ntp => NtpProbe::spawn(target.clone(), sender.clone(), repaint.clone()),
+                ProbeType::Snmp => SnmpProbe::spawn(target.clone(), config.snmp.clone(), sender.clone(), repaint.clone()),
+                ProbeType::Tcp => TcpProbe::spawn(target.clone(), config.tcp_expect.clone(), config.tcp_proxy.clone(), sender.clone(), repaint.clone()),
+                ProbeType::Dns => DnsProbe::spawn(target.clone(), query_name.clone(), sender.clone(), repaint.clone()),
+                ProbeType::Http => HttpProbe::spawn(target.clone(), config.http_proxy.clone(), sender.clone(), repaint.clone()),
+                _ => PingExecutor::resolve_and_ping(target.clone(), sender.clone(), repaint.clone()),
+            }
+            next_probe += interval;
+        }
+        let wait = next_probe.saturating_duration_since(Instant::now());
+        let Ok(result) = receiver.recv_timeout(wait) else {
+            continue;
+        };
+        limiter.finished(&target);
+        if let Ok(mut viewers) = viewers.lock() {
+            let message = Message::from_result(&result);
+            // Viewers that have gone away fail the write and are dropped
+            viewers.retain_mut(|stream| message.write_to(stream).is_ok());
+        }
+    }
+}
+
+fn handshake(stream: TcpStream, token: &str, target: &str, interval_secs: u64) -> Result<TcpStream, String> {
+    stream.set_read_timeout(Some(Duration::from_secs(HANDSHAKE_TIMEOUT_SECS))).map_err(|e| e.to_string())?;
+    let mut reader = LineReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut stream = stream;
+    let reply = match reader.next_message()? {
+        Some(Message::Hello { token: given }) if tokens_match(token, &given) => {
+            Message::Welcome { target: target.to_string(), interval_secs }
+        }
+        Some(Message::Hello { .. }) => Message::Denied { reason: "Wrong token".to_string() },
+        Some(_) => Message::Denied { reason: "Expected hello".to_string() },
+        None => return Err("No hello before timeout".to_string()),
+    };
+    // A viewer that stops reading must not stall the broadcast to the others
+    stream.set_write_timeout(Some(Duration::from_secs(HANDSHAKE_TIMEOUT_SECS))).map_err(|e| e.to_string())?;
+    reply.write_to(&mut stream).map_err(|e| e.to_string())?;
+    match reply {
+        Message::Denied { reason } => Err(reason),
+        _ => Ok(stream),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteStatus {
+    Connecting,
+    Connected { target: String, interval_secs: u64 },
+    Disconnected(String),
+}
+
+impl std::fmt::Display for RemoteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteStatus::Connecting => write!(f, "Connecting to agent…"),
+            RemoteStatus::Connected { target, interval_secs } => {
+                write!(f, "Agent is probing {target} every {interval_secs} s")
+            }
+            RemoteStatus::Disconnected(reason) => write!(f, "Agent unreachable: {reason}; retrying"),
+        }
+    }
+}
+
+/// Connection from the GUI to an agent. Results are delivered on the regular ping channel so
+/// they feed the clock face, statistics and outages like local probes; dropping it disconnects.
+pub struct RemoteViewer {
+    stop: Arc<AtomicBool>,
+    receiver: mpsc::Receiver<RemoteStatus>,
+    pub status: RemoteStatus,
+}
+
+impl RemoteViewer {
+    pub fn connect(address: String, token: String, results: mpsc::Sender<PingResult>, repaint: egui::Context) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let _ = sender.send(RemoteStatus::Connecting);
+                    let reason = Self::stream_results(&address, &token, &results, &sender, &repaint, &stop);
+                    if sender.send(RemoteStatus::Disconnected(reason)).is_err() {
+                        return;
+                    }
+                    repaint.request_repaint();
+                    thread::sleep(Duration::from_secs(RECONNECT_DELAY_SECS));
+                }
+            });
+        }
+        Self { stop, receiver, status: RemoteStatus::Connecting }
+    }
+
+    pub fn poll(&mut self) {
+        while let Ok(status) = self.receiver.try_recv() {
+            self.status = status;
+        }
+    }
+
+    /// Returns why the connection ended
+    fn stream_results(
+        address: &str,
+        token: &str,
+        results: &mpsc::Sender<PingResult>,
+        status: &mpsc::Sender<RemoteStatus>,
+        repaint: &egui::Context,
+        stop: &AtomicBool,
+    ) -> String {
+        let connected = config::with_default_port(address, DEFAULT_AGENT_PORT)
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())
+            .and_then(|mut addrs| addrs.next().ok_or_else(|| "address did not resolve".to_string()))
+            .and_then(|addr| {
+                TcpStream::connect_timeout(&addr, Duration::from_secs(CONNECT_TIMEOUT_SECS)).map_err(|e| e.to_string())
+            });
+        let mut stream = match connected {
+            Ok(stream) => stream,
+            Err(e) => return e,
+        };
+        if let Err(e) = stream
+            .set_read_timeout(Some(Duration::from_millis(READ_POLL_MS)))
+            .and_then(|_| Message::Hello { token: token.to_string() }.write_to(&mut stream))
+        {
+            return e.to_string();
+        }
+        let mut reader = match stream.try_clone() {
+            Ok(clone) => LineReader::new(clone),
+            Err(e) => return e.to_string(),
+        };
+
+        while !stop.load(Ordering::Relaxed) {
+            let message = match reader.next_message() {
+                Ok(Some(message)) => message,
+                Ok(None) => continue,
+                Err(e) => return e,
+            };
+            match message {
+                Message::Welcome { target, interval_secs } => {
+                    let _ = status.send(RemoteStatus::Connected { target, interval_secs });
+                }
+                Message::Denied { reason } => return reason,
+                Message::Result { timestamp_ms, success, response_ms } => {
+                    let timestamp = UNIX_EPOCH + Duration::from_millis(timestamp_ms);
+                    let result = match response_ms {
+                        Some(response_time) if success => PingResult::success(timestamp, Instant::now(), response_time, None),
+                        _ => PingResult::failure(timestamp, Instant::now()),
+                    };
+                    if results.send(result).is_err() {
+                        return "Viewer closed".to_string();
+                    }
+                }
+                Message::Hello { .. } => return "Unexpected hello from agent".to_string(),
+            }
+            repaint.request_repaint();
+        }
+        "Stopped".to_string()
+    }
+}
+
+impl Drop for RemoteViewer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant, SystemTime};
+use chrono::NaiveDate;
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::ping::PingResult;
+
+/// Playback speeds offered, as multiples of real time
+pub const REPLAY_SPEEDS: [f64; 5] = [1.0, 10.0, 60.0, 600.0, 3600.0];
+/// Stretches without results longer than this (monitoring was off) are skipped instead of waited out
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(60);
+
+/// A stored day of one target played back on a clock of its own
+pub struct Replay {
+    pub target: String,
+    pub day: NaiveDate,
+    records: Vec<HistoryRecord>,
+    next: usize,
+    /// Point of the recorded timeline that has been played up to
+    position: SystemTime,
+    /// Where the recorded timeline starts on the monotonic clock, so replayed results age by replay time
+    origin: Instant,
+    first: SystemTime,
+    pub speed: f64,
+    pub paused: bool,
+    last_tick: Instant,
+    /// When the replay was loaded; everything it plays back was recorded before this
+    pub started: SystemTime,
+}
+
+impl Replay {
+    /// None when nothing was recorded for `target` that day
+    pub fn load(store: &HistoryStore, target: &str, day: NaiveDate) -> Option<Self> {
+        let records: Vec<HistoryRecord> = store.load_days(day, day).into_iter().filter(|record| record.target == target).collect();
+        let position = records.first()?.timestamp();
+        Some(Self {
+            target: target.to_string(),
+            day,
+            records,
+            next: 0,
+            position,
+            origin: Instant::now(),
+            first: position,
+            speed: REPLAY_SPEEDS[2],
+            paused: false,
+            last_tick: Instant::now(),
+            started: SystemTime::now(),
+        })
+    }
+
+    /// Advances the replay clock by the real time since the last call and returns the results it passed
+    pub fn tick(&mut self) -> Vec<PingResult> {
+        let elapsed = self.last_tick.elapsed();
+        self.last_tick = Instant::now();
+        if self.paused || self.finished() {
+            return Vec::new();
+        }
+        self.position += elapsed.mul_f64(self.speed);
+        if let Some(next) = self.records.get(self.next).map(HistoryRecord::timestamp)
+            && next.duration_since(self.position).is_ok_and(|gap| gap > MAX_REPLAY_GAP)
+        {
+            self.position = next;
+        }
+        let due = self.records[self.next..].iter().take_while(|record| record.timestamp() <= self.position).count();
+        let results = self.records[self.next..self.next + due].iter().map(|record| record.to_result(self.instant_of(record.timestamp()))).collect();
+        self.next += due;
+        results
+    }
+
+    pub fn position(&self) -> SystemTime {
+        self.position
+    }
+
+    /// The replay position as a monotonic time, comparable with the `sent_at` of replayed results
+    pub fn clock(&self) -> Instant {
+        self.instant_of(self.position)
+    }
+
+    fn instant_of(&self, time: SystemTime) -> Instant {
+        self.origin + time.duration_since(self.first).unwrap_or_default()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.records.len()
+    }
+
+    /// Share of the recorded results played so far
+    pub fn progress(&self) -> f32 {
+        self.next as f32 / self.records.len() as f32
+    }
+}
@@ -0,0 +1,388 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, Timelike};
+use eframe::egui;
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::loss_pattern::LossPattern;
+use crate::outage::{OutageEvent, OutageTracker};
+use crate::rollup::{Resolution, Rollup};
+
+const REPORT_DAYS: usize = 7;
+const REPORT_WEEKS: usize = 4;
+/// Days covered by the long-term chart, which reads hourly rollups rather than samples
+pub const LONG_TERM_DAYS: u64 = 90;
+/// An hour counts as congested when its latency is this much above the typical hour
+const CONGESTED_LATENCY_FACTOR: f64 = 1.5;
+/// ...or when it loses at least this share of probes (percent)
+const CONGESTED_LOSS_PERCENT: f64 = 1.0;
+/// Minutes averaged into each point of the day overlay curves
+const CURVE_BUCKET_MINUTES: usize = 10;
+pub const CURVE_POINTS: usize = 24 * 60 / CURVE_BUCKET_MINUTES;
+
+/// Mean latency through one local day in 10-minute steps; None where nothing answered
+pub type DayCurve = Vec<Option<f64>>;
+
+/// Which earlier day today's curve is overlaid with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlayReference {
+    #[default]
+    Yesterday,
+    LastWeek,
+}
+
+impl OverlayReference {
+    pub fn label(self) -> &'static str {
+        match self {
+            OverlayReference::Yesterday => "Yesterday",
+            OverlayReference::LastWeek => "Same day last week",
+        }
+    }
+}
+
+/// Aggregates for one hour of the day within a period
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HourSummary {
+    pub probes: usize,
+    pub failed: usize,
+    pub mean_ms: Option<f64>,
+}
+
+impl HourSummary {
+    pub fn loss_rate(&self) -> f64 {
+        if self.probes > 0 { self.failed as f64 / self.probes as f64 * 100.0 } else { 0.0 }
+    }
+}
+
+/// Uptime, latency and outage figures for a day or a week
+#[derive(Debug, Clone)]
+pub struct PeriodSummary {
+    pub label: String,
+    pub probes: usize,
+    pub uptime: f64,
+    pub mean_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub outages: usize,
+    pub outage_duration: Duration,
+    pub hours: [HourSummary; 24],
+}
+
+impl PeriodSummary {
+    pub fn from_records(label: String, records: &[&HistoryRecord]) -> Self {
+        let counted: Vec<&HistoryRecord> = records.iter().copied().filter(|record| !record.in_maintenance).collect();
+        let probes = counted.len();
+        let successful = counted.iter().filter(|record| record.success).count();
+
+        let mut response_times: Vec<f64> = counted.iter().filter_map(|record| record.response_ms).collect();
+        response_times.sort_by(f64::total_cmp);
+        let mean_ms = (!response_times.is_empty()).then(|| response_times.iter().sum::<f64>() / response_times.len() as f64);
+        let p95_ms = (!response_times.is_empty()).then(|| {
+            let rank = (response_times.len() as f64 * 0.95).ceil() as usize;
+            response_times[rank.clamp(1, response_times.len()) - 1]
+        });
+
+        // Same rule as the live outage list, replayed over the stored results
+        let mut tracker = OutageTracker::new();
+        let mut outages = 0;
+        let mut outage_duration = Duration::ZERO;
+        for record in records {
+            match tracker.record(&record.to_result(Instant::now())) {
+                Some(OutageEvent::Started(_)) => outages += 1,
+                Some(OutageEvent::Ended(_)) => {
+                    outage_duration += tracker.outages.back().map(|outage| outage.duration()).unwrap_or_default();
+                }
+                None => {}
+            }
+        }
+        // An outage still open at the end of the data lasted at least until the last failure
+        if let (Some(ongoing), Some(last)) = (tracker.ongoing(), records.last()) {
+            outage_duration += last.timestamp().duration_since(ongoing.started).unwrap_or_default();
+        }
+
+        let hours = by_hour_of_day(&counted);
+
+        Self {
+            label,
+            probes,
+            uptime: if probes > 0 { successful as f64 / probes as f64 * 100.0 } else { 0.0 },
+            mean_ms,
+            p95_ms,
+            outages,
+            outage_duration,
+            hours,
+        }
+    }
+
+    /// Hour of day with the highest loss, then the highest mean response time
+    pub fn worst_hour(&self) -> Option<usize> {
+        (0..24)
+            .filter(|&hour| self.hours[hour].probes > 0)
+            .max_by(|&a, &b| {
+                let (a, b) = (&self.hours[a], &self.hours[b]);
+                a.loss_rate()
+                    .total_cmp(&b.loss_rate())
+                    .then(a.mean_ms.unwrap_or(0.0).total_cmp(&b.mean_ms.unwrap_or(0.0)))
+            })
+    }
+}
+
+/// Groups records by local hour of day, ignoring maintenance windows
+fn by_hour_of_day(records: &[&HistoryRecord]) -> [HourSummary; 24] {
+    let mut hours = [HourSummary::default(); 24];
+    let mut hour_totals = [0.0; 24];
+    let mut hour_replies = [0usize; 24];
+    for record in records.iter().filter(|record| !record.in_maintenance) {
+        let hour = DateTime::<Local>::from(record.timestamp()).hour() as usize;
+        hours[hour].probes += 1;
+        if !record.success {
+            hours[hour].failed += 1;
+        }
+        if let Some(response_ms) = record.response_ms {
+            hour_totals[hour] += response_ms;
+            hour_replies[hour] += 1;
+        }
+    }
+    for (hour, summary) in hours.iter_mut().enumerate() {
+        summary.mean_ms = (hour_replies[hour] > 0).then(|| hour_totals[hour] / hour_replies[hour] as f64);
+    }
+    hours
+}
+
+/// Runs of consecutive hours (start, end exclusive) whose loss or latency stands out from the
+/// typical hour, e.g. (20, 23) for evening congestion. A run may wrap past midnight.
+pub fn congested_windows(hours: &[HourSummary; 24]) -> Vec<(usize, usize)> {
+    let mut means: Vec<f64> = hours.iter().filter_map(|hour| hour.mean_ms).collect();
+    if means.is_empty() {
+        return Vec::new();
+    }
+    means.sort_by(f64::total_cmp);
+    let typical = means[means.len() / 2];
+    let congested = |hour: usize| {
+        let summary = &hours[hour % 24];
+        summary.probes > 0
+            && (summary.loss_rate() >= CONGESTED_LOSS_PERCENT
+                || summary.mean_ms.is_some_and(|mean| mean > typical * CONGESTED_LATENCY_FACTOR))
+    };
+    if (0..24).all(congested) {
+        return vec![(0, 24)];
+    }
+
+    // Start scanning just after a calm hour so a run spanning midnight is found whole
+    let calm = (0..24).find(|&hour| !congested(hour)).unwrap_or(0);
+    let mut windows = Vec::new();
+    let mut run_start = None;
+    for offset in 1..=24 {
+        let hour = calm + offset;
+        match (congested(hour), run_start) {
+            (true, None) => run_start = Some(hour),
+            (false, Some(start)) => {
+                windows.push((start % 24, hour % 24));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    windows.sort();
+    windows
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub generated: SystemTime,
+    pub target: String,
+    /// Newest first; days without data are left out
+    pub days: Vec<PeriodSummary>,
+    /// Monday-based weeks, newest first
+    pub weeks: Vec<PeriodSummary>,
+    /// Every record in the report, grouped by hour of day to expose recurring patterns
+    pub hour_of_day: [HourSummary; 24],
+    /// Whether losses over the whole report come singly or in runs
+    pub loss_pattern: LossPattern,
+    pub today_curve: DayCurve,
+    pub yesterday_curve: DayCurve,
+    pub last_week_curve: DayCurve,
+    /// Hourly rollups over the last 90 days, oldest first
+    pub long_term: Vec<Rollup>,
+}
+
+impl Report {
+    pub fn reference_curve(&self, reference: OverlayReference) -> &DayCurve {
+        match reference {
+            OverlayReference::Yesterday => &self.yesterday_curve,
+            OverlayReference::LastWeek => &self.last_week_curve,
+        }
+    }
+}
+
+/// Mean of today minus the reference over the times of day both have data for; positive means
+/// today is slower
+pub fn curve_difference(today: &DayCurve, reference: &DayCurve) -> Option<f64> {
+    let differences: Vec<f64> = today
+        .iter()
+        .zip(reference)
+        .filter_map(|(today, reference)| Some((*today)? - (*reference)?))
+        .collect();
+    (!differences.is_empty()).then(|| differences.iter().sum::<f64>() / differences.len() as f64)
+}
+
+fn day_curve(records: &[&HistoryRecord]) -> DayCurve {
+    let mut totals = vec![0.0; CURVE_POINTS];
+    let mut replies = vec![0usize; CURVE_POINTS];
+    for record in records.iter().filter(|record| !record.in_maintenance) {
+        let Some(response_ms) = record.response_ms else {
+            continue;
+        };
+        let time = DateTime::<Local>::from(record.timestamp());
+        let bucket = (time.hour() as usize * 60 + time.minute() as usize) / CURVE_BUCKET_MINUTES;
+        totals[bucket] += response_ms;
+        replies[bucket] += 1;
+    }
+    totals.iter().zip(&replies).map(|(total, count)| (*count > 0).then(|| total / *count as f64)).collect()
+}
+
+pub struct ReportBuilder;
+
+impl ReportBuilder {
+    /// Reads the last few weeks of history for `target` in the background and summarizes them
+    pub fn spawn(store: HistoryStore, target: String, sender: mpsc::Sender<Report>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let report = Self::build(&store, target);
+            let _ = sender.send(report);
+            repaint.request_repaint();
+        });
+    }
+
+    fn build(store: &HistoryStore, target: String) -> Report {
+        let today = Local::now().date_naive();
+        let this_monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+        let first_day = this_monday - Days::new(7 * (REPORT_WEEKS as u64 - 1));
+        let records: Vec<HistoryRecord> = store
+            .load_days(first_day, today)
+            .into_iter()
+            .filter(|record| record.target == target)
+            .collect();
+        let day_of = |record: &HistoryRecord| DateTime::<Local>::from(record.timestamp()).date_naive();
+        let records_between = |first: NaiveDate, last: NaiveDate| -> Vec<&HistoryRecord> {
+            records.iter().filter(|record| (first..=last).contains(&day_of(record))).collect()
+        };
+
+        let days = (0..REPORT_DAYS as u64)
+            .map(|offset| today - Days::new(offset))
+            .filter_map(|day| {
+                let records = records_between(day, day);
+                (!records.is_empty()).then(|| PeriodSummary::from_records(day.format("%a %Y-%m-%d").to_string(), &records))
+            })
+            .collect();
+        let weeks = (0..REPORT_WEEKS as u64)
+            .map(|offset| this_monday - Days::new(7 * offset))
+            .filter_map(|monday| {
+                let records = records_between(monday, monday + Days::new(6));
+                (!records.is_empty()).then(|| PeriodSummary::from_records(format!("Week of {}", monday.format("%Y-%m-%d")), &records))
+            })
+            .collect();
+
+        let all: Vec<&HistoryRecord> = records.iter().collect();
+        let hour_of_day = by_hour_of_day(&all);
+        let loss_pattern = LossPattern::from_outcomes(all.iter().filter(|record| !record.in_maintenance).map(|record| record.success));
+        let curve_of = |day: NaiveDate| day_curve(&records_between(day, day));
+        let long_term = store
+            .load_rollups(Resolution::Hour, today - Days::new(LONG_TERM_DAYS - 1), today)
+            .into_iter()
+            .filter(|rollup| rollup.target == target)
+            .collect();
+
+        Report {
+            generated: SystemTime::now(),
+            target,
+            days,
+            weeks,
+            hour_of_day,
+            loss_pattern,
+            today_curve: curve_of(today),
+            yesterday_curve: curve_of(today - Days::new(1)),
+            last_week_curve: curve_of(today - Days::new(7)),
+            long_term,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours_at(mean_ms: f64) -> [HourSummary; 24] {
+        [HourSummary { probes: 100, failed: 0, mean_ms: Some(mean_ms) }; 24]
+    }
+
+    #[test]
+    fn steady_hours_are_not_congested() {
+        assert!(congested_windows(&hours_at(20.0)).is_empty());
+        assert!(congested_windows(&[HourSummary::default(); 24]).is_empty());
+    }
+
+    #[test]
+    fn slow_or_lossy_hours_form_windows() {
+        let mut hours = hours_at(20.0);
+        for hour in &mut hours[20..23] {
+            hour.mean_ms = Some(40.0);
+        }
+        hours[3].failed = 2;
+        assert_eq!(congested_windows(&hours), vec![(3, 4), (20, 23)]);
+    }
+
+    #[test]
+    fn window_may_span_midnight() {
+        let mut hours = hours_at(20.0);
+        for hour in [22, 23, 0, 1] {
+            hours[hour].mean_ms = Some(40.0);
+        }
+        assert_eq!(congested_windows(&hours), vec![(22, 2)]);
+    }
+
+    #[test]
+    fn hours_without_probes_are_calm() {
+        let mut hours = hours_at(20.0);
+        hours[5] = HourSummary::default();
+        hours[6].failed = 5;
+        assert_eq!(congested_windows(&hours), vec![(6, 7)]);
+    }
+
+    fn record(hour: u32, minute: u32, response_ms: Option<f64>, in_maintenance: bool) -> HistoryRecord {
+        let time = NaiveDate::from_ymd_opt(2024, 6, 3).and_then(|day| day.and_hms_opt(hour, minute, 0)).unwrap();
+        HistoryRecord {
+            timestamp_ms: time.and_local_timezone(Local).earliest().unwrap().timestamp_millis() as u64,
+            target: "192.0.2.1".to_string(),
+            success: response_ms.is_some(),
+            response_ms,
+            in_maintenance,
+            clock_offset_ms: None,
+            degraded: false,
+            vpn: None,
+        }
+    }
+
+    #[test]
+    fn day_curve_averages_replies_per_bucket() {
+        let records = [
+            record(0, 0, Some(10.0), false),
+            record(0, 9, Some(20.0), false),
+            record(0, 10, None, false),
+            record(12, 30, Some(40.0), false),
+            record(12, 35, Some(400.0), true),
+        ];
+        let curve = day_curve(&records.iter().collect::<Vec<_>>());
+        assert_eq!(curve.len(), CURVE_POINTS);
+        assert_eq!(curve[0], Some(15.0));
+        assert_eq!(curve[1], None);
+        assert_eq!(curve[12 * 60 / CURVE_BUCKET_MINUTES + 3], Some(40.0));
+        assert_eq!(curve.iter().flatten().count(), 2);
+    }
+
+    #[test]
+    fn curve_difference_uses_shared_buckets_only() {
+        let today = vec![Some(30.0), Some(20.0), None, Some(50.0)];
+        let reference = vec![Some(10.0), None, Some(5.0), Some(40.0)];
+        assert_eq!(curve_difference(&today, &reference), Some(15.0));
+        assert_eq!(curve_difference(&today, &vec![None; 4]), None);
+    }
+}
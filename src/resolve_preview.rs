@@ -0,0 +1,55 @@
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use eframe::egui;
+use crate::network_watcher;
+use crate::target_parser;
+
+/// Addresses a target resolved to while it was being edited, or why it didn't
+#[derive(Debug, Clone)]
+pub struct ResolvePreview {
+    pub target: String,
+    pub result: Result<Vec<IpAddr>, String>,
+}
+
+impl ResolvePreview {
+    pub fn failed(target: &str, error: String) -> Self {
+        Self { target: target.to_string(), result: Err(error) }
+    }
+
+    /// Spawns a lookup of `target`'s host; IP addresses come back without a lookup
+    pub fn spawn(target: String, sender: mpsc::Sender<ResolvePreview>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let result = Self::resolve(&target);
+            let _ = sender.send(Self { target, result });
+            repaint.request_repaint();
+        });
+    }
+
+    fn resolve(target: &str) -> Result<Vec<IpAddr>, String> {
+        let parsed = target_parser::parse_target(target).ok_or("Not a usable host")?;
+        if let Some(zone) = &parsed.zone
+            && network_watcher::interface_index(zone).is_none()
+        {
+            return Err(format!("No network interface named {zone}"));
+        }
+        let host = parsed.host;
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        let resolved = (target_parser::to_ascii(&host).as_str(), 0)
+            .to_socket_addrs()
+            .map_err(|e| format!("Could not resolve {host}: {e}"))?;
+        // The resolver lists each address once per socket type
+        let mut addresses = Vec::new();
+        for ip in resolved.map(|address| address.ip()) {
+            if !addresses.contains(&ip) {
+                addresses.push(ip);
+            }
+        }
+        if addresses.is_empty() {
+            return Err(format!("{host} has no addresses"));
+        }
+        Ok(addresses)
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use crate::history::{HistoryRecord, HistoryStore};
+
+/// How often the background task looks for finished days to compact
+const COMPACTION_INTERVAL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Minute,
+    Hour,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 2] = [Resolution::Minute, Resolution::Hour];
+
+    pub fn secs(self) -> u64 {
+        match self {
+            Resolution::Minute => 60,
+            Resolution::Hour => 60 * 60,
+        }
+    }
+
+    /// Folder inside the history folder
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Resolution::Minute => "minutes",
+            Resolution::Hour => "hours",
+        }
+    }
+}
+
+/// Aggregate of one target's probes over a minute or an hour, outside maintenance windows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub start_ms: u64,
+    pub target: String,
+    pub probes: u32,
+    pub failed: u32,
+    pub min_ms: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+}
+
+impl Rollup {
+    pub fn loss_rate(&self) -> f64 {
+        if self.probes > 0 { self.failed as f64 / self.probes as f64 * 100.0 } else { 0.0 }
+    }
+}
+
+/// Groups records into rollups per target and period, ordered by target, then time
+pub fn aggregate(records: &[HistoryRecord], resolution: Resolution) -> Vec<Rollup> {
+    let period_ms = resolution.secs() * 1000;
+    let mut groups: BTreeMap<(&str, u64), Vec<&HistoryRecord>> = BTreeMap::new();
+    for record in records.iter().filter(|record| !record.in_maintenance) {
+        let start_ms = record.timestamp_ms - record.timestamp_ms % period_ms;
+        groups.entry((record.target.as_str(), start_ms)).or_default().push(record);
+    }
+    groups
+        .into_iter()
+        .map(|((target, start_ms), records)| {
+            let mut replies: Vec<f64> = records.iter().filter_map(|record| record.response_ms).collect();
+            replies.sort_by(f64::total_cmp);
+            let p95_ms = (!replies.is_empty()).then(|| {
+                let rank = (replies.len() as f64 * 0.95).ceil() as usize;
+                replies[rank.clamp(1, replies.len()) - 1]
+            });
+            Rollup {
+                start_ms,
+                target: target.to_string(),
+                probes: records.len() as u32,
+                failed: records.iter().filter(|record| !record.success).count() as u32,
+                min_ms: replies.first().copied(),
+                mean_ms: (!replies.is_empty()).then(|| replies.iter().sum::<f64>() / replies.len() as f64),
+                max_ms: replies.last().copied(),
+                p95_ms,
+            }
+        })
+        .collect()
+}
+
+pub struct Compactor;
+
+impl Compactor {
+    /// Rolls up every finished day once at startup and then hourly, so long-range charts read
+    /// a few thousand rows instead of months of samples. Raw files are kept, since outages and
+    /// loss patterns are replayed from individual probes.
+    pub fn spawn(store: HistoryStore) {
+        thread::spawn(move || loop {
+            let today = Local::now().date_naive();
+            let mut compacted = 0;
+            for day in store.recorded_days().into_iter().filter(|day| *day < today) {
+                match store.compact_day(day) {
+                    Ok(true) => compacted += 1,
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Could not compact history for {day}: {e}"),
+                }
+            }
+            if compacted > 0 {
+                log::info!("Rolled up history for {compacted} day(s)");
+            }
+            thread::sleep(Duration::from_secs(COMPACTION_INTERVAL_SECS));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(target: &str, timestamp_ms: u64, response_ms: Option<f64>) -> HistoryRecord {
+        HistoryRecord {
+            timestamp_ms,
+            target: target.to_string(),
+            success: response_ms.is_some(),
+            response_ms,
+            in_maintenance: false,
+            clock_offset_ms: None,
+            degraded: false,
+            vpn: None,
+        }
+    }
+
+    #[test]
+    fn groups_by_target_and_period() {
+        let records = [
+            record("b", 61_000, Some(5.0)),
+            record("a", 1_000, Some(10.0)),
+            record("a", 59_999, Some(30.0)),
+            record("a", 30_000, None),
+            record("a", 60_000, Some(20.0)),
+        ];
+        let rollups = aggregate(&records, Resolution::Minute);
+        let keys: Vec<(&str, u64)> = rollups.iter().map(|rollup| (rollup.target.as_str(), rollup.start_ms)).collect();
+        assert_eq!(keys, [("a", 0), ("a", 60_000), ("b", 60_000)]);
+        let first = &rollups[0];
+        assert_eq!((first.probes, first.failed), (3, 1));
+        assert_eq!((first.min_ms, first.mean_ms, first.max_ms, first.p95_ms), (Some(10.0), Some(20.0), Some(30.0), Some(30.0)));
+        assert_eq!(aggregate(&records, Resolution::Hour).len(), 2);
+    }
+
+    #[test]
+    fn p95_is_the_nearest_rank() {
+        let records: Vec<HistoryRecord> = (1..=40).map(|ms| record("a", ms * 10, Some(ms as f64))).collect();
+        assert_eq!(aggregate(&records, Resolution::Minute)[0].p95_ms, Some(38.0));
+    }
+
+    #[test]
+    fn maintenance_and_lost_probes() {
+        let mut during_maintenance = record("a", 0, Some(900.0));
+        during_maintenance.in_maintenance = true;
+        let rollups = aggregate(&[during_maintenance, record("a", 10, None)], Resolution::Minute);
+        assert_eq!(rollups.len(), 1);
+        assert_eq!((rollups[0].probes, rollups[0].mean_ms, rollups[0].p95_ms), (1, None, None));
+        assert_eq!(rollups[0].loss_rate(), 100.0);
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant, SystemTime};
+use crate::traceroute::TraceHop;
+
+const ROUTE_CHECK_INTERVAL_SECS: u64 = 600;
+/// Latency shifts only trigger an early check once this long after the previous one
+const MIN_ROUTE_CHECK_SPACING_SECS: u64 = 60;
+const LATENCY_SHIFT_MIN_MS: f64 = 20.0;
+const LATENCY_SHIFT_RATIO: f64 = 0.5;
+const MAX_ROUTE_CHANGES: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct RouteChange {
+    pub detected: SystemTime,
+    pub detected_at: Instant,
+    pub previous_hash: u64,
+    pub current_hash: u64,
+    pub hops: Vec<TraceHop>,
+}
+
+/// Hashes the sequence of responding hop addresses. Silent hops are skipped so a router
+/// that only sometimes answers doesn't look like a new path.
+pub fn path_hash(hops: &[TraceHop]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for address in hops.iter().filter_map(|hop| hop.address) {
+        address.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Schedules lightweight traceroutes (periodically, or early when latency shifts)
+/// and records when the hop sequence changes
+pub struct RouteMonitor {
+    last_hash: Option<u64>,
+    last_check: Option<Instant>,
+    baseline_latency: Option<f64>,
+    check_running: bool,
+    pub changes: VecDeque<RouteChange>,
+}
+
+impl RouteMonitor {
+    pub fn new() -> Self {
+        Self {
+            last_hash: None,
+            last_check: None,
+            baseline_latency: None,
+            check_running: false,
+            changes: VecDeque::new(),
+        }
+    }
+
+    pub fn should_check(&self, mean_latency: Option<f64>) -> bool {
+        if self.check_running {
+            return false;
+        }
+        let Some(last_check) = self.last_check else {
+            return true;
+        };
+
+        let elapsed = last_check.elapsed();
+        if elapsed >= Duration::from_secs(ROUTE_CHECK_INTERVAL_SECS) {
+            return true;
+        }
+
+        let latency_shifted = match (self.baseline_latency, mean_latency) {
+            (Some(baseline), Some(current)) => {
+                (current - baseline).abs() > LATENCY_SHIFT_MIN_MS.max(baseline * LATENCY_SHIFT_RATIO)
+            }
+            _ => false,
+        };
+        latency_shifted && elapsed >= Duration::from_secs(MIN_ROUTE_CHECK_SPACING_SECS)
+    }
+
+    pub fn check_started(&mut self, mean_latency: Option<f64>) {
+        self.check_running = true;
+        self.last_check = Some(Instant::now());
+        self.baseline_latency = mean_latency;
+    }
+
+    pub fn check_failed(&mut self) {
+        self.check_running = false;
+    }
+
+    /// Records a finished trace and returns the change if the path differs from the previous one
+    pub fn record(&mut self, hops: Vec<TraceHop>) -> Option<&RouteChange> {
+        self.check_running = false;
+        if hops.iter().all(|hop| hop.address.is_none()) {
+            return None;
+        }
+
+        let current_hash = path_hash(&hops);
+        let previous_hash = self.last_hash.replace(current_hash)?;
+        if previous_hash == current_hash {
+            return None;
+        }
+
+        self.changes.push_back(RouteChange {
+            detected: SystemTime::now(),
+            detected_at: Instant::now(),
+            previous_hash,
+            current_hash,
+            hops,
+        });
+        if self.changes.len() > MAX_ROUTE_CHANGES {
+            self.changes.pop_front();
+        }
+        self.changes.back()
+    }
+
+    /// Forgets the known path, e.g. after the target changes
+    pub fn reset(&mut self) {
+        self.last_hash = None;
+        self.last_check = None;
+        self.baseline_latency = None;
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// A weekly recurring time range, e.g. Sunday 03:00–04:00.
+/// A window whose end is not after its start runs past midnight into the next day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// Weekday names or abbreviations ("Sun", "Monday"); empty means every day
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Local start time as "HH:MM"
+    pub start: String,
+    /// Local end time as "HH:MM"
+    pub end: String,
+}
+
+impl TimeWindow {
+    /// Describes the first problem with the window, if any
+    pub fn validate(&self) -> Result<(), String> {
+        for day in &self.days {
+            day.parse::<Weekday>().map_err(|_| format!("Unknown weekday \"{day}\""))?;
+        }
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        Ok(())
+    }
+
+    /// True when `time` falls inside this window; invalid windows never match
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let now = time.time();
+        if start < end {
+            self.applies_on(time.weekday()) && now >= start && now < end
+        } else {
+            // Overnight: the part before midnight belongs to today, the rest to yesterday's window
+            (self.applies_on(time.weekday()) && now >= start)
+                || (self.applies_on((time - Duration::days(1)).weekday()) && now < end)
+        }
+    }
+
+    fn applies_on(&self, weekday: Weekday) -> bool {
+        self.days.is_empty() || self.days.iter().any(|day| day.parse::<Weekday>() == Ok(weekday))
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = if self.days.is_empty() { "Every day".to_string() } else { self.days.join(", ") };
+        write!(f, "{days} {}–{}", self.start, self.end)
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map_err(|_| format!("Invalid time \"{value}\" (expected HH:MM)"))
+}
+
+/// Returns the first window that contains `time`
+pub fn active_window(windows: &[TimeWindow], time: DateTime<Local>) -> Option<&TimeWindow> {
+    windows.iter().find(|window| window.contains(time))
+}
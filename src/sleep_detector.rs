@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant, SystemTime};
+
+const SLEEP_GAP_THRESHOLD_SECS: u64 = 15;
+
+/// A period during which the app was not running, typically system sleep
+#[derive(Debug, Clone)]
+pub struct SleepGap {
+    pub started: SystemTime,
+    pub duration: Duration,
+}
+
+impl SleepGap {
+    pub fn ended(&self) -> SystemTime {
+        self.started + self.duration
+    }
+}
+
+/// Detects suspend/resume by comparing wall-clock and monotonic progress between frames.
+/// The monotonic clock stops during sleep on macOS and Linux, while the wall clock keeps going;
+/// on platforms where it keeps counting, an overlong frame gap while monitoring gives it away.
+/// A large forward wall-clock step looks the same and is treated the same way.
+pub struct SleepDetector {
+    last_check: Option<(Instant, SystemTime)>,
+}
+
+impl SleepDetector {
+    pub fn new() -> Self {
+        Self {
+            last_check: None,
+        }
+    }
+
+    /// Call once per frame. `expected_frame_gap` is the longest gap expected between frames
+    /// (the ping interval while monitoring), or None when frames may legitimately be far apart.
+    pub fn check(&mut self, expected_frame_gap: Option<Duration>) -> Option<SleepGap> {
+        let now = (Instant::now(), SystemTime::now());
+        let previous = self.last_check.replace(now)?;
+
+        let mono_elapsed = now.0.duration_since(previous.0);
+        let wall_elapsed = now.1.duration_since(previous.1).unwrap_or(Duration::from_secs(0));
+        let threshold = Duration::from_secs(SLEEP_GAP_THRESHOLD_SECS);
+
+        let clock_paused = wall_elapsed.saturating_sub(mono_elapsed) > threshold;
+        let frames_stalled = expected_frame_gap
+            .is_some_and(|expected| mono_elapsed > expected * 2 + threshold);
+
+        if clock_paused || frames_stalled {
+            Some(SleepGap {
+                started: previous.1,
+                duration: wall_elapsed.max(mono_elapsed),
+            })
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use snmp2::{Oid, SyncSession, Value, v3};
+use crate::config;
+use crate::ping::PingResult;
+
+pub const SNMP_PORT: u16 = 161;
+/// sysUpTime.0, answered by practically every agent
+pub const SYS_UPTIME_OID: &str = "1.3.6.1.2.1.1.3.0";
+const SNMP_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnmpVersion {
+    #[default]
+    V2c,
+    V3,
+}
+
+impl SnmpVersion {
+    pub const ALL: [SnmpVersion; 2] = [SnmpVersion::V2c, SnmpVersion::V3];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SnmpVersion::V2c => "v2c",
+            SnmpVersion::V3 => "v3",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnmpAuthProtocol {
+    Md5,
+    #[default]
+    Sha1,
+    Sha256,
+}
+
+impl SnmpAuthProtocol {
+    pub const ALL: [SnmpAuthProtocol; 3] = [SnmpAuthProtocol::Md5, SnmpAuthProtocol::Sha1, SnmpAuthProtocol::Sha256];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SnmpAuthProtocol::Md5 => "MD5",
+            SnmpAuthProtocol::Sha1 => "SHA-1",
+            SnmpAuthProtocol::Sha256 => "SHA-256",
+        }
+    }
+
+    fn to_snmp(self) -> v3::AuthProtocol {
+        match self {
+            SnmpAuthProtocol::Md5 => v3::AuthProtocol::Md5,
+            SnmpAuthProtocol::Sha1 => v3::AuthProtocol::Sha1,
+            SnmpAuthProtocol::Sha256 => v3::AuthProtocol::Sha256,
+        }
+    }
+}
+
+/// Credentials and OID for the SNMP probe. v3 uses no authentication when the auth password
+/// is empty, and AES-128 privacy when a privacy password is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnmpSettings {
+    pub version: SnmpVersion,
+    pub community: String,
+    pub oid: String,
+    pub username: String,
+    pub auth_protocol: SnmpAuthProtocol,
+    pub auth_password: String,
+    pub privacy_password: String,
+}
+
+impl Default for SnmpSettings {
+    fn default() -> Self {
+        Self {
+            version: SnmpVersion::V2c,
+            community: "public".to_string(),
+            oid: SYS_UPTIME_OID.to_string(),
+            username: String::new(),
+            auth_protocol: SnmpAuthProtocol::Sha1,
+            auth_password: String::new(),
+            privacy_password: String::new(),
+        }
+    }
+}
+
+impl SnmpSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_oid(&self.oid)?;
+        match self.version {
+            SnmpVersion::V2c if self.community.is_empty() => Err("SNMP v2c needs a community string".to_string()),
+            SnmpVersion::V3 if self.username.trim().is_empty() => Err("SNMP v3 needs a user name".to_string()),
+            SnmpVersion::V3 if !self.privacy_password.is_empty() && self.auth_password.is_empty() => {
+                Err("SNMP v3 privacy needs an authentication password too".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses a dotted OID such as "1.3.6.1.2.1.1.3.0" (a leading dot is allowed)
+pub fn parse_oid(oid: &str) -> Result<Vec<u64>, String> {
+    let oid = oid.trim().trim_start_matches('.');
+    let arcs: Vec<u64> = oid
+        .split('.')
+        .map(|arc| arc.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("\"{oid}\" is not a numeric OID like {SYS_UPTIME_OID}"))?;
+    if arcs.len() < 2 {
+        return Err(format!("\"{oid}\" is not a numeric OID like {SYS_UPTIME_OID}"));
+    }
+    Ok(arcs)
+}
+
+pub struct SnmpProbe;
+
+impl SnmpProbe {
+    /// Sends one GET for the configured OID to `target` (host or host:port). The response time
+    /// covers the GET only; the v3 engine discovery that precedes it isn't counted.
+    pub fn spawn(target: String, settings: SnmpSettings, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let result = match Self::get(&target, &settings) {
+                Ok(response_time) => PingResult::success(timestamp, sent_at, response_time, None),
+                Err(_) => PingResult::failure(timestamp, sent_at),
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn get(target: &str, settings: &SnmpSettings) -> Result<f64, Box<dyn std::error::Error>> {
+        let arcs = parse_oid(&settings.oid)?;
+        let oid = Oid::from(&arcs).map_err(|_| format!("\"{}\" is not a valid OID", settings.oid))?;
+        let address = config::with_default_port(target, SNMP_PORT);
+        let timeout = Some(Duration::from_secs(SNMP_TIMEOUT_SECS));
+
+        let mut session = match settings.version {
+            SnmpVersion::V2c => SyncSession::new_v2c(address, settings.community.as_bytes(), timeout, 0)?,
+            SnmpVersion::V3 => {
+                let auth = if settings.auth_password.is_empty() {
+                    v3::Auth::NoAuthNoPriv
+                } else if settings.privacy_password.is_empty() {
+                    v3::Auth::AuthNoPriv
+                } else {
+                    v3::Auth::AuthPriv {
+                        cipher: v3::Cipher::Aes128,
+                        privacy_password: settings.privacy_password.as_bytes().to_vec(),
+                    }
+                };
+                let security = v3::Security::new(settings.username.trim().as_bytes(), settings.auth_password.as_bytes())
+                    .with_auth_protocol(settings.auth_protocol.to_snmp())
+                    .with_auth(auth);
+                let mut session = SyncSession::new_v3(address, timeout, 0, security)?;
+                session.init()?;
+                session
+            }
+        };
+
+        let started = Instant::now();
+        let mut response = session.get(&oid)?;
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        if response.error_status != 0 {
+            return Err(format!("agent returned error status {}", response.error_status).into());
+        }
+        match response.varbinds.next() {
+            Some((_, Value::NoSuchObject | Value::NoSuchInstance | Value::EndOfMibView)) | None => {
+                Err(format!("agent has no value for {}", settings.oid).into())
+            }
+            Some(_) => Ok(elapsed_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_oid_reads_dotted_arcs() {
+        assert_eq!(parse_oid(SYS_UPTIME_OID), Ok(vec![1, 3, 6, 1, 2, 1, 1, 3, 0]));
+        assert_eq!(parse_oid(" .1.3.6.1 "), Ok(vec![1, 3, 6, 1]));
+    }
+
+    #[test]
+    fn parse_oid_rejects_names_and_single_arcs() {
+        assert!(parse_oid("sysUpTime.0").is_err());
+        assert!(parse_oid("1.3..6").is_err());
+        assert!(parse_oid("1").is_err());
+        assert!(parse_oid("").is_err());
+    }
+}
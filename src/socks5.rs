@@ -0,0 +1,139 @@
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use crate::target_parser;
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const NO_ACCEPTABLE_METHODS: u8 = 0xff;
+const CONNECT: u8 = 1;
+const ATYP_IPV4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_IPV6: u8 = 4;
+
+/// Where a proxied connection failed: at the proxy itself, or between the proxy and the target
+#[derive(Debug)]
+pub enum Socks5Error {
+    Proxy(String),
+    Target(String),
+}
+
+/// Opens a connection to `target` ("host:port") through the SOCKS5 proxy at `proxy`, e.g. an
+/// `ssh -D` dynamic forward. Host names are resolved by the proxy, so the result reflects its
+/// vantage point. Returns the stream and the time the proxy took to connect, including one
+/// round trip to the proxy, in ms.
+pub fn connect(proxy: &str, target: &str, timeout: Duration) -> Result<(TcpStream, f64), Socks5Error> {
+    let proxy_error = |e: std::io::Error| Socks5Error::Proxy(e.to_string());
+    let (host, port) = split_target(target).ok_or_else(|| Socks5Error::Target(format!("{target} is not host:port")))?;
+    let proxy_address = proxy
+        .to_socket_addrs()
+        .map_err(proxy_error)?
+        .next()
+        .ok_or_else(|| Socks5Error::Proxy(format!("{proxy} has no address")))?;
+    let mut stream = TcpStream::connect_timeout(&proxy_address, timeout).map_err(proxy_error)?;
+    stream.set_read_timeout(Some(timeout)).map_err(proxy_error)?;
+    stream.set_write_timeout(Some(timeout)).map_err(proxy_error)?;
+
+    stream.write_all(&[VERSION, 1, NO_AUTHENTICATION]).map_err(proxy_error)?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).map_err(proxy_error)?;
+    match choice {
+        [VERSION, NO_AUTHENTICATION] => {}
+        [VERSION, NO_ACCEPTABLE_METHODS] => return Err(Socks5Error::Proxy("proxy requires authentication".to_string())),
+        _ => return Err(Socks5Error::Proxy("not a SOCKS5 proxy".to_string())),
+    }
+
+    let mut request = vec![VERSION, CONNECT, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(ATYP_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(ATYP_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            let host = target_parser::to_ascii(&host);
+            let length = u8::try_from(host.len()).map_err(|_| Socks5Error::Target(format!("{host} is too long")))?;
+            request.push(ATYP_DOMAIN);
+            request.push(length);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+
+    let started = Instant::now();
+    stream.write_all(&request).map_err(proxy_error)?;
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).map_err(proxy_error)?;
+    let connect_ms = started.elapsed().as_secs_f64() * 1000.0;
+    if reply[0] != VERSION {
+        return Err(Socks5Error::Proxy("not a SOCKS5 proxy".to_string()));
+    }
+    if reply[1] != 0 {
+        return Err(reply_error(reply[1]));
+    }
+    // Skip the address the proxy connected from, so the stream starts with the target's data
+    let bound_length = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => {
+            let mut length = [0u8; 1];
+            stream.read_exact(&mut length).map_err(proxy_error)?;
+            length[0] as usize
+        }
+        _ => return Err(Socks5Error::Proxy("malformed proxy reply".to_string())),
+    };
+    let mut bound = vec![0u8; bound_length + 2];
+    stream.read_exact(&mut bound).map_err(proxy_error)?;
+    stream.set_read_timeout(None).map_err(proxy_error)?;
+    stream.set_write_timeout(None).map_err(proxy_error)?;
+    Ok((stream, connect_ms))
+}
+
+/// "host:port" or "[v6]:port" into the host without brackets and the port
+fn split_target(target: &str) -> Option<(String, u16)> {
+    let (host, port) = target.trim().rsplit_once(':')?;
+    let host = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+    Some((host.to_string(), port.parse().ok()?))
+}
+
+/// Reply codes from RFC 1928; the server failure and ruleset codes are the proxy's own problem
+fn reply_error(code: u8) -> Socks5Error {
+    match code {
+        1 => Socks5Error::Proxy("general proxy failure".to_string()),
+        2 => Socks5Error::Proxy("connection not allowed by the proxy's rules".to_string()),
+        3 => Socks5Error::Target("network unreachable from the proxy".to_string()),
+        4 => Socks5Error::Target("host unreachable from the proxy".to_string()),
+        5 => Socks5Error::Target("connection refused".to_string()),
+        6 => Socks5Error::Target("timed out".to_string()),
+        7 => Socks5Error::Proxy("proxy does not support CONNECT".to_string()),
+        8 => Socks5Error::Proxy("proxy does not support this address type".to_string()),
+        code => Socks5Error::Proxy(format!("proxy replied with error {code}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_host_and_port() {
+        assert_eq!(split_target("example.com:443"), Some(("example.com".to_string(), 443)));
+        assert_eq!(split_target(" 192.0.2.1:22 "), Some(("192.0.2.1".to_string(), 22)));
+        assert_eq!(split_target("[2001:db8::1]:80"), Some(("2001:db8::1".to_string(), 80)));
+        assert_eq!(split_target("example.com"), None);
+        assert_eq!(split_target("example.com:http"), None);
+    }
+
+    #[test]
+    fn blames_the_proxy_or_the_target_by_reply_code() {
+        for code in [1, 2, 7, 8, 9] {
+            assert!(matches!(reply_error(code), Socks5Error::Proxy(_)), "code {code}");
+        }
+        for code in [3, 4, 5, 6] {
+            assert!(matches!(reply_error(code), Socks5Error::Target(_)), "code {code}");
+        }
+    }
+}
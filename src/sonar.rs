@@ -0,0 +1,87 @@
+use std::sync::mpsc;
+#[cfg(feature = "sound")]
+use std::thread;
+#[cfg(feature = "sound")]
+use std::time::Duration;
+#[cfg(feature = "sound")]
+use rodio::source::{SineWave, Source};
+#[cfg(feature = "sound")]
+use rodio::{OutputStream, Sink};
+
+#[cfg(feature = "sound")]
+const TONE_DURATION_MS: u64 = 120;
+#[cfg(feature = "sound")]
+const FADE_MS: u64 = 10;
+#[cfg(feature = "sound")]
+const VOLUME: f32 = 0.2;
+/// Pitch range: fast replies sound high, slow ones low
+const FAST_PITCH_HZ: f32 = 1500.0;
+const SLOW_PITCH_HZ: f32 = 200.0;
+const FAST_RTT_MS: f64 = 1.0;
+const SLOW_RTT_MS: f64 = 1000.0;
+
+/// Maps RTT to pitch on a log scale so 5 ms and 50 ms are as easy to tell apart as 50 ms and 500 ms
+pub fn pitch_for(response_time_ms: f64) -> f32 {
+    let clamped = response_time_ms.clamp(FAST_RTT_MS, SLOW_RTT_MS);
+    let position = ((clamped / FAST_RTT_MS).log10() / (SLOW_RTT_MS / FAST_RTT_MS).log10()) as f32;
+    FAST_PITCH_HZ * (SLOW_PITCH_HZ / FAST_PITCH_HZ).powf(position)
+}
+
+/// Plays a short tone per probe. The audio stream lives on its own thread (it can't be moved
+/// between threads) and is only opened the first time a tone is needed.
+pub struct Sonar {
+    sender: Option<mpsc::Sender<f32>>,
+}
+
+impl Sonar {
+    /// False when built without the `sound` feature; the tones are then never played
+    pub const AVAILABLE: bool = cfg!(feature = "sound");
+
+    pub fn new() -> Self {
+        Self { sender: None }
+    }
+
+    /// Beeps at a pitch for the response time; a lost probe stays silent
+    pub fn play(&mut self, response_time_ms: Option<f64>) {
+        let Some(response_time) = response_time_ms.filter(|_| Self::AVAILABLE) else {
+            return;
+        };
+        let pitch = pitch_for(response_time);
+        let sender = self.sender.get_or_insert_with(Self::start);
+        if sender.send(pitch).is_err() {
+            // The audio thread gave up (no output device); try again on the next probe
+            self.sender = None;
+        }
+    }
+
+    #[cfg(feature = "sound")]
+    fn start() -> mpsc::Sender<f32> {
+        let (sender, receiver) = mpsc::channel::<f32>();
+        thread::spawn(move || {
+            let (_stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(e) => {
+                    log::warn!("Sonar: no audio output: {e}");
+                    return;
+                }
+            };
+            let Ok(sink) = Sink::try_new(&handle) else {
+                return;
+            };
+            for pitch in receiver {
+                sink.append(
+                    SineWave::new(pitch)
+                        .take_duration(Duration::from_millis(TONE_DURATION_MS))
+                        .fade_in(Duration::from_millis(FADE_MS))
+                        .amplify(VOLUME),
+                );
+            }
+        });
+        sender
+    }
+
+    #[cfg(not(feature = "sound"))]
+    fn start() -> mpsc::Sender<f32> {
+        mpsc::channel().0
+    }
+}
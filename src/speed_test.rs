@@ -0,0 +1,48 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::load_generator::{Direction, LoadGenerator};
+
+const PHASE_SECS: u64 = 8;
+const STREAMS: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedTestResult {
+    pub finished: SystemTime,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    /// Mean ping latency of the monitored target when the test finished, for correlation
+    pub latency_ms: Option<f64>,
+}
+
+pub struct SpeedTest;
+
+impl SpeedTest {
+    /// Saturates the download, then the upload, with parallel transfers and reports the throughput
+    pub fn spawn(sender: mpsc::Sender<Result<SpeedTestResult, String>>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let download = Self::measure(Direction::Download);
+            let upload = Self::measure(Direction::Upload);
+            let result = if download == 0.0 && upload == 0.0 {
+                Err("The speed test server is unreachable".to_string())
+            } else {
+                Ok(SpeedTestResult {
+                    finished: SystemTime::now(),
+                    download_mbps: download,
+                    upload_mbps: upload,
+                    latency_ms: None,
+                })
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn measure(direction: Direction) -> f64 {
+        let load = LoadGenerator::start(direction, STREAMS);
+        thread::sleep(Duration::from_secs(PHASE_SECS));
+        load.stop().mbps()
+    }
+}
@@ -0,0 +1,84 @@
+/// Earlier successful samples whose median is the baseline a sample is judged against
+const BASELINE_SAMPLES: usize = 10;
+/// Fewer samples than this give no trustworthy baseline, so nothing counts as a spike yet
+const MIN_BASELINE_SAMPLES: usize = 5;
+/// A sample must exceed the baseline by this factor…
+const SPIKE_FACTOR: f64 = 3.0;
+/// …and by at least this many ms, so 1 ms → 4 ms on a LAN isn't a spike
+const SPIKE_MIN_EXCESS_MS: f64 = 20.0;
+/// Longer runs of high samples are a sustained shift (congestion, a new route), not spikes
+const MAX_SPIKE_RUN: usize = 2;
+
+/// Flags the samples that are isolated latency spikes: far above the rolling median of the
+/// samples before them, with latency back to normal right after. Lost probes are skipped, and
+/// a high sample at the end isn't flagged until the next sample shows it was isolated.
+pub fn spike_flags(response_times: impl IntoIterator<Item = Option<f64>>) -> Vec<bool> {
+    let response_times: Vec<Option<f64>> = response_times.into_iter().collect();
+    let mut flags = vec![false; response_times.len()];
+    let mut baseline: Vec<f64> = Vec::new();
+    // Indices of the current run of high samples
+    let mut run: Vec<usize> = Vec::new();
+
+    for (index, response_time) in response_times.iter().enumerate() {
+        let Some(ms) = *response_time else {
+            continue;
+        };
+        let recent = &baseline[baseline.len().saturating_sub(BASELINE_SAMPLES)..];
+        let high = recent.len() >= MIN_BASELINE_SAMPLES && {
+            let median = median(recent);
+            ms > median * SPIKE_FACTOR && ms > median + SPIKE_MIN_EXCESS_MS
+        };
+        if high {
+            run.push(index);
+        } else {
+            if run.len() <= MAX_SPIKE_RUN {
+                for &spike in &run {
+                    flags[spike] = true;
+                }
+            }
+            run.clear();
+        }
+        baseline.push(ms);
+    }
+    flags
+}
+
+fn median(samples: &[f64]) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) / 2.0 } else { sorted[middle] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(tail: &[Option<f64>]) -> Vec<bool> {
+        let samples: Vec<Option<f64>> = [Some(10.0); 6].into_iter().chain(tail.iter().copied()).collect();
+        spike_flags(samples)[6..].to_vec()
+    }
+
+    #[test]
+    fn isolated_high_samples_are_spikes() {
+        assert_eq!(flags(&[Some(100.0), Some(10.0)]), [true, false]);
+        assert_eq!(flags(&[Some(100.0), Some(90.0), Some(10.0)]), [true, true, false]);
+        assert_eq!(flags(&[Some(100.0), None, Some(10.0)]), [true, false, false]);
+    }
+
+    #[test]
+    fn sustained_shift_is_not_a_spike() {
+        assert_eq!(flags(&[Some(100.0), Some(100.0), Some(100.0), Some(10.0)]), [false; 4]);
+    }
+
+    #[test]
+    fn last_sample_waits_for_the_next() {
+        assert_eq!(flags(&[Some(100.0)]), [false]);
+    }
+
+    #[test]
+    fn small_or_early_jumps_are_not_spikes() {
+        assert_eq!(flags(&[Some(29.0), Some(10.0)]), [false, false]);
+        assert_eq!(spike_flags([Some(10.0), Some(10.0), Some(100.0), Some(10.0)]), [false; 4]);
+    }
+}
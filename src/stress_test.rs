@@ -0,0 +1,208 @@
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+use eframe::egui;
+use surge_ping::{Client, Config, ICMP, PingIdentifier, PingSequence};
+use tokio::time::MissedTickBehavior;
+use crate::probe_limiter::{self, ProbeLimiter, SharedLimiter};
+use crate::target_parser;
+
+pub const MIN_RATE: u32 = 10;
+pub const MAX_RATE: u32 = 50;
+pub const MAX_DURATION_SECS: u64 = 60;
+/// Replies slower than this count as lost; short so a burst doesn't hide behind a long wait
+const PROBE_TIMEOUT_SECS: u64 = 2;
+const PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// Aggregate of a rapid-ping run so far. Probes still waiting for a reply count as neither
+/// answered nor lost.
+#[derive(Debug, Clone)]
+pub struct StressSummary {
+    pub target: IpAddr,
+    pub rate: u32,
+    pub sent: usize,
+    pub total: usize,
+    pub answered: usize,
+    pub lost: usize,
+    pub min_ms: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    /// Mean difference between consecutive replies
+    pub jitter_ms: Option<f64>,
+    /// Most probes lost in a row, the sub-second bursts the normal cadence can't see
+    pub longest_loss_burst: usize,
+}
+
+impl StressSummary {
+    fn from_outcomes(target: IpAddr, rate: u32, sent: usize, outcomes: &[Option<Option<f64>>]) -> Self {
+        let replies: Vec<f64> = outcomes.iter().filter_map(|outcome| outcome.flatten()).collect();
+        let lost = outcomes.iter().filter(|outcome| matches!(outcome, Some(None))).count();
+        let mean_ms = (!replies.is_empty()).then(|| replies.iter().sum::<f64>() / replies.len() as f64);
+        let jitter_ms = (replies.len() > 1).then(|| {
+            replies.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum::<f64>() / (replies.len() - 1) as f64
+        });
+
+        let mut longest_loss_burst = 0;
+        let mut burst = 0;
+        for outcome in &outcomes[..sent] {
+            burst = if matches!(outcome, Some(None)) { burst + 1 } else { 0 };
+            longest_loss_burst = longest_loss_burst.max(burst);
+        }
+
+        Self {
+            target,
+            rate,
+            sent,
+            total: outcomes.len(),
+            answered: replies.len(),
+            lost,
+            min_ms: replies.iter().copied().reduce(f64::min),
+            mean_ms,
+            max_ms: replies.iter().copied().reduce(f64::max),
+            jitter_ms,
+            longest_loss_burst,
+        }
+    }
+
+    pub fn loss_rate(&self) -> f64 {
+        let completed = self.answered + self.lost;
+        if completed == 0 { 0.0 } else { self.lost as f64 / completed as f64 * 100.0 }
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.sent as f32 / self.total.max(1) as f32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StressUpdate {
+    Progress(StressSummary),
+    Finished(Result<StressSummary, String>),
+}
+
+pub struct StressTest;
+
+impl StressTest {
+    /// Pings `target` `rate` times a second for `duration_secs`, without waiting for replies
+    /// between probes, and streams the aggregate a few times a second. Probes take slots of the
+    /// app's probe limiter; when it is full the burst waits rather than skipping probes.
+    pub fn spawn(target: String, rate: u32, duration_secs: u64, limiter: SharedLimiter, sender: mpsc::Sender<StressUpdate>, repaint: egui::Context) {
+        let rate = rate.clamp(MIN_RATE, MAX_RATE);
+        let duration_secs = duration_secs.clamp(1, MAX_DURATION_SECS);
+        thread::spawn(move || {
+            let send = |update| {
+                let _ = sender.send(update);
+                repaint.request_repaint();
+            };
+            let result = Self::resolve(&target).and_then(|address| {
+                let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+                rt.block_on(Self::run(address, rate, duration_secs, &limiter, |summary| send(StressUpdate::Progress(summary))))
+            });
+            send(StressUpdate::Finished(result));
+        });
+    }
+
+    async fn run(
+        target: IpAddr,
+        rate: u32,
+        duration_secs: u64,
+        limiter: &SharedLimiter,
+        on_progress: impl Fn(StressSummary),
+    ) -> Result<StressSummary, String> {
+        let config = match target {
+            IpAddr::V4(_) => Config::default(),
+            IpAddr::V6(_) => Config::builder().kind(ICMP::V6).build(),
+        };
+        // One client shared by reference: dropping a clone of it cancels every ping in flight
+        let client = Arc::new(Client::new(&config).map_err(|e| format!("Could not open an ICMP socket: {e}"))?);
+        let identifier = PingIdentifier(std::process::id() as u16);
+        let total = rate as usize * duration_secs as usize;
+        let mut outcomes: Vec<Option<Option<f64>>> = vec![None; total];
+        let (reply_sender, mut replies) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / rate as f64));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        let mut next_progress = Instant::now();
+        let key = target.to_string();
+        for sequence in 0..total {
+            ticker.tick().await;
+            ProbeLimiter::reserve(limiter, &key).await;
+            let client = client.clone();
+            let reply_sender = reply_sender.clone();
+            let limiter = limiter.clone();
+            let key = key.clone();
+            tokio::spawn(async move {
+                let mut pinger = client.pinger(target, identifier).await;
+                pinger.timeout(Duration::from_secs(PROBE_TIMEOUT_SECS));
+                let reply = pinger.ping(PingSequence(sequence as u16), &[]).await.ok();
+                probe_limiter::lock(&limiter).finished(&key);
+                let _ = reply_sender.send((sequence, reply.map(|(_, rtt)| rtt.as_secs_f64() * 1000.0)));
+            });
+
+            while let Ok((sequence, reply)) = replies.try_recv() {
+                outcomes[sequence] = Some(reply);
+            }
+            if Instant::now() >= next_progress {
+                on_progress(StressSummary::from_outcomes(target, rate, sequence + 1, &outcomes));
+                next_progress = Instant::now() + Duration::from_millis(PROGRESS_INTERVAL_MS);
+            }
+        }
+
+        drop(reply_sender);
+        while let Some((sequence, reply)) = replies.recv().await {
+            outcomes[sequence] = Some(reply);
+        }
+        Ok(StressSummary::from_outcomes(target, rate, total, &outcomes))
+    }
+
+    fn resolve(target: &str) -> Result<IpAddr, String> {
+        let target = target.trim();
+        if let Ok(ip) = target.parse::<IpAddr>() {
+            return Ok(ip);
+        }
+        (target_parser::to_ascii(target).as_str(), 0)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .map(|address| address.ip())
+            .ok_or_else(|| format!("Could not resolve {target}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn summary(sent: usize, outcomes: &[Option<Option<f64>>]) -> StressSummary {
+        StressSummary::from_outcomes(Ipv4Addr::LOCALHOST.into(), MIN_RATE, sent, outcomes)
+    }
+
+    #[test]
+    fn figures_cover_replies_only() {
+        let summary = summary(5, &[Some(Some(10.0)), Some(None), Some(Some(14.0)), Some(Some(12.0)), None, None]);
+        assert_eq!((summary.total, summary.answered, summary.lost), (6, 3, 1));
+        assert_eq!((summary.min_ms, summary.mean_ms, summary.max_ms), (Some(10.0), Some(12.0), Some(14.0)));
+        assert_eq!(summary.jitter_ms, Some(3.0));
+        assert_eq!(summary.loss_rate(), 25.0);
+        assert_eq!(summary.progress(), 5.0 / 6.0);
+    }
+
+    #[test]
+    fn longest_loss_burst_counts_losses_in_a_row() {
+        let lost = Some(None);
+        let reply = Some(Some(5.0));
+        let summary = summary(7, &[lost, reply, lost, lost, lost, reply, lost]);
+        assert_eq!(summary.longest_loss_burst, 3);
+        assert_eq!(summary.jitter_ms, Some(0.0));
+    }
+
+    #[test]
+    fn nothing_back_yet() {
+        let summary = summary(0, &[None, None]);
+        assert_eq!((summary.mean_ms, summary.jitter_ms), (None, None));
+        assert_eq!(summary.loss_rate(), 0.0);
+        assert_eq!(summary.progress(), 0.0);
+    }
+}
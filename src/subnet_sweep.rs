@@ -0,0 +1,132 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Instant, SystemTime};
+use eframe::egui;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use crate::ping_executor::PingExecutor;
+use crate::probe_limiter::{self, ProbeLimiter, SharedLimiter};
+
+/// Pings in flight at once; enough to sweep a /24 in a few seconds without flooding the LAN
+const MAX_CONCURRENT_PINGS: usize = 64;
+/// Largest range accepted (a /20), so a typo like /8 doesn't start sixteen million pings
+const MAX_SWEEP_HOSTS: u32 = 4096;
+
+#[derive(Debug, Clone)]
+pub struct SweepHost {
+    pub address: IpAddr,
+    pub response_time: f64,
+}
+
+#[derive(Debug, Clone)]
+pub enum SweepUpdate {
+    Found(SweepHost),
+    Progress { done: usize, total: usize },
+    /// The sweep couldn't start; Finished follows
+    Failed(String),
+    Finished,
+}
+
+/// Parses an IPv4 CIDR range such as "192.168.1.0/24" into its host addresses.
+/// The network and broadcast addresses are left out for prefixes shorter than /31.
+pub fn parse_cidr(range: &str) -> Result<Vec<Ipv4Addr>, String> {
+    let (address, prefix) = range.trim().split_once('/').ok_or("Enter a range like 192.168.1.0/24")?;
+    let address: Ipv4Addr = address.parse().map_err(|_| format!("\"{address}\" is not an IPv4 address"))?;
+    let prefix: u32 = prefix
+        .parse()
+        .ok()
+        .filter(|prefix| *prefix <= 32)
+        .ok_or_else(|| format!("\"/{prefix}\" is not a prefix length between 0 and 32"))?;
+
+    let size = 1u64 << (32 - prefix);
+    if size > MAX_SWEEP_HOSTS as u64 {
+        return Err(format!("/{prefix} has {size} addresses; sweep at most {MAX_SWEEP_HOSTS} (a /20) at a time"));
+    }
+    let mask = u32::MAX << (32 - prefix);
+    let network = u32::from(address) & mask;
+    let (first, last) = if prefix < 31 {
+        (network + 1, network + (size as u32 - 2))
+    } else {
+        (network, network + (size as u32 - 1))
+    };
+    Ok((first..=last).map(Ipv4Addr::from).collect())
+}
+
+pub struct SubnetSweep;
+
+impl SubnetSweep {
+    /// Pings every host with bounded concurrency, reporting responsive hosts as they answer.
+    /// Each ping also takes a slot of the app's probe limiter, so a sweep can't crowd out monitoring.
+    pub fn spawn(hosts: Vec<Ipv4Addr>, limiter: SharedLimiter, sender: mpsc::Sender<SweepUpdate>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let rt = match tokio::runtime::Runtime::new() {
+                Ok(rt) => rt,
+                Err(e) => {
+                    let _ = sender.send(SweepUpdate::Failed(format!("Could not start the sweep: {e}")));
+                    let _ = sender.send(SweepUpdate::Finished);
+                    repaint.request_repaint();
+                    return;
+                }
+            };
+            rt.block_on(async {
+                let total = hosts.len();
+                let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_PINGS));
+                let mut pings = JoinSet::new();
+                for host in hosts {
+                    let permits = permits.clone();
+                    let limiter = limiter.clone();
+                    pings.spawn(async move {
+                        let _permit = permits.acquire_owned().await;
+                        let address = IpAddr::V4(host);
+                        let key = address.to_string();
+                        ProbeLimiter::reserve(&limiter, &key).await;
+                        let result = PingExecutor::execute_ping(address, SystemTime::now(), Instant::now(), None).await;
+                        probe_limiter::lock(&limiter).finished(&key);
+                        result.response_time.filter(|_| result.success).map(|response_time| SweepHost { address, response_time })
+                    });
+                }
+
+                let mut done = 0;
+                while let Some(finished) = pings.join_next().await {
+                    done += 1;
+                    if let Ok(Some(host)) = finished {
+                        let _ = sender.send(SweepUpdate::Found(host));
+                    }
+                    let _ = sender.send(SweepUpdate::Progress { done, total });
+                    repaint.request_repaint();
+                }
+            });
+            let _ = sender.send(SweepUpdate::Finished);
+            repaint.request_repaint();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slash_24_leaves_out_network_and_broadcast() {
+        let hosts = parse_cidr("192.168.1.77/24").unwrap();
+        assert_eq!(hosts.len(), 254);
+        assert_eq!(hosts.first(), Some(&Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(hosts.last(), Some(&Ipv4Addr::new(192, 168, 1, 254)));
+    }
+
+    #[test]
+    fn point_to_point_and_single_host_keep_every_address() {
+        assert_eq!(parse_cidr("10.0.0.4/31").unwrap(), vec![Ipv4Addr::new(10, 0, 0, 4), Ipv4Addr::new(10, 0, 0, 5)]);
+        assert_eq!(parse_cidr(" 10.0.0.9/32 ").unwrap(), vec![Ipv4Addr::new(10, 0, 0, 9)]);
+    }
+
+    #[test]
+    fn rejects_malformed_and_oversized_ranges() {
+        assert!(parse_cidr("192.168.1.0").is_err());
+        assert!(parse_cidr("192.168.1/24").is_err());
+        assert!(parse_cidr("192.168.1.0/33").is_err());
+        assert!(parse_cidr("10.0.0.0/8").is_err());
+        assert_eq!(parse_cidr("10.0.0.0/20").unwrap().len(), 4094);
+    }
+}
@@ -0,0 +1,42 @@
+use std::io::ErrorKind;
+use std::process::{Command, Output};
+
+/// Windows process creation flag that keeps console tools from flashing a window
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Builds a command for a system tool without popping up a console window on Windows
+pub fn command(program: &str) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// Runs a system tool and returns its stdout, or a readable error if it could not be started
+pub fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    run_if_installed(program, args).unwrap_or_else(|| Err(format!("Could not run {program}: it is not installed")))
+}
+
+/// Like `run`, but None when the tool isn't installed, so the caller can try an alternative
+/// without masking the tool's own failures
+pub fn run_if_installed(program: &str, args: &[&str]) -> Option<Result<String, String>> {
+    match command(program).args(args).output() {
+        Ok(output) => Some(stdout_of(program, output)),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => Some(Err(format!("Could not run {program}: {e}"))),
+    }
+}
+
+fn stdout_of(program: &str, Output { stdout, stderr, status }: Output) -> Result<String, String> {
+    let stdout = String::from_utf8_lossy(&stdout).into_owned();
+    if stdout.trim().is_empty() && !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(format!("{program} failed: {}", stderr.trim()));
+    }
+    Ok(stdout)
+}
@@ -0,0 +1,98 @@
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::ping::FailureCause;
+use crate::system_command;
+
+/// "time=12.3 ms" (Linux, macOS), "time=12ms" / "time<1ms" (Windows) and their translations,
+/// which keep the `=`/`<` and the unit
+static REPLY_TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([=<])\s*(\d+(?:[.,]\d+)?)\s*ms").expect("valid regex"));
+
+/// Sends one echo request with the operating system's `ping` command, which is allowed to send
+/// ICMP when this app isn't, and returns the round trip time in ms
+pub fn ping(ip: IpAddr, timeout_secs: u64) -> Result<f64, FailureCause> {
+    let ip = ip.to_string();
+    let timeout = timeout_arg(timeout_secs);
+    let output = system_command::command(program(&ip))
+        .args([COUNT_FLAG, "1", TIMEOUT_FLAG, &timeout, &ip])
+        .output()
+        .map_err(|e| {
+            log::debug!("Could not run ping: {e}");
+            if e.kind() == std::io::ErrorKind::PermissionDenied { FailureCause::PermissionDenied } else { FailureCause::NoReply }
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Windows exits with 0 when a router reports the destination unreachable
+    if let Some(cause) = parse_error_cause(&stdout) {
+        return Err(cause);
+    }
+    if !output.status.success() {
+        return Err(FailureCause::NoReply);
+    }
+    parse_reply_ms(&stdout).ok_or(FailureCause::NoReply)
+}
+
+/// ICMP errors as the English `ping` commands report them; translations count as no reply
+pub fn parse_error_cause(output: &str) -> Option<FailureCause> {
+    let output = output.to_lowercase();
+    if output.contains("unreachable") {
+        Some(FailureCause::Unreachable)
+    } else if output.contains("exceeded") || output.contains("expired in transit") {
+        Some(FailureCause::TimeExceeded)
+    } else {
+        None
+    }
+}
+
+/// Round trip time of the first reply in `ping` output. Windows only says "<1ms" for fast
+/// replies, which is taken as half the bound so it stays below a real 1 ms reply.
+pub fn parse_reply_ms(output: &str) -> Option<f64> {
+    let captures = REPLY_TIME.captures(output)?;
+    let ms: f64 = captures[2].replace(',', ".").parse().ok()?;
+    Some(if &captures[1] == "<" { ms / 2.0 } else { ms })
+}
+
+#[cfg(windows)]
+const COUNT_FLAG: &str = "-n";
+#[cfg(not(windows))]
+const COUNT_FLAG: &str = "-c";
+#[cfg(windows)]
+const TIMEOUT_FLAG: &str = "-w";
+#[cfg(not(windows))]
+const TIMEOUT_FLAG: &str = "-W";
+
+/// Windows and macOS take the timeout in ms, Linux in seconds
+fn timeout_arg(timeout_secs: u64) -> String {
+    if cfg!(any(windows, target_os = "macos")) { (timeout_secs * 1000).to_string() } else { timeout_secs.to_string() }
+}
+
+/// macOS has a separate command for IPv6
+fn program(ip: &str) -> &'static str {
+    if cfg!(target_os = "macos") && ip.contains(':') { "ping6" } else { "ping" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_reply_times_of_each_platform() {
+        assert_eq!(parse_reply_ms("64 bytes from 192.0.2.1: icmp_seq=1 ttl=57 time=12.3 ms"), Some(12.3));
+        assert_eq!(parse_reply_ms("Reply from 192.0.2.1: bytes=32 time=12ms TTL=57"), Some(12.0));
+        assert_eq!(parse_reply_ms("Antwort von 192.0.2.1: Bytes=32 Zeit=4,5 ms TTL=57"), Some(4.5));
+        assert_eq!(parse_reply_ms("Request timed out."), None);
+    }
+
+    #[test]
+    fn sub_millisecond_windows_reply_stays_below_one() {
+        let ms = parse_reply_ms("Reply from 192.168.1.1: bytes=32 time<1ms TTL=64").unwrap();
+        assert!(ms > 0.0 && ms < 1.0);
+    }
+
+    #[test]
+    fn tells_icmp_errors_apart() {
+        assert_eq!(parse_error_cause("Reply from 192.168.1.1: Destination host unreachable."), Some(FailureCause::Unreachable));
+        assert_eq!(parse_error_cause("From 10.0.0.1 icmp_seq=1 Time to live exceeded"), Some(FailureCause::TimeExceeded));
+        assert_eq!(parse_error_cause("Reply from 10.0.0.1: TTL expired in transit."), Some(FailureCause::TimeExceeded));
+        assert_eq!(parse_error_cause("64 bytes from 192.0.2.1: icmp_seq=1 ttl=57 time=12.3 ms"), None);
+    }
+}
@@ -0,0 +1,229 @@
+use std::net::{IpAddr, Ipv6Addr};
+
+/// Host and optional port extracted from whatever was typed or pasted into a target field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTarget {
+    pub host: String,
+    /// The explicit port, or the scheme's well-known port for a URL
+    pub port: Option<u16>,
+    /// Interface of a link-local IPv6 address, e.g. `eth0` in `fe80::1%eth0` (or `12` on Windows)
+    pub zone: Option<String>,
+}
+
+impl ParsedTarget {
+    /// "host:port" when a port is known, bracketing IPv6 addresses; otherwise just the host
+    pub fn with_port(&self) -> String {
+        let host = match &self.zone {
+            Some(zone) => format!("{}%{zone}", self.host),
+            None => self.host.clone(),
+        };
+        match self.port {
+            Some(port) if host.contains(':') => format!("[{host}]:{port}"),
+            Some(port) => format!("{host}:{port}"),
+            None => host,
+        }
+    }
+}
+
+/// Extracts the host (and port) from an IP address, hostname, `host:port`, `[v6]:port` or a URL
+/// such as `https://user@example.com:8443/path?q`, ignoring surrounding whitespace. IPv6
+/// addresses may carry a zone ID (`fe80::1%eth0`, or `%25eth0` inside a URL).
+/// Returns None when no usable host remains.
+pub fn parse_target(input: &str) -> Option<ParsedTarget> {
+    let input = input.trim();
+    let (scheme, rest) = match input.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+        None => (None, input),
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+
+    let (host, port) = split_port(authority)?;
+    let (host, zone) = match host.split_once('%') {
+        Some((address, zone)) => {
+            // URLs percent-encode the separator
+            let zone = if scheme.is_some() { zone.strip_prefix("25").unwrap_or(zone) } else { zone };
+            address.parse::<Ipv6Addr>().ok()?;
+            if zone.is_empty() || !zone.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+                return None;
+            }
+            (address, Some(zone.to_string()))
+        }
+        None => (host, None),
+    };
+    if host.parse::<IpAddr>().is_err() && !is_hostname(host) {
+        return None;
+    }
+    let port = port.or_else(|| scheme.as_deref().and_then(default_port));
+    Some(ParsedTarget { host: host.to_string(), port, zone })
+}
+
+/// Splits off a port; an empty port (a half-typed "host:") is ignored
+fn split_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        host.split('%').next()?.parse::<Ipv6Addr>().ok()?;
+        let port = match after {
+            "" | ":" => None,
+            _ => Some(parse_port(after.strip_prefix(':')?)?),
+        };
+        return Some((host, port));
+    }
+    // A bare IPv6 address has colons but no port
+    if authority.split('%').next()?.parse::<IpAddr>().is_ok() {
+        return Some((authority, None));
+    }
+    match authority.rsplit_once(':') {
+        Some((host, "")) => Some((host, None)),
+        Some((host, port)) => Some((host, Some(parse_port(port)?))),
+        None => Some((authority, None)),
+    }
+}
+
+/// `target` with an internationalized hostname converted to punycode, since resolvers only take
+/// ASCII; IP addresses, ports and names that can't be converted are left as they are
+pub fn to_ascii(target: &str) -> String {
+    map_host(target, |host| if host.is_ascii() { None } else { idna::domain_to_ascii(host).ok() })
+}
+
+/// `target` with punycode labels (`xn--…`) shown in Unicode
+pub fn to_unicode(target: &str) -> String {
+    map_host(target, |host| {
+        let lowercase = host.to_ascii_lowercase();
+        if !lowercase.split('.').any(|label| label.starts_with("xn--")) {
+            return None;
+        }
+        let (unicode, result) = idna::domain_to_unicode(host);
+        result.is_ok().then_some(unicode)
+    })
+}
+
+/// Replaces the host part of "host" or "host:port" where `convert` returns a new one
+fn map_host(target: &str, convert: impl Fn(&str) -> Option<String>) -> String {
+    let target = target.trim();
+    if target.parse::<IpAddr>().is_ok() || target.starts_with('[') || target.contains('%') {
+        return target.to_string();
+    }
+    let (host, port) = match target.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (target, None),
+    };
+    match (convert(host), port) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host,
+        (None, _) => target.to_string(),
+    }
+}
+
+fn parse_port(port: &str) -> Option<u16> {
+    port.parse::<u16>().ok().filter(|port| *port != 0)
+}
+
+fn is_hostname(host: &str) -> bool {
+    !host.is_empty() && host.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        "ftp" => Some(21),
+        "ssh" | "sftp" => Some(22),
+        "smtp" => Some(25),
+        "imap" => Some(143),
+        "imaps" => Some(993),
+        "rdp" => Some(3389),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(host: &str, port: Option<u16>) -> Option<ParsedTarget> {
+        Some(ParsedTarget { host: host.to_string(), port, zone: None })
+    }
+
+    fn zoned(host: &str, port: Option<u16>, zone: &str) -> Option<ParsedTarget> {
+        Some(ParsedTarget { host: host.to_string(), port, zone: Some(zone.to_string()) })
+    }
+
+    #[test]
+    fn plain_hosts_and_addresses() {
+        assert_eq!(parse_target("example.com"), parsed("example.com", None));
+        assert_eq!(parse_target("8.8.8.8"), parsed("8.8.8.8", None));
+        assert_eq!(parse_target("2001:db8::1"), parsed("2001:db8::1", None));
+        assert_eq!(parse_target("example.com."), parsed("example.com.", None));
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        assert_eq!(parse_target("  example.com\t"), parsed("example.com", None));
+        assert_eq!(parse_target("example.com:8443 "), parsed("example.com", Some(8443)));
+    }
+
+    #[test]
+    fn ports() {
+        assert_eq!(parse_target("192.168.1.1:22"), parsed("192.168.1.1", Some(22)));
+        assert_eq!(parse_target("[2001:db8::1]:8080"), parsed("2001:db8::1", Some(8080)));
+        assert_eq!(parse_target("[2001:db8::1]"), parsed("2001:db8::1", None));
+        assert_eq!(parse_target("example.com:"), parsed("example.com", None));
+        assert_eq!(parse_target("example.com:0"), None);
+        assert_eq!(parse_target("example.com:70000"), None);
+        assert_eq!(parse_target("example.com:ssh"), None);
+    }
+
+    #[test]
+    fn urls() {
+        assert_eq!(parse_target("https://example.com/path"), parsed("example.com", Some(443)));
+        assert_eq!(parse_target("http://example.com:8080/a?b=c#d"), parsed("example.com", Some(8080)));
+        assert_eq!(parse_target("HTTPS://Example.com"), parsed("Example.com", Some(443)));
+        assert_eq!(parse_target("ssh://user@host.lan"), parsed("host.lan", Some(22)));
+        assert_eq!(parse_target("https://[2001:db8::1]/"), parsed("2001:db8::1", Some(443)));
+        assert_eq!(parse_target("gopher://example.com"), parsed("example.com", None));
+        assert_eq!(parse_target("example.com/status"), parsed("example.com", None));
+    }
+
+    #[test]
+    fn unusable_input() {
+        assert_eq!(parse_target(""), None);
+        assert_eq!(parse_target("   "), None);
+        assert_eq!(parse_target("https://"), None);
+        assert_eq!(parse_target("exa mple.com"), None);
+        assert_eq!(parse_target("[not-an-address]:80"), None);
+    }
+
+    #[test]
+    fn internationalized_names() {
+        assert_eq!(parse_target("https://bücher.example/"), parsed("bücher.example", Some(443)));
+        assert_eq!(to_ascii("bücher.example"), "xn--bcher-kva.example");
+        assert_eq!(to_ascii("bücher.example:8443"), "xn--bcher-kva.example:8443");
+        assert_eq!(to_ascii("example.com:22"), "example.com:22");
+        assert_eq!(to_ascii("2001:db8::1"), "2001:db8::1");
+        assert_eq!(to_unicode("xn--bcher-kva.example"), "bücher.example");
+        assert_eq!(to_unicode("XN--BCHER-KVA.example:80"), "bücher.example:80");
+        assert_eq!(to_unicode("example.com"), "example.com");
+    }
+
+    #[test]
+    fn zone_ids() {
+        assert_eq!(parse_target("fe80::1%eth0"), zoned("fe80::1", None, "eth0"));
+        assert_eq!(parse_target("fe80::1%12"), zoned("fe80::1", None, "12"));
+        assert_eq!(parse_target("[fe80::1%en0]:22"), zoned("fe80::1", Some(22), "en0"));
+        assert_eq!(parse_target("http://[fe80::1%25eth0]/"), zoned("fe80::1", Some(80), "eth0"));
+        assert_eq!(parse_target("fe80::1%"), None);
+        assert_eq!(parse_target("192.168.1.1%eth0"), None);
+        assert_eq!(parse_target("example.com%eth0"), None);
+        assert_eq!(parse_target("[fe80::1%en0]:22").unwrap().with_port(), "[fe80::1%en0]:22");
+        assert_eq!(parse_target("fe80::1%eth0").unwrap().with_port(), "fe80::1%eth0");
+        assert_eq!(to_ascii("fe80::1%eth0"), "fe80::1%eth0");
+    }
+
+    #[test]
+    fn with_port_formats_ipv6_in_brackets() {
+        assert_eq!(parse_target("https://[2001:db8::1]").unwrap().with_port(), "[2001:db8::1]:443");
+        assert_eq!(parse_target("https://example.com").unwrap().with_port(), "example.com:443");
+        assert_eq!(parse_target("example.com").unwrap().with_port(), "example.com");
+    }
+}
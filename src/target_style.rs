@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use crate::target_parser;
+
+/// Icons offered in the style editor; any emoji or symbol can be typed instead
+pub const SUGGESTED_ICONS: [&str; 10] = ["🏠", "🏢", "🔒", "☁", "🌐", "📡", "🖥", "🖨", "🎮", "📞"];
+
+/// Accent colors offered in the style editor
+pub const ACCENT_COLORS: [[u8; 3]; 8] = [
+    [80, 160, 255],
+    [255, 140, 0],
+    [120, 200, 80],
+    [230, 80, 80],
+    [180, 110, 230],
+    [40, 190, 190],
+    [240, 200, 40],
+    [240, 120, 180],
+];
+
+/// Styles keyed by the target as typed (host, host:port, command or agent)
+pub type TargetStyles = BTreeMap<String, TargetStyle>;
+
+/// How a target is shown instead of its raw address
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetStyle {
+    /// Display name, e.g. "Office VPN"; empty shows the address
+    pub label: String,
+    /// Accent for the target's name and chart line
+    pub color: Option<[u8; 3]>,
+    /// Emoji or symbol shown before the name
+    pub icon: String,
+}
+
+impl TargetStyle {
+    pub fn is_empty(&self) -> bool {
+        self.label.trim().is_empty() && self.color.is_none() && self.icon.trim().is_empty()
+    }
+
+    /// The label, or `target` with punycode shown in Unicode
+    pub fn name(&self, target: &str) -> String {
+        if self.label.trim().is_empty() { target_parser::to_unicode(target) } else { self.label.trim().to_string() }
+    }
+
+    /// Icon and name, e.g. "🔒 Office VPN"
+    pub fn decorated(&self, target: &str) -> String {
+        match self.icon.trim() {
+            "" => self.name(target),
+            icon => format!("{icon} {}", self.name(target)),
+        }
+    }
+
+    /// Name with the address for files read outside the app, e.g. "Office VPN (10.8.0.1)";
+    /// no icon, since spreadsheets and PDF fonts may not have the glyph
+    pub fn export_name(&self, target: &str) -> String {
+        if self.label.trim().is_empty() { target.to_string() } else { format!("{} ({target})", self.label.trim()) }
+    }
+
+    pub fn accent(&self) -> Option<Color32> {
+        self.color.map(|[r, g, b]| Color32::from_rgb(r, g, b))
+    }
+}
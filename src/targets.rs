@@ -0,0 +1,349 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::circle_color::CircleColor;
+use crate::config::{ProbeType, validate_target};
+use crate::dns_probe::{DEFAULT_QUERY_NAME, DnsProbe};
+use crate::ping::PingResult;
+use crate::ping_executor::PingExecutor;
+use crate::probe_limiter::ProbeLimiter;
+use crate::schedule::{self, TimeWindow};
+use crate::target_parser;
+
+/// Results older than this don't count towards a target's loss rate
+const WATCH_WINDOW_SECS: u64 = 60;
+/// Header names accepted in the first column of an imported file
+const HEADER_NAMES: [&str; 4] = ["host", "hostname", "target", "address"];
+
+/// One host in the target list, with optional overrides of the global thresholds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetEntry {
+    pub host: String,
+    pub label: String,
+    pub green_threshold: Option<u64>,
+    pub yellow_threshold: Option<u64>,
+    /// Heading the entry is listed under, e.g. "Office" or "Cloud"
+    pub group: String,
+    /// `Dns` queries the host as a resolver; every other probe type pings it
+    pub probe: ProbeType,
+    /// Name a `Dns` entry looks up; empty means example.com
+    pub query_name: String,
+    /// Position in the path when the router was pinned from a traceroute
+    pub hop: Option<u32>,
+    /// Probed only within these windows, whether watched in the list or monitored as the main
+    /// target; empty means around the clock
+    pub monitoring_hours: Vec<TimeWindow>,
+    /// MAC address for Wake-on-LAN, e.g. "aa:bb:cc:dd:ee:ff"; empty when not set
+    pub mac: String,
+}
+
+impl TargetEntry {
+    /// True when monitoring hours are configured and none of them covers `now`
+    pub fn outside_schedule(&self, now: DateTime<Local>) -> bool {
+        !self.monitoring_hours.is_empty() && schedule::active_window(&self.monitoring_hours, now).is_none()
+    }
+
+    /// True when any per-target setting is set, which makes a settings-only entry worth keeping
+    pub fn has_settings(&self) -> bool {
+        !self.mac.trim().is_empty() || !self.query_name.trim().is_empty() || !self.monitoring_hours.is_empty()
+    }
+
+    /// Name the DNS probe looks up when this entry is probed as a resolver
+    pub fn lookup_name(&self) -> &str {
+        let name = self.query_name.trim();
+        if name.is_empty() { DEFAULT_QUERY_NAME } else { name }
+    }
+
+    /// The label, or the host with punycode shown in Unicode
+    pub fn display_name(&self) -> String {
+        if self.label.is_empty() { target_parser::to_unicode(&self.host) } else { self.label.clone() }
+    }
+}
+
+/// Parses a host inventory: one target per line as `host[,label[,green_ms[,yellow_ms[,group]]]]`.
+/// Blank lines, `#` comments and a header row are skipped. Returns the entries that parsed
+/// and a message for each line that didn't.
+pub fn parse_target_list(content: &str) -> (Vec<TargetEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut first_row = true;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split([',', ';', '\t']).map(|field| field.trim().trim_matches('"')).collect();
+        if std::mem::take(&mut first_row) && HEADER_NAMES.contains(&fields[0].to_lowercase().as_str()) {
+            continue;
+        }
+        match parse_entry(&fields) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("Line {}: {e}", index + 1)),
+        }
+    }
+    (entries, errors)
+}
+
+fn parse_entry(fields: &[&str]) -> Result<TargetEntry, String> {
+    let host = fields[0];
+    validate_target(host)?;
+    let threshold = |position: usize, name: &str| -> Result<Option<u64>, String> {
+        match fields.get(position).filter(|value| !value.is_empty()) {
+            Some(value) => value.parse().map(Some).map_err(|_| format!("{name} threshold \"{value}\" is not a whole number of ms")),
+            None => Ok(None),
+        }
+    };
+    let entry = TargetEntry {
+        host: host.to_string(),
+        label: fields.get(1).map(|label| label.to_string()).unwrap_or_default(),
+        green_threshold: threshold(2, "Green")?,
+        yellow_threshold: threshold(3, "Yellow")?,
+        group: fields.get(4).map(|group| group.to_string()).unwrap_or_default(),
+        probe: ProbeType::Icmp,
+        query_name: String::new(),
+        hop: None,
+        monitoring_hours: Vec::new(),
+        mac: String::new(),
+    };
+    if let (Some(green), Some(yellow)) = (entry.green_threshold, entry.yellow_threshold)
+        && green >= yellow
+    {
+        return Err(format!("green threshold ({green} ms) must be lower than yellow ({yellow} ms)"));
+    }
+    Ok(entry)
+}
+
+/// Reads and parses a target list file
+pub fn import_file(path: &Path) -> Result<(Vec<TargetEntry>, Vec<String>), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    Ok(parse_target_list(&content))
+}
+
+struct WatchedTarget {
+    entry: TargetEntry,
+    sender: mpsc::Sender<PingResult>,
+    receiver: mpsc::Receiver<PingResult>,
+    recent: VecDeque<PingResult>,
+}
+
+/// Latest figures for one entry of the target list
+pub struct TargetStatus {
+    pub color: CircleColor,
+    pub last_response_ms: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub loss_rate: f64,
+    /// Outcomes in a row like the last one: positive for replies, negative for losses
+    pub streak: i64,
+}
+
+impl TargetStatus {
+    /// Summary of `recent` results, oldest first; None when there are none yet
+    pub fn from_results(recent: &VecDeque<PingResult>, green_threshold: u64, yellow_threshold: u64, very_slow_threshold: u64) -> Option<Self> {
+        let last = recent.back()?;
+        let failed = recent.iter().filter(|result| !result.success).count();
+        let mut replies: Vec<f64> = recent.iter().filter(|result| result.success).filter_map(|result| result.response_time).collect();
+        replies.sort_by(f64::total_cmp);
+        let p95_ms = (!replies.is_empty()).then(|| {
+            let rank = (replies.len() as f64 * 0.95).ceil() as usize;
+            replies[rank.clamp(1, replies.len()) - 1]
+        });
+        let streak = recent.iter().rev().take_while(|result| result.success == last.success).count() as i64;
+        let color = if last.success {
+            CircleColor::from_ping_response(last.response_time, green_threshold, yellow_threshold, very_slow_threshold)
+        } else {
+            CircleColor::Red
+        };
+        Some(TargetStatus {
+            color,
+            last_response_ms: last.response_time,
+            mean_ms: (!replies.is_empty()).then(|| replies.iter().sum::<f64>() / replies.len() as f64),
+            p95_ms,
+            loss_rate: failed as f64 / recent.len() as f64 * 100.0,
+            streak: if last.success { streak } else { -streak },
+        })
+    }
+}
+
+/// Column of the target table, which can be sorted by any of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetColumn {
+    Target,
+    LastRtt,
+    Mean,
+    P95,
+    Loss,
+    Streak,
+    Status,
+}
+
+impl TargetColumn {
+    pub const ALL: [TargetColumn; 7] = [
+        TargetColumn::Target,
+        TargetColumn::LastRtt,
+        TargetColumn::Mean,
+        TargetColumn::P95,
+        TargetColumn::Loss,
+        TargetColumn::Streak,
+        TargetColumn::Status,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TargetColumn::Target => "Target",
+            TargetColumn::LastRtt => "Last RTT",
+            TargetColumn::Mean => "Mean",
+            TargetColumn::P95 => "p95",
+            TargetColumn::Loss => "Loss",
+            TargetColumn::Streak => "Streak",
+            TargetColumn::Status => "Status",
+        }
+    }
+
+    /// Orders two rows by this column, given each row's name and status; targets without
+    /// results yet sort last in either direction
+    pub fn compare(self, a: (&str, Option<&TargetStatus>), b: (&str, Option<&TargetStatus>), ascending: bool) -> Ordering {
+        let value = |status: Option<&TargetStatus>| {
+            status.and_then(|status| match self {
+                TargetColumn::Target => None,
+                TargetColumn::LastRtt => status.last_response_ms,
+                TargetColumn::Mean => status.mean_ms,
+                TargetColumn::P95 => status.p95_ms,
+                TargetColumn::Loss => Some(status.loss_rate),
+                TargetColumn::Streak => Some(status.streak as f64),
+                TargetColumn::Status => Some(status.color.severity() as f64),
+            })
+        };
+        let ordering = match self {
+            TargetColumn::Target => a.0.to_lowercase().cmp(&b.0.to_lowercase()),
+            _ => match (value(a.1), value(b.1)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        if ascending { ordering } else { ordering.reverse() }
+    }
+}
+
+/// Pings every entry of the target list on the main schedule and keeps a minute of results each
+pub struct TargetWatch {
+    watched: Vec<WatchedTarget>,
+}
+
+impl TargetWatch {
+    pub fn new() -> Self {
+        Self { watched: Vec::new() }
+    }
+
+    /// Follows edits to the list; entries that are unchanged keep their results
+    pub fn sync(&mut self, entries: &[TargetEntry]) {
+        self.watched.retain(|watched| entries.contains(&watched.entry));
+        for entry in entries {
+            if self.watched.iter().any(|watched| &watched.entry == entry) {
+                continue;
+            }
+            let (sender, receiver) = mpsc::channel();
+            self.watched.push(WatchedTarget { entry: entry.clone(), sender, receiver, recent: VecDeque::new() });
+        }
+    }
+
+    pub fn probe_all(&self, limiter: &mut ProbeLimiter, ctx: &egui::Context) {
+        let now = Local::now();
+        for watched in &self.watched {
+            if watched.entry.outside_schedule(now) || !limiter.try_start(&watched.entry.host) {
+                continue;
+            }
+            let (host, sender) = (watched.entry.host.clone(), watched.sender.clone());
+            match watched.entry.probe {
+                ProbeType::Dns => DnsProbe::spawn(host, watched.entry.lookup_name().to_string(), sender, ctx.clone()),
+                _ => PingExecutor::resolve_and_ping(host, sender, ctx.clone()),
+            }
+        }
+    }
+
+    pub fn poll(&mut self, limiter: &mut ProbeLimiter) {
+        let cutoff = Instant::now().checked_sub(Duration::from_secs(WATCH_WINDOW_SECS));
+        for watched in &mut self.watched {
+            for result in watched.receiver.try_iter() {
+                limiter.finished(&watched.entry.host);
+                watched.recent.push_back(result);
+            }
+            while watched.recent.front().is_some_and(|result| cutoff.is_some_and(|cutoff| result.sent_at < cutoff)) {
+                watched.recent.pop_front();
+            }
+        }
+    }
+
+    /// Status of `entry` colored with its own thresholds, falling back to the global ones
+    pub fn status(&self, entry: &TargetEntry, green_threshold: u64, yellow_threshold: u64, very_slow_threshold: u64) -> Option<TargetStatus> {
+        let watched = self.watched.iter().find(|watched| &watched.entry == entry)?;
+        TargetStatus::from_results(
+            &watched.recent,
+            entry.green_threshold.unwrap_or(green_threshold),
+            entry.yellow_threshold.unwrap_or(yellow_threshold),
+            very_slow_threshold,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(mean_ms: Option<f64>) -> TargetStatus {
+        TargetStatus { color: CircleColor::Green, last_response_ms: mean_ms, mean_ms, p95_ms: mean_ms, loss_rate: 0.0, streak: 1 }
+    }
+
+    fn sorted(ascending: bool) -> Vec<&'static str> {
+        let rows = [("b", Some(status(Some(20.0)))), ("none", None), ("a", Some(status(Some(10.0)))), ("lost", Some(status(None)))];
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by(|&a, &b| TargetColumn::Mean.compare((rows[a].0, rows[a].1.as_ref()), (rows[b].0, rows[b].1.as_ref()), ascending));
+        order.into_iter().map(|i| rows[i].0).collect()
+    }
+
+    #[test]
+    fn missing_values_sort_last_ascending() {
+        assert_eq!(&sorted(true)[..2], ["a", "b"]);
+    }
+
+    #[test]
+    fn missing_values_sort_last_descending() {
+        assert_eq!(&sorted(false)[..2], ["b", "a"]);
+    }
+
+    #[test]
+    fn names_sort_case_insensitively() {
+        let column = TargetColumn::Target;
+        assert_eq!(column.compare(("alpha", None), ("Beta", None), true), Ordering::Less);
+        assert_eq!(column.compare(("alpha", None), ("Beta", None), false), Ordering::Greater);
+    }
+
+    #[test]
+    fn target_list_reads_optional_columns() {
+        let (entries, errors) = parse_target_list("host,label\n# office\n\n192.168.1.1;Router;20;80;LAN\n\"example.com\"\t\t\t150\n");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].host.as_str(), entries[0].label.as_str(), entries[0].group.as_str()), ("192.168.1.1", "Router", "LAN"));
+        assert_eq!((entries[0].green_threshold, entries[0].yellow_threshold), (Some(20), Some(80)));
+        assert_eq!((entries[1].host.as_str(), entries[1].label.as_str()), ("example.com", ""));
+        assert_eq!((entries[1].green_threshold, entries[1].yellow_threshold), (None, Some(150)));
+    }
+
+    #[test]
+    fn target_list_reports_bad_lines_and_keeps_the_rest() {
+        let (entries, errors) = parse_target_list("192.168.1.1,Router,fast\n10.0.0.1\n10.0.0.2,,90,40\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].host, "10.0.0.1");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Line 1:"));
+        assert!(errors[1].starts_with("Line 3:"));
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::Read;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use eframe::egui;
+use regex::Regex;
+use crate::ping::{FailureCause, PingResult};
+use crate::network_watcher;
+use crate::socks5::{self, Socks5Error};
+use crate::target_parser;
+
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+/// Banners are sent right after accept; a daemon that stays silent this long counts as broken
+const BANNER_TIMEOUT_SECS: u64 = 3;
+const MAX_BANNER_BYTES: usize = 1024;
+
+/// Set while the SOCKS5 proxy itself fails, so the warning is logged once per outage rather than per probe
+static PROXY_FAILING: AtomicBool = AtomicBool::new(false);
+
+/// Checks that `target` is "host:port" with a usable port
+pub fn validate_address(target: &str) -> Result<(), String> {
+    match target.trim().rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port != 0) => Ok(()),
+        _ => Err("TCP probe needs host:port, e.g. example.com:22".to_string()),
+    }
+}
+
+/// Compiles the optional expect pattern; an empty string means any accepted connection is healthy
+pub fn parse_expect(expect: &str) -> Result<Option<Regex>, String> {
+    if expect.is_empty() {
+        return Ok(None);
+    }
+    Regex::new(expect).map(Some).map_err(|e| format!("Expect pattern \"{expect}\" is not a valid regex: {e}"))
+}
+
+pub struct TcpProbe;
+
+impl TcpProbe {
+    /// Connects to `target` and reports the connect time. With an expect pattern the first bytes
+    /// the server sends must match it, otherwise the result is marked degraded. With a `proxy`
+    /// ("host:port" of a SOCKS5 proxy) the connection is made from the proxy instead.
+    pub fn spawn(target: String, expect: String, proxy: String, sender: mpsc::Sender<PingResult>, repaint: egui::Context) {
+        let timestamp = SystemTime::now();
+        let sent_at = Instant::now();
+
+        thread::spawn(move || {
+            let connected = if proxy.trim().is_empty() {
+                Self::connect(&target).map_err(|_| None)
+            } else {
+                Self::connect_via(proxy.trim(), &target)
+            };
+            let result = match connected {
+                Ok((stream, response_time)) => {
+                    let result = PingResult::success(timestamp, sent_at, response_time, None);
+                    // Through a proxy the peer is the proxy, not the target
+                    let result = match stream.peer_addr() {
+                        Ok(address) if proxy.trim().is_empty() => result.with_target(address.ip()),
+                        _ => result,
+                    };
+                    match parse_expect(&expect) {
+                        Ok(Some(pattern)) if !Self::banner_matches(stream, &pattern) => result.with_degraded(),
+                        _ => result,
+                    }
+                }
+                Err(None) => PingResult::failure(timestamp, sent_at),
+                Err(Some(cause)) => PingResult::failure(timestamp, sent_at).with_cause(cause),
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn connect(target: &str) -> std::io::Result<(TcpStream, f64)> {
+        let scoped = target_parser::parse_target(target).filter(|parsed| parsed.zone.is_some());
+        let address = match scoped {
+            // The resolver doesn't understand zone IDs such as fe80::1%eth0
+            Some(parsed) => network_watcher::scoped_socket_addr(&parsed)
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("No interface for {target}")))?,
+            None => target_parser::to_ascii(target)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("{target} has no address")))?,
+        };
+        let started = Instant::now();
+        let stream = TcpStream::connect_timeout(&address, Duration::from_secs(CONNECT_TIMEOUT_SECS))?;
+        Ok((stream, started.elapsed().as_secs_f64() * 1000.0))
+    }
+
+    /// Fails with ProxyFailed when the proxy itself is the problem
+    fn connect_via(proxy: &str, target: &str) -> Result<(TcpStream, f64), Option<FailureCause>> {
+        match socks5::connect(proxy, target, Duration::from_secs(CONNECT_TIMEOUT_SECS)) {
+            Ok(connected) => {
+                if PROXY_FAILING.swap(false, Ordering::Relaxed) {
+                    log::info!("SOCKS5 proxy {proxy} is working again");
+                }
+                Ok(connected)
+            }
+            Err(Socks5Error::Proxy(e)) => {
+                if !PROXY_FAILING.swap(true, Ordering::Relaxed) {
+                    log::warn!("SOCKS5 proxy {proxy} failed ({e}); TCP probes through it fail until it works again");
+                }
+                Err(Some(FailureCause::ProxyFailed))
+            }
+            Err(Socks5Error::Target(e)) => {
+                PROXY_FAILING.store(false, Ordering::Relaxed);
+                log::debug!("{target} via {proxy}: {e}");
+                Err(None)
+            }
+        }
+    }
+
+    /// Reads until the pattern matches, the peer closes, the buffer fills or the timeout passes
+    fn banner_matches(mut stream: TcpStream, pattern: &Regex) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(BANNER_TIMEOUT_SECS);
+        let mut banner = Vec::new();
+        let mut chunk = [0u8; 256];
+        while banner.len() < MAX_BANNER_BYTES {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || stream.set_read_timeout(Some(remaining)).is_err() {
+                break;
+            }
+            match stream.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    banner.extend_from_slice(&chunk[..read]);
+                    if pattern.is_match(&String::from_utf8_lossy(&banner)) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How full timestamps are written in lists, tooltips and CSV exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// "2024-05-01 13:45:02" on screen; RFC 3339 with the local offset in exports
+    #[default]
+    Local,
+    /// "2024-05-01T04:45:02Z", comparable across machines and time zones
+    Rfc3339Utc,
+    /// Seconds since 1970-01-01 UTC, for scripts
+    UnixEpoch,
+}
+
+impl TimestampFormat {
+    pub const ALL: [TimestampFormat; 3] = [TimestampFormat::Local, TimestampFormat::Rfc3339Utc, TimestampFormat::UnixEpoch];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimestampFormat::Local => "Local time",
+            TimestampFormat::Rfc3339Utc => "RFC 3339 (UTC)",
+            TimestampFormat::UnixEpoch => "Unix epoch",
+        }
+    }
+
+    /// For reading on screen, to the second
+    pub fn display(self, time: SystemTime) -> String {
+        match self {
+            TimestampFormat::Local => DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string(),
+            TimestampFormat::Rfc3339Utc => DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true),
+            TimestampFormat::UnixEpoch => epoch_secs(time).floor().to_string(),
+        }
+    }
+
+    /// For files, to the millisecond and unambiguous about the time zone
+    pub fn export(self, time: SystemTime) -> String {
+        match self {
+            TimestampFormat::Local => DateTime::<Local>::from(time).to_rfc3339_opts(SecondsFormat::Millis, false),
+            TimestampFormat::Rfc3339Utc => DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true),
+            TimestampFormat::UnixEpoch => format!("{:.3}", epoch_secs(time)),
+        }
+    }
+}
+
+/// Negative before 1970, which only a badly set clock produces
+fn epoch_secs(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
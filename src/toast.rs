@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+use eframe::egui::Color32;
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// Most toasts on screen at once; errors and alerts with actions stay until dismissed or pushed out by newer ones
+const MAX_VISIBLE: usize = 4;
+/// Toasts kept for the history drawer
+const MAX_HISTORY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    pub fn icon(self) -> &'static str {
+        match self {
+            ToastLevel::Info => "ℹ",
+            ToastLevel::Success => "✔",
+            ToastLevel::Warning => "⚠",
+            ToastLevel::Error => "✖",
+        }
+    }
+
+    pub fn color(self) -> Color32 {
+        match self {
+            ToastLevel::Info => Color32::from_rgb(120, 170, 255),
+            ToastLevel::Success => Color32::from_rgb(100, 200, 100),
+            ToastLevel::Warning => Color32::from_rgb(255, 165, 0),
+            ToastLevel::Error => Color32::from_rgb(255, 90, 90),
+        }
+    }
+}
+
+/// Buttons offered on alert toasts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastAction {
+    /// Traces the route again for the outage with this id
+    RunTraceroute(u64),
+    /// Leaves focus mode and shows the monitor tab
+    OpenMonitor,
+    /// Holds back taskbar flashes and push notifications for an hour
+    Silence,
+}
+
+impl ToastAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            ToastAction::RunTraceroute(_) => "Run traceroute",
+            ToastAction::OpenMonitor => "Open monitor",
+            ToastAction::Silence => "Silence for 1 h",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: u64,
+    pub time: SystemTime,
+    pub level: ToastLevel,
+    pub message: String,
+    pub actions: Vec<ToastAction>,
+    shown_at: Instant,
+    dismissed: bool,
+}
+
+impl Toast {
+    fn is_visible(&self) -> bool {
+        // Errors and toasts offering actions stay until dismissed, so there is time to read or act on them
+        !self.dismissed && (self.level == ToastLevel::Error || !self.actions.is_empty() || self.shown_at.elapsed() < TOAST_DURATION)
+    }
+}
+
+/// Short messages shown over the window for a few seconds, with a history of the recent ones
+#[derive(Default)]
+pub struct Toasts {
+    history: VecDeque<Toast>,
+    next_id: u64,
+    /// Toasts pushed since the history drawer was last opened
+    pub unread: usize,
+}
+
+impl Toasts {
+    /// Shows `message`, unless the same message is still on screen
+    pub fn push(&mut self, level: ToastLevel, message: impl Into<String>) {
+        self.push_with_actions(level, message, Vec::new());
+    }
+
+    pub fn push_with_actions(&mut self, level: ToastLevel, message: impl Into<String>, actions: Vec<ToastAction>) {
+        let message = message.into();
+        if let Some(shown) = self.history.iter_mut().rev().find(|toast| toast.message == message && toast.is_visible()) {
+            shown.shown_at = Instant::now();
+            return;
+        }
+        self.next_id += 1;
+        self.history.push_back(Toast { id: self.next_id, time: SystemTime::now(), level, message, actions, shown_at: Instant::now(), dismissed: false });
+        if self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.unread += 1;
+    }
+
+    /// A success or an error toast for the outcome of an export or similar action
+    pub fn push_result(&mut self, result: &Result<String, String>) {
+        match result {
+            Ok(message) => self.push(ToastLevel::Success, message.clone()),
+            Err(e) => self.push(ToastLevel::Error, e.clone()),
+        }
+    }
+
+    /// Toasts to draw, oldest first
+    pub fn visible(&self) -> Vec<&Toast> {
+        let visible: Vec<&Toast> = self.history.iter().filter(|toast| toast.is_visible()).collect();
+        visible[visible.len().saturating_sub(MAX_VISIBLE)..].to_vec()
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        if let Some(toast) = self.history.iter_mut().find(|toast| toast.id == id) {
+            toast.dismissed = true;
+        }
+    }
+
+    /// Newest first
+    pub fn history(&self) -> impl Iterator<Item = &Toast> {
+        self.history.iter().rev()
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.unread = 0;
+    }
+}
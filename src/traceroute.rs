@@ -0,0 +1,108 @@
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::thread;
+use eframe::egui;
+use crate::ping;
+use crate::system_command;
+use crate::target_parser;
+
+const MAX_HOPS: u32 = 30;
+const HOP_TIMEOUT_SECS: u32 = 2;
+
+#[derive(Debug, Clone)]
+pub struct TraceHop {
+    pub number: u32,
+    pub address: Option<IpAddr>,
+    pub rtt_ms: Option<f64>,
+}
+
+impl std::fmt::Display for TraceHop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let address = self.address.map_or("*".to_string(), |ip| ip.to_string());
+        match self.rtt_ms {
+            Some(rtt) => write!(f, "{:>2}  {address}  {}", self.number, ping::format_ms(rtt)),
+            None => write!(f, "{:>2}  {address}", self.number),
+        }
+    }
+}
+
+pub type TracerouteResult = Result<Vec<TraceHop>, String>;
+
+pub struct Traceroute;
+
+impl Traceroute {
+    /// Runs a traceroute in the background and sends `(tag, result)` when it finishes
+    pub fn spawn<T: Send + 'static>(
+        target: String,
+        tag: T,
+        sender: mpsc::Sender<(T, TracerouteResult)>,
+        repaint: egui::Context,
+    ) {
+        thread::spawn(move || {
+            let result = Self::run(&target);
+            let _ = sender.send((tag, result));
+            repaint.request_repaint();
+        });
+    }
+
+    /// Runs the platform traceroute tool (numeric output, one probe per hop) and parses the hops
+    pub fn run(target: &str) -> TracerouteResult {
+        if target.is_empty() || target.starts_with('-') {
+            return Err(format!("Refusing to trace invalid target '{target}'"));
+        }
+        let output = Self::run_tool(&target_parser::to_ascii(target))?;
+        let hops: Vec<TraceHop> = output.lines().filter_map(parse_hop_line).collect();
+        if hops.is_empty() {
+            return Err(format!("No hops in traceroute output: {}", output.trim()));
+        }
+        Ok(hops)
+    }
+
+    #[cfg(windows)]
+    fn run_tool(target: &str) -> Result<String, String> {
+        let max_hops = MAX_HOPS.to_string();
+        let timeout_ms = (HOP_TIMEOUT_SECS * 1000).to_string();
+        system_command::run("tracert", &["-d", "-h", &max_hops, "-w", &timeout_ms, target])
+    }
+
+    #[cfg(not(windows))]
+    fn run_tool(target: &str) -> Result<String, String> {
+        let max_hops = MAX_HOPS.to_string();
+        let timeout = HOP_TIMEOUT_SECS.to_string();
+        // tracepath ships with iputils on Linux distributions that lack traceroute; a traceroute
+        // that is installed but fails (unreachable target, no permission) reports its own error
+        system_command::run_if_installed("traceroute", &["-n", "-q", "1", "-w", &timeout, "-m", &max_hops, target])
+            .unwrap_or_else(|| system_command::run("tracepath", &["-n", "-m", &max_hops, target]))
+    }
+}
+
+/// Parses one hop line from traceroute, tracepath or tracert output.
+/// Lines start with the hop number; the first IP-looking token is the hop address and
+/// round-trip times appear as `1.23 ms`, `1.23ms` or `<1 ms`.
+pub fn parse_hop_line(line: &str) -> Option<TraceHop> {
+    let mut tokens = line.split_whitespace().peekable();
+    let number = tokens.next()?.trim_end_matches(':').parse::<u32>().ok()?;
+
+    let mut address = None;
+    let mut rtts = Vec::new();
+    while let Some(token) = tokens.next() {
+        let token = token.trim_matches(|c| c == '(' || c == ')' || c == '[' || c == ']');
+        if address.is_none() && let Ok(ip) = token.parse::<IpAddr>() {
+            address = Some(ip);
+            continue;
+        }
+
+        let value = token.trim_start_matches('<');
+        let rtt = match value.strip_suffix("ms") {
+            Some(ms) => ms.parse::<f64>().ok(),
+            None if tokens.peek() == Some(&"ms") => value.parse::<f64>().ok(),
+            None => None,
+        };
+        if let Some(rtt) = rtt {
+            rtts.push(rtt);
+        }
+    }
+
+    let rtt_ms = (!rtts.is_empty()).then(|| rtts.iter().sum::<f64>() / rtts.len() as f64);
+    Some(TraceHop { number, address, rtt_ms })
+}
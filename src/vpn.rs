@@ -0,0 +1,26 @@
+use std::net::IpAddr;
+use crate::network_watcher::InterfaceStatus;
+
+/// Interface name prefixes of tunnels: TUN/TAP (OpenVPN), WireGuard, macOS utun, PPP/PPTP, IPsec,
+/// GlobalProtect, Cisco AnyConnect and ZeroTier
+const TUNNEL_PREFIXES: [&str; 10] = ["tun", "tap", "wg", "utun", "ppp", "ipsec", "gpd", "cscotun", "zt", "vti"];
+
+/// Words in Windows adapter names ("OpenVPN TAP-Windows6", "Wintun Userspace Tunnel") and in the
+/// interfaces of common clients
+const TUNNEL_WORDS: [&str; 9] = ["vpn", "tunnel", "wireguard", "wintun", "anyconnect", "globalprotect", "fortinet", "tailscale", "nordlynx"];
+
+/// Whether `name` looks like a VPN or other tunnel interface
+pub fn is_tunnel(name: &str) -> bool {
+    let name = name.to_lowercase();
+    TUNNEL_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) || TUNNEL_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// Name of the tunnel interface that owns `local_ip`, i.e. the VPN traffic from that address goes
+/// through. Matching the route's source address rather than listing tunnels matters on macOS,
+/// where several idle utun interfaces always exist.
+pub fn tunnel_carrying(interfaces: &[InterfaceStatus], local_ip: IpAddr) -> Option<String> {
+    interfaces
+        .iter()
+        .find(|interface| interface.addresses.contains(&local_ip) && is_tunnel(&interface.name))
+        .map(|interface| interface.name.clone())
+}
@@ -0,0 +1,31 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+/// Discard port, the conventional destination for magic packets
+const WOL_PORT: u16 = 9;
+const MAC_REPETITIONS: usize = 16;
+
+/// Parses "aa:bb:cc:dd:ee:ff", "aa-bb-cc-dd-ee-ff" or "aabbccddeeff"
+pub fn parse_mac(value: &str) -> Result<[u8; 6], String> {
+    let hex: String = value.chars().filter(|c| !matches!(c, ':' | '-' | '.' | ' ')).collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("\"{value}\" is not a MAC address (expected aa:bb:cc:dd:ee:ff)"));
+    }
+    let mut mac = [0u8; 6];
+    for (i, byte) in mac.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Ok(mac)
+}
+
+/// Broadcasts a magic packet: six 0xFF bytes followed by the MAC repeated sixteen times
+pub fn send_magic_packet(mac: [u8; 6]) -> std::io::Result<()> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..MAC_REPETITIONS {
+        packet.extend_from_slice(&mac);
+    }
+
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&packet, (Ipv4Addr::BROADCAST, WOL_PORT))?;
+    Ok(())
+}
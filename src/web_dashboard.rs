@@ -0,0 +1,336 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use serde::Serialize;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+use crate::remote_agent;
+
+pub const DEFAULT_DASHBOARD_PORT: u16 = 7879;
+/// The page, with its script and styles inline so there's nothing else to serve
+const PAGE: &str = include_str!("web_dashboard.html");
+/// Open pages are pushed a new snapshot at most this often
+const PUBLISH_INTERVAL_MS: u64 = 1000;
+const ACCEPT_POLL_MS: u64 = 200;
+const REQUEST_TIMEOUT_SECS: u64 = 5;
+/// Requests are a single GET line plus headers; anything larger isn't a browser asking for the page
+const MAX_REQUEST_BYTES: usize = 8192;
+/// Threads answering requests; live pages are handed to the pusher, so they don't keep one busy
+const WORKERS: usize = 4;
+/// Connections waiting for a worker; more are closed right away
+const MAX_QUEUED_CONNECTIONS: usize = 16;
+const MAX_LIVE_PAGES: usize = 16;
+const TEXT: &str = "text/plain; charset=utf-8";
+
+/// What the page shows, pushed over /live and served as /status.json
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardSnapshot {
+    pub target: String,
+    pub monitoring: bool,
+    pub status: String,
+    pub last_ms: Option<f64>,
+    pub mean_ms: f64,
+    pub loss_rate: f64,
+    pub interval_secs: u64,
+    /// Dial colors as "#rrggbb", starting at 0 seconds
+    pub circles: Vec<String>,
+    /// (send time in ms since the epoch, RTT in ms or null when lost), oldest first
+    pub results: Vec<(u64, Option<f64>)>,
+}
+
+/// Where the dashboard listens; a change restarts the listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    pub port: u16,
+    /// Reachable from other devices; otherwise only from this computer
+    pub lan: bool,
+    /// Serve HTTPS, so the token doesn't cross the network in the clear
+    pub tls: Option<TlsFiles>,
+}
+
+/// Paths of the PEM certificate chain and private key the dashboard serves HTTPS with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    pub cert: String,
+    pub key: String,
+}
+
+/// State the request workers and the pusher share with the app
+struct Shared {
+    /// Empty means no token is asked for, which is only allowed on this computer
+    token: Mutex<String>,
+    snapshot: Mutex<String>,
+    live_pages: Mutex<Vec<WebSocket<Connection>>>,
+}
+
+/// A browser's connection, encrypted when the dashboard has a certificate
+enum Connection {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ServerConnection, TcpStream>>),
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Serves a read-only status page, e.g. to a phone, and pushes it every change
+pub struct WebDashboard {
+    pub binding: Binding,
+    shared: Arc<Shared>,
+    pushes: mpsc::Sender<String>,
+    last_published: Option<Instant>,
+    stop: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl WebDashboard {
+    pub fn start(binding: Binding, token: &str) -> Result<Self, String> {
+        let (port, lan) = (binding.port, binding.lan);
+        if lan && token.trim().is_empty() {
+            return Err("Set a token before letting other devices in".to_string());
+        }
+        let tls = binding.tls.as_ref().map(tls_config).transpose()?;
+        let address = if lan { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        let listener = TcpListener::bind((address, port)).map_err(|e| format!("Could not open port {port}: {e}"))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        log::info!("Web dashboard listening on {address}:{port}{}", if tls.is_some() { " (HTTPS)" } else { "" });
+
+        let empty = serde_json::to_string(&DashboardSnapshot::default()).unwrap_or_else(|_| "{}".to_string());
+        let shared = Arc::new(Shared {
+            token: Mutex::new(token.to_string()),
+            snapshot: Mutex::new(empty),
+            live_pages: Mutex::new(Vec::new()),
+        });
+
+        let (connections, queue) = mpsc::sync_channel::<TcpStream>(MAX_QUEUED_CONNECTIONS);
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..WORKERS {
+            let (queue, shared, tls) = (queue.clone(), shared.clone(), tls.clone());
+            // Ends once the listener is gone and the queue is empty
+            thread::spawn(move || {
+                while let Ok(stream) = lock(&queue).recv() {
+                    if let Err(e) = serve(stream, tls.as_ref(), &shared) {
+                        log::debug!("Web dashboard request failed: {e}");
+                    }
+                }
+            });
+        }
+
+        let (pushes, pushed) = mpsc::channel::<String>();
+        {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for json in pushed {
+                    lock(&shared.live_pages).retain_mut(|page| page.send(Message::text(json.clone())).is_ok());
+                }
+                lock(&shared.live_pages).clear();
+            });
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let listener = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            if connections.try_send(stream).is_err() {
+                                log::debug!("Web dashboard busy; closed a connection");
+                            }
+                        }
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(ACCEPT_POLL_MS)),
+                        Err(e) => log::warn!("Web dashboard could not accept a connection: {e}"),
+                    }
+                }
+            })
+        };
+        Ok(Self { binding, shared, pushes, last_published: None, stop, listener: Some(listener) })
+    }
+
+    /// Takes effect for the next request; open pages stay open
+    pub fn set_token(&self, token: &str) {
+        let mut current = lock(&self.shared.token);
+        if *current != token {
+            *current = token.to_string();
+        }
+    }
+
+    /// Replaces what the page shows and pushes it to open pages; `build` only runs once per publish interval
+    pub fn publish(&mut self, build: impl FnOnce() -> DashboardSnapshot) {
+        if self.last_published.is_some_and(|last| last.elapsed() < Duration::from_millis(PUBLISH_INTERVAL_MS)) {
+            return;
+        }
+        self.last_published = Some(Instant::now());
+        match serde_json::to_string(&build()) {
+            Ok(json) => {
+                *lock(&self.shared.snapshot) = json.clone();
+                let _ = self.pushes.send(json);
+            }
+            Err(e) => log::warn!("Could not serialize the web dashboard status: {e}"),
+        }
+    }
+}
+
+impl Drop for WebDashboard {
+    /// Waits for the listener to close, so the port can be opened again right away
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+    }
+}
+
+/// The page's address on `host`, with `token` unless it's empty
+pub fn address(host: &str, port: u16, https: bool, token: &str) -> String {
+    let scheme = if https { "https" } else { "http" };
+    match token.trim() {
+        "" => format!("{scheme}://{host}:{port}/"),
+        token => format!("{scheme}://{host}:{port}/?token={token}"),
+    }
+}
+
+fn tls_config(files: &TlsFiles) -> Result<Arc<ServerConfig>, String> {
+    let (cert, key) = (files.cert.trim(), files.key.trim());
+    if cert.is_empty() || key.is_empty() {
+        return Err("HTTPS needs both a certificate and a key file".to_string());
+    }
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Could not read the certificate {cert}: {e}"))?;
+    if certs.is_empty() {
+        return Err(format!("No certificate in {cert}"));
+    }
+    let key = PrivateKeyDer::from_pem_file(key).map_err(|e| format!("Could not read the key {key}: {e}"))?;
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map(Arc::new)
+        .map_err(|e| format!("Certificate and key don't match: {e}"))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn serve(stream: TcpStream, tls: Option<&Arc<ServerConfig>>, shared: &Shared) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))?;
+    // The TLS handshake happens on the first read, within the same timeouts
+    let mut stream = match tls {
+        Some(config) => Connection::Tls(Box::new(StreamOwned::new(ServerConnection::new(config.clone()).map_err(io::Error::other)?, stream))),
+        None => Connection::Plain(stream),
+    };
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 || request.len() > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut words = request.split_whitespace();
+    let (method, target) = (words.next().unwrap_or_default(), words.next().unwrap_or_default());
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let header = |name: &str| {
+        request.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then_some(value.trim())
+        })
+    };
+    // Browsers can't add headers to a WebSocket, so the page passes the token in the address
+    let given = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .or_else(|| header("Authorization").and_then(|value| value.strip_prefix("Bearer ")));
+    let allowed = {
+        let token = lock(&shared.token);
+        token.is_empty() || given.is_some_and(|given| remote_agent::tokens_match(&token, given))
+    };
+
+    let (status, content_type, body) = match (method, path) {
+        _ if !allowed => ("401 Unauthorized", TEXT, "Open the address shown in the app, including its token".to_string()),
+        ("GET", "/live") => match header("Sec-WebSocket-Key") {
+            Some(key) => return open_live_page(stream, key, shared),
+            None => ("400 Bad Request", TEXT, "Expected a WebSocket".to_string()),
+        },
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        ("GET", "/status.json") => ("200 OK", "application/json", lock(&shared.snapshot).clone()),
+        ("GET", _) => ("404 Not Found", TEXT, "Not found".to_string()),
+        _ => ("405 Method Not Allowed", TEXT, "Only GET is supported".to_string()),
+    };
+    respond(&mut stream, status, content_type, &body)
+}
+
+fn respond(stream: &mut Connection, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+/// Accepts the WebSocket upgrade and hands the connection to the pusher, starting with the current snapshot
+fn open_live_page(mut stream: Connection, key: &str, shared: &Shared) -> io::Result<()> {
+    let mut live_pages = lock(&shared.live_pages);
+    if live_pages.len() >= MAX_LIVE_PAGES {
+        return respond(&mut stream, "503 Service Unavailable", TEXT, "Too many open dashboards");
+    }
+    let accept = tungstenite::handshake::derive_accept_key(key.as_bytes());
+    write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n")?;
+    stream.flush()?;
+    let mut page = WebSocket::from_raw_socket(stream, Role::Server, None);
+    let snapshot = lock(&shared.snapshot).clone();
+    if page.send(Message::text(snapshot)).is_ok() {
+        live_pages.push(page);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn https_address_and_missing_tls_files() {
+        assert_eq!(address("192.168.1.20", 7879, true, "abc"), "https://192.168.1.20:7879/?token=abc");
+        let files = |cert: &str, key: &str| TlsFiles { cert: cert.to_string(), key: key.to_string() };
+        assert!(tls_config(&files("cert.pem", "")).is_err_and(|e| e.contains("both")));
+        assert!(tls_config(&files("/nonexistent/cert.pem", "/nonexistent/key.pem")).is_err_and(|e| e.contains("certificate")));
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use eframe::egui;
+
+const WIFI_CHECK_INTERVAL_SECS: u64 = 10;
+
+#[derive(Debug, Clone)]
+pub struct WifiInfo {
+    pub ssid: Option<String>,
+    /// Received signal strength in dBm
+    pub rssi_dbm: Option<i32>,
+}
+
+impl WifiInfo {
+    /// Rough quality band; below about -70 dBm latency spikes and retransmits become common
+    pub fn quality(&self) -> Option<&'static str> {
+        self.rssi_dbm.map(|rssi| match rssi {
+            r if r >= -55 => "excellent",
+            r if r >= -67 => "good",
+            r if r >= -75 => "fair",
+            _ => "weak",
+        })
+    }
+}
+
+/// Platform-specific source of the current Wi-Fi association
+pub trait WifiBackend: Send {
+    /// Returns None when the machine is not connected over Wi-Fi (or the tool is unavailable)
+    fn query(&self) -> Option<WifiInfo>;
+}
+
+pub fn backend() -> Box<dyn WifiBackend> {
+    Box::new(platform::Backend)
+}
+
+/// Polls the Wi-Fi backend on a background thread, since the system tools can take a while to answer
+pub struct WifiMonitor {
+    pub current: Option<WifiInfo>,
+    last_check: Option<Instant>,
+    check_running: bool,
+    sender: mpsc::Sender<Option<WifiInfo>>,
+    receiver: mpsc::Receiver<Option<WifiInfo>>,
+}
+
+impl WifiMonitor {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            current: None,
+            last_check: None,
+            check_running: false,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Cheap to call every frame; starts a query once per check interval
+    pub fn poll(&mut self, repaint: &egui::Context) {
+        while let Ok(info) = self.receiver.try_recv() {
+            self.current = info;
+            self.check_running = false;
+        }
+
+        let check_interval = Duration::from_secs(WIFI_CHECK_INTERVAL_SECS);
+        if self.check_running || self.last_check.is_some_and(|last| last.elapsed() < check_interval) {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+        self.check_running = true;
+
+        let sender = self.sender.clone();
+        let repaint = repaint.clone();
+        thread::spawn(move || {
+            let _ = sender.send(backend().query());
+            repaint.request_repaint();
+        });
+    }
+}
+
+/// Reads "key: value" style output, tolerating varied padding around the separator
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn field<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == key).then(|| value.trim())
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    const WIRELESS_TABLE: &str = "/proc/net/wireless";
+
+    pub struct Backend;
+
+    impl Backend {
+        /// /proc/net/wireless lists associated wireless interfaces with the signal level in dBm:
+        /// "wlan0: 0000   60.  -50.  -256  ..."
+        fn wireless_interface() -> Option<(String, Option<i32>)> {
+            let table = std::fs::read_to_string(WIRELESS_TABLE).ok()?;
+            table.lines().skip(2).find_map(|line| {
+                let (name, rest) = line.split_once(':')?;
+                let level = rest
+                    .split_whitespace()
+                    .nth(2)
+                    .and_then(|level| level.trim_end_matches('.').parse::<f64>().ok())
+                    .map(|level| level as i32);
+                Some((name.trim().to_string(), level))
+            })
+        }
+    }
+
+    impl WifiBackend for Backend {
+        fn query(&self) -> Option<WifiInfo> {
+            let (interface, level) = Self::wireless_interface()?;
+            // "iw dev wlan0 link" prints "SSID: name" and "signal: -50 dBm" while associated
+            let link = system_command::run("iw", &["dev", &interface, "link"]).unwrap_or_default();
+            if link.starts_with("Not connected") {
+                return None;
+            }
+            let rssi_dbm = field(&link, "signal")
+                .and_then(|signal| signal.split_whitespace().next())
+                .and_then(|signal| signal.parse::<i32>().ok())
+                .or(level);
+            Some(WifiInfo {
+                ssid: field(&link, "SSID").map(str::to_string),
+                rssi_dbm,
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    const AIRPORT: &str =
+        "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+    pub struct Backend;
+
+    impl WifiBackend for Backend {
+        fn query(&self) -> Option<WifiInfo> {
+            // "airport -I" prints "agrCtlRSSI: -52" and "SSID: name"; it reports AirPort off when disconnected
+            let output = system_command::run(AIRPORT, &["-I"]).ok()?;
+            let rssi_dbm = field(&output, "agrCtlRSSI").and_then(|rssi| rssi.parse::<i32>().ok());
+            if rssi_dbm.is_none_or(|rssi| rssi == 0) {
+                return None;
+            }
+            Some(WifiInfo {
+                ssid: field(&output, "SSID").map(str::to_string),
+                rssi_dbm,
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use crate::system_command;
+
+    pub struct Backend;
+
+    impl WifiBackend for Backend {
+        fn query(&self) -> Option<WifiInfo> {
+            // "netsh wlan show interfaces" prints "SSID : name" and "Signal : 85%"
+            let output = system_command::run("netsh", &["wlan", "show", "interfaces"]).ok()?;
+            let ssid = field(&output, "SSID")?.to_string();
+            // Windows reports quality as a percentage that maps linearly onto -100..-50 dBm
+            let rssi_dbm = field(&output, "Signal")
+                .and_then(|signal| signal.trim_end_matches('%').trim().parse::<i32>().ok())
+                .map(|percent| percent / 2 - 100);
+            Some(WifiInfo { ssid: Some(ssid), rssi_dbm })
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    pub struct Backend;
+
+    impl WifiBackend for Backend {
+        fn query(&self) -> Option<WifiInfo> {
+            None
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::SystemTime;
+use chrono::{DateTime, Datelike, Days, Local, Timelike};
+use eframe::egui;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::report::PeriodSummary;
+
+/// Days of history written to the workbook, today included
+pub const EXPORT_DAYS: u64 = 7;
+const DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+const MS_FORMAT: &str = "0.000";
+const PERCENT_FORMAT: &str = "0.00";
+
+pub struct XlsxExport;
+
+impl XlsxExport {
+    /// Writes the last week of history for `target` to `path` in the background: a summary
+    /// sheet, one row per hour and every raw sample. `name` is how the target is labelled.
+    pub fn spawn(store: HistoryStore, target: String, name: String, path: PathBuf, sender: mpsc::Sender<Result<String, String>>, repaint: egui::Context) {
+        thread::spawn(move || {
+            let today = Local::now().date_naive();
+            let records: Vec<HistoryRecord> = store
+                .load_days(today - Days::new(EXPORT_DAYS - 1), today)
+                .into_iter()
+                .filter(|record| record.target == target)
+                .collect();
+            let result = if records.is_empty() {
+                Err(format!("No history for {target} in the last {EXPORT_DAYS} days"))
+            } else {
+                Self::write(&path, &name, &records)
+                    .map(|()| format!("Exported {} samples to {}", records.len(), path.display()))
+                    .map_err(|e| format!("Excel export failed: {e}"))
+            };
+            let _ = sender.send(result);
+            repaint.request_repaint();
+        });
+    }
+
+    fn write(path: &Path, target: &str, records: &[HistoryRecord]) -> Result<(), XlsxError> {
+        let mut workbook = Workbook::new();
+        Self::write_summary(workbook.add_worksheet().set_name("Summary")?, target, records)?;
+        Self::write_hourly(workbook.add_worksheet().set_name("Hourly")?, records)?;
+        Self::write_samples(workbook.add_worksheet().set_name("Samples")?, records)?;
+        workbook.save(path)
+    }
+
+    fn write_summary(sheet: &mut Worksheet, target: &str, records: &[HistoryRecord]) -> Result<(), XlsxError> {
+        let ms = Format::new().set_num_format(MS_FORMAT);
+        let percent = Format::new().set_num_format(PERCENT_FORMAT);
+        let bold = Format::new().set_bold();
+        sheet.write_with_format(0, 0, "Target", &bold)?;
+        sheet.write(0, 1, target)?;
+        sheet.write_with_format(1, 0, "Exported", &bold)?;
+        sheet.write_with_format(1, 1, excel_time(SystemTime::now())?, &Format::new().set_num_format(DATETIME_FORMAT))?;
+
+        let header = ["Period", "Probes", "Uptime %", "Mean ms", "95th percentile ms", "Outages", "Outage minutes"];
+        sheet.write_row_with_format(3, 0, header, &bold)?;
+        let all: Vec<&HistoryRecord> = records.iter().collect();
+        let mut periods = vec![PeriodSummary::from_records(format!("Last {EXPORT_DAYS} days"), &all)];
+        let mut by_day: BTreeMap<chrono::NaiveDate, Vec<&HistoryRecord>> = BTreeMap::new();
+        for record in records {
+            by_day.entry(DateTime::<Local>::from(record.timestamp()).date_naive()).or_default().push(record);
+        }
+        periods.extend(by_day.iter().rev().map(|(day, records)| PeriodSummary::from_records(day.format("%a %Y-%m-%d").to_string(), records)));
+        for (period, row) in periods.iter().zip(4..) {
+            sheet.write(row, 0, &period.label)?;
+            sheet.write(row, 1, period.probes as f64)?;
+            sheet.write_with_format(row, 2, period.uptime, &percent)?;
+            sheet.write_with_format(row, 3, period.mean_ms, &ms)?;
+            sheet.write_with_format(row, 4, period.p95_ms, &ms)?;
+            sheet.write(row, 5, period.outages as f64)?;
+            sheet.write_with_format(row, 6, period.outage_duration.as_secs_f64() / 60.0, &ms)?;
+        }
+        sheet.autofit();
+        Ok(())
+    }
+
+    fn write_hourly(sheet: &mut Worksheet, records: &[HistoryRecord]) -> Result<(), XlsxError> {
+        let hour_format = Format::new().set_num_format("yyyy-mm-dd hh:00");
+        let ms = Format::new().set_num_format(MS_FORMAT);
+        let percent = Format::new().set_num_format(PERCENT_FORMAT);
+        let header = ["Hour", "Probes", "Lost", "Loss %", "Mean ms", "Min ms", "Max ms", "In maintenance"];
+        sheet.write_row_with_format(0, 0, header, &Format::new().set_bold())?;
+        sheet.set_freeze_panes(1, 0)?;
+
+        // Keyed by the local hour's start so rows come out in order
+        let mut hours: BTreeMap<DateTime<Local>, Vec<&HistoryRecord>> = BTreeMap::new();
+        for record in records {
+            let time = DateTime::<Local>::from(record.timestamp());
+            let hour = time.with_minute(0).and_then(|time| time.with_second(0)).and_then(|time| time.with_nanosecond(0)).unwrap_or(time);
+            hours.entry(hour).or_default().push(record);
+        }
+        for ((hour, records), row) in hours.iter().zip(1..) {
+            let counted: Vec<&&HistoryRecord> = records.iter().filter(|record| !record.in_maintenance).collect();
+            let lost = counted.iter().filter(|record| !record.success).count();
+            let replies: Vec<f64> = counted.iter().filter_map(|record| record.response_ms).collect();
+            let mean = (!replies.is_empty()).then(|| replies.iter().sum::<f64>() / replies.len() as f64);
+            sheet.write_with_format(row, 0, local_excel_time(*hour)?, &hour_format)?;
+            sheet.write(row, 1, counted.len() as f64)?;
+            sheet.write(row, 2, lost as f64)?;
+            sheet.write_with_format(row, 3, lost as f64 * 100.0 / counted.len().max(1) as f64, &percent)?;
+            sheet.write_with_format(row, 4, mean, &ms)?;
+            sheet.write_with_format(row, 5, replies.iter().copied().reduce(f64::min), &ms)?;
+            sheet.write_with_format(row, 6, replies.iter().copied().reduce(f64::max), &ms)?;
+            sheet.write(row, 7, (records.len() - counted.len()) as f64)?;
+        }
+        sheet.autofit();
+        Ok(())
+    }
+
+    fn write_samples(sheet: &mut Worksheet, records: &[HistoryRecord]) -> Result<(), XlsxError> {
+        let time_format = Format::new().set_num_format(DATETIME_FORMAT);
+        let ms = Format::new().set_num_format(MS_FORMAT);
+        let header = ["Time", "Success", "Response ms", "Degraded", "In maintenance", "Clock offset ms"];
+        sheet.write_row_with_format(0, 0, header, &Format::new().set_bold())?;
+        sheet.set_freeze_panes(1, 0)?;
+        for (record, row) in records.iter().zip(1..) {
+            sheet.write_with_format(row, 0, excel_time(record.timestamp())?, &time_format)?;
+            sheet.write(row, 1, record.success)?;
+            sheet.write_with_format(row, 2, record.response_ms, &ms)?;
+            sheet.write(row, 3, record.degraded)?;
+            sheet.write(row, 4, record.in_maintenance)?;
+            sheet.write_with_format(row, 5, record.clock_offset_ms, &ms)?;
+        }
+        sheet.set_column_width(0, 20)?;
+        Ok(())
+    }
+}
+
+/// `time` in local time, which is what people reading the sheet expect
+fn excel_time(time: SystemTime) -> Result<ExcelDateTime, XlsxError> {
+    local_excel_time(DateTime::<Local>::from(time))
+}
+
+fn local_excel_time(time: DateTime<Local>) -> Result<ExcelDateTime, XlsxError> {
+    let seconds = time.second() as f64 + time.timestamp_subsec_millis() as f64 / 1000.0;
+    ExcelDateTime::from_ymd(time.year() as u16, time.month() as u8, time.day() as u8)?
+        .and_hms(time.hour() as u16, time.minute() as u8, seconds)
+}